@@ -6,7 +6,7 @@ fn main() {
 
     // Safety: We assume that the library that is loaded is a valid munlib
     let builder = Runtime::builder(lib_path);
-    let mut runtime = unsafe { builder.finish() }
+    let runtime = unsafe { builder.finish() }
         .expect("Failed to spawn Runtime");
 
     loop {
@@ -12,7 +12,7 @@ fn main() {
     // Safety: We assume that the library that is loaded is a valid munlib
     let builder = Runtime::builder(lib_dir)
         .insert_fn("log_f32", log_f32 as extern "C" fn(f32));
-    let mut runtime = unsafe { builder.finish() }
+    let runtime = unsafe { builder.finish() }
         .expect("Failed to spawn Runtime");
 
     let ctx = runtime.invoke::<StructRef, ()>("new_sim", ()).unwrap().root();
@@ -83,6 +83,63 @@ class GcRootPtr {
     MunGcPtr m_ptr;
     const Runtime* m_runtime;
 };
+
+class GcWeakPtr {
+   public:
+    /** Constructs a weak garbage collection pointer from the provided raw
+     * garbage collection handle, which does not keep the object alive.
+     *
+     * \param runtime a reference to a runtime
+     * \param obj a garbage collected object handle
+     * \return a weak garbage collection pointer
+    .*/
+    GcWeakPtr(const Runtime& runtime, MunGcPtr obj) noexcept
+        : m_weak(runtime.gc_downgrade_ptr(obj)), m_runtime(&runtime) {}
+
+    GcWeakPtr(const GcWeakPtr&) = delete;
+
+    /** Move constructs a `GcWeakPtr`
+     *
+     * \param other an rvalue reference to a `GcWeakPtr`
+     */
+    GcWeakPtr(GcWeakPtr&& other) noexcept : m_weak(other.m_weak), m_runtime(other.m_runtime) {
+        other.m_weak._0 = nullptr;
+    }
+
+    /** Move assignment operator for `GcWeakPtr`
+     *
+     * \param other an rvalue reference to a `GcWeakPtr`
+     * \return a reference this instance
+     */
+    GcWeakPtr& operator=(GcWeakPtr&& other) noexcept {
+        std::swap(m_weak, other.m_weak);
+        m_runtime = other.m_runtime;
+        return *this;
+    }
+
+    /** Destructs the `GcWeakPtr`. */
+    ~GcWeakPtr() noexcept { mun_gc_weak_destroy(m_weak); }
+
+    /** Roots the referenced object, if it has not been collected.
+     *
+     * \return possibly, a rooted garbage collection pointer
+     */
+    std::optional<GcRootPtr> upgrade() const noexcept {
+        const auto obj = m_runtime->gc_upgrade_ptr(m_weak);
+        if (!obj) {
+            return std::nullopt;
+        }
+
+        // The object has already been rooted by upgrading it
+        GcRootPtr rooted(*m_runtime, obj);
+        m_runtime->gc_unroot_ptr(obj);
+        return rooted;
+    }
+
+   private:
+    MunWeakHandle m_weak;
+    const Runtime* m_runtime;
+};
 }  // namespace mun
 
 #endif
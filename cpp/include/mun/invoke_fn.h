@@ -86,12 +86,29 @@ InvokeResult<Output, Args...> invoke_fn(Runtime& runtime, std::string_view fn_na
 
         auto fn = reinterpret_cast<typename Marshal<Output>::type(MUN_CALLTYPE*)(
             typename Marshal<Args>::type...)>(const_cast<void*>(fn_info->fn_ptr));
+
+        // An unreported heap limit error would make the function return early
+        runtime.gc_take_heap_limit_exceeded();
+        auto heap_limit_exceeded = [&runtime, fn_name]() {
+            if (runtime.gc_take_heap_limit_exceeded()) {
+                std::cerr << "Invocation of '" << fn_name << "' exceeded the maximum heap size."
+                          << std::endl;
+                return true;
+            }
+            return false;
+        };
+
         if constexpr (std::is_same_v<Output, void>) {
             fn(Marshal<Args>::to(args)...);
-            return InvokeResult<Output, Args...>(std::monostate{});
+            if (!heap_limit_exceeded()) {
+                return InvokeResult<Output, Args...>(std::monostate{});
+            }
         } else {
-            return InvokeResult<Output, Args...>(
-                Marshal<Output>::from(fn(Marshal<Args>::to(args)...), runtime));
+            // The result of a function that returned early must be discarded
+            auto result = fn(Marshal<Args>::to(args)...);
+            if (!heap_limit_exceeded()) {
+                return InvokeResult<Output, Args...>(Marshal<Output>::from(result, runtime));
+            }
         }
     }
 
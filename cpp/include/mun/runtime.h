@@ -3,6 +3,7 @@
 
 #include <cassert>
 #include <optional>
+#include <string>
 #include <string_view>
 
 #include "mun/error.h"
@@ -95,6 +96,75 @@ class Runtime {
         return reclaimed;
     }
 
+    /**
+     * Performs a step of a garbage collection cycle that spends approximately
+     * `budget_ns` nanoseconds.
+     *
+     * Returns `true` if the step completed the collection cycle, `false`
+     * otherwise.
+     */
+    bool gc_collect_step(uint64_t budget_ns) const noexcept {
+        bool completed;
+        auto error_handle = mun_gc_collect_step(m_handle, budget_ns, &completed);
+        assert(error_handle._0 == 0);
+
+        return completed;
+    }
+
+    /**
+     * Collects young objects that are no longer referenced by rooted objects
+     * or old objects.
+     *
+     * Returns `true` if memory was reclaimed, `false` otherwise.
+     */
+    bool gc_collect_minor() const noexcept {
+        bool reclaimed;
+        auto error_handle = mun_gc_collect_minor(m_handle, &reclaimed);
+        assert(error_handle._0 == 0);
+
+        return reclaimed;
+    }
+
+    /**
+     * Takes a snapshot of all objects that are currently allocated by the
+     * garbage collector, exported in the specified `format`.
+     */
+    std::string gc_snapshot(MunSnapshotFormat format) const noexcept {
+        const char* snapshot;
+        auto error_handle = mun_gc_snapshot(m_handle, format, &snapshot);
+        assert(error_handle._0 == 0);
+
+        std::string result(snapshot);
+        mun_destroy_string(snapshot);
+        return result;
+    }
+
+    /**
+     * Retrieves statistics about the garbage collector.
+     */
+    MunGcStats gc_stats() const noexcept {
+        MunGcStats stats;
+        auto error_handle = mun_gc_stats(m_handle, &stats);
+        assert(error_handle._0 == 0);
+
+        return stats;
+    }
+
+    /**
+     * Returns whether an allocation of a Mun function that was called on the
+     * current thread exceeded the maximum heap size, since the previous call.
+     *
+     * Until the error is reset, Mun functions return early after calling a
+     * function that might allocate.
+     */
+    bool gc_take_heap_limit_exceeded() const noexcept {
+        bool exceeded;
+        auto error_handle = mun_gc_take_heap_limit_exceeded(m_handle, &exceeded);
+        assert(error_handle._0 == 0);
+
+        return exceeded;
+    }
+
     /**
      * Roots the specified `obj`, which keeps it and objects it references
      * alive.
@@ -127,6 +197,83 @@ class Runtime {
         assert(error_handle._0 == 0);
     }
 
+    /**
+     * Notifies the garbage collector that a struct is about to be stored in a
+     * field of the specified `obj`.
+     *
+     * Incremental and generational garbage collectors rely on this
+     * notification to trace the stored struct.
+     *
+     * \param obj a garbage collection handle
+     */
+    void gc_write_barrier_ptr(MunGcPtr obj) const noexcept {
+        const auto error_handle = mun_gc_write_barrier(m_handle, obj);
+        assert(error_handle._0 == 0);
+    }
+
+    /**
+     * Creates a weak reference to the specified `obj`, which does not keep it
+     * alive.
+     *
+     * The weak reference must be destructed using `mun_gc_weak_destroy`.
+     *
+     * \param obj a garbage collection handle
+     * \return a weak reference handle
+     */
+    MunWeakHandle gc_downgrade_ptr(MunGcPtr obj) const noexcept {
+        MunWeakHandle weak;
+        const auto error_handle = mun_gc_downgrade(m_handle, obj, &weak);
+        assert(error_handle._0 == 0);
+        return weak;
+    }
+
+    /**
+     * Roots the object referenced by `weak`, returning a null pointer if the
+     * object has been collected.
+     *
+     * The returned object must be unrooted using `gc_unroot_ptr`.
+     *
+     * \param weak a weak reference handle
+     * \return a rooted garbage collection handle or a null pointer
+     */
+    MunGcPtr gc_upgrade_ptr(MunWeakHandle weak) const noexcept {
+        MunGcPtr obj;
+        const auto error_handle = mun_gc_upgrade(m_handle, weak, &obj);
+        assert(error_handle._0 == 0);
+        return obj;
+    }
+
+    /**
+     * Allocates a deep copy of `obj`, including all objects it (indirectly)
+     * references.
+     *
+     * The returned copy is not rooted.
+     *
+     * \param obj a garbage collection handle
+     * \return a garbage collection handle to the copy
+     */
+    MunGcPtr gc_deep_clone_ptr(MunGcPtr obj) const noexcept {
+        MunGcPtr clone;
+        const auto error_handle = mun_gc_deep_clone(m_handle, obj, &clone);
+        assert(error_handle._0 == 0);
+        return clone;
+    }
+
+    /**
+     * Checks whether `a` and `b` are of the same type and all of their
+     * fields, including those of referenced objects, are equal.
+     *
+     * \param a a garbage collection handle
+     * \param b a garbage collection handle
+     * \return whether the objects are structurally equal
+     */
+    bool gc_structural_eq(MunGcPtr a, MunGcPtr b) const noexcept {
+        bool equal;
+        const auto error_handle = mun_gc_structural_eq(m_handle, a, b, &equal);
+        assert(error_handle._0 == 0);
+        return equal;
+    }
+
     /**
      * Retrieves the type information for the specified `obj`.
      *
@@ -172,6 +319,48 @@ struct RuntimeOptions {
      * functions.
      */
     std::vector<RuntimeFunction> functions;
+
+    /**
+     * The factor by which the allocated memory may grow since the previous collection, before
+     * garbage is automatically collected. `0` disables the threshold.
+     */
+    double gc_growth_factor = 0.0;
+
+    /**
+     * The number of allocated bytes above which garbage is automatically collected. `0` disables
+     * the threshold.
+     */
+    size_t gc_max_bytes = 0;
+
+    /**
+     * The number of allocations since the previous collection, after which garbage is
+     * automatically collected. `0` disables the threshold.
+     */
+    size_t gc_max_allocations = 0;
+
+    /**
+     * The kind of garbage collector to use.
+     */
+    MunGcKind gc_kind = MarkSweep;
+
+    /**
+     * A list of finalizers that are invoked right before structs of their types are deallocated.
+     * The type names must outlive the construction of the runtime.
+     */
+    std::vector<MunFinalizer> finalizers;
+
+    /**
+     * The allocator that provides the memory of garbage collected structs. If its callbacks are
+     * null, the global allocator is used.
+     */
+    MunAllocator gc_allocator = {nullptr, nullptr, nullptr};
+
+    /**
+     * The maximum number of bytes that garbage collected structs can occupy. `0` disables the
+     * limit. Mun functions that exceed the limit return early, so their results must be
+     * discarded.
+     */
+    size_t gc_max_heap_size = 0;
 };
 
 /** Construct a new runtime that loads the library at `library_path` and its dependencies.
@@ -203,6 +392,14 @@ inline std::optional<Runtime> make_runtime(std::string_view library_path,
     runtime_options.functions =
         function_definitions.empty() ? nullptr : function_definitions.data();
     runtime_options.num_functions = static_cast<uint32_t>(function_definitions.size());
+    runtime_options.gc_growth_factor = options.gc_growth_factor;
+    runtime_options.gc_max_bytes = options.gc_max_bytes;
+    runtime_options.gc_max_allocations = options.gc_max_allocations;
+    runtime_options.gc_kind = options.gc_kind;
+    runtime_options.finalizers = options.finalizers.data();
+    runtime_options.num_finalizers = static_cast<uint32_t>(options.finalizers.size());
+    runtime_options.gc_allocator = options.gc_allocator;
+    runtime_options.gc_max_heap_size = options.gc_max_heap_size;
 
     MunRuntimeHandle handle;
     if (auto error = Error(mun_runtime_create(library_path.data(), runtime_options, &handle))) {
@@ -6,6 +6,54 @@
 #include <stdbool.h>
 #include <stdint.h>
 
+/**
+ * The kinds of garbage collectors that the `Runtime` can use.
+ */
+enum MunGcKind
+#ifdef __cplusplus
+  : uint8_t
+#endif // __cplusplus
+ {
+    /**
+     * A mark-sweep collector that collects all garbage at once.
+     */
+    MarkSweep,
+    /**
+     * An incremental tri-color collector, that allows a collection to be split into steps using
+     * [`Runtime::gc_collect_step`](crate::Runtime::gc_collect_step).
+     */
+    Incremental,
+    /**
+     * A generational collector, that bump-allocates objects in a nursery. Short-lived objects
+     * are collected by minor collections, which only trace the nursery.
+     */
+    Generational,
+};
+#ifndef __cplusplus
+typedef uint8_t MunGcKind;
+#endif // __cplusplus
+
+/**
+ * The formats that a heap snapshot can be exported to by [`mun_gc_snapshot`].
+ */
+enum MunSnapshotFormat
+#ifdef __cplusplus
+  : uint8_t
+#endif // __cplusplus
+ {
+    /**
+     * A JSON object that lists all objects with their type, size, root count and references
+     */
+    Json,
+    /**
+     * A Graphviz DOT graph of all objects and their references
+     */
+    Dot,
+};
+#ifndef __cplusplus
+typedef uint8_t MunSnapshotFormat;
+#endif // __cplusplus
+
 /**
  * Represents the kind of memory management a struct uses.
  */
@@ -181,6 +229,80 @@ typedef struct MunFunctionDefinition {
     const void *fn_ptr;
 } MunFunctionDefinition;
 
+/**
+ * `UnsafeTypeInfo` is a type that wraps a `NonNull<TypeInfo>` and indicates unsafe interior
+ * operations on the wrapped `TypeInfo`. The unsafety originates from uncertainty about the
+ * lifetime of the wrapped `TypeInfo`.
+ *
+ * Rust lifetime rules do not allow separate lifetimes for struct fields, but we can make `unsafe`
+ * guarantees about their lifetimes. Thus the `UnsafeTypeInfo` type is the only legal way to obtain
+ * shared references to the wrapped `TypeInfo`.
+ */
+typedef struct MunTypeInfo *MunUnsafeTypeInfo;
+
+/**
+ * A `RawGcPtr` is an unsafe version of a `GcPtr`. It represents the raw internal pointer
+ * semantics used by the runtime.
+ */
+typedef void *const *MunRawGcPtr;
+
+/**
+ * A `GcPtr` is what you interact with outside of the allocator. It is a pointer to a piece of
+ * memory that points to the actual data stored in memory.
+ *
+ * This creates an indirection that must be followed to get to the actual data of the object. Note
+ * that the `GcPtr` must therefore be pinned in memory whereas the contained memory pointer may
+ * change.
+ */
+typedef MunRawGcPtr MunGcPtr;
+
+/**
+ * A finalizer that is invoked right before a struct of the type with the specified name is
+ * deallocated, passed to [`mun_runtime_create`] through the runtime options.
+ *
+ * The callback is invoked while the garbage collector is collecting, so it must not call back
+ * into the runtime.
+ */
+typedef struct MunFinalizer {
+    /**
+     * The name of the struct type
+     */
+    const char *type_name;
+    /**
+     * The callback that is invoked with the struct, its type and the `user_data`
+     */
+    void (*callback)(MunGcPtr, MunUnsafeTypeInfo, void*);
+    /**
+     * User data that is passed to the callback
+     */
+    void *user_data;
+} MunFinalizer;
+
+/**
+ * An allocator that provides the memory of garbage collected structs, passed to
+ * [`mun_runtime_create`] through the runtime options. If both callbacks are null, the global
+ * allocator is used.
+ *
+ * The callbacks can be invoked from any thread that allocates or collects garbage.
+ */
+typedef struct MunAllocator {
+    /**
+     * The callback that allocates memory of the specified size and alignment, which is a power
+     * of two, and the `user_data`. It returns a null pointer if the allocation failed. The size
+     * can be zero.
+     */
+    void *(*alloc)(uintptr_t, uintptr_t, void*);
+    /**
+     * The callback that deallocates memory that was allocated by `alloc`, with the same size and
+     * alignment, and the `user_data`.
+     */
+    void (*dealloc)(void*, uintptr_t, uintptr_t, void*);
+    /**
+     * User data that is passed to the callbacks
+     */
+    void *user_data;
+} MunAllocator;
+
 /**
  * Options required to construct a [`RuntimeHandle`] through [`mun_runtime_create`]
  *
@@ -202,6 +324,47 @@ typedef struct MunRuntimeOptions {
      * The number of functions in the [`functions`] array.
      */
     uint32_t num_functions;
+    /**
+     * The factor by which the allocated memory may grow since the previous collection, before
+     * garbage is automatically collected. Zero disables the threshold.
+     */
+    double gc_growth_factor;
+    /**
+     * The number of allocated bytes above which garbage is automatically collected. Zero
+     * disables the threshold.
+     */
+    uintptr_t gc_max_bytes;
+    /**
+     * The number of allocations since the previous collection, after which garbage is
+     * automatically collected. Zero disables the threshold.
+     */
+    uintptr_t gc_max_allocations;
+    /**
+     * The kind of garbage collector to use.
+     */
+    MunGcKind gc_kind;
+    /**
+     * Finalizers that are invoked right before structs of their types are deallocated.
+     *
+     * If the [`num_finalizers`] field is non-zero this field must contain a pointer to an array
+     * of [`Finalizer`]s.
+     */
+    const struct MunFinalizer *finalizers;
+    /**
+     * The number of finalizers in the [`finalizers`] array.
+     */
+    uint32_t num_finalizers;
+    /**
+     * The allocator that provides the memory of garbage collected structs. If its callbacks are
+     * null, the global allocator is used.
+     */
+    struct MunAllocator gc_allocator;
+    /**
+     * The maximum number of bytes that garbage collected structs can occupy. Zero disables the
+     * limit. Allocations that exceed the limit are reported through
+     * [`mun_gc_take_heap_limit_exceeded`].
+     */
+    uintptr_t gc_max_heap_size;
 } MunRuntimeOptions;
 
 /**
@@ -212,31 +375,45 @@ typedef struct MunRuntimeHandle {
 } MunRuntimeHandle;
 
 /**
- * `UnsafeTypeInfo` is a type that wraps a `NonNull<TypeInfo>` and indicates unsafe interior
- * operations on the wrapped `TypeInfo`. The unsafety originates from uncertainty about the
- * lifetime of the wrapped `TypeInfo`.
- *
- * Rust lifetime rules do not allow separate lifetimes for struct fields, but we can make `unsafe`
- * guarantees about their lifetimes. Thus the `UnsafeTypeInfo` type is the only legal way to obtain
- * shared references to the wrapped `TypeInfo`.
- */
-typedef struct MunTypeInfo *MunUnsafeTypeInfo;
-
-/**
- * A `RawGcPtr` is an unsafe version of a `GcPtr`. It represents the raw internal pointer
- * semantics used by the runtime.
+ * A C-style handle to a weak reference to an object, created through [`mun_gc_downgrade`].
  */
-typedef void *const *MunRawGcPtr;
+typedef struct MunWeakHandle {
+    const void *_0;
+} MunWeakHandle;
 
 /**
- * A `GcPtr` is what you interact with outside of the allocator. It is a pointer to a piece of
- * memory that points to the actual data stored in memory.
- *
- * This creates an indirection that must be followed to get to the actual data of the object. Note
- * that the `GcPtr` must therefore be pinned in memory whereas the contained memory pointer may
- * change.
+ * Statistics about the garbage collector of a runtime, retrieved through [`mun_gc_stats`].
  */
-typedef MunRawGcPtr MunGcPtr;
+typedef struct MunGcStats {
+    /**
+     * The number of bytes that are currently allocated
+     */
+    uintptr_t allocated_memory;
+    /**
+     * The number of collections that have been run
+     */
+    uintptr_t collections;
+    /**
+     * The total number of bytes freed by all collections
+     */
+    uintptr_t freed_memory;
+    /**
+     * The duration of the most recent collection, in nanoseconds
+     */
+    uint64_t last_pause_ns;
+    /**
+     * The longest duration of a single collection, in nanoseconds
+     */
+    uint64_t max_pause_ns;
+    /**
+     * The combined duration of all collections, in nanoseconds
+     */
+    uint64_t total_pause_ns;
+    /**
+     * The maximum number of bytes that can be allocated, or zero if unlimited
+     */
+    uintptr_t max_heap_size;
+} MunGcStats;
 
 #ifdef __cplusplus
 extern "C" {
@@ -297,6 +474,23 @@ struct MunErrorHandle mun_runtime_get_function_definition(struct MunRuntimeHandl
  */
 struct MunErrorHandle mun_runtime_update(struct MunRuntimeHandle handle, bool *updated);
 
+/**
+ * Adds the independently compiled library at `library_path` to the runtime corresponding to
+ * `handle`. The functions of the library and its dependencies are accessible as
+ * `<namespace>::<function path>`.
+ *
+ * If a non-zero error handle is returned, it must be manually destructed using
+ * [`mun_error_destroy`].
+ *
+ * # Safety
+ *
+ * This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+ * an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+ */
+struct MunErrorHandle mun_runtime_add_package(struct MunRuntimeHandle handle,
+                                              const char *namespace_,
+                                              const char *library_path);
+
 /**
  * Deallocates a string that was allocated by the runtime.
  *
@@ -384,6 +578,102 @@ struct MunErrorHandle mun_gc_root(struct MunRuntimeHandle handle, MunGcPtr obj);
  */
 struct MunErrorHandle mun_gc_unroot(struct MunRuntimeHandle handle, MunGcPtr obj);
 
+/**
+ * Notifies the garbage collector that a struct is about to be stored in a field of the specified
+ * `obj`. If successful, the garbage collector has been notified, otherwise a non-zero error handle
+ * is returned.
+ *
+ * Incremental and generational garbage collectors rely on this notification to trace the stored
+ * struct, so it must be called every time the host stores a struct in a field of a garbage
+ * collected struct, right before writing to the memory of `obj`.
+ *
+ * If a non-zero error handle is returned, it must be manually destructed using
+ * [`mun_error_destroy`].
+ *
+ * # Safety
+ *
+ * This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+ * an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+ */
+struct MunErrorHandle mun_gc_write_barrier(struct MunRuntimeHandle handle, MunGcPtr obj);
+
+/**
+ * Creates a weak reference to the specified `obj`, which does not keep it alive. If successful,
+ * `weak` is set, otherwise a non-zero error handle is returned.
+ *
+ * The weak reference must be manually destructed using [`mun_gc_weak_destroy`].
+ *
+ * If a non-zero error handle is returned, it must be manually destructed using
+ * [`mun_error_destroy`].
+ *
+ * # Safety
+ *
+ * This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+ * an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+ */
+struct MunErrorHandle mun_gc_downgrade(struct MunRuntimeHandle handle,
+                                       MunGcPtr obj,
+                                       struct MunWeakHandle *weak);
+
+/**
+ * Roots the object referenced by `weak`. If successful, `obj` is set, otherwise a non-zero error
+ * handle is returned. If the object has been collected, `obj` is set to a null pointer.
+ * Otherwise, the object must be unrooted using [`mun_gc_unroot`].
+ *
+ * If a non-zero error handle is returned, it must be manually destructed using
+ * [`mun_error_destroy`].
+ *
+ * # Safety
+ *
+ * This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+ * an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+ */
+struct MunErrorHandle mun_gc_upgrade(struct MunRuntimeHandle handle,
+                                     struct MunWeakHandle weak,
+                                     MunGcPtr *obj);
+
+/**
+ * Destructs the weak reference corresponding to `weak`.
+ */
+void mun_gc_weak_destroy(struct MunWeakHandle weak);
+
+/**
+ * Allocates a deep copy of the specified `obj`. Objects that are (indirectly) referenced by
+ * `obj` are copied as well, preserving the references between them, including cycles. If
+ * successful, `clone` is set to the copy, otherwise a non-zero error handle is returned. The copy
+ * is not rooted.
+ *
+ * If a non-zero error handle is returned, it must be manually destructed using
+ * [`mun_error_destroy`].
+ *
+ * # Safety
+ *
+ * This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+ * an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+ */
+struct MunErrorHandle mun_gc_deep_clone(struct MunRuntimeHandle handle,
+                                        MunGcPtr obj,
+                                        MunGcPtr *clone);
+
+/**
+ * Compares the specified objects `a` and `b` structurally. If successful, `equal` is set,
+ * otherwise a non-zero error handle is returned. If `equal` is `true`, the objects are of the
+ * same type and all of their fields are equal, where referenced objects are compared
+ * recursively.
+ *
+ * If a non-zero error handle is returned, it must be manually destructed using
+ * [`mun_error_destroy`].
+ *
+ * # Safety
+ *
+ * This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+ * an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+ */
+struct MunErrorHandle mun_gc_structural_eq(struct MunRuntimeHandle handle,
+                                           MunGcPtr a,
+                                           MunGcPtr b,
+                                           bool *equal);
+
 /**
  * Collects all memory that is no longer referenced by rooted objects. If successful, `reclaimed`
  * is set, otherwise a non-zero error handle is returned. If `reclaimed` is `true`, memory was
@@ -399,6 +689,97 @@ struct MunErrorHandle mun_gc_unroot(struct MunRuntimeHandle handle, MunGcPtr obj
  */
 struct MunErrorHandle mun_gc_collect(struct MunRuntimeHandle handle, bool *reclaimed);
 
+/**
+ * Performs a step of a garbage collection cycle that spends approximately `budget_ns`
+ * nanoseconds. If successful, `completed` is set, otherwise a non-zero error handle is returned.
+ * If `completed` is `true`, the step completed the collection cycle.
+ *
+ * Only an incremental garbage collector can split a collection into steps, other garbage
+ * collectors perform a full collection.
+ *
+ * If a non-zero error handle is returned, it must be manually destructed using
+ * [`mun_error_destroy`].
+ *
+ * # Safety
+ *
+ * This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+ * an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+ */
+struct MunErrorHandle mun_gc_collect_step(struct MunRuntimeHandle handle,
+                                          uint64_t budget_ns,
+                                          bool *completed);
+
+/**
+ * Collects young objects that are no longer referenced by rooted objects or old objects. If
+ * successful, `reclaimed` is set, otherwise a non-zero error handle is returned. If `reclaimed`
+ * is `true`, memory was reclaimed, otherwise nothing happend.
+ *
+ * Only a generational garbage collector distinguishes young objects, other garbage collectors
+ * perform a full collection.
+ *
+ * If a non-zero error handle is returned, it must be manually destructed using
+ * [`mun_error_destroy`].
+ *
+ * # Safety
+ *
+ * This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+ * an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+ */
+struct MunErrorHandle mun_gc_collect_minor(struct MunRuntimeHandle handle, bool *reclaimed);
+
+/**
+ * Takes a snapshot of all objects that are currently allocated by the runtime and exports it in
+ * the specified `format`. If successful, `snapshot` is set, otherwise a non-zero error handle is
+ * returned.
+ *
+ * The snapshot must be manually destructed using [`mun_destroy_string`].
+ *
+ * If a non-zero error handle is returned, it must be manually destructed using
+ * [`mun_error_destroy`].
+ *
+ * # Safety
+ *
+ * This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+ * an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+ */
+struct MunErrorHandle mun_gc_snapshot(struct MunRuntimeHandle handle,
+                                      MunSnapshotFormat format,
+                                      const char **snapshot);
+
+/**
+ * Retrieves statistics about the garbage collector of the runtime corresponding to `handle`. If
+ * successful, `stats` is set, otherwise a non-zero error handle is returned.
+ *
+ * If a non-zero error handle is returned, it must be manually destructed using
+ * [`mun_error_destroy`].
+ *
+ * # Safety
+ *
+ * This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+ * an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+ */
+struct MunErrorHandle mun_gc_stats(struct MunRuntimeHandle handle, struct MunGcStats *stats);
+
+/**
+ * Determines whether an allocation of a Mun function that was called on the current thread
+ * exceeded the maximum heap size of the runtime corresponding to `handle`, since the previous
+ * call. If successful, `exceeded` is set, otherwise a non-zero error handle is returned.
+ *
+ * An allocation that exceeds the limit, after garbage was collected, fails and the Mun functions
+ * return early. The host should discard the result of the Mun function. Until the error is
+ * reset, Mun functions return early after calling a function that might allocate.
+ *
+ * If a non-zero error handle is returned, it must be manually destructed using
+ * [`mun_error_destroy`].
+ *
+ * # Safety
+ *
+ * This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+ * an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+ */
+struct MunErrorHandle mun_gc_take_heap_limit_exceeded(struct MunRuntimeHandle handle,
+                                                      bool *exceeded);
+
 #ifdef __cplusplus
 } // extern "C"
 #endif // __cplusplus
@@ -110,6 +110,17 @@ class StructRef {
     bool set(std::string_view field_name, T value) noexcept;
 
    private:
+    /** Notifies the garbage collector that a struct is about to be stored in a
+     * field of type `field_type`.
+     *
+     * \param field_type the type of the modified field
+     */
+    void write_barrier(const MunTypeInfo& field_type) const noexcept {
+        if (field_type.data.tag == MunTypeInfoData_Tag::Struct) {
+            m_runtime->gc_write_barrier_ptr(raw());
+        }
+    }
+
     const Runtime* m_runtime;
     GcRootPtr m_handle;
 };
@@ -253,10 +264,12 @@ std::optional<T> StructRef::replace(std::string_view field_name, T value) noexce
 
         const auto offset = static_cast<size_t>(struct_info.field_offsets[*idx]);
         auto byte_ptr = reinterpret_cast<std::byte*>(*raw());
-        return std::make_optional(Marshal<T>::swap_at(
+        write_barrier(*field_type);
+        auto old = Marshal<T>::swap_at(
             Marshal<T>::to(std::move(value)),
             reinterpret_cast<typename Marshal<T>::type*>(byte_ptr + offset), *m_runtime,
-            field_type ? std::make_optional(field_type) : std::nullopt));
+            field_type ? std::make_optional(field_type) : std::nullopt);
+        return std::make_optional(std::move(old));
     } else {
         return std::nullopt;
     }
@@ -284,6 +297,7 @@ bool StructRef::set(std::string_view field_name, T value) noexcept {
         const auto offset = static_cast<size_t>(struct_info.field_offsets[*idx]);
         auto byte_ptr = reinterpret_cast<std::byte*>(*raw());
 
+        write_barrier(*field_type);
         Marshal<T>::move_to(Marshal<T>::to(std::move(value)),
                             reinterpret_cast<typename Marshal<T>::type*>(byte_ptr + offset),
                             field_type ? std::make_optional(field_type) : std::nullopt);
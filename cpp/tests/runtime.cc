@@ -70,3 +70,112 @@ TEST_CASE("runtime can garbage collect", "[runtime]") {
         FAIL(err.message());
     }
 }
+
+TEST_CASE("runtime can weakly reference garbage collected objects", "[runtime]") {
+    mun::Error err;
+    if (auto runtime = mun::make_runtime(get_munlib_path("marshal/target/mod.munlib"), {}, &err)) {
+        REQUIRE(!err);
+        std::optional<mun::GcWeakPtr> weak;
+        {
+            auto res = mun::invoke_fn<mun::StructRef>(*runtime, "new_bool", true, false);
+            REQUIRE(res.is_ok());
+
+            auto obj = res.unwrap();
+            weak.emplace(*runtime, obj.raw());
+            REQUIRE(!runtime->gc_collect());
+            REQUIRE(weak->upgrade().has_value());
+        }
+        REQUIRE(runtime->gc_collect());
+        REQUIRE(!weak->upgrade().has_value());
+    } else {
+        REQUIRE(err);
+        FAIL(err.message());
+    }
+}
+
+TEST_CASE("runtime notifies incremental garbage collector of stored structs", "[runtime]") {
+    mun::Error err;
+    mun::RuntimeOptions options;
+    options.gc_kind = Incremental;
+    if (auto runtime =
+            mun::make_runtime(get_munlib_path("marshal/target/mod.munlib"), options, &err)) {
+        REQUIRE(!err);
+
+        auto gc_struct = mun::invoke_fn<mun::StructRef>(*runtime, "new_gc_struct", 1.0f, 2.0f);
+        REQUIRE(gc_struct.is_ok());
+        auto value_struct =
+            mun::invoke_fn<mun::StructRef>(*runtime, "new_value_struct", 3.0f, 4.0f);
+        REQUIRE(value_struct.is_ok());
+        auto gc_wrapper = mun::invoke_fn<mun::StructRef>(*runtime, "new_gc_wrapper",
+                                                         gc_struct.unwrap(), value_struct.unwrap());
+        REQUIRE(gc_wrapper.is_ok());
+        auto wrapper = gc_wrapper.unwrap();
+
+        // Start a collection cycle, before storing structs in `wrapper`
+        REQUIRE(!runtime->gc_collect_step(0));
+
+        auto replacement = mun::invoke_fn<mun::StructRef>(*runtime, "new_gc_struct", 5.0f, 6.0f);
+        REQUIRE(replacement.is_ok());
+        REQUIRE(wrapper.replace("0", replacement.unwrap()).has_value());
+        auto value_replacement =
+            mun::invoke_fn<mun::StructRef>(*runtime, "new_value_struct", 7.0f, 8.0f);
+        REQUIRE(value_replacement.is_ok());
+        REQUIRE(wrapper.set("1", value_replacement.unwrap()));
+
+        while (!runtime->gc_collect_step(0)) {
+        }
+
+        const auto gc = wrapper.get<mun::StructRef>("0");
+        REQUIRE(gc.has_value());
+        REQUIRE(gc->get<float>("0") == 5.0f);
+        REQUIRE(gc->get<float>("1") == 6.0f);
+
+        const auto value = wrapper.get<mun::StructRef>("1");
+        REQUIRE(value.has_value());
+        REQUIRE(value->get<float>("0") == 7.0f);
+        REQUIRE(value->get<float>("1") == 8.0f);
+    } else {
+        REQUIRE(err);
+        FAIL(err.message());
+    }
+}
+
+TEST_CASE("runtime notifies generational garbage collector of stored structs", "[runtime]") {
+    mun::Error err;
+    mun::RuntimeOptions options;
+    options.gc_kind = Generational;
+    if (auto runtime =
+            mun::make_runtime(get_munlib_path("marshal/target/mod.munlib"), options, &err)) {
+        REQUIRE(!err);
+
+        auto gc_struct = mun::invoke_fn<mun::StructRef>(*runtime, "new_gc_struct", 1.0f, 2.0f);
+        REQUIRE(gc_struct.is_ok());
+        auto value_struct =
+            mun::invoke_fn<mun::StructRef>(*runtime, "new_value_struct", 3.0f, 4.0f);
+        REQUIRE(value_struct.is_ok());
+        auto gc_wrapper = mun::invoke_fn<mun::StructRef>(*runtime, "new_gc_wrapper",
+                                                         gc_struct.unwrap(), value_struct.unwrap());
+        REQUIRE(gc_wrapper.is_ok());
+        auto wrapper = gc_wrapper.unwrap();
+
+        // Promote `wrapper` to the old generation
+        runtime->gc_collect();
+
+        {
+            auto replacement =
+                mun::invoke_fn<mun::StructRef>(*runtime, "new_gc_struct", 5.0f, 6.0f);
+            REQUIRE(replacement.is_ok());
+            REQUIRE(wrapper.replace("0", replacement.unwrap()).has_value());
+        }
+
+        runtime->gc_collect_minor();
+
+        const auto gc = wrapper.get<mun::StructRef>("0");
+        REQUIRE(gc.has_value());
+        REQUIRE(gc->get<float>("0") == 5.0f);
+        REQUIRE(gc->get<float>("1") == 6.0f);
+    } else {
+        REQUIRE(err);
+        FAIL(err.message());
+    }
+}
@@ -4,7 +4,7 @@ use std::ffi::OsString;
 
 use clap::{Parser, Subcommand};
 
-use ops::{build, init, language_server, new, start};
+use ops::{build, fmt, init, language_server, new, start};
 
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
@@ -30,6 +30,9 @@ enum Command {
 
     /// Invoke a function from a munlib
     Start(start::Args),
+
+    /// Format the source files of a Mun project
+    Fmt(fmt::Args),
 }
 
 #[derive(Copy, Debug, Clone, PartialEq, Eq)]
@@ -60,5 +63,6 @@ where
         Command::New(args) => new::new(args),
         Command::Init(args) => init::init(args),
         Command::Start(args) => start::start(args),
+        Command::Fmt(args) => fmt::fmt(args),
     }
 }
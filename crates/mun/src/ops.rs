@@ -1,4 +1,5 @@
 pub mod build;
+pub mod fmt;
 pub mod init;
 pub mod language_server;
 pub mod new;
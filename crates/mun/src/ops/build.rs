@@ -73,27 +73,7 @@ pub fn build(args: Args) -> Result<ExitStatus, anyhow::Error> {
         })
         .unwrap_or(DisplayColor::Auto);
 
-    // Locate the manifest
-    let manifest_path = match &args.manifest_path {
-        None => {
-            let current_dir =
-                std::env::current_dir().expect("could not determine current working directory");
-            find_manifest(&current_dir).ok_or_else(|| {
-                anyhow::anyhow!(
-                    "could not find {} in '{}' or a parent directory",
-                    MANIFEST_FILENAME,
-                    current_dir.display()
-                )
-            })?
-        }
-        Some(path) => std::fs::canonicalize(Path::new(&path)).map_err(|_| {
-            anyhow::anyhow!(
-                "'{}' does not refer to a valid manifest path",
-                path.display()
-            )
-        })?,
-    };
-
+    let manifest_path = locate_manifest(args.manifest_path.as_deref())?;
     log::info!("located build manifest at: {}", manifest_path.display());
 
     let compiler_options = Config {
@@ -117,6 +97,30 @@ pub fn build(args: Args) -> Result<ExitStatus, anyhow::Error> {
     .map(Into::into)
 }
 
+/// Returns the canonical path of the specified manifest or, if no path is specified, the path of the
+/// manifest in the current working directory or one of its parents.
+pub(crate) fn locate_manifest(manifest_path: Option<&Path>) -> Result<PathBuf, anyhow::Error> {
+    match manifest_path {
+        None => {
+            let current_dir =
+                std::env::current_dir().expect("could not determine current working directory");
+            find_manifest(&current_dir).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "could not find {} in '{}' or a parent directory",
+                    MANIFEST_FILENAME,
+                    current_dir.display()
+                )
+            })
+        }
+        Some(path) => std::fs::canonicalize(path).map_err(|_| {
+            anyhow::anyhow!(
+                "'{}' does not refer to a valid manifest path",
+                path.display()
+            )
+        }),
+    }
+}
+
 /// Find a Mun manifest file in the specified directory or one of its parents.
 fn find_manifest(directory: &Path) -> Option<PathBuf> {
     let mut current_dir = Some(directory);
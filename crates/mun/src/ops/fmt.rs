@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use mun_project::Package;
+use walkdir::WalkDir;
+
+use crate::ops::build::locate_manifest;
+use crate::ExitStatus;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Path to the manifest of the project
+    #[clap(long)]
+    manifest_path: Option<PathBuf>,
+
+    /// Only check whether the source files are formatted. Lists the files that are not formatted
+    /// and fails if there are any, without changing them.
+    #[clap(long)]
+    check: bool,
+}
+
+/// This method is invoked when the executable is run with the `fmt` argument indicating that a
+/// user requested us to format the source files of a project in the current directory or one of its
+/// parent directories.
+pub fn fmt(args: Args) -> Result<ExitStatus, anyhow::Error> {
+    let manifest_path = locate_manifest(args.manifest_path.as_deref())?;
+    log::info!("located manifest at: {}", manifest_path.display());
+
+    let package = Package::from_file(&manifest_path)?;
+    let options = package.manifest().format_options();
+
+    let mut success = true;
+    for path in iter_source_files(&package.source_directory()) {
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow!("failed to read `{}`: {}", path.display(), e))?;
+        let formatted = match mun_fmt::format(&text, options) {
+            Ok(formatted) => formatted,
+            Err(e) => {
+                eprintln!("error: failed to format `{}`: {}", path.display(), e);
+                success = false;
+                continue;
+            }
+        };
+
+        if formatted == text {
+            continue;
+        }
+
+        if args.check {
+            println!("{}", path.display());
+            success = false;
+        } else {
+            std::fs::write(&path, formatted)
+                .map_err(|e| anyhow!("failed to write `{}`: {}", path.display(), e))?;
+        }
+    }
+
+    Ok(success.into())
+}
+
+/// Returns the paths of all Mun source files in the `source_dir`, in a deterministic order.
+fn iter_source_files(source_dir: &Path) -> impl Iterator<Item = PathBuf> {
+    WalkDir::new(source_dir)
+        .sort_by(|a, b| a.file_name().cmp(b.file_name()))
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry.file_type().is_file()
+                && entry.path().extension().map_or(false, |ext| ext == "mun")
+        })
+        .map(|entry| entry.into_path())
+}
@@ -53,6 +53,45 @@ fn mun_emit_ir() {
     assert!(ir_path.is_file());
 }
 
+/// Verifies that `mun fmt` formats the source files of a project and that `mun fmt --check`
+/// detects unformatted files.
+#[test]
+fn mun_fmt() {
+    let project_dir = tempfile::Builder::new()
+        .prefix(PROJECT_DIR)
+        .tempdir()
+        .unwrap();
+
+    let project_path = project_dir.path().join(PROJECT_NAME);
+
+    let args: Vec<OsString> = vec!["mun".into(), "new".into(), project_path.as_path().into()];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+
+    let source_path = project_path.join("src/mod.mun");
+    std::fs::write(&source_path, "pub fn main()->f64{\n3.14159}").unwrap();
+
+    let fmt = |args: &[&str]| {
+        let args: Vec<OsString> = vec![
+            OsString::from("mun"),
+            OsString::from("fmt"),
+            OsString::from("--manifest-path"),
+            OsString::from(project_path.join("mun.toml")),
+        ]
+        .into_iter()
+        .chain(args.iter().map(|&arg| arg.into()))
+        .collect();
+        run_with_args(args).unwrap()
+    };
+
+    assert_eq!(fmt(&["--check"]), mun::ExitStatus::Error);
+    assert_eq!(fmt(&[]), mun::ExitStatus::Success);
+    assert_eq!(fmt(&["--check"]), mun::ExitStatus::Success);
+    assert_eq!(
+        std::fs::read_to_string(&source_path).unwrap(),
+        "pub fn main() -> f64 {\n    3.14159\n}\n"
+    );
+}
+
 fn build(project: &Path, args: &[&str]) {
     let args: Vec<OsString> = vec![
         OsString::from("mun"),
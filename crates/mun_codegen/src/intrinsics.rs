@@ -20,5 +20,12 @@ pub trait Intrinsic: Sync {
 
 intrinsics! {
     /// Allocates memory for the specified `type` in the allocator referred to by `alloc_handle`.
+    /// Returns `null` if the allocation exceeded the maximum heap size.
     pub fn new(type: *const TypeInfo, alloc_handle: *mut ffi::c_void) -> *const *mut ffi::c_void;
+    /// Notifies the allocator referred to by `alloc_handle` that a reference is about to be stored
+    /// in `obj`.
+    pub fn write_barrier(obj: *const *mut ffi::c_void, alloc_handle: *mut ffi::c_void) -> ();
+    /// Returns whether an allocation exceeded the maximum heap size of the allocator referred to
+    /// by `alloc_handle`, in which case the caller has to return early.
+    pub fn heap_limit_exceeded(alloc_handle: *mut ffi::c_void) -> bool;
 }
@@ -1,6 +1,7 @@
 use crate::module_group::ModuleGroup;
 use crate::{
     intrinsics,
+    ir::intrinsics::{is_heap_field, may_allocate},
     ir::ty::HirTypeCache,
     ir::types as ir,
     ir::{dispatch_table::DispatchTable, type_table::TypeTable},
@@ -16,6 +17,7 @@ use inkwell::{
     basic_block::BasicBlock,
     builder::Builder,
     context::Context,
+    types::BasicTypeEnum,
     values::{AggregateValueEnum, GlobalValue, PointerValue},
     values::{BasicValueEnum, CallSiteValue, FloatValue, FunctionValue, IntValue, StructValue},
     AddressSpace, FloatPredicate, IntPredicate,
@@ -52,6 +54,7 @@ pub(crate) struct BodyIrGenerator<'db, 'ink, 't> {
     hir_function: hir::Function,
     external_globals: ExternalGlobals<'ink>,
     module_group: &'t ModuleGroup,
+    heap_limit_exceeded_block: Option<BasicBlock<'ink>>,
 }
 
 impl<'db, 'ink, 't> BodyIrGenerator<'db, 'ink, 't> {
@@ -96,6 +99,7 @@ impl<'db, 'ink, 't> BodyIrGenerator<'db, 'ink, 't> {
             external_globals,
             hir_types,
             module_group,
+            heap_limit_exceeded_block: None,
         }
     }
 
@@ -149,6 +153,8 @@ impl<'db, 'ink, 't> BodyIrGenerator<'db, 'ink, 't> {
                 self.builder.build_return(Some(&value));
             }
         }
+
+        self.move_heap_limit_exceeded_block_to_end();
     }
 
     pub fn gen_fn_wrapper(&mut self) {
@@ -203,6 +209,8 @@ impl<'db, 'ink, 't> BodyIrGenerator<'db, 'ink, 't> {
                 self.builder.build_return(Some(&ret_value));
             }
         }
+
+        self.move_heap_limit_exceeded_block_to_end();
     }
 
     /// Generates IR for the specified expression. Dependending on the type of expression an IR
@@ -409,6 +417,12 @@ impl<'db, 'ink, 't> BodyIrGenerator<'db, 'ink, 't> {
             .unwrap()
             .into_pointer_value();
 
+        // If the allocation exceeded the maximum heap size, `null` is returned
+        let heap_limit_exceeded = self
+            .builder
+            .build_is_null(object_ptr, "heap_limit_exceeded");
+        self.gen_heap_limit_check(heap_limit_exceeded);
+
         // Cast the object pointer to the struct type
         let struct_ptr_ptr = self
             .builder
@@ -864,6 +878,7 @@ impl<'db, 'ink, 't> BodyIrGenerator<'db, 'ink, 't> {
                     None => rhs,
                 };
                 let place = self.gen_place_expr(lhs_expr);
+                self.gen_write_barrier(lhs_expr);
                 self.builder.build_store(place, rhs);
                 Some(self.gen_empty())
             }
@@ -893,6 +908,7 @@ impl<'db, 'ink, 't> BodyIrGenerator<'db, 'ink, 't> {
                     None => rhs,
                 };
                 let place = self.gen_place_expr(lhs_expr);
+                self.gen_write_barrier(lhs_expr);
                 self.builder.build_store(place, rhs);
                 Some(self.gen_empty())
             }
@@ -1053,6 +1069,77 @@ impl<'db, 'ink, 't> BodyIrGenerator<'db, 'ink, 't> {
         }
     }
 
+    /// Given a place expression that refers to a field stored in a heap struct, generate code that
+    /// results in the handle of that heap struct.
+    fn gen_place_owner_handle(&mut self, expr: ExprId) -> Option<PointerValue<'ink>> {
+        let body = self.body.clone();
+        if let Expr::Field {
+            expr: receiver_expr,
+            ..
+        } = &body[expr]
+        {
+            let hir_struct = self.infer[*receiver_expr]
+                .as_struct()
+                .expect("expected a struct");
+            match hir_struct.data(self.db.upcast()).memory_kind {
+                hir::StructMemoryKind::Gc => {
+                    let receiver_ptr = self.gen_place_expr(*receiver_expr);
+                    Some(
+                        self.builder
+                            .build_load(receiver_ptr, "owner_handle")
+                            .into_pointer_value(),
+                    )
+                }
+                hir::StructMemoryKind::Value => self.gen_place_owner_handle(*receiver_expr),
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Generates IR to notify the allocator that a struct is about to be stored in the place
+    /// expression, if the place refers to a field stored in a heap struct. This allows incremental
+    /// garbage collectors to trace the stored struct.
+    fn gen_write_barrier(&mut self, place_expr: ExprId) {
+        if !is_heap_field(self.db, place_expr, &self.body, &self.infer) {
+            return;
+        }
+        let owner_handle = self
+            .gen_place_owner_handle(place_expr)
+            .expect("expected a field of a heap struct");
+
+        let write_barrier_fn_ptr = self.dispatch_table.gen_intrinsic_lookup(
+            self.external_globals.dispatch_table,
+            &self.builder,
+            &intrinsics::write_barrier,
+        );
+
+        // An object pointer is stored in a `*const *mut std::ffi::c_void` to make it struct type
+        // agnostic.
+        let owner_handle = self.builder.build_bitcast(
+            owner_handle,
+            self.context
+                .i8_type()
+                .ptr_type(AddressSpace::Generic)
+                .ptr_type(AddressSpace::Generic),
+            "owner_handle_to_i8_ptr_ptr",
+        );
+
+        let allocator_handle = self.builder.build_load(
+            self.external_globals
+                .alloc_handle
+                .expect("no allocator handle was specified, this is required for structs")
+                .as_pointer_value(),
+            "allocator_handle",
+        );
+
+        self.builder.build_call(
+            write_barrier_fn_ptr,
+            &[owner_handle.into(), allocator_handle.into()],
+            "",
+        );
+    }
+
     /// Returns true if the specified expression refers to an expression that results in a memory
     /// address that can be used for other place operations.
     fn is_place_expr(&self, expr: ExprId) -> bool {
@@ -1076,7 +1163,7 @@ impl<'db, 'ink, 't> BodyIrGenerator<'db, 'ink, 't> {
         function: hir::Function,
         args: &[BasicMetadataValueEnum<'ink>],
     ) -> CallSiteValue<'ink> {
-        if self.should_use_dispatch_table(function) {
+        let call_site = if self.should_use_dispatch_table(function) {
             let ptr_value = self.dispatch_table.gen_function_lookup(
                 self.db,
                 self.external_globals.dispatch_table,
@@ -1094,6 +1181,96 @@ impl<'db, 'ink, 't> BodyIrGenerator<'db, 'ink, 't> {
             });
             self.builder
                 .build_call(*llvm_function, args, &function.name(self.db).to_string())
+        };
+
+        // If the called function exceeded the maximum heap size, it returned early
+        if may_allocate(self.db, self.module_group, function) {
+            let heap_limit_exceeded_fn_ptr = self.dispatch_table.gen_intrinsic_lookup(
+                self.external_globals.dispatch_table,
+                &self.builder,
+                &intrinsics::heap_limit_exceeded,
+            );
+
+            let allocator_handle = self.builder.build_load(
+                self.external_globals
+                    .alloc_handle
+                    .expect("no allocator handle was specified, this is required for structs")
+                    .as_pointer_value(),
+                "allocator_handle",
+            );
+
+            let heap_limit_exceeded = self
+                .builder
+                .build_call(
+                    heap_limit_exceeded_fn_ptr,
+                    &[allocator_handle.into()],
+                    "heap_limit_exceeded",
+                )
+                .try_as_basic_value()
+                .left()
+                .unwrap()
+                .into_int_value();
+            self.gen_heap_limit_check(heap_limit_exceeded);
+        }
+
+        call_site
+    }
+
+    /// Generates IR that returns early from the function if `heap_limit_exceeded` is true.
+    /// Otherwise, code generation continues in a new block.
+    fn gen_heap_limit_check(&mut self, heap_limit_exceeded: IntValue<'ink>) {
+        let exceeded_block = self.heap_limit_exceeded_block();
+        let current_block = self.builder.get_insert_block().unwrap();
+        let continue_block = self
+            .context
+            .append_basic_block(self.fn_value, "heap_limit_ok");
+        continue_block.move_after(current_block).unwrap();
+
+        self.builder
+            .build_conditional_branch(heap_limit_exceeded, exceeded_block, continue_block);
+        self.builder.position_at_end(continue_block);
+    }
+
+    /// Returns the block that returns early from the function when the maximum heap size was
+    /// exceeded. Mun code cannot be aborted, so a zero value is returned and the result of the
+    /// function is discarded by the runtime.
+    fn heap_limit_exceeded_block(&mut self) -> BasicBlock<'ink> {
+        if let Some(block) = self.heap_limit_exceeded_block {
+            return block;
+        }
+
+        let block = self
+            .context
+            .append_basic_block(self.fn_value, "heap_limit_exceeded_ret");
+        let builder = self.context.create_builder();
+        builder.position_at_end(block);
+        match self.fn_value.get_type().get_return_type() {
+            Some(ty) => {
+                let zero: BasicValueEnum = match ty {
+                    BasicTypeEnum::ArrayType(ty) => ty.const_zero().into(),
+                    BasicTypeEnum::FloatType(ty) => ty.const_zero().into(),
+                    BasicTypeEnum::IntType(ty) => ty.const_zero().into(),
+                    BasicTypeEnum::PointerType(ty) => ty.const_zero().into(),
+                    BasicTypeEnum::StructType(ty) => ty.const_zero().into(),
+                    BasicTypeEnum::VectorType(ty) => ty.const_zero().into(),
+                };
+                builder.build_return(Some(&zero))
+            }
+            None => builder.build_return(None),
+        };
+
+        self.heap_limit_exceeded_block = Some(block);
+        block
+    }
+
+    /// Moves the block that returns early when the maximum heap size was exceeded, to the end of
+    /// the function.
+    fn move_heap_limit_exceeded_block_to_end(&self) {
+        if let Some(block) = self.heap_limit_exceeded_block {
+            let last_block = self.fn_value.get_last_basic_block().unwrap();
+            if last_block != block {
+                block.move_after(last_block).unwrap();
+            }
         }
     }
 
@@ -51,6 +51,7 @@ pub(crate) fn gen_file_group_ir<'db, 'ink>(
                     code_gen.context,
                     code_gen.target_machine.get_target_data(),
                     code_gen.db,
+                    module_group,
                     &mut intrinsics_map,
                     &mut needs_alloc,
                     &f.body(code_gen.db),
@@ -64,6 +65,9 @@ pub(crate) fn gen_file_group_ir<'db, 'ink>(
                     intrinsics::collect_wrapper_body(
                         code_gen.context,
                         code_gen.target_machine.get_target_data(),
+                        code_gen.db,
+                        module_group,
+                        f,
                         &mut intrinsics_map,
                         &mut needs_alloc,
                     );
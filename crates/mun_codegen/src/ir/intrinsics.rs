@@ -1,9 +1,11 @@
 use crate::{
     intrinsics::{self, Intrinsic},
     ir::dispatch_table::FunctionPrototype,
+    module_group::ModuleGroup,
 };
-use hir::{Body, Expr, ExprId, HirDatabase, InferenceResult, ValueNs};
+use hir::{BinaryOp, Body, Expr, ExprId, HirDatabase, InferenceResult, ValueNs};
 use inkwell::{context::Context, targets::TargetData, types::FunctionType};
+use rustc_hash::FxHashSet;
 use std::{collections::BTreeMap, sync::Arc};
 
 // Use a `BTreeMap` to guarantee deterministically ordered output
@@ -28,6 +30,7 @@ fn collect_expr<'db, 'ink>(
     context: &'ink Context,
     target: &TargetData,
     db: &'db dyn HirDatabase,
+    module_group: &ModuleGroup,
     intrinsics: &mut IntrinsicsMap<'ink>,
     needs_alloc: &mut bool,
     expr_id: ExprId,
@@ -44,7 +47,18 @@ fn collect_expr<'db, 'ink>(
                 // self.collect_intrinsic(module, entries, &intrinsics::drop);
                 *needs_alloc = true;
             }
-            Some(hir::CallableDef::Function(_)) => (),
+            // If the function exceeds the maximum heap size, the caller returns early as well
+            Some(hir::CallableDef::Function(function)) => {
+                if may_allocate(db, module_group, function) {
+                    collect_intrinsic(
+                        context,
+                        target,
+                        &intrinsics::heap_limit_exceeded,
+                        intrinsics,
+                    );
+                    *needs_alloc = true;
+                }
+            }
             None => panic!("expected a callable expression"),
         }
     }
@@ -66,12 +80,26 @@ fn collect_expr<'db, 'ink>(
         }
     }
 
+    // If this expression stores a struct in a field of a heap struct, notify the allocator
+    if let Expr::BinaryOp {
+        lhs,
+        op: Some(BinaryOp::Assignment { .. }),
+        ..
+    } = expr
+    {
+        if infer[*lhs].as_struct().is_some() && is_heap_field(db, *lhs, body, infer) {
+            collect_intrinsic(context, target, &intrinsics::write_barrier, intrinsics);
+            *needs_alloc = true;
+        }
+    }
+
     // Recurse further
     expr.walk_child_exprs(|expr_id| {
         collect_expr(
             context,
             target,
             db,
+            module_group,
             intrinsics,
             needs_alloc,
             expr_id,
@@ -81,11 +109,107 @@ fn collect_expr<'db, 'ink>(
     })
 }
 
+/// Returns true if the specified place expression refers to a field that is stored in a heap
+/// struct, either directly or through fields of value structs.
+pub(crate) fn is_heap_field(
+    db: &dyn HirDatabase,
+    expr_id: ExprId,
+    body: &Body,
+    infer: &InferenceResult,
+) -> bool {
+    if let Expr::Field { expr: receiver, .. } = &body[expr_id] {
+        let receiver_struct = infer[*receiver].as_struct().expect("expected a struct");
+        match receiver_struct.data(db.upcast()).memory_kind {
+            hir::StructMemoryKind::Gc => true,
+            hir::StructMemoryKind::Value => is_heap_field(db, *receiver, body, infer),
+        }
+    } else {
+        false
+    }
+}
+
+/// Returns true if calling the specified `function` might allocate memory, i.e. if it might
+/// exceed the maximum heap size. Functions that are linked at runtime, except for extern
+/// functions, can be hot reloaded, so they are assumed to allocate.
+pub(crate) fn may_allocate(
+    db: &dyn HirDatabase,
+    module_group: &ModuleGroup,
+    function: hir::Function,
+) -> bool {
+    fn_may_allocate(db, module_group, function, &mut FxHashSet::default())
+}
+
+/// Returns true if calling the specified `function` might allocate memory, without revisiting the
+/// functions in `visited`.
+fn fn_may_allocate(
+    db: &dyn HirDatabase,
+    module_group: &ModuleGroup,
+    function: hir::Function,
+    visited: &mut FxHashSet<hir::Function>,
+) -> bool {
+    if function.is_extern(db) {
+        false
+    } else if module_group.should_runtime_link_fn(db, function) {
+        true
+    } else if !visited.insert(function) {
+        // Recursive calls allocate if the rest of the function does
+        false
+    } else {
+        let body = function.body(db);
+        let infer = function.infer(db);
+        expr_may_allocate(db, module_group, body.body_expr(), &body, &infer, visited)
+    }
+}
+
+/// Returns true if evaluating the specified expression might allocate memory.
+fn expr_may_allocate(
+    db: &dyn HirDatabase,
+    module_group: &ModuleGroup,
+    expr_id: ExprId,
+    body: &Arc<Body>,
+    infer: &InferenceResult,
+    visited: &mut FxHashSet<hir::Function>,
+) -> bool {
+    let is_gc_struct = |expr_id: ExprId| {
+        infer[expr_id].as_struct().map_or(false, |s| {
+            s.data(db.upcast()).memory_kind == hir::StructMemoryKind::Gc
+        })
+    };
+
+    let expr = &body[expr_id];
+    let mut allocates = match expr {
+        Expr::Call { callee, .. } => match infer[*callee].as_callable_def() {
+            Some(hir::CallableDef::Struct(_)) => is_gc_struct(expr_id),
+            Some(hir::CallableDef::Function(function)) => {
+                fn_may_allocate(db, module_group, function, visited)
+            }
+            None => panic!("expected a callable expression"),
+        },
+        Expr::RecordLit { .. } => is_gc_struct(expr_id),
+        Expr::Path(path) => {
+            let resolver = hir::resolver_for_expr(db.upcast(), body.owner(), expr_id);
+            matches!(
+                resolver.resolve_path_as_value_fully(db.upcast(), path),
+                Some((ValueNs::StructId(_), _))
+            ) && is_gc_struct(expr_id)
+        }
+        _ => false,
+    };
+
+    // Recurse further
+    expr.walk_child_exprs(|expr_id| {
+        allocates = allocates || expr_may_allocate(db, module_group, expr_id, body, infer, visited);
+    });
+    allocates
+}
+
 /// Collects all intrinsics from the specified `body`.
+#[allow(clippy::too_many_arguments)]
 pub fn collect_fn_body<'db, 'ink>(
     context: &'ink Context,
     target: TargetData,
     db: &'db dyn HirDatabase,
+    module_group: &ModuleGroup,
     intrinsics: &mut IntrinsicsMap<'ink>,
     needs_alloc: &mut bool,
     body: &Arc<Body>,
@@ -95,6 +219,7 @@ pub fn collect_fn_body<'db, 'ink>(
         context,
         &target,
         db,
+        module_group,
         intrinsics,
         needs_alloc,
         body.body_expr(),
@@ -103,13 +228,24 @@ pub fn collect_fn_body<'db, 'ink>(
     );
 }
 
-/// Collects all intrinsics from a function wrapper body.
+/// Collects all intrinsics from the body of the wrapper of `function`.
 pub fn collect_wrapper_body<'ink>(
     context: &'ink Context,
     target: TargetData,
+    db: &dyn HirDatabase,
+    module_group: &ModuleGroup,
+    function: hir::Function,
     intrinsics: &mut IntrinsicsMap<'ink>,
     needs_alloc: &mut bool,
 ) {
+    if may_allocate(db, module_group, function) {
+        collect_intrinsic(
+            context,
+            &target,
+            &intrinsics::heap_limit_exceeded,
+            intrinsics,
+        );
+    }
     collect_intrinsic(context, &target, &intrinsics::new, intrinsics);
     // self.collect_intrinsic(entries, &intrinsics::drop, module);
     *needs_alloc = true;
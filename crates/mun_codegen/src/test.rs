@@ -899,6 +899,23 @@ fn nested_structs() {
     );
 }
 
+#[test]
+fn write_barrier() {
+    test_snapshot_unoptimized(
+        r#"
+    pub struct(gc) GcStruct(f32, f32);
+    pub struct(value) ValueStruct(f32, f32);
+
+    pub struct(gc) GcWrapper(GcStruct, ValueStruct);
+
+    pub fn assign_fields(wrapper: GcWrapper, a: GcStruct, b: ValueStruct) {
+        wrapper.0 = a;
+        wrapper.1 = b;
+    }
+    "#,
+    );
+}
+
 #[test]
 fn nested_private_fn() {
     test_snapshot(
@@ -3,7 +3,9 @@ mod access_unknown_field;
 mod duplicate_definition_error;
 mod expected_function;
 mod exported_private;
+mod fixes;
 mod mismatched_type;
+mod missing_else_branch;
 mod missing_fields;
 mod possibly_unitialized_variable;
 mod unresolved_type;
@@ -39,6 +41,8 @@ impl<DB: mun_hir::HirDatabase> DiagnosticForWith<DB> for dyn mun_hir::Diagnostic
             f(&missing_fields::MissingFields::new(with, v))
         } else if let Some(v) = self.downcast_ref::<mun_hir::diagnostics::ExportedPrivate>() {
             f(&exported_private::ExportedPrivate::new(with, v))
+        } else if let Some(v) = self.downcast_ref::<mun_hir::diagnostics::MissingElseBranch>() {
+            f(&missing_else_branch::MissingElseBranch::new(with, v))
         } else {
             f(&GenericHirDiagnostic { diagnostic: self })
         }
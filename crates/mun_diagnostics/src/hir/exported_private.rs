@@ -1,10 +1,11 @@
 use super::HirDiagnostic;
-use crate::{Diagnostic, SourceAnnotation};
-use mun_syntax::{AstNode, TextRange};
+use crate::{Diagnostic, Fix, SourceAnnotation, SourceEdit};
+use mun_hir::{HasSource, InFile, TyKind};
+use mun_syntax::{ast::VisibilityOwner, AstNode, SyntaxNode, TextRange};
 
 /// An error that is emitted when trying to leak a private type
 pub struct ExportedPrivate<'db, 'diag, DB: mun_hir::HirDatabase> {
-    _db: &'db DB,
+    db: &'db DB,
     diag: &'diag mun_hir::diagnostics::ExportedPrivate,
     value_name: String,
 }
@@ -24,6 +25,51 @@ impl<'db, 'diag, DB: mun_hir::HirDatabase> Diagnostic for ExportedPrivate<'db, '
             message: self.diag.message(),
         })
     }
+
+    fn fixes(&self) -> Vec<Fix> {
+        let (name, source) = match self.diag.ty.interned() {
+            TyKind::Struct(strukt) => {
+                let source = strukt.source(self.db.upcast());
+                (strukt.name(self.db), source.map(|it| visibility_edit(&it)))
+            }
+            TyKind::TypeAlias(alias) => {
+                let source = alias.source(self.db.upcast());
+                (alias.name(self.db), source.map(|it| visibility_edit(&it)))
+            }
+            _ => return Vec::new(),
+        };
+
+        let (range, replacement) = source.value;
+        vec![Fix {
+            label: format!("Make `{}` public", name),
+            edits: vec![SourceEdit {
+                range: InFile::new(source.file_id, range),
+                replacement,
+            }],
+        }]
+    }
+}
+
+/// Returns the edit that makes the `item` public: its visibility is replaced or, if it has none,
+/// a visibility is inserted in front of the item.
+fn visibility_edit(item: &impl VisibilityOwner) -> (TextRange, String) {
+    match item.visibility() {
+        Some(visibility) => (visibility.syntax().text_range(), "pub".to_owned()),
+        None => (
+            TextRange::empty(item_start(item.syntax())),
+            "pub ".to_owned(),
+        ),
+    }
+}
+
+/// Returns the offset of the first token of the `node` that is not trivia, e.g. a doc comment.
+fn item_start(node: &SyntaxNode) -> mun_syntax::TextSize {
+    node.children_with_tokens()
+        .find(|child| !child.kind().is_trivia())
+        .map_or_else(
+            || node.text_range().start(),
+            |child| child.text_range().start(),
+        )
 }
 
 impl<'db, 'diag, DB: mun_hir::HirDatabase> ExportedPrivate<'db, 'diag, DB> {
@@ -40,7 +86,7 @@ impl<'db, 'diag, DB: mun_hir::HirDatabase> ExportedPrivate<'db, 'diag, DB> {
             .to_string();
 
         ExportedPrivate {
-            _db: db,
+            db,
             diag,
             value_name,
         }
@@ -0,0 +1,118 @@
+//! Helpers that are shared between the fixes of several diagnostics.
+
+use crate::{Fix, SourceEdit};
+use mun_hir::{HasVisibility, InFile, ModuleDef, Ty, TyKind};
+use mun_syntax::{
+    ast::{self, ModuleItemOwner},
+    AstNode, TextRange,
+};
+
+/// Returns source code for a value of type `ty` that can stand in until the user writes the
+/// actual value, or `None` if the type has no literal representation.
+pub(super) fn placeholder_value(db: &dyn mun_hir::HirDatabase, ty: &Ty) -> Option<String> {
+    match ty.interned() {
+        TyKind::Int(_) => Some(String::from("0")),
+        TyKind::Float(_) => Some(String::from("0.0")),
+        TyKind::Bool => Some(String::from("false")),
+        TyKind::Tuple(0, _) => Some(String::from("()")),
+        TyKind::TypeAlias(alias) => placeholder_value(db, &alias.target_type(db)),
+        _ => None,
+    }
+}
+
+/// Returns a fix for every item named `name` that can be imported into the module of `file_id`
+/// and that matches the `filter`.
+pub(super) fn import_fixes(
+    db: &dyn mun_hir::HirDatabase,
+    file_id: mun_hir::FileId,
+    name: &str,
+    filter: impl Fn(ModuleDef) -> bool,
+) -> Vec<Fix> {
+    let module = match mun_hir::Module::from_file(db, file_id) {
+        Some(module) => module,
+        None => return Vec::new(),
+    };
+
+    let mut paths = mun_hir::Package::all(db)
+        .into_iter()
+        .flat_map(|package| package.modules(db))
+        .filter(|&candidate| candidate != module)
+        .flat_map(|candidate| {
+            candidate
+                .declarations(db)
+                .into_iter()
+                .filter(|&def| filter(def) && is_importable(db, def, name, module))
+                .map(move |_| {
+                    let module_path = candidate.full_name(db);
+                    if module_path.is_empty() {
+                        format!("package::{}", name)
+                    } else {
+                        format!("package::{}::{}", module_path, name)
+                    }
+                })
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths.dedup();
+
+    let insertion = use_insertion(db, file_id);
+    paths
+        .into_iter()
+        .map(|path| Fix {
+            label: format!("Import `{}`", path),
+            edits: vec![SourceEdit {
+                range: InFile::new(file_id, TextRange::empty(insertion.offset)),
+                replacement: format!("{}use {};{}", insertion.prefix, path, insertion.suffix),
+            }],
+        })
+        .collect()
+}
+
+/// Returns true if `def` is named `name` and is visible from the `module`.
+fn is_importable(
+    db: &dyn mun_hir::HirDatabase,
+    def: ModuleDef,
+    name: &str,
+    module: mun_hir::Module,
+) -> bool {
+    let (def_name, is_visible) = match def {
+        ModuleDef::Function(it) => (it.name(db), it.is_visible_from(db, module)),
+        ModuleDef::Struct(it) => (it.name(db), it.is_visible_from(db, module)),
+        ModuleDef::TypeAlias(it) => (it.name(db), it.is_visible_from(db, module)),
+        ModuleDef::Module(_) | ModuleDef::PrimitiveType(_) => return false,
+    };
+    is_visible && def_name.to_string() == name
+}
+
+/// Describes where a new `use` item is inserted into a file.
+struct UseInsertion {
+    offset: mun_syntax::TextSize,
+    prefix: &'static str,
+    suffix: &'static str,
+}
+
+/// Returns the location to insert a new `use` item: after the last existing `use` item or, if
+/// there is none, before the first item of the file.
+fn use_insertion(db: &dyn mun_hir::HirDatabase, file_id: mun_hir::FileId) -> UseInsertion {
+    let source_file = db.parse(file_id).tree();
+    let last_use = source_file
+        .items()
+        .filter(|item| ast::Use::can_cast(item.syntax().kind()))
+        .last();
+    match last_use {
+        Some(last_use) => UseInsertion {
+            offset: last_use.syntax().text_range().end(),
+            prefix: "\n",
+            suffix: "",
+        },
+        None => UseInsertion {
+            offset: source_file
+                .items()
+                .next()
+                .map(|item| item.syntax().text_range().start())
+                .unwrap_or_default(),
+            prefix: "",
+            suffix: "\n\n",
+        },
+    }
+}
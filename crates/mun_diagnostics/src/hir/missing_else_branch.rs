@@ -0,0 +1,59 @@
+use super::{fixes::placeholder_value, HirDiagnostic};
+use crate::{Diagnostic, Fix, SourceAnnotation, SourceEdit};
+use mun_hir::{HirDisplay, InFile};
+use mun_syntax::TextRange;
+
+/// An error that is emitted when an `if` expression that evaluates to a value other than `()` has
+/// no `else` branch.
+///
+/// ```mun
+/// fn foo(a: bool) -> i32 {
+///     if a { 5 } // `if` may be missing an `else` clause
+/// }
+/// ```
+pub struct MissingElseBranch<'db, 'diag, DB: mun_hir::HirDatabase> {
+    db: &'db DB,
+    diag: &'diag mun_hir::diagnostics::MissingElseBranch,
+}
+
+impl<'db, 'diag, DB: mun_hir::HirDatabase> Diagnostic for MissingElseBranch<'db, 'diag, DB> {
+    fn range(&self) -> TextRange {
+        self.diag.highlight_range()
+    }
+
+    fn title(&self) -> String {
+        "`if` may be missing an `else` clause".to_owned()
+    }
+
+    fn primary_annotation(&self) -> Option<SourceAnnotation> {
+        Some(SourceAnnotation {
+            range: self.diag.highlight_range(),
+            message: format!(
+                "expected `{}`, found `()`",
+                self.diag.found.display(self.db)
+            ),
+        })
+    }
+
+    fn fixes(&self) -> Vec<Fix> {
+        let value = match placeholder_value(self.db, &self.diag.found) {
+            Some(value) => value,
+            None => return Vec::new(),
+        };
+        let end = self.diag.if_expr.range().end();
+        vec![Fix {
+            label: "Add `else` branch".to_owned(),
+            edits: vec![SourceEdit {
+                range: InFile::new(self.diag.file, TextRange::empty(end)),
+                replacement: format!(" else {{ {} }}", value),
+            }],
+        }]
+    }
+}
+
+impl<'db, 'diag, DB: mun_hir::HirDatabase> MissingElseBranch<'db, 'diag, DB> {
+    /// Constructs a new instance of `MissingElseBranch`
+    pub fn new(db: &'db DB, diag: &'diag mun_hir::diagnostics::MissingElseBranch) -> Self {
+        MissingElseBranch { db, diag }
+    }
+}
@@ -1,7 +1,7 @@
-use super::HirDiagnostic;
-use crate::{Diagnostic, SourceAnnotation};
-use mun_hir::HirDisplay;
-use mun_syntax::{ast, AstNode, TextRange};
+use super::{fixes::placeholder_value, HirDiagnostic};
+use crate::{Diagnostic, Fix, SourceAnnotation, SourceEdit};
+use mun_hir::{HirDisplay, InFile};
+use mun_syntax::{ast, AstNode, SyntaxKind, SyntaxToken, TextRange, T};
 
 /// An error that is emitted when a field is missing from a struct initializer.
 ///
@@ -40,6 +40,100 @@ impl<'db, 'diag, DB: mun_hir::HirDatabase> Diagnostic for MissingFields<'db, 'di
             message: format!("missing {}", self.missing_fields.clone()),
         })
     }
+
+    fn fixes(&self) -> Vec<Fix> {
+        let parse = self.db.parse(self.diag.file);
+        let field_list = match ast::RecordLit::cast(self.diag.fields.to_node(&parse.syntax_node()))
+            .and_then(|record_lit| record_lit.record_field_list())
+        {
+            Some(field_list) if field_list.spread().is_none() => field_list,
+            _ => return Vec::new(),
+        };
+        let r_curly = match field_list.syntax().last_token() {
+            Some(token) if token.kind() == T!['}'] => token,
+            _ => return Vec::new(),
+        };
+        let last_token = match previous_non_trivia_token(&r_curly) {
+            Some(token) => token,
+            None => return Vec::new(),
+        };
+
+        // The fix is only offered if every missing field can be filled in with a value of the
+        // correct type
+        let strukt = match self.diag.struct_ty.as_struct() {
+            Some(strukt) => strukt,
+            None => return Vec::new(),
+        };
+        let new_fields = match self
+            .diag
+            .field_names
+            .iter()
+            .map(|name| {
+                let field = strukt.field(self.db, name)?;
+                let value = placeholder_value(self.db, &field.ty(self.db))?;
+                Some(format!("{}: {}", name, value))
+            })
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(new_fields) => new_fields.into_iter(),
+            None => return Vec::new(),
+        };
+
+        let last_field = field_list.fields().last();
+        let needs_comma = last_field.is_some() && last_token.kind() != T![,];
+        let comma = if needs_comma { "," } else { "" };
+
+        // Multi-line literals receive a field per line, single-line literals are kept on a single
+        // line.
+        let replacement = match indentation_before(&r_curly) {
+            Some(r_curly_indent) => {
+                let indent = last_field
+                    .and_then(|field| field.syntax().first_token())
+                    .and_then(|token| indentation_before(&token))
+                    .unwrap_or_else(|| format!("{}    ", r_curly_indent));
+                let new_fields = new_fields
+                    .map(|field| format!("\n{}{},", indent, field))
+                    .collect::<String>();
+                format!("{}{}", comma, new_fields)
+            }
+            None => {
+                let new_fields = new_fields.collect::<Vec<_>>().join(", ");
+                let separator = if needs_comma { ", " } else { " " };
+                let trailing_space = if last_token.next_token().as_ref() == Some(&r_curly) {
+                    " "
+                } else {
+                    ""
+                };
+                format!("{}{}{}", separator, new_fields, trailing_space)
+            }
+        };
+
+        vec![Fix {
+            label: "Add missing fields".to_owned(),
+            edits: vec![SourceEdit {
+                range: InFile::new(
+                    self.diag.file,
+                    TextRange::empty(last_token.text_range().end()),
+                ),
+                replacement,
+            }],
+        }]
+    }
+}
+
+/// Returns the closest token before `token` that is not trivia.
+fn previous_non_trivia_token(token: &SyntaxToken) -> Option<SyntaxToken> {
+    std::iter::successors(token.prev_token(), |token| token.prev_token())
+        .find(|token| !token.kind().is_trivia())
+}
+
+/// Returns the indentation of `token` if it is the first token on its line.
+fn indentation_before(token: &SyntaxToken) -> Option<String> {
+    let whitespace = token
+        .prev_token()
+        .filter(|token| token.kind() == SyntaxKind::WHITESPACE)?;
+    let (_, indent) = whitespace.text().rsplit_once('\n')?;
+    Some(indent.to_owned())
 }
 
 impl<'db, 'diag, DB: mun_hir::HirDatabase> MissingFields<'db, 'diag, DB> {
@@ -1,6 +1,7 @@
-use super::HirDiagnostic;
-use crate::{Diagnostic, SourceAnnotation};
-use mun_syntax::{AstNode, TextRange};
+use super::{fixes::import_fixes, HirDiagnostic};
+use crate::{Diagnostic, Fix, SourceAnnotation};
+use mun_hir::ModuleDef;
+use mun_syntax::{ast, AstNode, TextRange};
 
 /// An error that is emitted when trying to use a type that doesnt exist within the scope.
 ///
@@ -10,7 +11,7 @@ use mun_syntax::{AstNode, TextRange};
 /// #}
 /// ```
 pub struct UnresolvedType<'db, 'diag, DB: mun_hir::HirDatabase> {
-    _db: &'db DB,
+    db: &'db DB,
     diag: &'diag mun_hir::diagnostics::UnresolvedType,
     value_name: String,
 }
@@ -30,6 +31,24 @@ impl<'db, 'diag, DB: mun_hir::HirDatabase> Diagnostic for UnresolvedType<'db, 'd
             message: "not found in this scope".to_owned(),
         })
     }
+
+    fn fixes(&self) -> Vec<Fix> {
+        // Only a single identifier can be imported
+        let parse = self.db.parse(self.diag.file);
+        let is_identifier = match self.diag.type_ref.to_node(&parse.syntax_node()).kind() {
+            ast::TypeRefKind::PathType(path_type) => path_type
+                .path()
+                .map_or(false, |path| path.qualifier().is_none()),
+            _ => false,
+        };
+        if !is_identifier {
+            return Vec::new();
+        }
+
+        import_fixes(self.db, self.diag.file, &self.value_name, |def| {
+            matches!(def, ModuleDef::Struct(_) | ModuleDef::TypeAlias(_))
+        })
+    }
 }
 
 impl<'db, 'diag, DB: mun_hir::HirDatabase> UnresolvedType<'db, 'diag, DB> {
@@ -46,7 +65,7 @@ impl<'db, 'diag, DB: mun_hir::HirDatabase> UnresolvedType<'db, 'diag, DB> {
             .to_string();
 
         UnresolvedType {
-            _db: db,
+            db,
             diag,
             value_name,
         }
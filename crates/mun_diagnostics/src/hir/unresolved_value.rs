@@ -1,6 +1,7 @@
-use super::HirDiagnostic;
-use crate::{Diagnostic, SourceAnnotation};
-use mun_syntax::{AstNode, TextRange};
+use super::{fixes::import_fixes, HirDiagnostic};
+use crate::{Diagnostic, Fix, SourceAnnotation};
+use mun_hir::ModuleDef;
+use mun_syntax::{ast, AstNode, TextRange};
 
 /// An error that is emitted when trying to use a value that doesnt exist within the scope.
 ///
@@ -10,7 +11,7 @@ use mun_syntax::{AstNode, TextRange};
 /// #}
 /// ```
 pub struct UnresolvedValue<'db, 'diag, DB: mun_hir::HirDatabase> {
-    _db: &'db DB,
+    db: &'db DB,
     diag: &'diag mun_hir::diagnostics::UnresolvedValue,
     value_name: String,
 }
@@ -30,6 +31,25 @@ impl<'db, 'diag, DB: mun_hir::HirDatabase> Diagnostic for UnresolvedValue<'db, '
             message: "not found in this scope".to_owned(),
         })
     }
+
+    fn fixes(&self) -> Vec<Fix> {
+        // Only a single identifier can be imported
+        let parse = self.db.parse(self.diag.file);
+        let is_identifier = ast::PathExpr::cast(self.diag.expr.to_node(parse.tree().syntax()))
+            .and_then(|path_expr| path_expr.path())
+            .map_or(false, |path| path.qualifier().is_none());
+        if !is_identifier {
+            return Vec::new();
+        }
+
+        import_fixes(self.db, self.diag.file, &self.value_name, |def| match def {
+            ModuleDef::Function(_) => true,
+            ModuleDef::Struct(strukt) => {
+                strukt.data(self.db.upcast()).kind != mun_hir::StructKind::Record
+            }
+            _ => false,
+        })
+    }
 }
 
 impl<'db, 'diag, DB: mun_hir::HirDatabase> UnresolvedValue<'db, 'diag, DB> {
@@ -41,7 +61,7 @@ impl<'db, 'diag, DB: mun_hir::HirDatabase> UnresolvedValue<'db, 'diag, DB> {
         let value_name = diag.expr.to_node(parse.tree().syntax()).text().to_string();
 
         UnresolvedValue {
-            _db: db,
+            db,
             diag,
             value_name,
         }
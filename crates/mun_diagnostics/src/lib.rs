@@ -30,6 +30,26 @@ pub struct SecondaryAnnotation {
     pub message: String,
 }
 
+/// A suggested change to the source code that resolves a diagnostic.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Fix {
+    /// A short description of the change
+    pub label: String,
+
+    /// The edits that make up the change
+    pub edits: Vec<SourceEdit>,
+}
+
+/// Replaces a range of text in a source file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SourceEdit {
+    /// The location in the source that is replaced
+    pub range: InFile<TextRange>,
+
+    /// The text to replace the range with
+    pub replacement: String,
+}
+
 /// The base trait for all diagnostics in this crate.
 pub trait Diagnostic {
     /// Returns the primary message of the diagnostic.
@@ -50,6 +70,11 @@ pub trait Diagnostic {
     fn footer(&self) -> Vec<String> {
         Vec::new()
     }
+
+    /// Returns suggested changes to the source code that resolve this diagnostic.
+    fn fixes(&self) -> Vec<Fix> {
+        Vec::new()
+    }
 }
 
 /// When implemented enables requesting `Diagnostic`s for the implementer.
@@ -0,0 +1,80 @@
+//! Formats the source code of Mun files.
+//!
+//! The formatter operates on the lossless syntax tree of `mun_syntax`. It only rewrites the
+//! whitespace in between tokens: indentation, line breaks and spacing are normalized while all
+//! other tokens, including comments, are preserved as they are. Line breaks that the user wrote are
+//! kept, so the formatter never joins or splits long expressions.
+
+mod whitespace;
+
+#[cfg(test)]
+mod tests;
+
+pub use mun_project::FormatOptions;
+
+use mun_syntax::{AstNode, SourceFile, SyntaxError, SyntaxKind, TextRange, TextSize};
+use ra_ap_text_edit::TextEdit;
+use std::fmt;
+
+/// An error that occurs when formatting source code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    /// The source code contains syntax errors. Formatting erroneous code could change its meaning
+    /// so no attempt is made.
+    SyntaxErrors(Vec<SyntaxError>),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::SyntaxErrors(errors) => {
+                write!(f, "cannot format source code that contains syntax errors")?;
+                if let Some(error) = errors.first() {
+                    write!(f, ": {}", error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+/// Returns the formatted version of the source code `text`.
+pub fn format(text: &str, options: &FormatOptions) -> Result<String, FormatError> {
+    let edit = format_edit(text, options)?;
+    let mut text = text.to_owned();
+    edit.apply(&mut text);
+    Ok(text)
+}
+
+/// Returns the edit that formats the source code `text`. The edit only replaces whitespace.
+pub fn format_edit(text: &str, options: &FormatOptions) -> Result<TextEdit, FormatError> {
+    format_range(text, TextRange::up_to(TextSize::of(text)), options)
+}
+
+/// Returns the edit that formats the part of the source code `text` that intersects with `range`.
+/// The source code outside of the range is left untouched.
+pub fn format_range(
+    text: &str,
+    range: TextRange,
+    options: &FormatOptions,
+) -> Result<TextEdit, FormatError> {
+    let parse = SourceFile::parse(text);
+    let source_file = parse.tree();
+    let has_error_tokens = source_file
+        .syntax()
+        .descendants_with_tokens()
+        .any(|element| element.kind() == SyntaxKind::ERROR);
+    if !parse.errors().is_empty() || has_error_tokens {
+        return Err(FormatError::SyntaxErrors(parse.errors().to_vec()));
+    }
+
+    let mut builder = TextEdit::builder();
+    for (gap, whitespace) in whitespace::whitespace(&source_file, options) {
+        if gap.intersect(range).is_some() && text[gap] != whitespace {
+            builder.replace(gap, whitespace);
+        }
+    }
+    Ok(builder.finish())
+}
@@ -0,0 +1,269 @@
+use crate::{format, format_range, FormatOptions};
+use mun_syntax::{TextRange, TextSize};
+
+fn format_default(text: &str) -> String {
+    format(text, &FormatOptions::default()).unwrap()
+}
+
+#[test]
+fn items() {
+    insta::assert_snapshot!(format_default(
+        "  use package::{foo,bar::Baz}  ;pub  struct(gc)Foo{a:i32,pub b : f32,}
+struct  Bar ( i32 , bool ) ;
+type Int=i32;extern fn log ( message:i32 ) ;
+
+pub( package )fn main ( a:i32 , b:Foo )->i32{a}"
+    ), @r###"
+    use package::{foo, bar::Baz};
+    pub struct(gc) Foo { a: i32, pub b: f32, }
+    struct Bar(i32, bool);
+    type Int = i32;
+    extern fn log(message: i32);
+
+    pub(package) fn main(a: i32, b: Foo) -> i32 { a }
+    "###);
+}
+
+#[test]
+fn expressions() {
+    insta::assert_snapshot!(format_default(
+        r#"fn main(a: i32) -> bool {
+let b=-a*( a+5 ) ;
+    let c :Foo=Foo{a:b,b:1.0};
+        b+=c.a;
+  if b>5{return true}else if !c.b{ b = 3 }   else{}
+  loop{ break ; }
+while b<10 { b+=1 }
+    foo ( 1,2 ) .0 == 3
+}"#
+    ), @r###"
+    fn main(a: i32) -> bool {
+        let b = -a * (a + 5);
+        let c: Foo = Foo { a: b, b: 1.0 };
+        b += c.a;
+        if b > 5 { return true } else if !c.b { b = 3 } else {}
+        loop { break; }
+        while b < 10 { b += 1 }
+        foo(1, 2).0 == 3
+    }
+    "###);
+}
+
+#[test]
+fn single_line_blocks() {
+    insta::assert_snapshot!(format_default(
+        "fn foo() -> i32 {   5   }
+fn bar() {}
+fn baz() {
+}
+fn qux() { let a = 5; a }"
+    ), @r###"
+    fn foo() -> i32 { 5 }
+    fn bar() {}
+    fn baz() {}
+    fn qux() {
+        let a = 5;
+        a
+    }
+    "###);
+}
+
+#[test]
+fn comments() {
+    insta::assert_snapshot!(format_default(
+        "// A leading comment
+
+   /// Documentation
+fn main() {   // a trailing comment
+        let a = 5;
+    /* a block
+       comment */
+  // a comment before the end of the block
+}
+// A comment at the end of the file"
+    ), @r###"
+    // A leading comment
+
+    /// Documentation
+    fn main() { // a trailing comment
+        let a = 5;
+        /* a block
+           comment */
+        // a comment before the end of the block
+    }
+    // A comment at the end of the file
+    "###);
+}
+
+#[test]
+fn multiline_lists() {
+    insta::assert_snapshot!(format_default(
+        "struct Foo {
+a: i32,
+
+    b: f32,
+}
+
+fn main(
+a: i32,
+        b: i32,
+) {
+    let foo = Foo {
+a: add(
+        a,
+    b),
+            b: 1.0,
+    };
+    let c = foo.a
+    + foo
+    .a;
+}"
+    ), @r###"
+    struct Foo {
+        a: i32,
+
+        b: f32,
+    }
+
+    fn main(
+        a: i32,
+        b: i32,
+    ) {
+        let foo = Foo {
+            a: add(
+                a,
+                b),
+            b: 1.0,
+        };
+        let c = foo.a
+            + foo
+            .a;
+    }
+    "###);
+}
+
+#[test]
+fn blank_lines() {
+    insta::assert_snapshot!(format_default(
+        "
+
+
+fn foo() {
+
+    let a = 5;
+
+
+
+    let b = 6;
+
+}
+
+
+
+fn bar() {}
+
+
+"
+    ), @r###"
+    fn foo() {
+        let a = 5;
+
+        let b = 6;
+    }
+
+    fn bar() {}
+    "###);
+}
+
+#[test]
+fn options() {
+    let options = FormatOptions {
+        indent_width: 2,
+        hard_tabs: false,
+        max_blank_lines: 2,
+    };
+    insta::assert_snapshot!(format(
+        "fn foo() {
+let a = 5;
+
+
+
+if a > 3 {
+a = 3;
+}
+}",
+        &options
+    ).unwrap(), @r###"
+    fn foo() {
+      let a = 5;
+
+
+      if a > 3 {
+        a = 3;
+      }
+    }
+    "###);
+
+    let options = FormatOptions {
+        hard_tabs: true,
+        ..FormatOptions::default()
+    };
+    insta::assert_snapshot!(format(
+        "fn foo() {
+let a = 5;
+if a > 3 {
+a = 3;
+}
+}",
+        &options
+    ).unwrap().replace('\t', "<tab>"), @r###"
+    fn foo() {
+    <tab>let a = 5;
+    <tab>if a > 3 {
+    <tab><tab>a = 3;
+    <tab>}
+    }
+    "###);
+}
+
+#[test]
+fn idempotent() {
+    let text = "// Comment
+pub struct Foo { a: i32 }
+
+fn main(a: i32) -> i32 {
+    let foo = Foo { a };
+    if foo.a > 5 {
+        foo.a
+    } else {
+        -foo.a
+            + 5
+    }
+}
+";
+    assert_eq!(format_default(text), text);
+}
+
+#[test]
+fn syntax_errors() {
+    let err = format("fn main( {", &FormatOptions::default()).unwrap_err();
+    insta::assert_snapshot!(err.to_string(), @"cannot format source code that contains syntax errors: expected value parameter");
+}
+
+#[test]
+fn range() {
+    let text = "fn foo(){let a=5;}\nfn bar(){let b=6;}\n";
+    let bar_start = TextSize::from(text.find("fn bar").unwrap() as u32);
+    let edit = format_range(
+        text,
+        TextRange::new(bar_start, TextSize::of(text)),
+        &FormatOptions::default(),
+    )
+    .unwrap();
+    let mut result = text.to_owned();
+    edit.apply(&mut result);
+    insta::assert_snapshot!(result, @r###"
+    fn foo(){let a=5;}
+    fn bar() { let b = 6; }
+    "###);
+}
@@ -0,0 +1,309 @@
+//! Computes the whitespace that should separate the tokens of a source file.
+
+use crate::FormatOptions;
+use mun_syntax::{ast, AstNode, SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken, TextRange};
+
+/// Returns the whitespace that should separate each pair of consecutive tokens of the
+/// `source_file`, together with the range of the whitespace that currently separates them. This
+/// includes the whitespace at the start and at the end of the file.
+pub(crate) fn whitespace(
+    source_file: &ast::SourceFile,
+    options: &FormatOptions,
+) -> Vec<(TextRange, String)> {
+    let tokens = source_file
+        .syntax()
+        .descendants_with_tokens()
+        .filter_map(SyntaxElement::into_token)
+        .filter(|token| token.kind() != SyntaxKind::WHITESPACE);
+
+    let mut result = Vec::new();
+    let mut prev: Option<SyntaxToken> = None;
+    for token in tokens {
+        match &prev {
+            // Whitespace at the start of the file is removed
+            None => result.push((TextRange::up_to(token.text_range().start()), String::new())),
+            Some(prev) => result.push((
+                TextRange::new(prev.text_range().end(), token.text_range().start()),
+                whitespace_between(prev, &token, options),
+            )),
+        }
+        prev = Some(token);
+    }
+
+    // Every non-empty file ends with a single line break
+    let end = source_file.syntax().text_range().end();
+    match prev {
+        Some(last) => result.push((
+            TextRange::new(last.text_range().end(), end),
+            String::from("\n"),
+        )),
+        None => result.push((TextRange::up_to(end), String::new())),
+    }
+
+    result
+}
+
+/// Returns the whitespace that should separate the tokens `prev` and `next`.
+fn whitespace_between(prev: &SyntaxToken, next: &SyntaxToken, options: &FormatOptions) -> String {
+    let original = original_whitespace(prev, next);
+    let original_line_breaks = original.matches('\n').count();
+
+    let line_breaks = if is_line_comment(prev) {
+        original_line_breaks.max(1)
+    } else if must_be_on_same_line(prev, next) {
+        0
+    } else if must_start_line(prev, next) {
+        original_line_breaks.max(1)
+    } else {
+        original_line_breaks
+    };
+
+    if line_breaks == 0 {
+        return if is_spaced(prev, next) {
+            String::from(" ")
+        } else {
+            String::new()
+        };
+    }
+
+    // Empty lines directly after an opening or before a closing delimiter are removed
+    let max_line_breaks = if is_opening_delimiter(prev) || is_closing_delimiter(next) {
+        1
+    } else {
+        options.max_blank_lines + 1
+    };
+
+    let mut result = "\n".repeat(line_breaks.min(max_line_breaks));
+    let indent_level = indent_level(next);
+    if options.hard_tabs {
+        result.push_str(&"\t".repeat(indent_level));
+    } else {
+        result.push_str(&" ".repeat(indent_level * options.indent_width));
+    }
+    result
+}
+
+/// Returns the whitespace that currently separates `prev` and `next`.
+fn original_whitespace(prev: &SyntaxToken, next: &SyntaxToken) -> String {
+    let mut result = String::new();
+    let mut token = prev.next_token();
+    while let Some(current) = token {
+        if &current == next {
+            break;
+        }
+        result.push_str(current.text());
+        token = current.next_token();
+    }
+    result
+}
+
+/// Returns true if `next` has to be placed on the same line as `prev`.
+fn must_be_on_same_line(prev: &SyntaxToken, next: &SyntaxToken) -> bool {
+    match next.kind() {
+        // `} else {`
+        SyntaxKind::ELSE_KW => prev.kind() == SyntaxKind::R_CURLY,
+        SyntaxKind::COMMA | SyntaxKind::SEMI => true,
+        // Braces and parentheses are placed on the line of the item or expression that they belong
+        // to, e.g. `fn foo() {` instead of `fn foo()\n{`
+        SyntaxKind::L_CURLY => true,
+        SyntaxKind::L_PAREN => !next
+            .parent()
+            .map_or(false, |parent| parent.kind() == SyntaxKind::PAREN_EXPR),
+        // Empty delimiters, e.g. `{}` or `()`
+        SyntaxKind::R_CURLY | SyntaxKind::R_PAREN => is_opening_delimiter(prev),
+        _ => false,
+    }
+}
+
+/// Returns true if `next` has to be placed at the start of a line because it starts an item or a
+/// statement, or because it closes a block that spans multiple lines.
+fn must_start_line(prev: &SyntaxToken, next: &SyntaxToken) -> bool {
+    // Comments that trail code are left on their line
+    if next.kind() == SyntaxKind::COMMENT {
+        return false;
+    }
+
+    // The closing brace of a block that spans multiple lines is placed on its own line
+    if next.kind() == SyntaxKind::R_CURLY {
+        return next.parent().map_or(false, |parent| {
+            parent.kind() == SyntaxKind::BLOCK_EXPR
+                && is_multiline_block(&parent)
+                && !is_opening_delimiter(prev)
+        });
+    }
+
+    let (unit, container) = enclosing_unit(next);
+    match container.kind() {
+        SyntaxKind::SOURCE_FILE => first_token(&unit).as_ref() == Some(next),
+        SyntaxKind::BLOCK_EXPR if is_multiline_block(&container) => {
+            first_token(&unit).as_ref() == Some(next)
+        }
+        _ => false,
+    }
+}
+
+/// Returns true if the tokens `prev` and `next`, which are on the same line, are separated by a
+/// space.
+fn is_spaced(prev: &SyntaxToken, next: &SyntaxToken) -> bool {
+    let prev_parent = prev.parent().map(|parent| parent.kind());
+    let next_parent = next.parent().map(|parent| parent.kind());
+
+    if prev.kind() == SyntaxKind::COMMENT || next.kind() == SyntaxKind::COMMENT {
+        return true;
+    }
+
+    match (prev.kind(), next.kind()) {
+        // `a, b;`
+        (_, SyntaxKind::COMMA) | (_, SyntaxKind::SEMI) => false,
+        // `a: i32`
+        (_, SyntaxKind::COLON) => false,
+        // `a.b`, `a.0`, `package::a` and `0..5`
+        (SyntaxKind::DOT, _)
+        | (_, SyntaxKind::DOT)
+        | (_, SyntaxKind::INDEX)
+        | (SyntaxKind::COLONCOLON, _)
+        | (_, SyntaxKind::COLONCOLON)
+        | (SyntaxKind::DOTDOT, _)
+        | (_, SyntaxKind::DOTDOT)
+        | (SyntaxKind::DOTDOTEQ, _)
+        | (_, SyntaxKind::DOTDOTEQ) => false,
+        // `(a)` and `[a]`
+        (SyntaxKind::L_PAREN, _)
+        | (SyntaxKind::L_BRACKET, _)
+        | (_, SyntaxKind::R_PAREN)
+        | (_, SyntaxKind::R_BRACKET) => false,
+        // `{}` and `use a::{b, c}`
+        (SyntaxKind::L_CURLY, SyntaxKind::R_CURLY) => false,
+        (SyntaxKind::L_CURLY, _) => prev_parent != Some(SyntaxKind::USE_TREE_LIST),
+        (_, SyntaxKind::R_CURLY) => next_parent != Some(SyntaxKind::USE_TREE_LIST),
+        // `foo(a)`, `fn foo(a: i32)`, `struct(gc) Foo(i32)` and `pub(package)`
+        (_, SyntaxKind::L_PAREN) => !matches!(
+            next_parent,
+            Some(SyntaxKind::ARG_LIST)
+                | Some(SyntaxKind::PARAM_LIST)
+                | Some(SyntaxKind::TUPLE_FIELD_DEF_LIST)
+                | Some(SyntaxKind::MEMORY_TYPE_SPECIFIER)
+                | Some(SyntaxKind::VISIBILITY)
+        ),
+        // `-a` and `!a`
+        _ if prev_parent == Some(SyntaxKind::PREFIX_EXPR) => false,
+        _ => true,
+    }
+}
+
+/// Returns the number of indentation levels of a line that starts with `token`.
+fn indent_level(token: &SyntaxToken) -> usize {
+    let level = token
+        .parent()
+        .into_iter()
+        .flat_map(|parent| parent.ancestors())
+        .filter(|node| is_indenting(node) && is_strictly_inside(token, node))
+        .count();
+
+    // Lines that continue an expression or statement are indented once more, e.g.
+    //
+    // ```mun
+    // let a = foo
+    //     + bar;
+    // ```
+    let (unit, _) = enclosing_unit(token);
+    let is_continuation = !is_closing_delimiter(token)
+        && token.kind() != SyntaxKind::ELSE_KW
+        && first_non_trivia_token(&unit).as_ref() != Some(token)
+        && first_token(&unit).as_ref() != Some(token);
+
+    level + usize::from(is_continuation)
+}
+
+/// Returns the element that contains `token` and that is a direct child of the innermost
+/// indenting node or of the source file, together with that node. For instance, this is the
+/// statement of a block or the argument of an argument list.
+fn enclosing_unit(token: &SyntaxToken) -> (SyntaxElement, SyntaxNode) {
+    let mut element = SyntaxElement::Token(token.clone());
+    loop {
+        let parent = match element.parent() {
+            Some(parent) => parent,
+            None => unreachable!("every token is part of the source file"),
+        };
+        if parent.kind() == SyntaxKind::SOURCE_FILE
+            || (is_indenting(&parent) && is_strictly_inside(token, &parent))
+        {
+            return (element, parent);
+        }
+        element = SyntaxElement::Node(parent);
+    }
+}
+
+/// Returns true if the contents of the `node` are indented when they span multiple lines.
+fn is_indenting(node: &SyntaxNode) -> bool {
+    matches!(
+        node.kind(),
+        SyntaxKind::BLOCK_EXPR
+            | SyntaxKind::RECORD_FIELD_DEF_LIST
+            | SyntaxKind::TUPLE_FIELD_DEF_LIST
+            | SyntaxKind::PARAM_LIST
+            | SyntaxKind::ARG_LIST
+            | SyntaxKind::RECORD_FIELD_LIST
+            | SyntaxKind::USE_TREE_LIST
+            | SyntaxKind::PAREN_EXPR
+    )
+}
+
+/// Returns true if `token` is part of the `node` but is not its opening or closing delimiter.
+fn is_strictly_inside(token: &SyntaxToken, node: &SyntaxNode) -> bool {
+    node.first_token().as_ref() != Some(token) && node.last_token().as_ref() != Some(token)
+}
+
+/// Returns true if the statements of a block are placed on separate lines. This is the case if the
+/// block has more than one statement or if the block already spans multiple lines.
+fn is_multiline_block(block: &SyntaxNode) -> bool {
+    let statements = block
+        .children_with_tokens()
+        .filter(|element| {
+            !element.kind().is_trivia()
+                && element.kind() != SyntaxKind::L_CURLY
+                && element.kind() != SyntaxKind::R_CURLY
+        })
+        .count();
+    statements > 1 || (statements == 1 && block.text().contains_char('\n'))
+}
+
+/// Returns the first token of the `element`.
+fn first_token(element: &SyntaxElement) -> Option<SyntaxToken> {
+    match element {
+        SyntaxElement::Node(node) => node.first_token(),
+        SyntaxElement::Token(token) => Some(token.clone()),
+    }
+}
+
+/// Returns the first token of the `element` that is not whitespace or a comment.
+fn first_non_trivia_token(element: &SyntaxElement) -> Option<SyntaxToken> {
+    match element {
+        SyntaxElement::Node(node) => node
+            .descendants_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .find(|token| !token.kind().is_trivia()),
+        SyntaxElement::Token(token) => Some(token.clone()),
+    }
+}
+
+/// Returns true if the `token` is a comment that ends at the end of the line.
+fn is_line_comment(token: &SyntaxToken) -> bool {
+    token.kind() == SyntaxKind::COMMENT && token.text().starts_with("//")
+}
+
+/// Returns true if the `token` opens a delimited list, e.g. a block or an argument list.
+fn is_opening_delimiter(token: &SyntaxToken) -> bool {
+    matches!(
+        token.kind(),
+        SyntaxKind::L_CURLY | SyntaxKind::L_PAREN | SyntaxKind::L_BRACKET
+    )
+}
+
+/// Returns true if the `token` closes a delimited list, e.g. a block or an argument list.
+fn is_closing_delimiter(token: &SyntaxToken) -> bool {
+    matches!(
+        token.kind(),
+        SyntaxKind::R_CURLY | SyntaxKind::R_PAREN | SyntaxKind::R_BRACKET
+    )
+}
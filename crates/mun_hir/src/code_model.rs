@@ -13,7 +13,7 @@ pub use self::{
     module::{Module, ModuleDef},
     package::Package,
     r#struct::{Field, LocalFieldId, Struct, StructKind, StructMemoryKind},
-    src::HasSource,
+    src::{FieldSource, HasSource},
     type_alias::TypeAlias,
 };
 
@@ -65,6 +65,14 @@ impl Module {
             .collect()
     }
 
+    /// Returns true if an item with the specified name is declared in or imported into this module.
+    pub fn has_item_named(self, db: &dyn HirDatabase, name: &str) -> bool {
+        let package_defs = db.package_defs(self.id.package);
+        !package_defs.modules[self.id.local_id]
+            .get(&Name::new(name))
+            .is_none()
+    }
+
     /// Iterate over all diagnostics from this `Module` by placing them in the `sink`
     pub fn diagnostics(self, db: &dyn HirDatabase, sink: &mut DiagnosticSink) {
         // Add diagnostics from the package definitions
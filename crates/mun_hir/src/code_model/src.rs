@@ -60,16 +60,24 @@ impl HasSource for Struct {
     }
 }
 
+/// The source of a [`Field`], which is either a named field of a record struct or a positional
+/// field of a tuple struct.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FieldSource {
+    Named(ast::RecordFieldDef),
+    Pos(ast::TupleFieldDef),
+}
+
 impl HasSource for Field {
-    type Ast = ast::RecordFieldDef;
+    type Ast = FieldSource;
 
     fn source(&self, db: &dyn DefDatabase) -> InFile<Self::Ast> {
         let src = self.parent.source(db);
         let file_id = src.file_id;
-        let field_sources = if let ast::StructKind::Record(r) = src.value.kind() {
-            r.fields().collect()
-        } else {
-            Vec::new()
+        let field_sources: Vec<FieldSource> = match src.value.kind() {
+            ast::StructKind::Record(r) => r.fields().map(FieldSource::Named).collect(),
+            ast::StructKind::Tuple(t) => t.fields().map(FieldSource::Pos).collect(),
+            ast::StructKind::Unit => Vec::new(),
         };
 
         let ast = field_sources
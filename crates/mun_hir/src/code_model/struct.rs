@@ -58,6 +58,11 @@ impl Field {
         self.id.into_raw().into()
     }
 
+    /// Returns the struct that contains this field
+    pub fn parent_struct(self) -> Struct {
+        self.parent
+    }
+
     /// Returns the ID of the field with relation to the parent struct
     pub(crate) fn id(self) -> LocalFieldId {
         self.id
@@ -253,3 +258,11 @@ impl HasVisibility for Struct {
             .resolve(db.upcast(), &self.id.resolver(db.upcast()))
     }
 }
+
+impl HasVisibility for Field {
+    fn visibility(&self, db: &dyn HirDatabase) -> Visibility {
+        self.parent.data(db.upcast()).fields[self.id]
+            .visibility
+            .resolve(db.upcast(), &self.parent.id.resolver(db.upcast()))
+    }
+}
@@ -52,13 +52,14 @@ impl<'a> StructValidator<'a> {
 
         field_types
             .filter(|(ty, _)| !type_is_allowed(ty))
-            .for_each(|(_, type_ref)| {
+            .for_each(|(ty, type_ref)| {
                 sink.push(ExportedPrivate {
                     file: self.file_id,
                     type_ref: struct_data
                         .type_ref_source_map()
                         .type_ref_syntax(type_ref)
                         .unwrap(),
+                    ty,
                 })
             });
     }
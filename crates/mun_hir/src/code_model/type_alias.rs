@@ -5,7 +5,7 @@ use crate::{
     ids::TypeAliasId,
     type_ref::{LocalTypeRefId, TypeRefBuilder, TypeRefMap, TypeRefSourceMap},
     visibility::RawVisibility,
-    DefDatabase, DiagnosticSink, FileId, HasVisibility, HirDatabase, Name, Visibility,
+    DefDatabase, DiagnosticSink, FileId, HasVisibility, HirDatabase, Name, Ty, Visibility,
 };
 
 use super::Module;
@@ -51,6 +51,11 @@ impl TypeAlias {
         db.lower_type_alias(self)
     }
 
+    /// Returns the type that this alias refers to
+    pub fn target_type(self, db: &dyn HirDatabase) -> Ty {
+        self.lower(db)[self.type_ref(db)].clone()
+    }
+
     pub fn diagnostics(self, db: &dyn HirDatabase, sink: &mut DiagnosticSink) {
         let data = self.data(db.upcast());
         let lower = self.lower(db);
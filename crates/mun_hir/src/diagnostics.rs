@@ -181,6 +181,7 @@ impl Diagnostic for ExpectedFunction {
 pub struct ExportedPrivate {
     pub file: FileId,
     pub type_ref: AstPtr<ast::TypeRef>,
+    pub ty: Ty,
 }
 
 impl Diagnostic for ExportedPrivate {
@@ -70,13 +70,14 @@ impl<'a> ExprValidator<'a> {
         let file_id = self.func.source(self.db.upcast()).file_id;
         param_types
             .filter(|(ty, _)| !type_is_allowed(ty))
-            .for_each(|(_, type_ref)| {
+            .for_each(|(ty, type_ref)| {
                 sink.push(ExportedPrivate {
                     file: file_id,
                     type_ref: fn_data
                         .type_ref_source_map()
                         .type_ref_syntax(*type_ref)
                         .unwrap(),
+                    ty,
                 })
             });
     }
@@ -22,6 +22,7 @@ pub use crate::{
     ids::{ItemLoc, ModuleId},
     in_file::InFile,
     input::{FileId, SourceRoot, SourceRootId},
+    item_tree::{ItemTree, ModItem},
     name::Name,
     name_resolution::PerNs,
     package_set::{PackageId, PackageSet},
@@ -37,8 +38,8 @@ pub use crate::{
 use crate::{name::AsName, source_id::AstIdMap};
 
 pub use self::code_model::{
-    Field, Function, FunctionData, HasSource, Module, ModuleDef, Package, Struct, StructMemoryKind,
-    TypeAlias,
+    Field, FieldSource, Function, FunctionData, HasSource, Module, ModuleDef, Package, Struct,
+    StructKind, StructMemoryKind, TypeAlias,
 };
 
 #[macro_use]
@@ -1,6 +1,6 @@
 use crate::{AsName, InFile, Name};
-use mun_syntax::ast;
-use mun_syntax::ast::{NameOwner, PathSegmentKind};
+use mun_syntax::ast::{self, NameOwner, PathSegmentKind};
+use mun_syntax::AstNode;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Path {
@@ -60,6 +60,44 @@ impl Path {
         Some(Path { kind, segments })
     }
 
+    /// Converts an `ast::Path` that is part of a `use` item to a `Path`. Unlike
+    /// [`Path::from_ast`], this includes the prefixes of the use trees that contain the path. For
+    /// the use statement:
+    /// ```mun
+    /// use foo::{self, Bar};
+    /// ```
+    /// the paths `self` and `Bar` are converted to `foo` and `foo::Bar` respectively.
+    pub fn from_use_path(path: &ast::Path) -> Option<Path> {
+        let use_tree = path.syntax().ancestors().find_map(ast::UseTree::cast)?;
+
+        // Collect the prefixes of all use trees that contain the path, from the outermost tree
+        // inwards
+        let mut parent_trees = use_tree
+            .syntax()
+            .ancestors()
+            .skip(1)
+            .filter_map(ast::UseTree::cast)
+            .collect::<Vec<_>>();
+        parent_trees.reverse();
+        let mut prefix = None;
+        for tree in parent_trees {
+            if let Some(tree_path) = tree.path() {
+                prefix = Some(convert_path(prefix, &tree_path)?);
+            }
+        }
+
+        // Handle `self` in a use tree list
+        if path.qualifier().is_none() {
+            if let Some(ast::PathSegmentKind::SelfKw) = path.segment().and_then(|s| s.kind()) {
+                if prefix.is_some() {
+                    return prefix;
+                }
+            }
+        }
+
+        convert_path(prefix, path)
+    }
+
     /// Converts an `ast::NameRef` into a single-identifier `Path`.
     pub fn from_name_ref(name_ref: &ast::NameRef) -> Path {
         name_ref.as_name().into()
@@ -100,27 +100,27 @@ impl Resolver {
 }
 
 impl Resolver {
-    // TODO: This function is useful when we need to resolve paths between modules
-    // /// Resolves a path
-    // fn resolve_module_path(
-    //     &self,
-    //     db: &dyn DefDatabase,
-    //     path: &Path,
-    // ) -> PerNs<(ItemDefinitionId, Visibility)> {
-    //     let (defs, module) = match self.module_scope() {
-    //         None => return PerNs::none(),
-    //         Some(it) => it,
-    //     };
-    //
-    //     let (module_res, segment_index) = defs.resolve_path_in_module(db, module, &path);
-    //
-    //     // If the `segment_index` contains a value it means the path didn't resolve completely yet
-    //     if segment_index.is_some() {
-    //         return PerNs::none();
-    //     }
-    //
-    //     module_res
-    // }
+    /// Resolves the specified `path` as an item of a module. Returns `PerNs::none` if the path
+    /// could not be resolved completely.
+    pub(crate) fn resolve_module_path(
+        &self,
+        db: &dyn DefDatabase,
+        path: &Path,
+    ) -> PerNs<(ItemDefinitionId, Visibility)> {
+        let (defs, module) = match self.module_scope() {
+            None => return PerNs::none(),
+            Some(it) => it,
+        };
+
+        let (module_res, segment_index) = defs.resolve_path_in_module(db, module, path);
+
+        // If the `segment_index` contains a value it means the path didn't resolve completely yet
+        if segment_index.is_some() {
+            return PerNs::none();
+        }
+
+        module_res
+    }
 
     /// Returns the `Module` scope of the resolver
     fn module_scope(&self) -> Option<(&PackageDefs, LocalModuleId)> {
@@ -12,13 +12,18 @@ mod source_to_def;
 
 use crate::{
     ids::{DefWithBodyId, ItemDefinitionId},
+    name::AsName,
     resolve,
     resolve::HasResolver,
     semantics::source_to_def::{SourceToDefCache, SourceToDefContainer, SourceToDefContext},
     source_analyzer::SourceAnalyzer,
-    FileId, HirDatabase, InFile, ModuleDef, Name, PatId, PerNs, Resolver, Ty, Visibility,
+    Field, FileId, Function, HirDatabase, InFile, ModuleDef, Name, Pat, PatId, PerNs, Resolver,
+    Struct, Ty, TypeAlias, Visibility,
+};
+use mun_syntax::{
+    ast::{self, NameOwner},
+    AstNode, SyntaxNode, TextSize,
 };
-use mun_syntax::{ast, AstNode, SyntaxNode, TextSize};
 use rustc_hash::FxHashMap;
 use smallvec::SmallVec;
 use std::cell::RefCell;
@@ -68,6 +73,61 @@ impl<'db> Semantics<'db> {
         self.analyze(expr.syntax()).type_of_expr(self.db, expr)
     }
 
+    /// Returns the type of the given pattern
+    pub fn type_of_pat(&self, pat: &ast::Pat) -> Option<Ty> {
+        self.analyze(pat.syntax()).type_of_pat(self.db, pat)
+    }
+
+    /// Returns the definition that the given path refers to.
+    pub fn resolve_path(&self, path: &ast::Path) -> Option<PathResolution> {
+        self.analyze(path.syntax()).resolve_path(self.db, path)
+    }
+
+    /// Returns the field that is accessed by the given field expression.
+    pub fn resolve_field(&self, field: &ast::FieldExpr) -> Option<Field> {
+        self.analyze(field.syntax()).resolve_field(self.db, field)
+    }
+
+    /// Returns the field that is initialized by the given field of a record literal. If the field
+    /// uses the shorthand syntax (e.g. `Foo { a }`), also returns the local that initializes it.
+    pub fn resolve_record_field(&self, field: &ast::RecordField) -> Option<(Field, Option<Local>)> {
+        self.analyze(field.syntax())
+            .resolve_record_field(self.db, field)
+    }
+
+    /// Returns the function that is defined by the given function definition.
+    pub fn function_to_def(&self, src: &ast::FunctionDef) -> Option<Function> {
+        let src = self.find_file(src.syntax().clone()).with_value(src.clone());
+        self.with_source_to_def_context(|ctx| ctx.fn_to_def(src))
+            .map(Into::into)
+    }
+
+    /// Returns the struct that is defined by the given struct definition.
+    pub fn struct_to_def(&self, src: &ast::StructDef) -> Option<Struct> {
+        let src = self.find_file(src.syntax().clone()).with_value(src.clone());
+        self.with_source_to_def_context(|ctx| ctx.struct_to_def(src))
+            .map(Into::into)
+    }
+
+    /// Returns the type alias that is defined by the given type alias definition.
+    pub fn type_alias_to_def(&self, src: &ast::TypeAliasDef) -> Option<TypeAlias> {
+        let src = self.find_file(src.syntax().clone()).with_value(src.clone());
+        self.with_source_to_def_context(|ctx| ctx.type_alias_to_def(src))
+            .map(Into::into)
+    }
+
+    /// Returns the field that is defined by the given field definition of a record struct.
+    pub fn record_field_to_def(&self, src: &ast::RecordFieldDef) -> Option<Field> {
+        let strukt = src.syntax().ancestors().find_map(ast::StructDef::cast)?;
+        let name = src.name()?.as_name();
+        self.struct_to_def(&strukt)?.field(self.db, &name)
+    }
+
+    /// Returns the local that is bound by the given pattern.
+    pub fn bind_pat_to_def(&self, src: &ast::BindPat) -> Option<Local> {
+        self.analyze(src.syntax()).resolve_bind_pat(src)
+    }
+
     /// Returns the source analyzer for the given node.
     fn analyze(&self, node: &SyntaxNode) -> SourceAnalyzer {
         self.build_analyzer(node, None)
@@ -185,6 +245,15 @@ impl ScopeDef {
     }
 }
 
+/// The definition that a path refers to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PathResolution {
+    /// An item, e.g. a function, a struct, or a module
+    Def(ModuleDef),
+    /// A local variable
+    Local(Local),
+}
+
 /// A local variable in a body
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Local {
@@ -198,6 +267,31 @@ impl Local {
         let infer = db.infer(self.parent);
         infer[self.pat_id].clone()
     }
+
+    /// Returns the name of this local, or `None` if the local is not bound to a name
+    pub fn name(self, db: &dyn HirDatabase) -> Option<Name> {
+        match &db.body(self.parent)[self.pat_id] {
+            Pat::Bind { name } => Some(name.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns the function that contains this local
+    pub fn parent(self) -> Function {
+        match self.parent {
+            DefWithBodyId::FunctionId(id) => id.into(),
+        }
+    }
+
+    /// Returns the pattern that binds this local
+    pub fn source(self, db: &dyn HirDatabase) -> InFile<ast::Pat> {
+        let (_, source_map) = db.body_with_source_map(self.parent);
+        let src = source_map
+            .pat_syntax(self.pat_id)
+            .expect("a local must have a source");
+        let root = src.file_syntax(db.upcast());
+        src.map(|ptr| ptr.to_node(&root))
+    }
 }
 
 impl<'a> SemanticsScope<'a> {
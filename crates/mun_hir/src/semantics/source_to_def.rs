@@ -44,14 +44,41 @@ impl SourceToDefContext<'_, '_> {
     }
 
     /// Find the `FunctionId` associated with the specified syntax tree node.
-    fn fn_to_def(&mut self, src: InFile<ast::FunctionDef>) -> Option<FunctionId> {
-        let container = self.find_container(src.as_ref().map(|it| it.syntax()))?;
+    pub(super) fn fn_to_def(&mut self, src: InFile<ast::FunctionDef>) -> Option<FunctionId> {
+        self.def_map_for(src.as_ref().map(|it| it.syntax()))?
+            .functions
+            .get(&src)
+            .copied()
+    }
+
+    /// Find the `StructId` associated with the specified syntax tree node.
+    pub(super) fn struct_to_def(&mut self, src: InFile<ast::StructDef>) -> Option<StructId> {
+        self.def_map_for(src.as_ref().map(|it| it.syntax()))?
+            .structs
+            .get(&src)
+            .copied()
+    }
+
+    /// Find the `TypeAliasId` associated with the specified syntax tree node.
+    pub(super) fn type_alias_to_def(
+        &mut self,
+        src: InFile<ast::TypeAliasDef>,
+    ) -> Option<TypeAliasId> {
+        self.def_map_for(src.as_ref().map(|it| it.syntax()))?
+            .type_aliases
+            .get(&src)
+            .copied()
+    }
+
+    /// Returns the `SourceToDefMap` of the container of the specified syntax tree node.
+    fn def_map_for(&mut self, src: InFile<&SyntaxNode>) -> Option<&SourceToDefMap> {
+        let container = self.find_container(src)?;
         let db = self.db;
-        let def_map = &*self
-            .cache
-            .entry(container)
-            .or_insert_with(|| container.source_to_def_map(db));
-        def_map.functions.get(&src).copied()
+        Some(
+            self.cache
+                .entry(container)
+                .or_insert_with(|| container.source_to_def_map(db)),
+        )
     }
 
     /// Finds the `ModuleId` associated with the specified `file`
@@ -1,6 +1,13 @@
 use crate::{
-    expr::scope::LocalScopeId, expr::BodySourceMap, ids::DefWithBodyId, resolver_for_scope, Body,
-    ExprId, ExprScopes, FileId, HirDatabase, InFile, InferenceResult, Resolver, Ty,
+    expr::scope::LocalScopeId,
+    expr::BodySourceMap,
+    ids::DefWithBodyId,
+    name::AsName,
+    resolve::{TypeNs, ValueNs},
+    resolver_for_scope,
+    semantics::{Local, PathResolution},
+    Body, ExprId, ExprScopes, Field, FileId, Function, HirDatabase, InFile, InferenceResult, PatId,
+    Path, Resolver, Struct, Ty, TypeAlias,
 };
 use mun_syntax::{ast, AstNode, SyntaxNode, TextRange, TextSize};
 use std::sync::Arc;
@@ -14,6 +21,10 @@ pub(crate) struct SourceAnalyzer {
     /// The resolver used to resolve names
     pub(crate) resolver: Resolver,
 
+    /// The definition that owns the body, if any. The resolver might not know about the owner if
+    /// the analyzed node is not part of an expression, e.g. a function parameter.
+    owner: Option<DefWithBodyId>,
+
     /// Optional body to res
     body: Option<Arc<Body>>,
     body_source_map: Option<Arc<BodySourceMap>>,
@@ -39,6 +50,7 @@ impl SourceAnalyzer {
         let resolver = resolver_for_scope(db.upcast(), def, scope);
         SourceAnalyzer {
             resolver,
+            owner: Some(def),
             body: Some(body),
             body_source_map: Some(source_map),
             infer: Some(db.infer(def)),
@@ -54,6 +66,7 @@ impl SourceAnalyzer {
     ) -> SourceAnalyzer {
         SourceAnalyzer {
             resolver,
+            owner: None,
             body: None,
             body_source_map: None,
             infer: None,
@@ -68,11 +81,146 @@ impl SourceAnalyzer {
         Some(self.infer.as_ref()?[expr_id].clone())
     }
 
+    /// Returns the type of the specified pattern
+    pub(crate) fn type_of_pat(&self, _db: &dyn HirDatabase, pat: &ast::Pat) -> Option<Ty> {
+        let pat_id = self.pat_id(pat)?;
+        Some(self.infer.as_ref()?[pat_id].clone())
+    }
+
+    /// Returns the field that is accessed by the specified field expression
+    pub(crate) fn resolve_field(
+        &self,
+        db: &dyn HirDatabase,
+        field: &ast::FieldExpr,
+    ) -> Option<Field> {
+        let receiver_ty = self.type_of_expr(db, &field.expr()?)?;
+        let name = field.field_access()?.as_name();
+        receiver_ty.as_struct()?.field(db, &name)
+    }
+
+    /// Returns the field that is initialized by the specified record field, and the local that
+    /// initializes it if the field uses the shorthand syntax (e.g. `Foo { a }`).
+    pub(crate) fn resolve_record_field(
+        &self,
+        db: &dyn HirDatabase,
+        field: &ast::RecordField,
+    ) -> Option<(Field, Option<Local>)> {
+        let name_ref = field.name_ref()?;
+        let record_lit = field
+            .syntax()
+            .ancestors()
+            .find_map(ast::RecordLit::cast)
+            .and_then(|lit| ast::Expr::cast(lit.syntax().clone()))?;
+        let strukt = self.type_of_expr(db, &record_lit)?.as_struct()?;
+        let field_def = strukt.field(db, &name_ref.as_name())?;
+
+        let local = if field.expr().is_none() {
+            match self
+                .resolver
+                .resolve_path_as_value_fully(db.upcast(), &Path::from_name_ref(&name_ref))
+            {
+                Some((ValueNs::LocalBinding(pat_id), _)) => self.local(pat_id),
+                _ => None,
+            }
+        } else {
+            None
+        };
+
+        Some((field_def, local))
+    }
+
+    /// Returns the definition that the specified path refers to.
+    pub(crate) fn resolve_path(
+        &self,
+        db: &dyn HirDatabase,
+        path: &ast::Path,
+    ) -> Option<PathResolution> {
+        // Paths in `use` items are always resolved relative to the module
+        if path
+            .syntax()
+            .ancestors()
+            .any(|it| ast::Use::can_cast(it.kind()))
+        {
+            let hir_path = Path::from_use_path(path)?;
+            return self.resolve_module_path(db, &hir_path);
+        }
+
+        let hir_path = Path::from_ast(path.clone())?;
+        let parent = path.syntax().parent()?;
+        if ast::PathExpr::can_cast(parent.kind()) {
+            if let Some(resolution) = self.resolve_value_path(db, &hir_path) {
+                return Some(resolution);
+            }
+        } else if ast::PathType::can_cast(parent.kind()) || ast::RecordLit::can_cast(parent.kind())
+        {
+            if let Some(resolution) = self.resolve_type_path(db, &hir_path) {
+                return Some(resolution);
+            }
+        }
+
+        // The qualifier of a path refers to a module or a type
+        self.resolve_module_path(db, &hir_path)
+    }
+
+    /// Resolves the specified `path` in the value namespace.
+    fn resolve_value_path(&self, db: &dyn HirDatabase, path: &Path) -> Option<PathResolution> {
+        let resolution = match self
+            .resolver
+            .resolve_path_as_value_fully(db.upcast(), path)?
+            .0
+        {
+            ValueNs::LocalBinding(pat_id) => PathResolution::Local(self.local(pat_id)?),
+            ValueNs::FunctionId(id) => PathResolution::Def(Function::from(id).into()),
+            ValueNs::StructId(id) => PathResolution::Def(Struct::from(id).into()),
+        };
+        Some(resolution)
+    }
+
+    /// Resolves the specified `path` in the type namespace.
+    fn resolve_type_path(&self, db: &dyn HirDatabase, path: &Path) -> Option<PathResolution> {
+        let resolution = match self
+            .resolver
+            .resolve_path_as_type_fully(db.upcast(), path)?
+            .0
+        {
+            TypeNs::StructId(id) => Struct::from(id).into(),
+            TypeNs::TypeAliasId(id) => TypeAlias::from(id).into(),
+            TypeNs::PrimitiveType(id) => id.into(),
+        };
+        Some(PathResolution::Def(resolution))
+    }
+
+    /// Resolves the specified `path` as an item of a module, preferring types over values.
+    fn resolve_module_path(&self, db: &dyn HirDatabase, path: &Path) -> Option<PathResolution> {
+        let per_ns = self.resolver.resolve_module_path(db.upcast(), path);
+        let (def, _) = per_ns.take_types().or_else(|| per_ns.take_values())?;
+        Some(PathResolution::Def(def.into()))
+    }
+
+    /// Returns the local that is bound by the specified pattern of the body.
+    pub(crate) fn resolve_bind_pat(&self, pat: &ast::BindPat) -> Option<Local> {
+        let pat = ast::Pat::cast(pat.syntax().clone())?;
+        self.local(self.pat_id(&pat)?)
+    }
+
+    fn local(&self, pat_id: PatId) -> Option<Local> {
+        Some(Local {
+            parent: self.owner?,
+            pat_id,
+        })
+    }
+
     /// Returns the expression id of the given expression or None if it could not be found.
     fn expr_id(&self, _db: &dyn HirDatabase, expr: &ast::Expr) -> Option<ExprId> {
         let sm = self.body_source_map.as_ref()?;
         sm.node_expr(expr)
     }
+
+    /// Returns the pattern id of the given pattern or None if it could not be found.
+    fn pat_id(&self, pat: &ast::Pat) -> Option<PatId> {
+        let sm = self.body_source_map.as_ref()?;
+        sm.node_pat(pat)
+    }
 }
 
 /// Returns the id of the scope that is active at the location of `node`.
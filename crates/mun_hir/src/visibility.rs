@@ -1,5 +1,5 @@
 use crate::module_tree::{LocalModuleId, ModuleTree};
-use crate::{ids::ModuleId, DefDatabase, HirDatabase, Resolver};
+use crate::{ids::ModuleId, DefDatabase, HirDatabase, Module, Resolver};
 use mun_syntax::ast;
 use std::iter::successors;
 
@@ -99,4 +99,9 @@ impl Visibility {
 
 pub trait HasVisibility {
     fn visibility(&self, db: &dyn HirDatabase) -> Visibility;
+
+    /// Returns true if this item is accessible from the specified `module`.
+    fn is_visible_from(&self, db: &dyn HirDatabase, module: Module) -> bool {
+        self.visibility(db).is_visible_from(db, module.id)
+    }
 }
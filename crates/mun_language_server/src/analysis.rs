@@ -1,9 +1,17 @@
 use crate::{
-    cancelation::Canceled, change::AnalysisChange, completion, db::AnalysisDatabase, diagnostics,
-    diagnostics::Diagnostic, file_structure, FilePosition,
+    cancelation::Canceled, change::AnalysisChange, completion, config::InlayHintsConfig,
+    db::AnalysisDatabase, diagnostics, diagnostics::Diagnostic, document_highlight,
+    document_highlight::HighlightedRange, file_structure, folding_ranges, folding_ranges::Fold,
+    goto_definition, goto_type_definition, hover, hover::HoverResult, inlay_hints,
+    inlay_hints::InlayHint, navigation_target::NavigationTarget, references,
+    references::ReferenceSearchResult, rename, rename::RenameResult, signature_help,
+    signature_help::SignatureHelp, source_change::SourceChange, symbol_index, syntax_highlighting,
+    syntax_highlighting::HlRange, FilePosition, FileRange,
 };
 use hir::{line_index::LineIndex, AstDatabase, SourceDatabase};
-use mun_syntax::SourceFile;
+use mun_fmt::FormatOptions;
+use mun_syntax::{SourceFile, TextRange};
+use ra_ap_text_edit::TextEdit;
 use salsa::{ParallelDatabase, Snapshot};
 use std::sync::Arc;
 
@@ -68,6 +76,11 @@ impl AnalysisSnapshot {
         })
     }
 
+    /// Returns the text of the specified file
+    pub fn file_text(&self, file_id: hir::FileId) -> Cancelable<Arc<str>> {
+        self.with_db(|db| db.file_text(file_id))
+    }
+
     /// Returns the line index for the specified file
     pub fn file_line_index(&self, file_id: hir::FileId) -> Cancelable<Arc<LineIndex>> {
         self.with_db(|db| db.line_index(file_id))
@@ -81,6 +94,16 @@ impl AnalysisSnapshot {
         self.with_db(|db| file_structure::file_structure(&db.parse(file_id).tree()))
     }
 
+    /// Returns the ranges of a file that can be collapsed.
+    pub fn folding_ranges(&self, file_id: hir::FileId) -> Cancelable<Vec<Fold>> {
+        self.with_db(|db| folding_ranges::folding_ranges(&db.parse(file_id).tree()))
+    }
+
+    /// Searches the symbols of all packages whose name fuzzily matches the `query`.
+    pub fn symbol_search(&self, query: &str) -> Cancelable<Vec<NavigationTarget>> {
+        self.with_db(|db| symbol_index::world_symbols(db, query))
+    }
+
     /// Computes completions at the given position
     pub fn completions(
         &self,
@@ -89,6 +112,95 @@ impl AnalysisSnapshot {
         self.with_db(|db| completion::completions(db, position).map(Into::into))
     }
 
+    /// Returns the locations where the entity at the given position is defined
+    pub fn goto_definition(
+        &self,
+        position: FilePosition,
+    ) -> Cancelable<Option<Vec<NavigationTarget>>> {
+        self.with_db(|db| goto_definition::goto_definition(db, position))
+    }
+
+    /// Returns the locations where the type of the entity at the given position is defined
+    pub fn goto_type_definition(
+        &self,
+        position: FilePosition,
+    ) -> Cancelable<Option<Vec<NavigationTarget>>> {
+        self.with_db(|db| goto_type_definition::goto_type_definition(db, position))
+    }
+
+    /// Returns the information to show when hovering over the given position
+    pub fn hover(&self, position: FilePosition) -> Cancelable<Option<HoverResult>> {
+        self.with_db(|db| hover::hover(db, position))
+    }
+
+    /// Returns the inlay hints of the given file
+    pub fn inlay_hints(
+        &self,
+        file_id: hir::FileId,
+        config: &InlayHintsConfig,
+    ) -> Cancelable<Vec<InlayHint>> {
+        self.with_db(|db| inlay_hints::inlay_hints(db, file_id, config))
+    }
+
+    /// Returns the signature of the function that is called at the given position
+    pub fn signature_help(&self, position: FilePosition) -> Cancelable<Option<SignatureHelp>> {
+        self.with_db(|db| signature_help::signature_help(db, position))
+    }
+
+    /// Returns the declaration and all references of the entity at the given position
+    pub fn find_all_refs(
+        &self,
+        position: FilePosition,
+    ) -> Cancelable<Option<ReferenceSearchResult>> {
+        self.with_db(|db| references::find_all_refs(db, position))
+    }
+
+    /// Returns the ranges to highlight in a document when the cursor is at the given position
+    pub fn document_highlight(
+        &self,
+        position: FilePosition,
+    ) -> Cancelable<Option<Vec<HighlightedRange>>> {
+        self.with_db(|db| document_highlight::document_highlight(db, position))
+    }
+
+    /// Returns the highlighted ranges of the given file
+    pub fn highlight(&self, file_id: hir::FileId) -> Cancelable<Vec<HlRange>> {
+        self.with_db(|db| syntax_highlighting::highlight(db, file_id))
+    }
+
+    /// Returns the range of the identifier at the given position if the entity it refers to can be
+    /// renamed
+    pub fn prepare_rename(&self, position: FilePosition) -> Cancelable<RenameResult<FileRange>> {
+        self.with_db(|db| rename::prepare_rename(db, position))
+    }
+
+    /// Returns the edits required to rename the entity at the given position to `new_name`
+    pub fn rename(
+        &self,
+        position: FilePosition,
+        new_name: &str,
+    ) -> Cancelable<RenameResult<SourceChange>> {
+        self.with_db(|db| rename::rename(db, position, new_name))
+    }
+
+    /// Returns the edit that formats the given file or, if a `range` is specified, only the part of
+    /// the file that intersects with it. Returns `None` if the file contains syntax errors.
+    pub fn format(
+        &self,
+        file_id: hir::FileId,
+        options: &FormatOptions,
+        range: Option<TextRange>,
+    ) -> Cancelable<Option<TextEdit>> {
+        self.with_db(|db| {
+            let text = db.file_text(file_id);
+            match range {
+                Some(range) => mun_fmt::format_range(&text, range, options),
+                None => mun_fmt::format_edit(&text, options),
+            }
+            .ok()
+        })
+    }
+
     /// Performs an operation on that may be Canceled.
     fn with_db<F: FnOnce(&AnalysisDatabase) -> T + std::panic::UnwindSafe, T>(
         &self,
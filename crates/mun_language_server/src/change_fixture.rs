@@ -1,4 +1,4 @@
-use crate::change::AnalysisChange;
+use crate::{change::AnalysisChange, db::AnalysisDatabase, FilePosition};
 use hir::fixture::Fixture;
 use mun_syntax::{TextRange, TextSize};
 use std::sync::Arc;
@@ -101,3 +101,18 @@ pub fn extract_range_or_offset(text: &str) -> (RangeOrOffset, String) {
         panic!("text should contain a cursor marker")
     }
 }
+
+/// Creates an analysis database from a multi-file fixture and a position marked with `$0`.
+pub(crate) fn position(fixture: &str) -> (AnalysisDatabase, FilePosition) {
+    let change_fixture = ChangeFixture::parse(fixture);
+    let mut database = AnalysisDatabase::default();
+    database.apply_change(change_fixture.change);
+    let (file_id, range_or_offset) = change_fixture
+        .file_position
+        .expect("expected a marker ($0)");
+    let offset = match range_or_offset {
+        RangeOrOffset::Range(_) => panic!(),
+        RangeOrOffset::Offset(it) => it,
+    };
+    (database, FilePosition { file_id, offset })
+}
@@ -1,5 +1,6 @@
 use paths::AbsPathBuf;
 use project::ProjectManifest;
+use serde_derive::Deserialize;
 
 /// The configuration used by the language server.
 #[derive(Debug, Clone)]
@@ -11,6 +12,9 @@ pub struct Config {
 
     /// A collection of projects discovered within the workspace
     pub discovered_projects: Option<Vec<ProjectManifest>>,
+
+    /// Which kinds of inlay hints are shown
+    pub inlay_hints: InlayHintsConfig,
 }
 
 impl Config {
@@ -20,6 +24,65 @@ impl Config {
             watcher: FilesWatcher::Notify,
             root_dir: root_path,
             discovered_projects: None,
+            inlay_hints: InlayHintsConfig::default(),
+        }
+    }
+
+    /// Updates the configuration with the options that the client passed when initializing the
+    /// server, e.g.:
+    ///
+    /// ```json
+    /// { "inlayHints": { "typeHints": true, "parameterHints": false, "chainingHints": true } }
+    /// ```
+    ///
+    /// Options that are not specified retain their current value.
+    pub fn update(&mut self, options: serde_json::Value) -> anyhow::Result<()> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Options {
+            inlay_hints: Option<InlayHintsOptions>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct InlayHintsOptions {
+            type_hints: Option<bool>,
+            parameter_hints: Option<bool>,
+            chaining_hints: Option<bool>,
+        }
+
+        let options: Options = crate::from_json("initialization options", options)?;
+        if let Some(inlay_hints) = options.inlay_hints {
+            let config = &mut self.inlay_hints;
+            config.type_hints = inlay_hints.type_hints.unwrap_or(config.type_hints);
+            config.parameter_hints = inlay_hints
+                .parameter_hints
+                .unwrap_or(config.parameter_hints);
+            config.chaining_hints = inlay_hints.chaining_hints.unwrap_or(config.chaining_hints);
+        }
+        Ok(())
+    }
+}
+
+/// Specifies which kinds of inlay hints are shown.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct InlayHintsConfig {
+    /// Show the inferred type of `let` bindings without a type annotation
+    pub type_hints: bool,
+
+    /// Show the names of parameters at the arguments of function calls
+    pub parameter_hints: bool,
+
+    /// Show the type of each intermediate expression in a chain of field accesses
+    pub chaining_hints: bool,
+}
+
+impl Default for InlayHintsConfig {
+    fn default() -> Self {
+        InlayHintsConfig {
+            type_hints: true,
+            parameter_hints: true,
+            chaining_hints: true,
         }
     }
 }
@@ -29,3 +92,31 @@ pub enum FilesWatcher {
     Client,
     Notify,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, InlayHintsConfig};
+    use paths::AbsPathBuf;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_update_inlay_hints() {
+        let root_dir = AbsPathBuf::try_from(std::env::current_dir().unwrap()).unwrap();
+        let mut config = Config::new(root_dir);
+        config
+            .update(serde_json::json!({ "inlayHints": { "parameterHints": false } }))
+            .unwrap();
+        assert_eq!(
+            config.inlay_hints,
+            InlayHintsConfig {
+                type_hints: true,
+                parameter_hints: false,
+                chaining_hints: true,
+            }
+        );
+
+        assert!(config
+            .update(serde_json::json!({ "inlayHints": { "typeHints": 5 } }))
+            .is_err());
+    }
+}
@@ -22,7 +22,8 @@ use std::panic;
     hir::DefDatabaseStorage,
     hir::HirDatabaseStorage,
     hir::AstDatabaseStorage,
-    hir::InternDatabaseStorage
+    hir::InternDatabaseStorage,
+    crate::symbol_index::SymbolsDatabaseStorage
 )]
 pub(crate) struct AnalysisDatabase {
     storage: salsa::Storage<Self>,
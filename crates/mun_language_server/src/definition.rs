@@ -0,0 +1,130 @@
+//! Provides the [`Definition`] type, which describes what a name in the source code refers to, and
+//! the means to classify names in the source code.
+
+use crate::navigation_target::{NavigationTarget, ToNav, TryToNav};
+use hir::semantics::{Local, PathResolution, Semantics};
+use mun_syntax::{ast, match_ast, AstNode};
+
+/// An entity that can be referred to by a name in the source code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Definition {
+    ModuleDef(hir::ModuleDef),
+    Field(hir::Field),
+    Local(Local),
+}
+
+impl Definition {
+    /// Returns the location in the source code where this entity is defined, if any.
+    pub fn try_to_nav(&self, db: &dyn hir::HirDatabase) -> Option<NavigationTarget> {
+        match self {
+            Definition::ModuleDef(it) => it.try_to_nav(db),
+            Definition::Field(it) => Some(it.to_nav(db)),
+            Definition::Local(it) => Some(it.to_nav(db)),
+        }
+    }
+
+    /// Returns the name of this entity, or `None` if it has no name that can be referred to.
+    pub fn name(&self, db: &dyn hir::HirDatabase) -> Option<hir::Name> {
+        match self {
+            Definition::ModuleDef(hir::ModuleDef::Module(it)) => it.name(db),
+            Definition::ModuleDef(hir::ModuleDef::Function(it)) => Some(it.name(db)),
+            Definition::ModuleDef(hir::ModuleDef::Struct(it)) => Some(it.name(db)),
+            Definition::ModuleDef(hir::ModuleDef::TypeAlias(it)) => Some(it.name(db)),
+            Definition::ModuleDef(hir::ModuleDef::PrimitiveType(_)) => None,
+            Definition::Field(it) => Some(it.name(db)),
+            Definition::Local(it) => it.name(db),
+        }
+    }
+
+    /// Returns the files in which this entity can be referred to.
+    pub fn search_scope(&self, db: &dyn hir::HirDatabase) -> Vec<hir::FileId> {
+        if let Definition::Local(local) = self {
+            return vec![local.parent().file_id(db)];
+        }
+
+        let packages = db.packages();
+        packages
+            .iter()
+            .flat_map(|package_id| {
+                let source_root = db.source_root(packages[package_id].source_root);
+                source_root.files().collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Determines the entity that is defined by the specified `name`.
+    pub fn from_name(sema: &Semantics, name: &ast::Name) -> Option<Definition> {
+        let parent = name.syntax().parent()?;
+        match_ast! {
+            match parent {
+                ast::FunctionDef(it) => {
+                    sema.function_to_def(&it).map(|it| Definition::ModuleDef(it.into()))
+                },
+                ast::StructDef(it) => {
+                    sema.struct_to_def(&it).map(|it| Definition::ModuleDef(it.into()))
+                },
+                ast::TypeAliasDef(it) => {
+                    sema.type_alias_to_def(&it).map(|it| Definition::ModuleDef(it.into()))
+                },
+                ast::RecordFieldDef(it) => sema.record_field_to_def(&it).map(Definition::Field),
+                ast::BindPat(it) => sema.bind_pat_to_def(&it).map(Definition::Local),
+                _ => None,
+            }
+        }
+    }
+}
+
+impl From<PathResolution> for Definition {
+    fn from(resolution: PathResolution) -> Self {
+        match resolution {
+            PathResolution::Def(def) => Definition::ModuleDef(def),
+            PathResolution::Local(local) => Definition::Local(local),
+        }
+    }
+}
+
+/// The classification of an `ast::NameRef`: the entity that the name refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NameRefClass {
+    Definition(Definition),
+
+    /// A field that is initialized with the shorthand syntax, as in `Foo { a }`. The name refers to
+    /// both a field and a local.
+    FieldShorthand {
+        local: Local,
+        field: hir::Field,
+    },
+}
+
+impl NameRefClass {
+    /// Determines the entity that the specified `name_ref` refers to.
+    pub fn classify(sema: &Semantics, name_ref: &ast::NameRef) -> Option<NameRefClass> {
+        let parent = name_ref.syntax().parent()?;
+
+        if let Some(field_expr) = ast::FieldExpr::cast(parent.clone()) {
+            let field = sema.resolve_field(&field_expr)?;
+            return Some(NameRefClass::Definition(Definition::Field(field)));
+        }
+
+        if let Some(record_field) = ast::RecordField::cast(parent.clone()) {
+            let (field, local) = sema.resolve_record_field(&record_field)?;
+            return Some(match local {
+                Some(local) => NameRefClass::FieldShorthand { local, field },
+                None => NameRefClass::Definition(Definition::Field(field)),
+            });
+        }
+
+        let path = ast::PathSegment::cast(parent)?.parent_path();
+        let resolution = sema.resolve_path(&path)?;
+        Some(NameRefClass::Definition(resolution.into()))
+    }
+
+    /// Returns the entity whose value is referred to. For a field that uses the shorthand syntax,
+    /// this is the local that initializes the field.
+    pub fn referenced(self) -> Definition {
+        match self {
+            NameRefClass::Definition(def) => def,
+            NameRefClass::FieldShorthand { local, .. } => Definition::Local(local),
+        }
+    }
+}
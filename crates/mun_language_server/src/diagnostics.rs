@@ -1,7 +1,9 @@
-use crate::db::AnalysisDatabase;
+use crate::{db::AnalysisDatabase, source_change::SourceChange};
 use hir::{AstDatabase, InFile, ModuleId, PackageId, SourceDatabase};
 use mun_diagnostics::DiagnosticForWith;
 use mun_syntax::{Location, TextRange};
+use ra_ap_text_edit::TextEdit;
+use rustc_hash::FxHashMap;
 use std::cell::RefCell;
 
 #[derive(Debug)]
@@ -15,10 +17,41 @@ pub struct Diagnostic {
     pub message: String,
     pub range: TextRange,
     pub additional_annotations: Vec<SourceAnnotation>,
-    // pub fix: Option<SourceChange>,
+    pub fixes: Vec<Fix>,
     // pub severity: Severity,
 }
 
+/// A suggested change to the source code that resolves a diagnostic.
+#[derive(Debug)]
+pub struct Fix {
+    pub label: String,
+    pub source_change: SourceChange,
+}
+
+impl From<mun_diagnostics::Fix> for Fix {
+    fn from(fix: mun_diagnostics::Fix) -> Self {
+        let mut edits: FxHashMap<hir::FileId, Vec<mun_diagnostics::SourceEdit>> =
+            FxHashMap::default();
+        for edit in fix.edits {
+            edits.entry(edit.range.file_id).or_default().push(edit);
+        }
+
+        let mut source_change = SourceChange::default();
+        for (file_id, edits) in edits {
+            let mut builder = TextEdit::builder();
+            for edit in edits {
+                builder.replace(edit.range.value, edit.replacement);
+            }
+            source_change.insert_source_edit(file_id, builder.finish());
+        }
+
+        Fix {
+            label: fix.label,
+            source_change,
+        }
+    }
+}
+
 /// Converts a location to a a range for use in diagnostics
 fn location_to_range(location: Location) -> TextRange {
     match location {
@@ -37,6 +70,7 @@ pub(crate) fn diagnostics(db: &AnalysisDatabase, file_id: hir::FileId) -> Vec<Di
         message: format!("parse error: {}", err),
         range: location_to_range(err.location()),
         additional_annotations: vec![],
+        fixes: vec![],
     }));
 
     // Add all HIR diagnostics
@@ -56,6 +90,7 @@ pub(crate) fn diagnostics(db: &AnalysisDatabase, file_id: hir::FileId) -> Vec<Di
                         range: annotation.range,
                     })
                     .collect(),
+                fixes: d.fixes().into_iter().map(Fix::from).collect(),
             }
         }));
     });
@@ -74,3 +109,226 @@ pub(crate) fn diagnostics(db: &AnalysisDatabase, file_id: hir::FileId) -> Vec<Di
     // Returns the result
     result.into_inner()
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::change_fixture::position;
+    use hir::SourceDatabase;
+
+    /// Renders the files that result from applying each fix of the diagnostics at the cursor in
+    /// the `fixture`.
+    fn fixes(fixture: &str) -> String {
+        let (db, position) = position(fixture);
+        let mut result = Vec::new();
+        for diagnostic in super::diagnostics(&db, position.file_id) {
+            if !diagnostic.range.contains_inclusive(position.offset) {
+                continue;
+            }
+            for fix in diagnostic.fixes {
+                let mut files = fix
+                    .source_change
+                    .source_file_edits
+                    .into_iter()
+                    .map(|(file_id, edit)| {
+                        let mut text = db.file_text(file_id).to_string();
+                        edit.apply(&mut text);
+                        format!("//- /{}\n{}", db.file_relative_path(file_id), text.trim())
+                    })
+                    .collect::<Vec<_>>();
+                files.sort();
+                result.push(format!("{}\n{}", fix.label, files.join("\n")));
+            }
+        }
+        result.join("\n\n")
+    }
+
+    #[test]
+    fn missing_fields() {
+        insta::assert_snapshot!(fixes(
+            r#"
+        struct Foo { a: i32, b: f32, c: bool }
+        fn foo() {
+            let foo = Fo$0o { a: 3 };
+        }
+        "#
+        ), @r###"
+        Add missing fields
+        //- /mod.mun
+        struct Foo { a: i32, b: f32, c: bool }
+        fn foo() {
+            let foo = Foo { a: 3, b: 0.0, c: false };
+        }
+        "###);
+    }
+
+    #[test]
+    fn missing_fields_empty() {
+        insta::assert_snapshot!(fixes(
+            r#"
+        struct Foo { a: i32, b: f32 }
+        fn foo() {
+            let foo = Fo$0o {};
+        }
+        "#
+        ), @r###"
+        Add missing fields
+        //- /mod.mun
+        struct Foo { a: i32, b: f32 }
+        fn foo() {
+            let foo = Foo { a: 0, b: 0.0 };
+        }
+        "###);
+    }
+
+    #[test]
+    fn missing_fields_multi_line() {
+        insta::assert_snapshot!(fixes(
+            r#"
+        struct Foo { a: i32, b: f32, c: bool }
+        fn foo() {
+            let foo = Fo$0o {
+                a: 3
+            };
+        }
+        "#
+        ), @r###"
+        Add missing fields
+        //- /mod.mun
+        struct Foo { a: i32, b: f32, c: bool }
+        fn foo() {
+            let foo = Foo {
+                a: 3,
+                b: 0.0,
+                c: false,
+            };
+        }
+        "###);
+    }
+
+    #[test]
+    fn missing_fields_without_placeholder() {
+        insta::assert_snapshot!(fixes(
+            r#"
+        struct Bar;
+        struct Foo { a: i32, b: Bar }
+        fn foo() {
+            let foo = Fo$0o { a: 3 };
+        }
+        "#
+        ), @"");
+    }
+
+    #[test]
+    fn unresolved_value() {
+        insta::assert_snapshot!(fixes(
+            r#"
+        //- /mod.mun
+        use bar::Bar;
+        fn foo() {
+            ba$0z();
+        }
+
+        //- /bar.mun
+        pub struct Bar;
+        pub fn baz() {}
+
+        //- /bar/qux.mun
+        pub(super) fn baz() {}
+
+        //- /quux.mun
+        fn baz() {}
+        "#
+        ), @r###"
+        Import `package::bar::baz`
+        //- /mod.mun
+        use bar::Bar;
+        use package::bar::baz;
+        fn foo() {
+            baz();
+        }
+        "###);
+    }
+
+    #[test]
+    fn unresolved_type() {
+        insta::assert_snapshot!(fixes(
+            r#"
+        //- /mod.mun
+        fn foo(bar: Ba$0r) {}
+
+        //- /bar.mun
+        pub(package) struct Bar;
+        "#
+        ), @r###"
+        Import `package::bar::Bar`
+        //- /mod.mun
+        use package::bar::Bar;
+
+        fn foo(bar: Bar) {}
+        "###);
+    }
+
+    #[test]
+    fn exported_private() {
+        insta::assert_snapshot!(fixes(
+            r#"
+        //- /mod.mun
+        struct Foo;
+        pub fn foo() -> Fo$0o { Foo }
+        "#
+        ), @r###"
+        Make `Foo` public
+        //- /mod.mun
+        pub struct Foo;
+        pub fn foo() -> Foo { Foo }
+        "###);
+    }
+
+    #[test]
+    fn exported_private_with_visibility() {
+        insta::assert_snapshot!(fixes(
+            r#"
+        //- /mod.mun
+        pub fn foo() -> bar::Bar { bar::Bar }
+
+        //- /bar.mun
+        pub(super) struct Bar { a: i32 }
+        pub struct Baz { pub bar: Ba$0r }
+        "#
+        ), @r###"
+        Make `Bar` public
+        //- /bar.mun
+        pub struct Bar { a: i32 }
+        pub struct Baz { pub bar: Bar }
+        "###);
+    }
+
+    #[test]
+    fn missing_else_branch() {
+        insta::assert_snapshot!(fixes(
+            r#"
+        fn foo(a: bool) -> i32 {
+            i$0f a { 5 }
+        }
+        "#
+        ), @r###"
+        Add `else` branch
+        //- /mod.mun
+        fn foo(a: bool) -> i32 {
+            if a { 5 } else { 0 }
+        }
+        "###);
+    }
+
+    #[test]
+    fn missing_else_branch_without_placeholder() {
+        insta::assert_snapshot!(fixes(
+            r#"
+        struct Foo;
+        fn foo(a: bool) -> Foo {
+            i$0f a { Foo }
+        }
+        "#
+        ), @"");
+    }
+}
@@ -0,0 +1,132 @@
+//! Provides the [`document_highlight`] function, which finds the ranges in a document that should
+//! be highlighted when the cursor is on an entity.
+
+use crate::{
+    db::AnalysisDatabase,
+    definition::Definition,
+    references::{definition_at, find_references, ReferenceAccess},
+    FilePosition,
+};
+use hir::semantics::Semantics;
+use mun_syntax::TextRange;
+
+/// A range in a document that refers to the entity under the cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighlightedRange {
+    pub range: TextRange,
+    pub access: Option<ReferenceAccess>,
+}
+
+/// Returns the declaration and all references of the entity at the specified `position` that are
+/// located in the same file.
+pub(crate) fn document_highlight(
+    db: &AnalysisDatabase,
+    position: FilePosition,
+) -> Option<Vec<HighlightedRange>> {
+    let sema = Semantics::new(db);
+    let definition = definition_at(&sema, position)?;
+
+    let declaration = definition
+        .try_to_nav(db)
+        .filter(|nav| nav.file_id == position.file_id)
+        .and_then(|nav| nav.focus_range)
+        .map(|range| HighlightedRange {
+            range,
+            access: declaration_access(&definition),
+        });
+
+    let references = find_references(&sema, definition, &[position.file_id])
+        .into_iter()
+        .map(|reference| HighlightedRange {
+            range: reference.file_range.range,
+            access: reference.access,
+        });
+
+    Some(declaration.into_iter().chain(references).collect())
+}
+
+/// Returns how the declaration of the `definition` accesses it.
+fn declaration_access(definition: &Definition) -> Option<ReferenceAccess> {
+    match definition {
+        // A local is always initialized when it is declared
+        Definition::Local(_) => Some(ReferenceAccess::Write),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::change_fixture::position;
+    use hir::SourceDatabase;
+
+    /// Renders the highlighted ranges of the entity at the cursor in the `fixture`.
+    fn document_highlight(fixture: &str) -> String {
+        let (db, position) = position(fixture);
+        let text = db.file_text(position.file_id);
+        super::document_highlight(&db, position)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|highlight| {
+                format!(
+                    "{} {:?} {:?}",
+                    &text[highlight.range], highlight.range, highlight.access
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn local() {
+        insta::assert_snapshot!(document_highlight(
+            r#"
+        fn foo() {
+            let bar = 5;
+            let baz = ba$0r + 1;
+            bar = baz;
+        }
+        "#
+        ), @r###"
+        bar 19..22 Some(Write)
+        bar 42..45 Some(Read)
+        bar 55..58 Some(Write)
+        "###);
+    }
+
+    #[test]
+    fn only_current_file() {
+        insta::assert_snapshot!(document_highlight(
+            r#"
+        //- /mod.mun
+        use bar::baz;
+        fn foo() {
+            ba$0z();
+        }
+
+        //- /bar.mun
+        pub fn baz() {}
+        fn qux() {
+            baz()
+        }
+        "#
+        ), @r###"
+        baz 9..12 None
+        baz 29..32 None
+        "###);
+    }
+
+    #[test]
+    fn function() {
+        insta::assert_snapshot!(document_highlight(
+            r#"
+        fn ba$0z() {}
+        fn foo() {
+            baz();
+        }
+        "#
+        ), @r###"
+        baz 3..6 None
+        baz 27..30 None
+        "###);
+    }
+}
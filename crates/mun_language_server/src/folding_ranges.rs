@@ -0,0 +1,241 @@
+//! Provides the [`folding_ranges`] function, which computes the ranges of a source file that an
+//! editor can collapse.
+
+use mun_syntax::{AstNode, SourceFile, SyntaxKind, SyntaxToken, TextRange};
+use rustc_hash::FxHashSet;
+
+/// The kind of a [`Fold`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FoldKind {
+    /// A block or the body of a struct, which ends with a closing delimiter
+    Block,
+
+    /// A block comment or a run of line comments
+    Comment,
+}
+
+/// A range of the source code that can be collapsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fold {
+    pub range: TextRange,
+    pub kind: FoldKind,
+}
+
+/// Returns the folds of the specified file, ordered by their start. Only ranges that span multiple
+/// lines can be folded.
+pub(crate) fn folding_ranges(file: &SourceFile) -> Vec<Fold> {
+    let mut folds = Vec::new();
+    let mut visited_comments = FxHashSet::default();
+
+    for element in file.syntax().descendants_with_tokens() {
+        match element.kind() {
+            SyntaxKind::BLOCK_EXPR
+            | SyntaxKind::RECORD_FIELD_DEF_LIST
+            | SyntaxKind::TUPLE_FIELD_DEF_LIST
+                if element.to_string().contains('\n') =>
+            {
+                folds.push(Fold {
+                    range: element.text_range(),
+                    kind: FoldKind::Block,
+                });
+            }
+            SyntaxKind::COMMENT => {
+                let comment = match element.into_token() {
+                    Some(comment) => comment,
+                    None => continue,
+                };
+                if visited_comments.contains(&comment) {
+                    continue;
+                }
+                if let Some(range) = comment_run(&comment, &mut visited_comments) {
+                    folds.push(Fold {
+                        range,
+                        kind: FoldKind::Comment,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    folds
+}
+
+/// Returns the range of the run of line comments that starts with the `first` comment, or of the
+/// `first` comment itself if it is a block comment. Comments are part of the same run if they are
+/// of the same style and are only separated by a single line break. Returns `None` if the range
+/// does not span multiple lines.
+fn comment_run(
+    first: &SyntaxToken,
+    visited_comments: &mut FxHashSet<SyntaxToken>,
+) -> Option<TextRange> {
+    if first.text().starts_with("/*") {
+        return if first.text().contains('\n') {
+            Some(first.text_range())
+        } else {
+            None
+        };
+    }
+
+    let style = comment_style(first);
+    let mut last = first.clone();
+    while let Some(whitespace) = last.next_token() {
+        if whitespace.kind() != SyntaxKind::WHITESPACE
+            || whitespace.text().matches('\n').count() > 1
+        {
+            break;
+        }
+        match whitespace.next_token() {
+            Some(next) if next.kind() == SyntaxKind::COMMENT && comment_style(&next) == style => {
+                visited_comments.insert(next.clone());
+                last = next;
+            }
+            _ => break,
+        }
+    }
+
+    if &last == first {
+        None
+    } else {
+        Some(TextRange::new(
+            first.text_range().start(),
+            last.text_range().end(),
+        ))
+    }
+}
+
+/// Returns the prefix that determines the style of a line comment, e.g. `///` for documentation.
+fn comment_style(comment: &SyntaxToken) -> &'static str {
+    let text = comment.text();
+    if text.starts_with("///") && !text.starts_with("////") {
+        "///"
+    } else if text.starts_with("//!") {
+        "//!"
+    } else if text.starts_with("/*") {
+        "/*"
+    } else {
+        "//"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FoldKind;
+    use crate::{change_fixture::ChangeFixture, db::AnalysisDatabase};
+    use hir::{AstDatabase, SourceDatabase};
+
+    /// Renders the folds of the single file in the `fixture` by enclosing each fold in markers that
+    /// denote its kind.
+    fn folding_ranges(fixture: &str) -> String {
+        let change_fixture = ChangeFixture::parse(fixture);
+        let mut db = AnalysisDatabase::default();
+        db.apply_change(change_fixture.change);
+
+        let file_id = change_fixture.files[0];
+        let folds = super::folding_ranges(&db.parse(file_id).tree());
+
+        let mut markers = Vec::new();
+        for fold in folds {
+            let kind = match fold.kind {
+                FoldKind::Block => "block",
+                FoldKind::Comment => "comment",
+            };
+            markers.push((usize::from(fold.range.start()), format!("<{}>", kind)));
+            markers.push((usize::from(fold.range.end()), format!("</{}>", kind)));
+        }
+        markers.sort_by_key(|(offset, _)| *offset);
+
+        let mut text = db.file_text(file_id).to_string();
+        for (offset, marker) in markers.into_iter().rev() {
+            text.insert_str(offset, &marker);
+        }
+        text
+    }
+
+    #[test]
+    fn test_fold_blocks() {
+        insta::assert_snapshot!(folding_ranges(
+            r#"
+        fn main() {
+            let a = 5;
+            if a > 3 {
+                a = 3;
+            } else { a = 4; }
+        }
+
+        fn foo() {}
+        "#
+        ), @r###"
+        fn main() <block>{
+            let a = 5;
+            if a > 3 <block>{
+                a = 3;
+            }</block> else { a = 4; }
+        }</block>
+
+        fn foo() {}
+        "###);
+    }
+
+    #[test]
+    fn test_fold_struct_bodies() {
+        insta::assert_snapshot!(folding_ranges(
+            r#"
+        struct Foo {
+            a: i32,
+            b: f32,
+        }
+
+        struct Bar { a: i32 }
+
+        struct Baz(
+            i32,
+            f32,
+        );
+        "#
+        ), @r###"
+        struct Foo <block>{
+            a: i32,
+            b: f32,
+        }</block>
+
+        struct Bar { a: i32 }
+
+        struct Baz<block>(
+            i32,
+            f32,
+        );</block>
+        "###);
+    }
+
+    #[test]
+    fn test_fold_comments() {
+        insta::assert_snapshot!(folding_ranges(
+            r#"
+        // A run of
+        // line comments
+
+        // A separate comment
+        /// Documentation
+        /// of a function
+        // A regular comment
+        fn main() {}
+
+        /* A block
+           comment */
+        "#
+        ), @r###"
+        <comment>// A run of
+        // line comments</comment>
+
+        // A separate comment
+        <comment>/// Documentation
+        /// of a function</comment>
+        // A regular comment
+        fn main() {}
+
+        <comment>/* A block
+           comment */</comment>
+        "###);
+    }
+}
@@ -0,0 +1,243 @@
+//! Provides the [`goto_definition`] function, which computes the location where the entity under
+//! the cursor is defined.
+
+use crate::{
+    db::AnalysisDatabase,
+    definition::{Definition, NameRefClass},
+    navigation_target::NavigationTarget,
+    FilePosition,
+};
+use hir::semantics::Semantics;
+use mun_syntax::{ast, utils::pick_best_token, AstNode};
+
+/// Returns the locations where the entity at the specified `position` is defined. Returns `None`
+/// if there is no entity at the position.
+pub(crate) fn goto_definition(
+    db: &AnalysisDatabase,
+    position: FilePosition,
+) -> Option<Vec<NavigationTarget>> {
+    let sema = Semantics::new(db);
+    let file = sema.parse(position.file_id);
+    let token = pick_best_token(file.syntax(), position.offset)?;
+    let parent = token.parent()?;
+
+    let definition = if let Some(name_ref) = ast::NameRef::cast(parent.clone()) {
+        NameRefClass::classify(&sema, &name_ref)?.referenced()
+    } else {
+        // Tuple fields (e.g. `foo.0`) are not name references but index tokens
+        let field_expr = ast::FieldExpr::cast(parent)?;
+        Definition::Field(sema.resolve_field(&field_expr)?)
+    };
+
+    Some(definition.try_to_nav(db).into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::change_fixture::position;
+
+    /// Renders the locations that the cursor in the `fixture` navigates to.
+    fn goto_definition(fixture: &str) -> String {
+        let (db, position) = position(fixture);
+        super::goto_definition(&db, position)
+            .unwrap_or_default()
+            .iter()
+            .map(|nav| nav.debug_render(&db))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn local() {
+        insta::assert_snapshot!(goto_definition(
+            r#"
+        fn foo() {
+            let bar = 5;
+            let baz = ba$0r;
+        }
+        "#
+        ), @"mod.mun Local bar (bar)");
+    }
+
+    #[test]
+    fn param() {
+        insta::assert_snapshot!(goto_definition(
+            r#"
+        fn foo(bar: i32) -> i32 {
+            ba$0r
+        }
+        "#
+        ), @"mod.mun Local bar (bar)");
+    }
+
+    #[test]
+    fn function() {
+        insta::assert_snapshot!(goto_definition(
+            r#"
+        fn bar() {}
+        fn foo() {
+            ba$0r()
+        }
+        "#
+        ), @"mod.mun Function fn bar() {} (bar)");
+    }
+
+    #[test]
+    fn struct_type() {
+        insta::assert_snapshot!(goto_definition(
+            r#"
+        struct Bar;
+        fn foo(bar: Ba$0r) {}
+        "#
+        ), @"mod.mun Struct struct Bar; (Bar)");
+    }
+
+    #[test]
+    fn struct_literal() {
+        insta::assert_snapshot!(goto_definition(
+            r#"
+        struct Bar { a: i32 }
+        fn foo() {
+            let bar = Ba$0r { a: 3 };
+        }
+        "#
+        ), @"mod.mun Struct struct Bar { a: i32 } (Bar)");
+    }
+
+    #[test]
+    fn type_alias() {
+        insta::assert_snapshot!(goto_definition(
+            r#"
+        type Bar = i32;
+        fn foo(bar: Ba$0r) {}
+        "#
+        ), @"mod.mun TypeAlias type Bar = i32; (Bar)");
+    }
+
+    #[test]
+    fn primitive_type() {
+        insta::assert_snapshot!(goto_definition(
+            r#"
+        fn foo(bar: i3$02) {}
+        "#
+        ), @"");
+    }
+
+    #[test]
+    fn field() {
+        insta::assert_snapshot!(goto_definition(
+            r#"
+        struct Foo { bar: i32 }
+        fn foo(foo: Foo) -> i32 {
+            foo.ba$0r
+        }
+        "#
+        ), @"mod.mun Field bar: i32 (bar)");
+    }
+
+    #[test]
+    fn tuple_field() {
+        insta::assert_snapshot!(goto_definition(
+            r#"
+        struct Foo(i32, f64);
+        fn foo(foo: Foo) -> f64 {
+            foo.$01
+        }
+        "#
+        ), @"mod.mun Field f64");
+    }
+
+    #[test]
+    fn record_field() {
+        insta::assert_snapshot!(goto_definition(
+            r#"
+        struct Foo { bar: i32 }
+        fn foo() {
+            let foo = Foo { ba$0r: 3 };
+        }
+        "#
+        ), @"mod.mun Field bar: i32 (bar)");
+    }
+
+    #[test]
+    fn record_field_shorthand() {
+        insta::assert_snapshot!(goto_definition(
+            r#"
+        struct Foo { bar: i32 }
+        fn foo() {
+            let bar = 3;
+            let foo = Foo { ba$0r };
+        }
+        "#
+        ), @"mod.mun Local bar (bar)");
+    }
+
+    #[test]
+    fn other_file() {
+        insta::assert_snapshot!(goto_definition(
+            r#"
+        //- /mod.mun
+        fn foo() {
+            bar::ba$0z()
+        }
+
+        //- /bar.mun
+        pub fn baz() {}
+        "#
+        ), @"bar.mun Function pub fn baz() {} (baz)");
+    }
+
+    #[test]
+    fn module() {
+        insta::assert_snapshot!(goto_definition(
+            r#"
+        //- /mod.mun
+        fn foo() {
+            ba$0r::baz()
+        }
+
+        //- /bar.mun
+        pub fn baz() {}
+        "#
+        ), @"bar.mun Module pub fn baz() {}");
+    }
+
+    #[test]
+    fn use_item() {
+        insta::assert_snapshot!(goto_definition(
+            r#"
+        //- /mod.mun
+        use bar::{Ba$0z, qux};
+
+        //- /bar.mun
+        pub struct Baz;
+        pub fn qux() {}
+        "#
+        ), @"bar.mun Struct pub struct Baz; (Baz)");
+    }
+
+    #[test]
+    fn imported_item() {
+        insta::assert_snapshot!(goto_definition(
+            r#"
+        //- /mod.mun
+        use bar::Baz;
+        fn foo(baz: Ba$0z) {}
+
+        //- /bar.mun
+        pub struct Baz;
+        "#
+        ), @"bar.mun Struct pub struct Baz; (Baz)");
+    }
+
+    #[test]
+    fn unresolved() {
+        insta::assert_snapshot!(goto_definition(
+            r#"
+        fn foo() {
+            ba$0r()
+        }
+        "#
+        ), @"");
+    }
+}
@@ -0,0 +1,132 @@
+//! Provides the [`goto_type_definition`] function, which computes the location where the type of
+//! the entity under the cursor is defined.
+
+use crate::{
+    db::AnalysisDatabase,
+    navigation_target::{NavigationTarget, ToNav},
+    FilePosition,
+};
+use hir::{semantics::Semantics, TyKind};
+use mun_syntax::{ast, utils::pick_best_token, AstNode};
+
+/// Returns the locations where the type of the expression or pattern at the specified `position`
+/// is defined. Returns `None` if there is no expression or pattern at the position, or if its type
+/// is not defined in the source code (e.g. a primitive type).
+pub(crate) fn goto_type_definition(
+    db: &AnalysisDatabase,
+    position: FilePosition,
+) -> Option<Vec<NavigationTarget>> {
+    let sema = Semantics::new(db);
+    let file = sema.parse(position.file_id);
+    let token = pick_best_token(file.syntax(), position.offset)?;
+
+    let ty = token.parent()?.ancestors().find_map(|node| {
+        if let Some(expr) = ast::Expr::cast(node.clone()) {
+            sema.type_of_expr(&expr)
+        } else {
+            ast::Pat::cast(node).and_then(|pat| sema.type_of_pat(&pat))
+        }
+    })?;
+
+    let nav = match ty.interned() {
+        TyKind::Struct(strukt) => strukt.to_nav(db),
+        TyKind::TypeAlias(type_alias) => type_alias.to_nav(db),
+        _ => return None,
+    };
+
+    Some(vec![nav])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::change_fixture::position;
+
+    /// Renders the locations that the cursor in the `fixture` navigates to.
+    fn goto_type_definition(fixture: &str) -> String {
+        let (db, position) = position(fixture);
+        super::goto_type_definition(&db, position)
+            .unwrap_or_default()
+            .iter()
+            .map(|nav| nav.debug_render(&db))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn local() {
+        insta::assert_snapshot!(goto_type_definition(
+            r#"
+        struct Foo;
+        fn foo() {
+            let bar = Foo;
+            let baz = ba$0r;
+        }
+        "#
+        ), @"mod.mun Struct struct Foo; (Foo)");
+    }
+
+    #[test]
+    fn binding() {
+        insta::assert_snapshot!(goto_type_definition(
+            r#"
+        struct Foo;
+        fn foo() {
+            let ba$0r = Foo;
+        }
+        "#
+        ), @"mod.mun Struct struct Foo; (Foo)");
+    }
+
+    #[test]
+    fn field() {
+        insta::assert_snapshot!(goto_type_definition(
+            r#"
+        struct Foo;
+        struct Bar { foo: Foo }
+        fn foo(bar: Bar) {
+            let foo = bar.fo$0o;
+        }
+        "#
+        ), @"mod.mun Struct struct Foo; (Foo)");
+    }
+
+    #[test]
+    fn call() {
+        insta::assert_snapshot!(goto_type_definition(
+            r#"
+        //- /mod.mun
+        fn foo() {
+            let ba$0z = bar::bar();
+        }
+
+        //- /bar.mun
+        pub struct Bar;
+        pub fn bar() -> Bar { Bar }
+        "#
+        ), @"bar.mun Struct pub struct Bar; (Bar)");
+    }
+
+    #[test]
+    fn type_alias() {
+        insta::assert_snapshot!(goto_type_definition(
+            r#"
+        struct Foo;
+        type Bar = Foo;
+        fn foo(bar: Bar) {
+            let baz = ba$0r;
+        }
+        "#
+        ), @"mod.mun Struct struct Foo; (Foo)");
+    }
+
+    #[test]
+    fn primitive() {
+        insta::assert_snapshot!(goto_type_definition(
+            r#"
+        fn foo() {
+            let ba$0r = 3;
+        }
+        "#
+        ), @"");
+    }
+}
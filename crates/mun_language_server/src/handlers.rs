@@ -1,4 +1,7 @@
-use crate::{from_lsp, state::LanguageServerSnapshot, to_lsp, FilePosition};
+use crate::{
+    from_lsp, navigation_target::NavigationTarget, semantic_tokens, state::LanguageServerSnapshot,
+    to_lsp, FilePosition,
+};
 use lsp_types::{CompletionContext, CompletionItem, DocumentSymbol};
 use mun_syntax::{AstNode, TextSize};
 
@@ -33,6 +36,37 @@ pub(crate) fn handle_document_symbol(
     Ok(Some(build_hierarchy_from_flat_list(parents).into()))
 }
 
+/// Searches the functions, structs and type aliases of all packages whose name fuzzily matches the
+/// query.
+pub(crate) fn handle_workspace_symbol(
+    snapshot: LanguageServerSnapshot,
+    params: lsp_types::WorkspaceSymbolParams,
+) -> anyhow::Result<Option<Vec<lsp_types::SymbolInformation>>> {
+    let symbols = snapshot
+        .analysis
+        .symbol_search(&params.query)?
+        .into_iter()
+        .map(|symbol| to_lsp::symbol_information(&snapshot, symbol))
+        .collect::<anyhow::Result<_>>()?;
+    Ok(Some(symbols))
+}
+
+/// Computes the ranges of a document that can be collapsed, like blocks and runs of comments.
+pub(crate) fn handle_folding_range(
+    snapshot: LanguageServerSnapshot,
+    params: lsp_types::FoldingRangeParams,
+) -> anyhow::Result<Option<Vec<lsp_types::FoldingRange>>> {
+    let file_id = from_lsp::file_id(&snapshot, &params.text_document.uri)?;
+    let line_index = snapshot.analysis.file_line_index(file_id)?;
+    let folds = snapshot
+        .analysis
+        .folding_ranges(file_id)?
+        .into_iter()
+        .map(|fold| to_lsp::folding_range(&line_index, fold))
+        .collect();
+    Ok(Some(folds))
+}
+
 /// Computes completion items that should be presented to the user when the cursor is at a specific
 /// location.
 pub(crate) fn handle_completion(
@@ -82,6 +116,302 @@ pub(crate) fn handle_completion(
     }
 }
 
+/// Computes the locations where the entity at a specific location in a document is defined.
+pub(crate) fn handle_goto_definition(
+    snapshot: LanguageServerSnapshot,
+    params: lsp_types::GotoDefinitionParams,
+) -> anyhow::Result<Option<lsp_types::GotoDefinitionResponse>> {
+    let position = from_lsp::file_position(&snapshot, params.text_document_position_params)?;
+    let targets = match snapshot.analysis.goto_definition(position)? {
+        None => return Ok(None),
+        Some(targets) => targets,
+    };
+    goto_response(&snapshot, targets).map(Some)
+}
+
+/// Computes the locations where the type of the entity at a specific location in a document is
+/// defined.
+pub(crate) fn handle_goto_type_definition(
+    snapshot: LanguageServerSnapshot,
+    params: lsp_types::request::GotoTypeDefinitionParams,
+) -> anyhow::Result<Option<lsp_types::request::GotoTypeDefinitionResponse>> {
+    let position = from_lsp::file_position(&snapshot, params.text_document_position_params)?;
+    let targets = match snapshot.analysis.goto_type_definition(position)? {
+        None => return Ok(None),
+        Some(targets) => targets,
+    };
+    goto_response(&snapshot, targets).map(Some)
+}
+
+/// Computes the information to show when hovering over a specific location in a document.
+pub(crate) fn handle_hover(
+    snapshot: LanguageServerSnapshot,
+    params: lsp_types::HoverParams,
+) -> anyhow::Result<Option<lsp_types::Hover>> {
+    let position = from_lsp::file_position(&snapshot, params.text_document_position_params)?;
+    let result = match snapshot.analysis.hover(position)? {
+        None => return Ok(None),
+        Some(result) => result,
+    };
+    let line_index = snapshot.analysis.file_line_index(position.file_id)?;
+    Ok(Some(lsp_types::Hover {
+        contents: lsp_types::HoverContents::Markup(lsp_types::MarkupContent {
+            kind: lsp_types::MarkupKind::Markdown,
+            value: result.markup,
+        }),
+        range: Some(to_lsp::range(result.range, &line_index)),
+    }))
+}
+
+/// Computes the inlay hints in the requested range of a document. The kinds of hints that are
+/// returned are determined by the configuration of the server.
+pub(crate) fn handle_inlay_hints(
+    snapshot: LanguageServerSnapshot,
+    params: lsp_types::InlayHintParams,
+) -> anyhow::Result<Option<Vec<lsp_types::InlayHint>>> {
+    let file_id = from_lsp::file_id(&snapshot, &params.text_document.uri)?;
+    let line_index = snapshot.analysis.file_line_index(file_id)?;
+    let range = from_lsp::text_range(&line_index, params.range);
+
+    let hints = snapshot
+        .analysis
+        .inlay_hints(file_id, &snapshot.config.inlay_hints)?
+        .into_iter()
+        .filter(|hint| range.contains_inclusive(hint.offset()))
+        .map(|hint| to_lsp::inlay_hint(&line_index, hint))
+        .collect();
+    Ok(Some(hints))
+}
+
+/// Computes the signature of the function that is called at the cursor, so it can be shown while
+/// the arguments are typed.
+pub(crate) fn handle_signature_help(
+    snapshot: LanguageServerSnapshot,
+    params: lsp_types::SignatureHelpParams,
+) -> anyhow::Result<Option<lsp_types::SignatureHelp>> {
+    let position = from_lsp::file_position(&snapshot, params.text_document_position_params)?;
+    let help = match snapshot.analysis.signature_help(position)? {
+        None => return Ok(None),
+        Some(help) => help,
+    };
+    Ok(Some(to_lsp::signature_help(help)))
+}
+
+/// Computes the locations of all references to the entity at a specific location in a document.
+pub(crate) fn handle_references(
+    snapshot: LanguageServerSnapshot,
+    params: lsp_types::ReferenceParams,
+) -> anyhow::Result<Option<Vec<lsp_types::Location>>> {
+    let position = from_lsp::file_position(&snapshot, params.text_document_position)?;
+    let result = match snapshot.analysis.find_all_refs(position)? {
+        None => return Ok(None),
+        Some(result) => result,
+    };
+
+    let declaration = result
+        .declaration
+        .filter(|_| params.context.include_declaration)
+        .map(|nav| to_lsp::location_from_nav(&snapshot, &nav));
+    let references = result
+        .references
+        .into_iter()
+        .map(|reference| to_lsp::location(&snapshot, reference.file_range));
+
+    let locations = declaration
+        .into_iter()
+        .chain(references)
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(Some(locations))
+}
+
+/// Computes the ranges in a document to highlight because they refer to the entity at a specific
+/// location in that document.
+pub(crate) fn handle_document_highlight(
+    snapshot: LanguageServerSnapshot,
+    params: lsp_types::DocumentHighlightParams,
+) -> anyhow::Result<Option<Vec<lsp_types::DocumentHighlight>>> {
+    let position = from_lsp::file_position(&snapshot, params.text_document_position_params)?;
+    let highlights = match snapshot.analysis.document_highlight(position)? {
+        None => return Ok(None),
+        Some(highlights) => highlights,
+    };
+
+    let line_index = snapshot.analysis.file_line_index(position.file_id)?;
+    let highlights = highlights
+        .into_iter()
+        .map(|highlight| lsp_types::DocumentHighlight {
+            range: to_lsp::range(highlight.range, &line_index),
+            kind: Some(to_lsp::document_highlight_kind(highlight.access)),
+        })
+        .collect();
+    Ok(Some(highlights))
+}
+
+/// Computes the range of the identifier at a specific location if the entity it refers to can be
+/// renamed.
+pub(crate) fn handle_prepare_rename(
+    snapshot: LanguageServerSnapshot,
+    params: lsp_types::TextDocumentPositionParams,
+) -> anyhow::Result<Option<lsp_types::PrepareRenameResponse>> {
+    let position = from_lsp::file_position(&snapshot, params)?;
+    let range = snapshot.analysis.prepare_rename(position)??;
+    let line_index = snapshot.analysis.file_line_index(range.file_id)?;
+    Ok(Some(lsp_types::PrepareRenameResponse::Range(
+        to_lsp::range(range.range, &line_index),
+    )))
+}
+
+/// Computes the edits to the workspace that rename the entity at a specific location and all
+/// references to it.
+pub(crate) fn handle_rename(
+    snapshot: LanguageServerSnapshot,
+    params: lsp_types::RenameParams,
+) -> anyhow::Result<Option<lsp_types::WorkspaceEdit>> {
+    let position = from_lsp::file_position(&snapshot, params.text_document_position)?;
+    let source_change = snapshot
+        .analysis
+        .rename(position, params.new_name.trim())??;
+    Ok(Some(to_lsp::workspace_edit(&snapshot, source_change)?))
+}
+
+/// Computes the quick fixes for the diagnostics that overlap with a specific range in a document.
+pub(crate) fn handle_code_action(
+    snapshot: LanguageServerSnapshot,
+    params: lsp_types::CodeActionParams,
+) -> anyhow::Result<Option<lsp_types::CodeActionResponse>> {
+    let file_id = from_lsp::file_id(&snapshot, &params.text_document.uri)?;
+    let line_index = snapshot.analysis.file_line_index(file_id)?;
+    let range = from_lsp::text_range(&line_index, params.range);
+
+    let mut actions = Vec::new();
+    for diagnostic in snapshot.analysis.diagnostics(file_id)? {
+        if diagnostic.range.intersect(range).is_none() {
+            continue;
+        }
+
+        let lsp_diagnostic = to_lsp::diagnostic(&snapshot, &diagnostic, &line_index)?;
+        for fix in diagnostic.fixes {
+            actions.push(lsp_types::CodeActionOrCommand::CodeAction(
+                lsp_types::CodeAction {
+                    title: fix.label,
+                    kind: Some(lsp_types::CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![lsp_diagnostic.clone()]),
+                    edit: Some(to_lsp::workspace_edit(&snapshot, fix.source_change)?),
+                    ..Default::default()
+                },
+            ));
+        }
+    }
+    Ok(Some(actions))
+}
+
+/// Computes the semantic tokens of a document and remembers them so that a subsequent delta request
+/// can be answered with only the tokens that changed.
+pub(crate) fn handle_semantic_tokens_full(
+    snapshot: LanguageServerSnapshot,
+    params: lsp_types::SemanticTokensParams,
+) -> anyhow::Result<Option<lsp_types::SemanticTokensResult>> {
+    let file_id = from_lsp::file_id(&snapshot, &params.text_document.uri)?;
+    let text = snapshot.analysis.file_text(file_id)?;
+    let line_index = snapshot.analysis.file_line_index(file_id)?;
+
+    let highlights = snapshot.analysis.highlight(file_id)?;
+    let semantic_tokens = to_lsp::semantic_tokens(&text, &line_index, highlights);
+
+    snapshot
+        .semantic_tokens_cache
+        .lock()
+        .insert(params.text_document.uri, semantic_tokens.clone());
+
+    Ok(Some(semantic_tokens.into()))
+}
+
+/// Computes the semantic tokens of a document. If the client still has the tokens that were last
+/// sent for the document, only the edits with respect to those tokens are returned.
+pub(crate) fn handle_semantic_tokens_full_delta(
+    snapshot: LanguageServerSnapshot,
+    params: lsp_types::SemanticTokensDeltaParams,
+) -> anyhow::Result<Option<lsp_types::SemanticTokensFullDeltaResult>> {
+    let file_id = from_lsp::file_id(&snapshot, &params.text_document.uri)?;
+    let text = snapshot.analysis.file_text(file_id)?;
+    let line_index = snapshot.analysis.file_line_index(file_id)?;
+
+    let highlights = snapshot.analysis.highlight(file_id)?;
+    let semantic_tokens = to_lsp::semantic_tokens(&text, &line_index, highlights);
+
+    let mut cache = snapshot.semantic_tokens_cache.lock();
+    let cached_tokens = cache.entry(params.text_document.uri).or_default();
+
+    if cached_tokens.result_id.as_ref() == Some(&params.previous_result_id) {
+        let delta = lsp_types::SemanticTokensDelta {
+            result_id: semantic_tokens.result_id.clone(),
+            edits: semantic_tokens::diff_tokens(&cached_tokens.data, &semantic_tokens.data),
+        };
+        *cached_tokens = semantic_tokens;
+        return Ok(Some(delta.into()));
+    }
+
+    *cached_tokens = semantic_tokens.clone();
+    Ok(Some(semantic_tokens.into()))
+}
+
+/// Computes the edits that format a document according to the format options of the package that
+/// contains it.
+pub(crate) fn handle_formatting(
+    snapshot: LanguageServerSnapshot,
+    params: lsp_types::DocumentFormattingParams,
+) -> anyhow::Result<Option<Vec<lsp_types::TextEdit>>> {
+    format(&snapshot, &params.text_document.uri, None)
+}
+
+/// Computes the edits that format a range of a document according to the format options of the
+/// package that contains it.
+pub(crate) fn handle_range_formatting(
+    snapshot: LanguageServerSnapshot,
+    params: lsp_types::DocumentRangeFormattingParams,
+) -> anyhow::Result<Option<Vec<lsp_types::TextEdit>>> {
+    format(&snapshot, &params.text_document.uri, Some(params.range))
+}
+
+/// Computes the edits that format the document at `url`, or only the specified `range` of it.
+/// Documents that contain syntax errors are not formatted.
+fn format(
+    snapshot: &LanguageServerSnapshot,
+    url: &lsp_types::Url,
+    range: Option<lsp_types::Range>,
+) -> anyhow::Result<Option<Vec<lsp_types::TextEdit>>> {
+    let file_id = from_lsp::file_id(snapshot, url)?;
+    let line_index = snapshot.analysis.file_line_index(file_id)?;
+    let range = range.map(|range| from_lsp::text_range(&line_index, range));
+
+    // Use the format options of the package that contains the document, if any
+    let path = from_lsp::abs_path(url)?;
+    let options = snapshot
+        .packages
+        .iter()
+        .find(|package| path.starts_with(package.source_directory()))
+        .map(|package| package.manifest().format_options().clone())
+        .unwrap_or_default();
+
+    let edit = match snapshot.analysis.format(file_id, &options, range)? {
+        None => return Ok(None),
+        Some(edit) => edit,
+    };
+    Ok(Some(to_lsp::text_edits(edit, &line_index)))
+}
+
+/// Converts a list of navigation targets to a response for a goto request.
+fn goto_response(
+    snapshot: &LanguageServerSnapshot,
+    targets: Vec<NavigationTarget>,
+) -> anyhow::Result<lsp_types::GotoDefinitionResponse> {
+    let locations = targets
+        .iter()
+        .map(|nav| to_lsp::location_from_nav(snapshot, nav))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(locations.into())
+}
+
 /// Constructs a hierarchy of DocumentSymbols for a list of symbols that specify which index is the
 /// parent of a symbol. The parent index must always be smaller than the current index.
 fn build_hierarchy_from_flat_list(
@@ -0,0 +1,511 @@
+//! Provides the [`hover`] function, which computes the information that is shown when hovering
+//! over an entity in the source code.
+
+use crate::{
+    db::AnalysisDatabase,
+    definition::{Definition, NameRefClass},
+    signature_help::SignatureHelp,
+    FilePosition,
+};
+use hir::{semantics::Semantics, HasSource, HirDisplay, StructMemoryKind};
+use mun_syntax::{
+    ast::{self, VisibilityOwner},
+    utils::pick_best_token,
+    AstNode, TextRange,
+};
+use std::fmt::Write;
+
+/// The information that is shown when hovering over an entity in the source code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HoverResult {
+    /// The range in the source code that the information applies to
+    pub range: TextRange,
+
+    /// The information formatted as markdown
+    pub markup: String,
+}
+
+/// Returns the information that should be shown when hovering over the specified `position`. If
+/// the position refers to a definition, like a function or a local, its signature is shown.
+/// Otherwise, the type of the expression or pattern at the position is shown.
+pub(crate) fn hover(db: &AnalysisDatabase, position: FilePosition) -> Option<HoverResult> {
+    let sema = Semantics::new(db);
+    let file = sema.parse(position.file_id);
+    let token = pick_best_token(file.syntax(), position.offset)?;
+    if token.kind().is_trivia() {
+        return None;
+    }
+    let parent = token.parent()?;
+
+    let definition = if let Some(name_ref) = ast::NameRef::cast(parent.clone()) {
+        NameRefClass::classify(&sema, &name_ref).map(NameRefClass::referenced)
+    } else if let Some(name) = ast::Name::cast(parent.clone()) {
+        Definition::from_name(&sema, &name)
+    } else {
+        ast::FieldExpr::cast(parent.clone())
+            .and_then(|field_expr| sema.resolve_field(&field_expr))
+            .map(Definition::Field)
+    };
+
+    if let Some(markup) = definition.and_then(|def| hover_for_definition(db, def)) {
+        return Some(HoverResult {
+            range: token.text_range(),
+            markup,
+        });
+    }
+
+    // Fall back to the type of the expression or pattern under the cursor
+    parent.ancestors().find_map(|node| {
+        let ty = if let Some(expr) = ast::Expr::cast(node.clone()) {
+            sema.type_of_expr(&expr)
+        } else {
+            sema.type_of_pat(&ast::Pat::cast(node.clone())?)
+        }?;
+        if ty.is_unknown() {
+            return None;
+        }
+        Some(HoverResult {
+            range: node.text_range(),
+            markup: code_block(ty.display(db)),
+        })
+    })
+}
+
+/// Returns the markdown that describes the specified definition.
+fn hover_for_definition(db: &AnalysisDatabase, def: Definition) -> Option<String> {
+    let markup = match def {
+        Definition::ModuleDef(hir::ModuleDef::Module(module)) => {
+            code_block(format_args!("mod {}", module.full_name(db)))
+        }
+        Definition::ModuleDef(hir::ModuleDef::Function(function)) => with_container(
+            &function.module(db).full_name(db),
+            &render_function(db, function)?,
+        ),
+        Definition::ModuleDef(hir::ModuleDef::Struct(strukt)) => {
+            with_container(&strukt.module(db).full_name(db), &render_struct(db, strukt))
+        }
+        Definition::ModuleDef(hir::ModuleDef::TypeAlias(type_alias)) => {
+            let src = type_alias.source(db);
+            with_container(
+                &type_alias.module(db).full_name(db),
+                &format!(
+                    "{}type {} = {}",
+                    visibility_prefix(src.value.visibility()),
+                    type_alias.name(db),
+                    type_alias.target_type(db).display(db)
+                ),
+            )
+        }
+        Definition::ModuleDef(hir::ModuleDef::PrimitiveType(ty)) => code_block(ty),
+        Definition::Field(field) => {
+            let visibility = match field.source(db).value {
+                hir::FieldSource::Named(it) => visibility_prefix(it.visibility()),
+                hir::FieldSource::Pos(it) => visibility_prefix(it.visibility()),
+            };
+            with_container(
+                &field.parent_struct().full_name(db),
+                &format!(
+                    "{}{}: {}",
+                    visibility,
+                    field.name(db),
+                    field.ty(db).display(db)
+                ),
+            )
+        }
+        Definition::Local(local) => {
+            let is_param = local
+                .source(db)
+                .value
+                .syntax()
+                .parent()
+                .map_or(false, |parent| ast::Param::can_cast(parent.kind()));
+            code_block(format_args!(
+                "{}{}: {}",
+                if is_param { "" } else { "let " },
+                local.name(db)?,
+                local.ty(db).display(db)
+            ))
+        }
+    };
+    Some(markup)
+}
+
+/// Renders the signature of a function, e.g. `pub fn foo(a: i32) -> i32`.
+fn render_function(db: &AnalysisDatabase, function: hir::Function) -> Option<String> {
+    let src = function.source(db).value;
+    let help = SignatureHelp::for_function(db, function)?;
+    Some(format!(
+        "{}{}",
+        visibility_prefix(src.visibility()),
+        help.signature
+    ))
+}
+
+/// Renders the definition of a struct including its memory kind and fields, e.g.
+/// `struct(gc) Foo { a: i32 }`.
+fn render_struct(db: &AnalysisDatabase, strukt: hir::Struct) -> String {
+    let src = strukt.source(db).value;
+    let data = strukt.data(db);
+    let memory_kind = match data.memory_kind {
+        StructMemoryKind::Gc => "gc",
+        StructMemoryKind::Value => "value",
+    };
+
+    let mut result = format!(
+        "{}struct({}) {}",
+        visibility_prefix(src.visibility()),
+        memory_kind,
+        strukt.name(db)
+    );
+    let fields = strukt.fields(db);
+    match data.kind {
+        hir::StructKind::Record => {
+            result.push_str(" {\n");
+            for field in fields {
+                let _ = writeln!(
+                    result,
+                    "    {}: {},",
+                    field.name(db),
+                    field.ty(db).display(db)
+                );
+            }
+            result.push('}');
+        }
+        hir::StructKind::Tuple => {
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|field| field.ty(db).display(db).to_string())
+                .collect();
+            let _ = write!(result, "({})", fields.join(", "));
+        }
+        hir::StructKind::Unit => {}
+    }
+    result
+}
+
+/// Returns the visibility of an item as it is written in the source code, followed by a space.
+fn visibility_prefix(visibility: Option<ast::Visibility>) -> String {
+    visibility
+        .map(|visibility| format!("{} ", visibility.syntax().text()))
+        .unwrap_or_default()
+}
+
+/// Renders the `definition` preceded by the path of its container, if any.
+fn with_container(container: &str, definition: &str) -> String {
+    if container.is_empty() {
+        code_block(definition)
+    } else {
+        format!("{}\n\n{}", code_block(container), code_block(definition))
+    }
+}
+
+/// Renders the `code` as a markdown code block.
+fn code_block(code: impl std::fmt::Display) -> String {
+    format!("```mun\n{}\n```", code)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::change_fixture::position;
+
+    /// Renders the hover information at the cursor in the `fixture`.
+    fn hover(fixture: &str) -> String {
+        let (db, position) = position(fixture);
+        match super::hover(&db, position) {
+            Some(result) => {
+                let text = hir::SourceDatabase::file_text(&db, position.file_id);
+                format!("{}\n---\n{}", &text[result.range], result.markup)
+            }
+            None => String::new(),
+        }
+    }
+
+    #[test]
+    fn local() {
+        insta::assert_snapshot!(hover(
+            r#"
+        fn foo() {
+            let bar = 5;
+            let baz = ba$0r + 1;
+        }
+        "#
+        ), @r###"
+        bar
+        ---
+        ```mun
+        let bar: i32
+        ```
+        "###);
+    }
+
+    #[test]
+    fn binding() {
+        insta::assert_snapshot!(hover(
+            r#"
+        fn foo() {
+            let ba$0r = 5.0;
+        }
+        "#
+        ), @r###"
+        bar
+        ---
+        ```mun
+        let bar: f64
+        ```
+        "###);
+    }
+
+    #[test]
+    fn param() {
+        insta::assert_snapshot!(hover(
+            r#"
+        fn foo(bar: i32) -> i32 {
+            ba$0r
+        }
+        "#
+        ), @r###"
+        bar
+        ---
+        ```mun
+        bar: i32
+        ```
+        "###);
+    }
+
+    #[test]
+    fn function() {
+        insta::assert_snapshot!(hover(
+            r#"
+        pub fn bar(a: i32, b: f64) -> f64 { b }
+        fn foo() {
+            ba$0r(3, 4.0);
+        }
+        "#
+        ), @r###"
+        bar
+        ---
+        ```mun
+        pub fn bar(a: i32, b: f64) -> f64
+        ```
+        "###);
+    }
+
+    #[test]
+    fn function_definition() {
+        insta::assert_snapshot!(hover(
+            r#"
+        fn fo$0o() {}
+        "#
+        ), @r###"
+        foo
+        ---
+        ```mun
+        fn foo()
+        ```
+        "###);
+    }
+
+    #[test]
+    fn extern_function() {
+        insta::assert_snapshot!(hover(
+            r#"
+        extern fn bar(a: i32);
+        fn foo() {
+            ba$0r(3);
+        }
+        "#
+        ), @r###"
+        bar
+        ---
+        ```mun
+        extern fn bar(a: i32)
+        ```
+        "###);
+    }
+
+    #[test]
+    fn function_in_module() {
+        insta::assert_snapshot!(hover(
+            r#"
+        //- /mod.mun
+        fn foo() {
+            bar::ba$0z();
+        }
+
+        //- /bar.mun
+        pub(package) fn baz() -> bool { true }
+        "#
+        ), @r###"
+        baz
+        ---
+        ```mun
+        bar
+        ```
+
+        ```mun
+        pub(package) fn baz() -> bool
+        ```
+        "###);
+    }
+
+    #[test]
+    fn gc_struct() {
+        insta::assert_snapshot!(hover(
+            r#"
+        pub struct Fo$0o {
+            a: i32,
+            b: Bar,
+        }
+        struct(value) Bar(f64, bool);
+        "#
+        ), @r###"
+        Foo
+        ---
+        ```mun
+        pub struct(gc) Foo {
+            a: i32,
+            b: Bar,
+        }
+        ```
+        "###);
+    }
+
+    #[test]
+    fn value_struct() {
+        insta::assert_snapshot!(hover(
+            r#"
+        struct(value) Foo(f64, bool);
+        fn foo() {
+            let foo = Fo$0o(3.0, true);
+        }
+        "#
+        ), @r###"
+        Foo
+        ---
+        ```mun
+        struct(value) Foo(f64, bool)
+        ```
+        "###);
+    }
+
+    #[test]
+    fn unit_struct() {
+        insta::assert_snapshot!(hover(
+            r#"
+        struct Foo;
+        fn foo(foo: Fo$0o) {}
+        "#
+        ), @r###"
+        Foo
+        ---
+        ```mun
+        struct(gc) Foo
+        ```
+        "###);
+    }
+
+    #[test]
+    fn field() {
+        insta::assert_snapshot!(hover(
+            r#"
+        struct Foo { pub bar: f32 }
+        fn foo(foo: Foo) -> f32 {
+            foo.ba$0r
+        }
+        "#
+        ), @r###"
+        bar
+        ---
+        ```mun
+        Foo
+        ```
+
+        ```mun
+        pub bar: f32
+        ```
+        "###);
+    }
+
+    #[test]
+    fn tuple_field() {
+        insta::assert_snapshot!(hover(
+            r#"
+        struct Foo(i32, f64);
+        fn foo(foo: Foo) -> f64 {
+            foo.$01
+        }
+        "#
+        ), @r###"
+        .1
+        ---
+        ```mun
+        Foo
+        ```
+
+        ```mun
+        1: f64
+        ```
+        "###);
+    }
+
+    #[test]
+    fn type_alias() {
+        insta::assert_snapshot!(hover(
+            r#"
+        struct Foo;
+        type Bar = Foo;
+        fn foo(bar: Ba$0r) {}
+        "#
+        ), @r###"
+        Bar
+        ---
+        ```mun
+        type Bar = Foo
+        ```
+        "###);
+    }
+
+    #[test]
+    fn primitive_type() {
+        insta::assert_snapshot!(hover(
+            r#"
+        fn foo(bar: i6$04) {}
+        "#
+        ), @r###"
+        i64
+        ---
+        ```mun
+        i64
+        ```
+        "###);
+    }
+
+    #[test]
+    fn expression() {
+        insta::assert_snapshot!(hover(
+            r#"
+        fn foo() -> i32 {
+            let a = 3;
+            (a $0+ 4) * 2
+        }
+        "#
+        ), @r###"
+        a + 4
+        ---
+        ```mun
+        i32
+        ```
+        "###);
+    }
+
+    #[test]
+    fn nothing() {
+        insta::assert_snapshot!(hover(
+            r#"
+        fn foo() {
+            $0
+        }
+        "#
+        ), @"");
+    }
+}
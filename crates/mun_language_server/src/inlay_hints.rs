@@ -0,0 +1,305 @@
+//! Provides the [`inlay_hints`] function, which computes the hints that an editor shows inline
+//! with the source code, like the inferred types of `let` bindings.
+
+use crate::{config::InlayHintsConfig, db::AnalysisDatabase};
+use hir::{semantics::Semantics, HasSource, HirDisplay};
+use mun_syntax::{
+    ast::{self, ArgListOwner, TypeAscriptionOwner},
+    AstNode, TextRange, TextSize,
+};
+
+/// The kind of an [`InlayHint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InlayKind {
+    /// The inferred type of a `let` binding
+    Type,
+
+    /// The name of the parameter that an argument is passed to
+    Parameter,
+
+    /// The type of an intermediate expression in a chain of field accesses
+    Chaining,
+}
+
+/// A hint that is shown inline with the source code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlayHint {
+    /// The range of the source code that the hint applies to. Type and chaining hints are shown
+    /// after the range, parameter hints in front of it.
+    pub range: TextRange,
+    pub kind: InlayKind,
+    pub label: String,
+}
+
+impl InlayHint {
+    /// Returns the offset at which the hint is shown.
+    pub fn offset(&self) -> TextSize {
+        match self.kind {
+            InlayKind::Type | InlayKind::Chaining => self.range.end(),
+            InlayKind::Parameter => self.range.start(),
+        }
+    }
+}
+
+/// Returns the inlay hints of the specified file, in the order in which they appear. The `config`
+/// determines which kinds of hints are returned.
+pub(crate) fn inlay_hints(
+    db: &AnalysisDatabase,
+    file_id: hir::FileId,
+    config: &InlayHintsConfig,
+) -> Vec<InlayHint> {
+    let sema = Semantics::new(db);
+    let file = sema.parse(file_id);
+
+    let mut hints = Vec::new();
+    for node in file.syntax().descendants() {
+        if let Some(let_stmt) = ast::LetStmt::cast(node.clone()) {
+            if config.type_hints {
+                type_hint(&sema, &mut hints, &let_stmt);
+            }
+        } else if let Some(call_expr) = ast::CallExpr::cast(node.clone()) {
+            if config.parameter_hints {
+                parameter_hints(&sema, &mut hints, &call_expr);
+            }
+        } else if let Some(field_expr) = ast::FieldExpr::cast(node) {
+            if config.chaining_hints {
+                chaining_hint(&sema, &mut hints, &field_expr);
+            }
+        }
+    }
+
+    hints.sort_by_key(InlayHint::offset);
+    hints
+}
+
+/// Adds the inferred type of a `let` binding without a type annotation.
+fn type_hint(sema: &Semantics, hints: &mut Vec<InlayHint>, let_stmt: &ast::LetStmt) {
+    if let_stmt.ascribed_type().is_some() {
+        return;
+    }
+
+    let pat = match let_stmt.pat() {
+        Some(pat) => pat,
+        None => return,
+    };
+    let ty = match sema.type_of_pat(&pat) {
+        Some(ty) if !ty.is_unknown() => ty,
+        _ => return,
+    };
+
+    hints.push(InlayHint {
+        range: pat.syntax().text_range(),
+        kind: InlayKind::Type,
+        label: ty.display(sema.db).to_string(),
+    });
+}
+
+/// Adds the names of the parameters in front of the arguments of a function call. Arguments that
+/// are named after their parameter are skipped.
+fn parameter_hints(sema: &Semantics, hints: &mut Vec<InlayHint>, call_expr: &ast::CallExpr) {
+    let function = match call_expr
+        .expr()
+        .and_then(|callee| sema.type_of_expr(&callee))
+        .and_then(|ty| ty.as_callable_def())
+    {
+        Some(hir::CallableDef::Function(function)) => function,
+        _ => return,
+    };
+    let (params, args) = match (
+        function.source(sema.db).value.param_list(),
+        call_expr.arg_list(),
+    ) {
+        (Some(params), Some(args)) => (params, args),
+        _ => return,
+    };
+
+    for (param, arg) in params.params().zip(args.args()) {
+        let param_name = match param.pat() {
+            Some(pat) => pat.syntax().text().to_string(),
+            None => continue,
+        };
+        if param_name.starts_with('_') || arg.syntax().text() == param_name.as_str() {
+            continue;
+        }
+
+        hints.push(InlayHint {
+            range: arg.syntax().text_range(),
+            kind: InlayKind::Parameter,
+            label: param_name,
+        });
+    }
+}
+
+/// Adds the type of the receiver of a field access if the receiver is itself a field access, e.g.
+/// the type of `a.b` in `a.b.c`.
+fn chaining_hint(sema: &Semantics, hints: &mut Vec<InlayHint>, field_expr: &ast::FieldExpr) {
+    let receiver = match field_expr.expr() {
+        Some(receiver) => receiver,
+        None => return,
+    };
+    if !ast::FieldExpr::can_cast(receiver.syntax().kind()) {
+        return;
+    }
+    let ty = match sema.type_of_expr(&receiver) {
+        Some(ty) if !ty.is_unknown() => ty,
+        _ => return,
+    };
+
+    hints.push(InlayHint {
+        range: receiver.syntax().text_range(),
+        kind: InlayKind::Chaining,
+        label: ty.display(sema.db).to_string(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{change_fixture::ChangeFixture, config::InlayHintsConfig, db::AnalysisDatabase};
+    use hir::SourceDatabase;
+
+    /// Renders the inlay hints of the single file in the `fixture` by inserting them into its text.
+    fn inlay_hints(fixture: &str, config: InlayHintsConfig) -> String {
+        let change_fixture = ChangeFixture::parse(fixture);
+        let mut db = AnalysisDatabase::default();
+        db.apply_change(change_fixture.change);
+
+        let file_id = change_fixture.files[0];
+        let mut text = db.file_text(file_id).to_string();
+        for hint in super::inlay_hints(&db, file_id, &config).iter().rev() {
+            let label = match hint.kind {
+                super::InlayKind::Type | super::InlayKind::Chaining => {
+                    format!("[: {}]", hint.label)
+                }
+                super::InlayKind::Parameter => format!("[{}:]", hint.label),
+            };
+            text.insert_str(usize::from(hint.offset()), &label);
+        }
+        text
+    }
+
+    #[test]
+    fn test_type_hints() {
+        insta::assert_snapshot!(inlay_hints(
+            r#"
+        struct Foo { a: i32 }
+
+        fn main() {
+            let a = 5;
+            let b: f32 = 1.0;
+            let foo = Foo { a };
+            let c = foo.a + a;
+            let d = b > 0.0;
+        }
+        "#,
+            InlayHintsConfig::default(),
+        ), @r###"
+        struct Foo { a: i32 }
+
+        fn main() {
+            let a[: i32] = 5;
+            let b: f32 = 1.0;
+            let foo[: Foo] = Foo { a };
+            let c[: i32] = foo.a + a;
+            let d[: bool] = b > 0.0;
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_type_hint_unknown_type() {
+        insta::assert_snapshot!(inlay_hints(
+            r#"
+        fn main() {
+            let a = unknown();
+        }
+        "#,
+            InlayHintsConfig::default(),
+        ), @r###"
+        fn main() {
+            let a = unknown();
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_parameter_hints() {
+        insta::assert_snapshot!(inlay_hints(
+            r#"
+        extern fn log(message: i32, level: i32);
+        fn add(a: i32, b: i32, _c: i32) -> i32 { a + b }
+        struct Bar(i32);
+
+        fn main(b: i32) {
+            log(add(1, b, 3), 2);
+            let bar: Bar = Bar(1);
+        }
+        "#,
+            InlayHintsConfig::default(),
+        ), @r###"
+        extern fn log(message: i32, level: i32);
+        fn add(a: i32, b: i32, _c: i32) -> i32 { a + b }
+        struct Bar(i32);
+
+        fn main(b: i32) {
+            log([message:]add([a:]1, b, 3), [level:]2);
+            let bar: Bar = Bar(1);
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_chaining_hints() {
+        insta::assert_snapshot!(inlay_hints(
+            r#"
+        struct Foo { bar: Bar }
+        struct Bar { baz: Baz }
+        struct(value) Baz(f32, bool);
+
+        fn main(foo: Foo) -> bool {
+            foo.bar
+                .baz
+                .1
+        }
+        "#,
+            InlayHintsConfig::default(),
+        ), @r###"
+        struct Foo { bar: Bar }
+        struct Bar { baz: Baz }
+        struct(value) Baz(f32, bool);
+
+        fn main(foo: Foo) -> bool {
+            foo.bar[: Bar]
+                .baz[: Baz]
+                .1
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_disabled_hints() {
+        insta::assert_snapshot!(inlay_hints(
+            r#"
+        struct Foo { bar: Bar }
+        struct Bar { baz: i32 }
+        fn add(a: i32, b: i32) -> i32 { a + b }
+
+        fn main(foo: Foo) {
+            let a = add(foo.bar.baz, 2);
+        }
+        "#,
+            InlayHintsConfig {
+                type_hints: false,
+                parameter_hints: true,
+                chaining_hints: false,
+            },
+        ), @r###"
+        struct Foo { bar: Bar }
+        struct Bar { baz: i32 }
+        fn add(a: i32, b: i32) -> i32 { a + b }
+
+        fn main(foo: Foo) {
+            let a = add([a:]foo.bar.baz, [b:]2);
+        }
+        "###);
+    }
+}
@@ -2,7 +2,7 @@ use std::convert::TryFrom;
 
 use serde::{de::DeserializeOwned, Serialize};
 
-pub use config::{Config, FilesWatcher};
+pub use config::{Config, FilesWatcher, InlayHintsConfig};
 pub use main_loop::main_loop;
 use mun_syntax::{TextRange, TextSize};
 use paths::AbsPathBuf;
@@ -19,14 +19,29 @@ mod change_fixture;
 mod completion;
 mod config;
 mod db;
+mod definition;
 mod diagnostics;
+mod document_highlight;
 mod file_structure;
+mod folding_ranges;
 mod from_lsp;
+mod goto_definition;
+mod goto_type_definition;
 mod handlers;
+mod hover;
+mod inlay_hints;
 mod lsp_utils;
 mod main_loop;
+mod navigation_target;
+mod references;
+mod rename;
+mod semantic_tokens;
+mod signature_help;
+mod source_change;
 mod state;
+mod symbol_index;
 mod symbol_kind;
+mod syntax_highlighting;
 mod to_lsp;
 
 /// Represents a position in a file
@@ -142,6 +157,13 @@ pub fn run_server() -> anyhow::Result<()> {
         }
         config.discovered_projects = Some(discovered);
 
+        // Apply the options of the client
+        if let Some(options) = initialize_params.initialization_options {
+            if let Err(e) = config.update(options) {
+                log::error!("invalid initialization options: {}", e);
+            }
+        }
+
         config
     };
 
@@ -0,0 +1,156 @@
+use crate::SymbolKind;
+use hir::{HasSource, InFile};
+use mun_syntax::{
+    ast::{self, NameOwner},
+    AstNode, TextRange,
+};
+
+/// A location in the source code that an editor can navigate to, like the definition of a
+/// function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NavigationTarget {
+    /// The file that contains the target
+    pub file_id: hir::FileId,
+
+    /// The range of the entire target, e.g. a function including its body
+    pub full_range: TextRange,
+
+    /// The range that is selected when navigating to the target, usually its name. If `None`, the
+    /// `full_range` is selected.
+    pub focus_range: Option<TextRange>,
+
+    /// The name of the target
+    pub name: String,
+
+    /// The type of symbol that the target refers to
+    pub kind: Option<SymbolKind>,
+}
+
+impl NavigationTarget {
+    /// Returns the range that is selected when navigating to this target.
+    pub fn focus_or_full_range(&self) -> TextRange {
+        self.focus_range.unwrap_or(self.full_range)
+    }
+
+    /// Constructs a `NavigationTarget` from a declaration that has a name.
+    pub(crate) fn from_named<N: NameOwner>(src: InFile<N>, kind: SymbolKind) -> NavigationTarget {
+        let name = src.value.name();
+        NavigationTarget {
+            file_id: src.file_id,
+            full_range: src.value.syntax().text_range(),
+            focus_range: name.as_ref().map(|name| name.syntax().text_range()),
+            name: name.map(|name| name.text().to_string()).unwrap_or_default(),
+            kind: Some(kind),
+        }
+    }
+
+    /// Returns a textual representation of this target that is used in tests.
+    #[cfg(test)]
+    pub(crate) fn debug_render(&self, db: &dyn hir::SourceDatabase) -> String {
+        let text = db.file_text(self.file_id);
+        let mut result = format!("{}", db.file_relative_path(self.file_id));
+        if let Some(kind) = self.kind {
+            result.push_str(&format!(" {:?}", kind));
+        }
+        result.push_str(&format!(" {}", &text[self.full_range]));
+        if let Some(focus_range) = self.focus_range {
+            result.push_str(&format!(" ({})", &text[focus_range]));
+        }
+        result
+    }
+}
+
+/// A trait for items that can be navigated to.
+pub(crate) trait ToNav {
+    fn to_nav(&self, db: &dyn hir::HirDatabase) -> NavigationTarget;
+}
+
+/// A trait for items that can possibly be navigated to.
+pub(crate) trait TryToNav {
+    fn try_to_nav(&self, db: &dyn hir::HirDatabase) -> Option<NavigationTarget>;
+}
+
+impl ToNav for hir::Function {
+    fn to_nav(&self, db: &dyn hir::HirDatabase) -> NavigationTarget {
+        NavigationTarget::from_named(self.source(db.upcast()), SymbolKind::Function)
+    }
+}
+
+impl ToNav for hir::Struct {
+    fn to_nav(&self, db: &dyn hir::HirDatabase) -> NavigationTarget {
+        NavigationTarget::from_named(self.source(db.upcast()), SymbolKind::Struct)
+    }
+}
+
+impl ToNav for hir::TypeAlias {
+    fn to_nav(&self, db: &dyn hir::HirDatabase) -> NavigationTarget {
+        NavigationTarget::from_named(self.source(db.upcast()), SymbolKind::TypeAlias)
+    }
+}
+
+impl ToNav for hir::Field {
+    fn to_nav(&self, db: &dyn hir::HirDatabase) -> NavigationTarget {
+        let src = self.source(db.upcast());
+        match src.value {
+            hir::FieldSource::Named(it) => {
+                NavigationTarget::from_named(InFile::new(src.file_id, it), SymbolKind::Field)
+            }
+            hir::FieldSource::Pos(it) => NavigationTarget {
+                file_id: src.file_id,
+                full_range: it.syntax().text_range(),
+                focus_range: None,
+                name: self.name(db).to_string(),
+                kind: Some(SymbolKind::Field),
+            },
+        }
+    }
+}
+
+impl ToNav for hir::semantics::Local {
+    fn to_nav(&self, db: &dyn hir::HirDatabase) -> NavigationTarget {
+        let src = self.source(db);
+        let name = match src.value.kind() {
+            ast::PatKind::BindPat(pat) => pat.name(),
+            ast::PatKind::PlaceholderPat(_) => None,
+        };
+        NavigationTarget {
+            file_id: src.file_id,
+            full_range: src.value.syntax().text_range(),
+            focus_range: name.map(|name| name.syntax().text_range()),
+            name: self
+                .name(db)
+                .map(|name| name.to_string())
+                .unwrap_or_default(),
+            kind: Some(SymbolKind::Local),
+        }
+    }
+}
+
+impl TryToNav for hir::Module {
+    fn try_to_nav(&self, db: &dyn hir::HirDatabase) -> Option<NavigationTarget> {
+        let file_id = self.file_id(db)?;
+        let source_file = db.parse(file_id).tree();
+        Some(NavigationTarget {
+            file_id,
+            full_range: source_file.syntax().text_range(),
+            focus_range: None,
+            name: self
+                .name(db)
+                .map(|name| name.to_string())
+                .unwrap_or_default(),
+            kind: Some(SymbolKind::Module),
+        })
+    }
+}
+
+impl TryToNav for hir::ModuleDef {
+    fn try_to_nav(&self, db: &dyn hir::HirDatabase) -> Option<NavigationTarget> {
+        match self {
+            hir::ModuleDef::Module(it) => it.try_to_nav(db),
+            hir::ModuleDef::Function(it) => Some(it.to_nav(db)),
+            hir::ModuleDef::Struct(it) => Some(it.to_nav(db)),
+            hir::ModuleDef::TypeAlias(it) => Some(it.to_nav(db)),
+            hir::ModuleDef::PrimitiveType(_) => None,
+        }
+    }
+}
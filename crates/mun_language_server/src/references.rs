@@ -0,0 +1,377 @@
+//! Provides the [`find_all_refs`] function, which finds all the places in the source code where the
+//! entity under the cursor is referred to.
+//!
+//! References are found by searching all files in which the entity can be referred to for its
+//! name. Each occurrence is then resolved through name resolution to determine whether it actually
+//! refers to the entity.
+
+use crate::{
+    db::AnalysisDatabase,
+    definition::{Definition, NameRefClass},
+    navigation_target::NavigationTarget,
+    FilePosition, FileRange,
+};
+use hir::semantics::Semantics;
+use mun_syntax::{ast, utils::pick_best_token, AstNode, SyntaxKind, TextSize};
+
+/// The result of searching for all references of an entity.
+#[derive(Debug, Clone)]
+pub struct ReferenceSearchResult {
+    /// The location where the entity is declared
+    pub declaration: Option<NavigationTarget>,
+
+    /// All the places where the entity is referred to
+    pub references: Vec<Reference>,
+}
+
+/// A place in the source code where an entity is referred to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    pub file_range: FileRange,
+    pub access: Option<ReferenceAccess>,
+}
+
+/// Describes how a reference accesses a local or a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceAccess {
+    Read,
+    Write,
+}
+
+/// Finds the declaration and all references of the entity at the specified `position` in all
+/// files in which the entity can be referred to.
+pub(crate) fn find_all_refs(
+    db: &AnalysisDatabase,
+    position: FilePosition,
+) -> Option<ReferenceSearchResult> {
+    let sema = Semantics::new(db);
+    let definition = definition_at(&sema, position)?;
+    let references = find_references(&sema, definition, &definition.search_scope(db));
+    Some(ReferenceSearchResult {
+        declaration: definition.try_to_nav(db),
+        references,
+    })
+}
+
+/// Returns the entity that is referred to or defined at the specified `position`.
+pub(crate) fn definition_at(sema: &Semantics, position: FilePosition) -> Option<Definition> {
+    let file = sema.parse(position.file_id);
+    let token = pick_best_token(file.syntax(), position.offset)?;
+    let parent = token.parent()?;
+
+    if let Some(name_ref) = ast::NameRef::cast(parent.clone()) {
+        NameRefClass::classify(sema, &name_ref).map(NameRefClass::referenced)
+    } else if let Some(name) = ast::Name::cast(parent.clone()) {
+        Definition::from_name(sema, &name)
+    } else {
+        let field_expr = ast::FieldExpr::cast(parent)?;
+        sema.resolve_field(&field_expr).map(Definition::Field)
+    }
+}
+
+/// Finds all references to the `definition` in the specified `files`.
+pub(crate) fn find_references(
+    sema: &Semantics,
+    definition: Definition,
+    files: &[hir::FileId],
+) -> Vec<Reference> {
+    let name = match definition.name(sema.db) {
+        Some(name) => name.to_string(),
+        None => return Vec::new(),
+    };
+
+    let mut references = Vec::new();
+    for &file_id in files {
+        let text = sema.db.file_text(file_id);
+        let file = sema.parse(file_id);
+        for (idx, _) in text.match_indices(&name) {
+            let offset = TextSize::from(idx as u32);
+            let token = match file.syntax().token_at_offset(offset).right_biased() {
+                Some(token) => token,
+                None => continue,
+            };
+
+            // Skip occurrences that are only part of an identifier
+            if token.kind() != SyntaxKind::IDENT
+                || token.text_range().start() != offset
+                || token.text() != name
+            {
+                continue;
+            }
+
+            let name_ref = match token.parent().and_then(ast::NameRef::cast) {
+                Some(name_ref) => name_ref,
+                None => continue,
+            };
+
+            let is_reference = match NameRefClass::classify(sema, &name_ref) {
+                Some(NameRefClass::Definition(def)) => def == definition,
+                Some(NameRefClass::FieldShorthand { local, field }) => {
+                    definition == Definition::Local(local) || definition == Definition::Field(field)
+                }
+                None => false,
+            };
+
+            if is_reference {
+                references.push(Reference {
+                    file_range: FileRange {
+                        file_id,
+                        range: name_ref.syntax().text_range(),
+                    },
+                    access: reference_access(&definition, &name_ref),
+                });
+            }
+        }
+    }
+    references
+}
+
+/// Returns how the `name_ref` accesses the `definition`. Only locals and fields are accessed.
+fn reference_access(definition: &Definition, name_ref: &ast::NameRef) -> Option<ReferenceAccess> {
+    if !matches!(definition, Definition::Local(_) | Definition::Field(_)) {
+        return None;
+    }
+
+    // Initializing a field in a record literal writes the field, but reads the local that is used
+    // with the shorthand syntax.
+    if name_ref
+        .syntax()
+        .parent()
+        .and_then(ast::RecordField::cast)
+        .is_some()
+    {
+        return Some(match definition {
+            Definition::Field(_) => ReferenceAccess::Write,
+            _ => ReferenceAccess::Read,
+        });
+    }
+
+    let expr = name_ref.syntax().ancestors().find_map(ast::Expr::cast)?;
+    let is_assignee = expr
+        .syntax()
+        .parent()
+        .and_then(ast::BinExpr::cast)
+        .filter(|bin_expr| {
+            bin_expr
+                .op_kind()
+                .map_or(false, |op_kind| op_kind.is_assignment())
+        })
+        .and_then(|bin_expr| bin_expr.lhs())
+        .map_or(false, |lhs| lhs.syntax() == expr.syntax());
+
+    Some(if is_assignee {
+        ReferenceAccess::Write
+    } else {
+        ReferenceAccess::Read
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::change_fixture::position;
+    use hir::SourceDatabase;
+
+    /// Renders the declaration and references of the entity at the cursor in the `fixture`.
+    fn find_all_refs(fixture: &str) -> String {
+        let (db, position) = position(fixture);
+        let result = match super::find_all_refs(&db, position) {
+            Some(result) => result,
+            None => return String::new(),
+        };
+
+        let mut lines = Vec::new();
+        if let Some(declaration) = result.declaration {
+            lines.push(declaration.debug_render(&db));
+        }
+        for reference in result.references {
+            lines.push(format!(
+                "{} {:?} {:?}",
+                db.file_relative_path(reference.file_range.file_id),
+                reference.file_range.range,
+                reference.access
+            ));
+        }
+        lines.join("\n")
+    }
+
+    #[test]
+    fn local() {
+        insta::assert_snapshot!(find_all_refs(
+            r#"
+        fn foo() {
+            let ba$0r = 5;
+            let baz = bar + 1;
+            bar = baz;
+            bar += 1;
+            let barbar = bar;
+        }
+        "#
+        ), @r###"
+        mod.mun Local bar (bar)
+        mod.mun 42..45 Some(Read)
+        mod.mun 55..58 Some(Write)
+        mod.mun 70..73 Some(Write)
+        mod.mun 97..100 Some(Read)
+        "###);
+    }
+
+    #[test]
+    fn shadowed_local() {
+        insta::assert_snapshot!(find_all_refs(
+            r#"
+        fn foo() {
+            let bar = 5;
+            let bar = ba$0r + 1;
+            bar
+        }
+        "#
+        ), @r###"
+        mod.mun Local bar (bar)
+        mod.mun 42..45 Some(Read)
+        "###);
+    }
+
+    #[test]
+    fn param() {
+        insta::assert_snapshot!(find_all_refs(
+            r#"
+        fn foo(bar: i32) -> i32 {
+            ba$0r
+        }
+        fn baz(bar: i32) -> i32 {
+            bar
+        }
+        "#
+        ), @r###"
+        mod.mun Local bar (bar)
+        mod.mun 30..33 Some(Read)
+        "###);
+    }
+
+    #[test]
+    fn function() {
+        insta::assert_snapshot!(find_all_refs(
+            r#"
+        //- /mod.mun
+        use bar::baz;
+        fn foo() {
+            ba$0z();
+            bar::baz();
+        }
+
+        //- /bar.mun
+        pub fn baz() {}
+        fn qux() {
+            baz()
+        }
+        "#
+        ), @r###"
+        bar.mun Function pub fn baz() {} (baz)
+        mod.mun 9..12 None
+        mod.mun 29..32 None
+        mod.mun 45..48 None
+        bar.mun 31..34 None
+        "###);
+    }
+
+    #[test]
+    fn function_definition() {
+        insta::assert_snapshot!(find_all_refs(
+            r#"
+        //- /mod.mun
+        use bar::{baz, Qux};
+        fn foo() {
+            baz();
+        }
+
+        //- /bar.mun
+        pub fn ba$0z() {}
+        pub struct Qux;
+        "#
+        ), @r###"
+        bar.mun Function pub fn baz() {} (baz)
+        mod.mun 10..13 None
+        mod.mun 36..39 None
+        "###);
+    }
+
+    #[test]
+    fn struct_def() {
+        insta::assert_snapshot!(find_all_refs(
+            r#"
+        //- /mod.mun
+        use bar::Baz;
+        fn foo(baz: Baz) -> Baz {
+            let a = Ba$0z { a: 3 };
+            a
+        }
+
+        //- /bar.mun
+        pub struct Baz { pub a: i32 }
+        pub fn baz() -> Baz { Baz { a: 4 } }
+        "#
+        ), @r###"
+        bar.mun Struct pub struct Baz { pub a: i32 } (Baz)
+        mod.mun 9..12 None
+        mod.mun 26..29 None
+        mod.mun 34..37 None
+        mod.mun 52..55 None
+        bar.mun 46..49 None
+        bar.mun 52..55 None
+        "###);
+    }
+
+    #[test]
+    fn field() {
+        insta::assert_snapshot!(find_all_refs(
+            r#"
+        struct Foo { bar: i32 }
+        fn foo() {
+            let bar = 3;
+            let a = Foo { bar };
+            let b = Foo { bar: 4 };
+            b.ba$0r = a.bar;
+        }
+        "#
+        ), @r###"
+        mod.mun Field bar: i32 (bar)
+        mod.mun 70..73 Some(Write)
+        mod.mun 95..98 Some(Write)
+        mod.mun 111..114 Some(Write)
+        mod.mun 119..122 Some(Read)
+        "###);
+    }
+
+    #[test]
+    fn local_in_shorthand() {
+        insta::assert_snapshot!(find_all_refs(
+            r#"
+        struct Foo { bar: i32 }
+        fn foo() {
+            let ba$0r = 3;
+            let a = Foo { bar };
+        }
+        "#
+        ), @r###"
+        mod.mun Local bar (bar)
+        mod.mun 70..73 Some(Read)
+        "###);
+    }
+
+    #[test]
+    fn type_alias() {
+        insta::assert_snapshot!(find_all_refs(
+            r#"
+        struct Foo;
+        type Ba$0r = Foo;
+        fn foo(bar: Bar) -> Bar {
+            bar
+        }
+        "#
+        ), @r###"
+        mod.mun TypeAlias type Bar = Foo; (Bar)
+        mod.mun 40..43 None
+        mod.mun 48..51 None
+        "###);
+    }
+}
@@ -0,0 +1,557 @@
+//! Provides the [`rename`] and [`prepare_rename`] functions, which rename the entity under the
+//! cursor and all the places in the source code where it is referred to.
+
+use crate::{
+    db::AnalysisDatabase,
+    definition::Definition,
+    references::{definition_at, find_references, Reference},
+    source_change::SourceChange,
+    FilePosition, FileRange,
+};
+use hir::semantics::Semantics;
+use mun_syntax::{
+    ast,
+    utils::{find_node_at_offset, pick_best_token},
+    AstNode, SyntaxKind, TextRange,
+};
+use ra_ap_text_edit::TextEdit;
+use rustc_hash::FxHashMap;
+use std::fmt;
+
+/// The reason why an entity cannot be renamed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameError(String);
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl std::error::Error for RenameError {}
+
+pub type RenameResult<T> = Result<T, RenameError>;
+
+/// Constructs a `RenameError` from a format string.
+macro_rules! format_err {
+    ($($args:tt)*) => {
+        RenameError(format!($($args)*))
+    };
+}
+
+/// Returns the range of the identifier at the specified `position` if the entity it refers to can
+/// be renamed.
+pub(crate) fn prepare_rename(
+    db: &AnalysisDatabase,
+    position: FilePosition,
+) -> RenameResult<FileRange> {
+    let sema = Semantics::new(db);
+    let (range, _) = find_definition(&sema, position)?;
+    Ok(range)
+}
+
+/// Renames the entity at the specified `position` to `new_name`. Returns the edits to apply to the
+/// source code, which include the declaration of the entity and all references to it.
+pub(crate) fn rename(
+    db: &AnalysisDatabase,
+    position: FilePosition,
+    new_name: &str,
+) -> RenameResult<SourceChange> {
+    check_identifier(new_name)?;
+
+    let sema = Semantics::new(db);
+    let (_, definition) = find_definition(&sema, position)?;
+    let old_name = definition
+        .name(db)
+        .ok_or_else(|| format_err!("cannot rename an entity without a name"))?
+        .to_string();
+    if old_name == new_name {
+        return Ok(SourceChange::default());
+    }
+
+    let references = find_references(&sema, definition, &definition.search_scope(db));
+    check_duplicate_definition(&sema, definition, &references, new_name)?;
+
+    let mut edits: FxHashMap<hir::FileId, Vec<(TextRange, String)>> = FxHashMap::default();
+    if let Some(declaration) = definition.try_to_nav(db) {
+        if let Some(range) = declaration.focus_range {
+            edits
+                .entry(declaration.file_id)
+                .or_default()
+                .push((range, new_name.to_owned()));
+        }
+    }
+
+    for reference in references {
+        // A field that is initialized with the shorthand syntax, as in `Foo { a }`, is expanded
+        // so that the field and the local keep referring to each other.
+        let replacement = match (definition, is_field_shorthand(&sema, &reference)) {
+            (Definition::Field(_), true) => format!("{}: {}", new_name, old_name),
+            (Definition::Local(_), true) => format!("{}: {}", old_name, new_name),
+            _ => new_name.to_owned(),
+        };
+        edits
+            .entry(reference.file_range.file_id)
+            .or_default()
+            .push((reference.file_range.range, replacement));
+    }
+
+    let mut source_change = SourceChange::default();
+    for (file_id, edits) in edits {
+        let mut builder = TextEdit::builder();
+        for (range, replacement) in edits {
+            builder.replace(range, replacement);
+        }
+        source_change.insert_source_edit(file_id, builder.finish());
+    }
+    Ok(source_change)
+}
+
+/// Returns the range of the identifier at the specified `position` and the entity that it refers
+/// to, if that entity can be renamed.
+fn find_definition(
+    sema: &Semantics,
+    position: FilePosition,
+) -> RenameResult<(FileRange, Definition)> {
+    let file = sema.parse(position.file_id);
+    let token = pick_best_token(file.syntax(), position.offset)
+        .filter(|token| token.kind() == SyntaxKind::IDENT)
+        .ok_or_else(|| format_err!("no identifier at the cursor"))?;
+    let definition = definition_at(sema, position)
+        .ok_or_else(|| format_err!("no references found at the cursor"))?;
+
+    match definition {
+        Definition::ModuleDef(hir::ModuleDef::Module(_)) => {
+            return Err(format_err!("renaming modules is not supported"));
+        }
+        Definition::ModuleDef(hir::ModuleDef::PrimitiveType(_)) => {
+            return Err(format_err!("cannot rename a builtin type"));
+        }
+        _ => {}
+    }
+
+    let range = FileRange {
+        file_id: position.file_id,
+        range: token.text_range(),
+    };
+    Ok((range, definition))
+}
+
+/// Verifies that `new_name` is a valid identifier.
+fn check_identifier(new_name: &str) -> RenameResult<()> {
+    match mun_syntax::tokenize(new_name).as_slice() {
+        [token]
+            if token.kind == SyntaxKind::IDENT && SyntaxKind::from_keyword(new_name).is_none() =>
+        {
+            Ok(())
+        }
+        _ => Err(format_err!("`{}` is not a valid identifier", new_name)),
+    }
+}
+
+/// Verifies that renaming the `definition` to `new_name` does not result in multiple entities with
+/// the same name in a module or a struct.
+fn check_duplicate_definition(
+    sema: &Semantics,
+    definition: Definition,
+    references: &[Reference],
+    new_name: &str,
+) -> RenameResult<()> {
+    let db = sema.db;
+    match definition {
+        Definition::ModuleDef(def) => {
+            let declaring_module = match def {
+                hir::ModuleDef::Function(it) => it.module(db),
+                hir::ModuleDef::Struct(it) => it.module(db),
+                hir::ModuleDef::TypeAlias(it) => it.module(db),
+                hir::ModuleDef::Module(_) | hir::ModuleDef::PrimitiveType(_) => return Ok(()),
+            };
+
+            // The renamed entity is also renamed in the modules that import it
+            let importing_modules = references
+                .iter()
+                .filter_map(|reference| imported_into(sema, reference));
+
+            for module in std::iter::once(declaring_module).chain(importing_modules) {
+                if module.has_item_named(db, new_name) {
+                    let module_name = module.full_name(db);
+                    return Err(if module_name.is_empty() {
+                        format_err!(
+                            "the name `{}` is already defined in the root module",
+                            new_name
+                        )
+                    } else {
+                        format_err!(
+                            "the name `{}` is already defined in module `{}`",
+                            new_name,
+                            module_name
+                        )
+                    });
+                }
+            }
+        }
+        Definition::Field(field) => {
+            let strukt = field.parent_struct();
+            if strukt
+                .fields(db)
+                .iter()
+                .any(|field| field.name(db).to_string() == new_name)
+            {
+                return Err(format_err!(
+                    "the struct `{}` already has a field named `{}`",
+                    strukt.name(db),
+                    new_name
+                ));
+            }
+        }
+        Definition::Local(_) => {}
+    }
+    Ok(())
+}
+
+/// Returns the module into which the `reference` imports an entity under its own name, as in
+/// `use foo::bar;`.
+fn imported_into(sema: &Semantics, reference: &Reference) -> Option<hir::Module> {
+    let FileRange { file_id, range } = reference.file_range;
+    let file = sema.parse(file_id);
+    let name_ref = find_node_at_offset::<ast::NameRef>(file.syntax(), range.start())?;
+    let segment = ast::PathSegment::cast(name_ref.syntax().parent()?)?;
+    let path = segment.parent_path();
+    let use_tree = ast::UseTree::cast(path.syntax().parent()?)?;
+    if use_tree.rename().is_some() || use_tree.use_tree_list().is_some() {
+        return None;
+    }
+    hir::Module::from_file(sema.db, file_id)
+}
+
+/// Returns true if the `reference` is a field that is initialized with the shorthand syntax, as in
+/// `Foo { a }`.
+fn is_field_shorthand(sema: &Semantics, reference: &Reference) -> bool {
+    let file = sema.parse(reference.file_range.file_id);
+    find_node_at_offset::<ast::NameRef>(file.syntax(), reference.file_range.range.start())
+        .and_then(|name_ref| name_ref.syntax().parent())
+        .and_then(ast::RecordField::cast)
+        .map_or(false, |record_field| record_field.expr().is_none())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::change_fixture::position;
+    use hir::SourceDatabase;
+
+    /// Renders the files that are changed by renaming the entity at the cursor in the `fixture`
+    /// to `new_name`, or the reason why it cannot be renamed.
+    fn rename(fixture: &str, new_name: &str) -> String {
+        let (db, position) = position(fixture);
+        let source_change = match super::rename(&db, position, new_name) {
+            Ok(source_change) => source_change,
+            Err(err) => return format!("error: {}", err),
+        };
+
+        let mut files = source_change
+            .source_file_edits
+            .into_iter()
+            .map(|(file_id, edit)| {
+                let mut text = db.file_text(file_id).to_string();
+                edit.apply(&mut text);
+                (db.file_relative_path(file_id).to_string(), text)
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+
+        files
+            .into_iter()
+            .map(|(path, text)| format!("//- /{}\n{}", path, text.trim()))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// Renders the range that is renamed when renaming the entity at the cursor in the `fixture`,
+    /// or the reason why it cannot be renamed.
+    fn prepare_rename(fixture: &str) -> String {
+        let (db, position) = position(fixture);
+        match super::prepare_rename(&db, position) {
+            Ok(range) => {
+                let text = db.file_text(range.file_id);
+                format!("{} {:?}", &text[range.range], range.range)
+            }
+            Err(err) => format!("error: {}", err),
+        }
+    }
+
+    #[test]
+    fn local() {
+        insta::assert_snapshot!(rename(
+            r#"
+        fn foo() {
+            let ba$0r = 5;
+            let baz = bar + 1;
+            bar = baz;
+        }
+        "#,
+            "qux"
+        ), @r###"
+        //- /mod.mun
+        fn foo() {
+            let qux = 5;
+            let baz = qux + 1;
+            qux = baz;
+        }
+        "###);
+    }
+
+    #[test]
+    fn param() {
+        insta::assert_snapshot!(rename(
+            r#"
+        fn foo(bar: i32) -> i32 {
+            ba$0r
+        }
+        "#,
+            "qux"
+        ), @r###"
+        //- /mod.mun
+        fn foo(qux: i32) -> i32 {
+            qux
+        }
+        "###);
+    }
+
+    #[test]
+    fn function_across_files() {
+        insta::assert_snapshot!(rename(
+            r#"
+        //- /mod.mun
+        use bar::{baz, Qux};
+        fn foo() {
+            baz();
+            bar::baz();
+        }
+
+        //- /bar.mun
+        pub fn ba$0z() {}
+        pub struct Qux;
+        "#,
+            "quux"
+        ), @r###"
+        //- /bar.mun
+        pub fn quux() {}
+        pub struct Qux;
+
+        //- /mod.mun
+        use bar::{quux, Qux};
+        fn foo() {
+            quux();
+            bar::quux();
+        }
+        "###);
+    }
+
+    #[test]
+    fn struct_def() {
+        insta::assert_snapshot!(rename(
+            r#"
+        //- /mod.mun
+        use bar::Baz;
+        fn foo(baz: Baz) -> Baz {
+            Ba$0z { a: 3 }
+        }
+
+        //- /bar.mun
+        pub struct Baz { pub a: i32 }
+        "#,
+            "Qux"
+        ), @r###"
+        //- /bar.mun
+        pub struct Qux { pub a: i32 }
+
+        //- /mod.mun
+        use bar::Qux;
+        fn foo(baz: Qux) -> Qux {
+            Qux { a: 3 }
+        }
+        "###);
+    }
+
+    #[test]
+    fn type_alias() {
+        insta::assert_snapshot!(rename(
+            r#"
+        type Ba$0r = i32;
+        fn foo(bar: Bar) -> Bar {
+            bar
+        }
+        "#,
+            "Baz"
+        ), @r###"
+        //- /mod.mun
+        type Baz = i32;
+        fn foo(bar: Baz) -> Baz {
+            bar
+        }
+        "###);
+    }
+
+    #[test]
+    fn field() {
+        insta::assert_snapshot!(rename(
+            r#"
+        struct Foo { bar: i32 }
+        fn foo() {
+            let b = Foo { bar: 4 };
+            b.ba$0r = b.bar;
+        }
+        "#,
+            "baz"
+        ), @r###"
+        //- /mod.mun
+        struct Foo { baz: i32 }
+        fn foo() {
+            let b = Foo { baz: 4 };
+            b.baz = b.baz;
+        }
+        "###);
+    }
+
+    #[test]
+    fn field_in_shorthand() {
+        insta::assert_snapshot!(rename(
+            r#"
+        struct Foo { ba$0r: i32 }
+        fn foo() {
+            let bar = 3;
+            let a = Foo { bar };
+        }
+        "#,
+            "baz"
+        ), @r###"
+        //- /mod.mun
+        struct Foo { baz: i32 }
+        fn foo() {
+            let bar = 3;
+            let a = Foo { baz: bar };
+        }
+        "###);
+    }
+
+    #[test]
+    fn local_in_shorthand() {
+        insta::assert_snapshot!(rename(
+            r#"
+        struct Foo { bar: i32 }
+        fn foo() {
+            let ba$0r = 3;
+            let a = Foo { bar };
+        }
+        "#,
+            "baz"
+        ), @r###"
+        //- /mod.mun
+        struct Foo { bar: i32 }
+        fn foo() {
+            let baz = 3;
+            let a = Foo { bar: baz };
+        }
+        "###);
+    }
+
+    #[test]
+    fn duplicate_definition() {
+        insta::assert_snapshot!(rename(
+            r#"
+        fn fo$0o() {}
+        fn bar() {}
+        "#,
+            "bar"
+        ), @"error: the name `bar` is already defined in the root module");
+    }
+
+    #[test]
+    fn duplicate_definition_in_importing_module() {
+        insta::assert_snapshot!(rename(
+            r#"
+        //- /mod.mun
+        use bar::baz;
+        fn qux() {}
+
+        //- /bar.mun
+        pub fn ba$0z() {}
+        "#,
+            "qux"
+        ), @"error: the name `qux` is already defined in the root module");
+    }
+
+    #[test]
+    fn duplicate_field() {
+        insta::assert_snapshot!(rename(
+            r#"
+        struct Foo { ba$0r: i32, baz: i32 }
+        "#,
+            "baz"
+        ), @"error: the struct `Foo` already has a field named `baz`");
+    }
+
+    #[test]
+    fn invalid_identifier() {
+        insta::assert_snapshot!(rename(
+            r#"
+        fn fo$0o() {}
+        "#,
+            "struct"
+        ), @"error: `struct` is not a valid identifier");
+        insta::assert_snapshot!(rename(
+            r#"
+        fn fo$0o() {}
+        "#,
+            "foo bar"
+        ), @"error: `foo bar` is not a valid identifier");
+    }
+
+    #[test]
+    fn module() {
+        insta::assert_snapshot!(rename(
+            r#"
+        //- /mod.mun
+        fn foo() {
+            ba$0r::baz()
+        }
+
+        //- /bar.mun
+        pub fn baz() {}
+        "#,
+            "qux"
+        ), @"error: renaming modules is not supported");
+    }
+
+    #[test]
+    fn prepare_rename_function() {
+        insta::assert_snapshot!(prepare_rename(
+            r#"
+        fn foo() {
+            ba$0r()
+        }
+        fn bar() {}
+        "#
+        ), @"bar 15..18");
+    }
+
+    #[test]
+    fn prepare_rename_primitive_type() {
+        insta::assert_snapshot!(prepare_rename(
+            r#"
+        fn foo(bar: i3$02) {}
+        "#
+        ), @"error: cannot rename a builtin type");
+    }
+
+    #[test]
+    fn prepare_rename_no_identifier() {
+        insta::assert_snapshot!(prepare_rename(
+            r#"
+        fn foo() {
+            let a = 3 $0+ 4;
+        }
+        "#
+        ), @"error: no identifier at the cursor");
+    }
+}
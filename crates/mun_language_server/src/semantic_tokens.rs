@@ -0,0 +1,224 @@
+//! Provides the legend of the semantic tokens that the server reports and the means to encode and
+//! diff semantic tokens as described by the LSP specification.
+
+use lsp_types::{
+    Range, SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens,
+    SemanticTokensEdit,
+};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+pub(crate) const BUILTIN_TYPE: SemanticTokenType = SemanticTokenType::new("builtinType");
+pub(crate) const TYPE_ALIAS: SemanticTokenType = SemanticTokenType::new("typeAlias");
+
+pub(crate) const MUTABLE: SemanticTokenModifier = SemanticTokenModifier::new("mutable");
+pub(crate) const EXTERN: SemanticTokenModifier = SemanticTokenModifier::new("extern");
+pub(crate) const PUBLIC: SemanticTokenModifier = SemanticTokenModifier::new("public");
+pub(crate) const GC: SemanticTokenModifier = SemanticTokenModifier::new("gc");
+pub(crate) const VALUE: SemanticTokenModifier = SemanticTokenModifier::new("value");
+
+/// The token types that the server reports. The index of a type in this list is its identifier.
+pub(crate) const SUPPORTED_TYPES: &[SemanticTokenType] = &[
+    SemanticTokenType::COMMENT,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::PROPERTY,
+    SemanticTokenType::STRING,
+    SemanticTokenType::STRUCT,
+    SemanticTokenType::VARIABLE,
+    BUILTIN_TYPE,
+    TYPE_ALIAS,
+];
+
+/// The token modifiers that the server reports. The index of a modifier in this list is the bit
+/// that represents it in a modifier set.
+pub(crate) const SUPPORTED_MODIFIERS: &[SemanticTokenModifier] = &[
+    SemanticTokenModifier::DECLARATION,
+    MUTABLE,
+    EXTERN,
+    PUBLIC,
+    GC,
+    VALUE,
+];
+
+/// Returns the identifier of the specified token type.
+pub(crate) fn type_index(token_type: SemanticTokenType) -> u32 {
+    SUPPORTED_TYPES
+        .iter()
+        .position(|it| *it == token_type)
+        .expect("unsupported semantic token type") as u32
+}
+
+/// A set of token modifiers, encoded as a bitset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct ModifierSet(pub(crate) u32);
+
+impl std::ops::BitOrAssign<SemanticTokenModifier> for ModifierSet {
+    fn bitor_assign(&mut self, rhs: SemanticTokenModifier) {
+        let index = SUPPORTED_MODIFIERS
+            .iter()
+            .position(|it| *it == rhs)
+            .expect("unsupported semantic token modifier");
+        self.0 |= 1 << index;
+    }
+}
+
+/// Builds the relative encoding of semantic tokens as described by the LSP specification. Tokens
+/// must be pushed in the order in which they appear in the document.
+pub(crate) struct SemanticTokensBuilder {
+    id: String,
+    prev_line: u32,
+    prev_char: u32,
+    data: Vec<SemanticToken>,
+}
+
+impl SemanticTokensBuilder {
+    /// Constructs a new builder for a result with the specified `id`.
+    pub fn new(id: String) -> Self {
+        SemanticTokensBuilder {
+            id,
+            prev_line: 0,
+            prev_char: 0,
+            data: Vec::new(),
+        }
+    }
+
+    /// Adds a token that spans the specified single-line `range`.
+    pub fn push(&mut self, range: Range, token_index: u32, modifier_bitset: u32) {
+        let mut push_line = range.start.line;
+        let mut push_char = range.start.character;
+
+        if !self.data.is_empty() {
+            push_line -= self.prev_line;
+            if push_line == 0 {
+                push_char -= self.prev_char;
+            }
+        }
+
+        self.data.push(SemanticToken {
+            delta_line: push_line,
+            delta_start: push_char,
+            length: range.end.character - range.start.character,
+            token_type: token_index,
+            token_modifiers_bitset: modifier_bitset,
+        });
+
+        self.prev_line = range.start.line;
+        self.prev_char = range.start.character;
+    }
+
+    /// Returns the encoded tokens.
+    pub fn build(self) -> SemanticTokens {
+        SemanticTokens {
+            result_id: Some(self.id),
+            data: self.data,
+        }
+    }
+}
+
+/// Returns a new unique identifier for a semantic tokens result.
+pub(crate) fn next_result_id() -> String {
+    static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+    NEXT_ID.fetch_add(1, Ordering::SeqCst).to_string()
+}
+
+/// Returns the edits that transform the `old` tokens into the `new` tokens. The edits are
+/// expressed in terms of the integers of the encoded tokens.
+pub(crate) fn diff_tokens(old: &[SemanticToken], new: &[SemanticToken]) -> Vec<SemanticTokensEdit> {
+    let offset = new
+        .iter()
+        .zip(old.iter())
+        .take_while(|&(n, p)| n == p)
+        .count();
+
+    let (_, old) = old.split_at(offset);
+    let (_, new) = new.split_at(offset);
+
+    let offset_from_end = new
+        .iter()
+        .rev()
+        .zip(old.iter().rev())
+        .take_while(|&(n, p)| n == p)
+        .count();
+
+    let (old, _) = old.split_at(old.len() - offset_from_end);
+    let (new, _) = new.split_at(new.len() - offset_from_end);
+
+    if old.is_empty() && new.is_empty() {
+        vec![]
+    } else {
+        // The lsp data field is actually a byte-diff but we travel in tokens so `start` and
+        // `delete_count` are in multiples of the serialized size of `SemanticToken`.
+        vec![SemanticTokensEdit {
+            start: 5 * offset as u32,
+            delete_count: 5 * old.len() as u32,
+            data: Some(new.into()),
+        }]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff_tokens;
+    use lsp_types::{SemanticToken, SemanticTokensEdit};
+
+    fn token(delta_line: u32, delta_start: u32) -> SemanticToken {
+        SemanticToken {
+            delta_line,
+            delta_start,
+            length: 3,
+            token_type: 0,
+            token_modifiers_bitset: 0,
+        }
+    }
+
+    #[test]
+    fn diff_unchanged() {
+        let tokens = [token(0, 1), token(1, 2)];
+        assert_eq!(diff_tokens(&tokens, &tokens), vec![]);
+    }
+
+    #[test]
+    fn diff_insert_in_middle() {
+        let old = [token(0, 1), token(1, 2)];
+        let new = [token(0, 1), token(0, 5), token(1, 2)];
+        assert_eq!(
+            diff_tokens(&old, &new),
+            vec![SemanticTokensEdit {
+                start: 5,
+                delete_count: 0,
+                data: Some(vec![token(0, 5)]),
+            }]
+        );
+    }
+
+    #[test]
+    fn diff_remove_from_end() {
+        let old = [token(0, 1), token(1, 2), token(3, 4)];
+        let new = [token(0, 1)];
+        assert_eq!(
+            diff_tokens(&old, &new),
+            vec![SemanticTokensEdit {
+                start: 5,
+                delete_count: 10,
+                data: Some(vec![]),
+            }]
+        );
+    }
+
+    #[test]
+    fn diff_replace() {
+        let old = [token(0, 1), token(1, 2), token(3, 4)];
+        let new = [token(0, 1), token(2, 2), token(3, 4)];
+        assert_eq!(
+            diff_tokens(&old, &new),
+            vec![SemanticTokensEdit {
+                start: 5,
+                delete_count: 5,
+                data: Some(vec![token(2, 2)]),
+            }]
+        );
+    }
+}
@@ -0,0 +1,291 @@
+//! Provides the [`signature_help`] function, which computes the signature of the function that is
+//! being called at a position in the source code.
+
+use crate::{db::AnalysisDatabase, FilePosition};
+use hir::{semantics::Semantics, HasSource, HirDisplay};
+use mun_syntax::{
+    ast::{self, ArgListOwner},
+    AstNode, SyntaxKind, TextRange, TextSize,
+};
+use std::fmt::Write;
+
+/// The signature of a callable and the parameter that is currently being written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureHelp {
+    /// The signature as it is presented to the user, e.g. `fn foo(a: i32) -> i32`
+    pub signature: String,
+
+    /// The ranges of the parameters in `signature`
+    pub parameters: Vec<TextRange>,
+
+    /// The index of the parameter that the cursor is at, if any
+    pub active_parameter: Option<usize>,
+}
+
+impl SignatureHelp {
+    /// Constructs the signature of a function without its visibility, e.g.
+    /// `extern fn foo(a: i32) -> i32`.
+    pub(crate) fn for_function(db: &AnalysisDatabase, function: hir::Function) -> Option<Self> {
+        let sig = function.ty(db).callable_sig(db)?;
+
+        let mut help = SignatureHelp {
+            signature: String::new(),
+            parameters: Vec::new(),
+            active_parameter: None,
+        };
+        if function.is_extern(db) {
+            help.signature.push_str("extern ");
+        }
+        let _ = write!(help.signature, "fn {}(", function.name(db));
+
+        let param_names = function
+            .source(db)
+            .value
+            .param_list()
+            .into_iter()
+            .flat_map(|param_list| param_list.params())
+            .map(|param| {
+                param
+                    .pat()
+                    .map(|pat| pat.syntax().text().to_string())
+                    .unwrap_or_else(|| String::from("_"))
+            });
+        for (name, ty) in param_names.zip(sig.params()) {
+            help.push_param(&format!("{}: {}", name, ty.display(db)));
+        }
+
+        help.signature.push(')');
+        if !sig.ret().is_empty() {
+            let _ = write!(help.signature, " -> {}", sig.ret().display(db));
+        }
+        Some(help)
+    }
+
+    /// Adds a parameter to the signature.
+    fn push_param(&mut self, param: &str) {
+        if !self.parameters.is_empty() {
+            self.signature.push_str(", ");
+        }
+        let start = TextSize::of(&self.signature);
+        self.signature.push_str(param);
+        self.parameters
+            .push(TextRange::at(start, TextSize::of(param)));
+    }
+}
+
+/// Returns the signature of the function or tuple struct that is called at the specified
+/// `position`. The callee is resolved through type inference, so this also works if the callee is
+/// not referred to by its name.
+pub(crate) fn signature_help(
+    db: &AnalysisDatabase,
+    position: FilePosition,
+) -> Option<SignatureHelp> {
+    let sema = Semantics::new(db);
+    let file = sema.parse(position.file_id);
+
+    // Start searching from the token in front of the cursor, skipping whitespace. Otherwise the
+    // cursor in `foo(a, $0` would not be part of the call because trailing whitespace is not
+    // attached to the unclosed argument list.
+    let mut token = file
+        .syntax()
+        .token_at_offset(position.offset)
+        .left_biased()?;
+    while token.kind().is_trivia() {
+        token = token.prev_token()?;
+    }
+
+    // Find the innermost call of which the cursor is inside the parentheses of the argument list
+    let (call_expr, arg_list) = token
+        .parent()?
+        .ancestors()
+        .filter_map(ast::CallExpr::cast)
+        .find_map(|call_expr| {
+            let arg_list = call_expr.arg_list()?;
+            let range = arg_list.syntax().text_range();
+            let is_closed = arg_list
+                .syntax()
+                .last_token()
+                .map_or(false, |token| token.kind() == SyntaxKind::R_PAREN);
+            if position.offset > range.start() && (position.offset < range.end() || !is_closed) {
+                Some((call_expr, arg_list))
+            } else {
+                None
+            }
+        })?;
+
+    let callee_ty = sema.type_of_expr(&call_expr.expr()?)?;
+    let mut help = match callee_ty.as_callable_def()? {
+        hir::CallableDef::Function(function) => SignatureHelp::for_function(db, function)?,
+        hir::CallableDef::Struct(strukt) => {
+            let sig = callee_ty.callable_sig(db)?;
+            let mut help = SignatureHelp {
+                signature: format!("struct {}(", strukt.name(db)),
+                parameters: Vec::new(),
+                active_parameter: None,
+            };
+            for ty in sig.params() {
+                help.push_param(&ty.display(db).to_string());
+            }
+            help.signature.push(')');
+            help
+        }
+    };
+
+    // The active parameter is the number of commas in front of the cursor
+    let active_parameter = arg_list
+        .syntax()
+        .children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| token.kind() == SyntaxKind::COMMA)
+        .take_while(|token| token.text_range().start() < position.offset)
+        .count();
+    if active_parameter < help.parameters.len() {
+        help.active_parameter = Some(active_parameter);
+    }
+
+    Some(help)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::change_fixture::position;
+
+    /// Renders the signature of the call at the cursor in the `fixture` with the active parameter
+    /// enclosed in angle brackets.
+    fn signature_help(fixture: &str) -> String {
+        let (db, position) = position(fixture);
+        let help = match super::signature_help(&db, position) {
+            Some(help) => help,
+            None => return String::from("no signature"),
+        };
+        match help.active_parameter {
+            Some(idx) => {
+                let range = help.parameters[idx];
+                format!(
+                    "{}<{}>{}",
+                    &help.signature[..usize::from(range.start())],
+                    &help.signature[range],
+                    &help.signature[usize::from(range.end())..]
+                )
+            }
+            None => help.signature,
+        }
+    }
+
+    #[test]
+    fn first_param() {
+        insta::assert_snapshot!(signature_help(
+            r#"
+        fn add(a: i32, b: i32) -> i32 { a + b }
+        fn main() { add($0) }
+        "#,
+        ), @"fn add(<a: i32>, b: i32) -> i32");
+    }
+
+    #[test]
+    fn second_param() {
+        insta::assert_snapshot!(signature_help(
+            r#"
+        fn add(a: i32, b: i32) -> i32 { a + b }
+        fn main() { add(1, $0) }
+        "#,
+        ), @"fn add(a: i32, <b: i32>) -> i32");
+    }
+
+    #[test]
+    fn unclosed_call() {
+        insta::assert_snapshot!(signature_help(
+            r#"
+        fn add(a: i32, b: i32) -> i32 { a + b }
+        fn main() {
+            let a = add(1, $0
+        }
+        "#,
+        ), @"fn add(a: i32, <b: i32>) -> i32");
+    }
+
+    #[test]
+    fn extern_function() {
+        insta::assert_snapshot!(signature_help(
+            r#"
+        extern fn log(message: f64, level: i32);
+        fn main() { log($0, 3) }
+        "#,
+        ), @"extern fn log(<message: f64>, level: i32)");
+    }
+
+    #[test]
+    fn no_return_type() {
+        insta::assert_snapshot!(signature_help(
+            r#"
+        fn foo(a: bool) {}
+        fn main() { foo(tr$0ue) }
+        "#,
+        ), @"fn foo(<a: bool>)");
+    }
+
+    #[test]
+    fn too_many_args() {
+        insta::assert_snapshot!(signature_help(
+            r#"
+        fn foo(a: bool) {}
+        fn main() { foo(true, $0) }
+        "#,
+        ), @"fn foo(a: bool)");
+    }
+
+    #[test]
+    fn nested_call() {
+        insta::assert_snapshot!(signature_help(
+            r#"
+        fn add(a: i32, b: i32) -> i32 { a + b }
+        fn neg(a: i32) -> i32 { -a }
+        fn main() { add(neg(1)$0, 2) }
+        "#,
+        ), @"fn add(<a: i32>, b: i32) -> i32");
+    }
+
+    #[test]
+    fn inner_call() {
+        insta::assert_snapshot!(signature_help(
+            r#"
+        fn add(a: i32, b: i32) -> i32 { a + b }
+        fn neg(a: i32) -> i32 { -a }
+        fn main() { add(1, neg($0)) }
+        "#,
+        ), @"fn neg(<a: i32>) -> i32");
+    }
+
+    #[test]
+    fn other_module() {
+        insta::assert_snapshot!(signature_help(
+            r#"
+        //- /mod.mun
+        fn main() { package::foo::bar(5, $0) }
+
+        //- /foo.mun
+        pub fn bar(a: i32, b: f32) -> f64 { 0.0 }
+        "#,
+        ), @"fn bar(a: i32, <b: f32>) -> f64");
+    }
+
+    #[test]
+    fn tuple_struct() {
+        insta::assert_snapshot!(signature_help(
+            r#"
+        struct Foo(i32, bool);
+        fn main() { let a = Foo(1, $0); }
+        "#,
+        ), @"struct Foo(i32, <bool>)");
+    }
+
+    #[test]
+    fn outside_parens() {
+        insta::assert_snapshot!(signature_help(
+            r#"
+        fn foo(a: bool) {}
+        fn main() { fo$0o(true) }
+        "#,
+        ), @"no signature");
+    }
+}
@@ -0,0 +1,27 @@
+use ra_ap_text_edit::TextEdit;
+use rustc_hash::FxHashMap;
+
+/// A set of edits to one or more source files, e.g. as the result of a refactoring.
+#[derive(Debug, Clone, Default)]
+pub struct SourceChange {
+    pub source_file_edits: FxHashMap<hir::FileId, TextEdit>,
+}
+
+impl SourceChange {
+    /// Adds an edit of the file with the specified `file_id`. If the file is already edited, the
+    /// edits are merged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the edit overlaps with an existing edit of the same file.
+    pub fn insert_source_edit(&mut self, file_id: hir::FileId, edit: TextEdit) {
+        match self.source_file_edits.get_mut(&file_id) {
+            Some(existing) => existing
+                .union(edit)
+                .expect("cannot merge overlapping source edits"),
+            None => {
+                self.source_file_edits.insert(file_id, edit);
+            }
+        }
+    }
+}
@@ -10,10 +10,10 @@ use lsp_server::{ReqQueue, Response};
 use lsp_types::{
     notification::Notification, notification::PublishDiagnostics, PublishDiagnosticsParams,
 };
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use paths::AbsPathBuf;
-use rustc_hash::FxHashSet;
-use std::{ops::Deref, sync::Arc, time::Instant};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{sync::Arc, time::Instant};
 use vfs::VirtualFileSystem;
 
 mod protocol;
@@ -46,7 +46,7 @@ pub(crate) struct LanguageServerState {
     pub(crate) request_queue: lsp_server::ReqQueue<(String, Instant), RequestHandler>,
 
     /// The configuration passed by the client
-    pub config: Config,
+    pub config: Arc<Config>,
 
     /// Thread pool for async execution
     pub thread_pool: threadpool::ThreadPool,
@@ -75,12 +75,18 @@ pub(crate) struct LanguageServerState {
     /// All the packages known to the server
     pub packages: Arc<Vec<project::Package>>,
 
+    /// The semantic tokens that were last sent to the client for each document
+    pub semantic_tokens_cache: Arc<Mutex<FxHashMap<lsp_types::Url, lsp_types::SemanticTokens>>>,
+
     /// True if the client requested that we shut down
     pub shutdown_requested: bool,
 }
 
 /// A snapshot of the state of the language server
 pub(crate) struct LanguageServerSnapshot {
+    /// The configuration passed by the client
+    pub config: Arc<Config>,
+
     /// The virtual filesystem that holds all the file contents
     pub vfs: Arc<RwLock<VirtualFileSystem>>,
 
@@ -89,6 +95,9 @@ pub(crate) struct LanguageServerSnapshot {
 
     /// All the packages known to the server
     pub packages: Arc<Vec<project::Package>>,
+
+    /// The semantic tokens that were last sent to the client for each document
+    pub semantic_tokens_cache: Arc<Mutex<FxHashMap<lsp_types::Url, lsp_types::SemanticTokens>>>,
 }
 
 impl LanguageServerState {
@@ -115,7 +124,7 @@ impl LanguageServerState {
         LanguageServerState {
             sender,
             request_queue: ReqQueue::default(),
-            config,
+            config: Arc::new(config),
             vfs: Arc::new(RwLock::new(Default::default())),
             vfs_monitor,
             vfs_monitor_receiver,
@@ -125,6 +134,7 @@ impl LanguageServerState {
             task_receiver,
             analysis,
             packages: Arc::new(Vec::new()),
+            semantic_tokens_cache: Arc::new(Default::default()),
             shutdown_requested: false,
         }
     }
@@ -251,46 +261,10 @@ fn handle_diagnostics(state: LanguageServerSnapshot, sender: Sender<Task>) -> an
             let uri = to_lsp::url(&state, file)?;
             let diagnostics = state.analysis.diagnostics(file)?;
 
-            let diagnostics = {
-                let mut lsp_diagnostics = Vec::with_capacity(diagnostics.len());
-                for d in diagnostics {
-                    lsp_diagnostics.push(lsp_types::Diagnostic {
-                        range: to_lsp::range(d.range, &line_index),
-                        severity: Some(lsp_types::DiagnosticSeverity::ERROR),
-                        code: None,
-                        code_description: None,
-                        source: Some("mun".to_string()),
-                        message: d.message,
-                        related_information: {
-                            let mut annotations =
-                                Vec::with_capacity(d.additional_annotations.len());
-                            for annotation in d.additional_annotations {
-                                annotations.push(lsp_types::DiagnosticRelatedInformation {
-                                    location: lsp_types::Location {
-                                        uri: to_lsp::url(&state, annotation.range.file_id)?,
-                                        range: to_lsp::range(
-                                            annotation.range.value,
-                                            state
-                                                .analysis
-                                                .file_line_index(annotation.range.file_id)?
-                                                .deref(),
-                                        ),
-                                    },
-                                    message: annotation.message,
-                                });
-                            }
-                            if annotations.is_empty() {
-                                None
-                            } else {
-                                Some(annotations)
-                            }
-                        },
-                        tags: None,
-                        data: None,
-                    });
-                }
-                lsp_diagnostics
-            };
+            let diagnostics = diagnostics
+                .iter()
+                .map(|d| to_lsp::diagnostic(&state, d, &line_index))
+                .collect::<anyhow::Result<Vec<_>>>()?;
 
             sender
                 .send(Task::Notify(lsp_server::Notification {
@@ -315,6 +289,8 @@ impl LanguageServerState {
             vfs: self.vfs.clone(),
             analysis: self.analysis.snapshot(),
             packages: self.packages.clone(),
+            semantic_tokens_cache: self.semantic_tokens_cache.clone(),
+            config: self.config.clone(),
         }
     }
 
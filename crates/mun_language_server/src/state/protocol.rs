@@ -53,6 +53,9 @@ impl LanguageServerState {
         let path = from_lsp::abs_path(&params.text_document.uri)?;
         self.open_docs.remove(&path);
         self.vfs_monitor.reload(&path);
+        self.semantic_tokens_cache
+            .lock()
+            .remove(&params.text_document.uri);
         Ok(())
     }
 
@@ -93,7 +96,29 @@ impl LanguageServerState {
                 Ok(())
             })?
             .on::<lsp_types::request::DocumentSymbolRequest>(handlers::handle_document_symbol)?
+            .on::<lsp_types::request::WorkspaceSymbol>(handlers::handle_workspace_symbol)?
+            .on::<lsp_types::request::FoldingRangeRequest>(handlers::handle_folding_range)?
             .on::<lsp_types::request::Completion>(handlers::handle_completion)?
+            .on::<lsp_types::request::GotoDefinition>(handlers::handle_goto_definition)?
+            .on::<lsp_types::request::GotoTypeDefinition>(handlers::handle_goto_type_definition)?
+            .on::<lsp_types::request::HoverRequest>(handlers::handle_hover)?
+            .on::<lsp_types::request::SignatureHelpRequest>(handlers::handle_signature_help)?
+            .on::<lsp_types::request::InlayHintRequest>(handlers::handle_inlay_hints)?
+            .on::<lsp_types::request::References>(handlers::handle_references)?
+            .on::<lsp_types::request::DocumentHighlightRequest>(
+                handlers::handle_document_highlight,
+            )?
+            .on::<lsp_types::request::PrepareRenameRequest>(handlers::handle_prepare_rename)?
+            .on::<lsp_types::request::Rename>(handlers::handle_rename)?
+            .on::<lsp_types::request::CodeActionRequest>(handlers::handle_code_action)?
+            .on::<lsp_types::request::Formatting>(handlers::handle_formatting)?
+            .on::<lsp_types::request::RangeFormatting>(handlers::handle_range_formatting)?
+            .on::<lsp_types::request::SemanticTokensFullRequest>(
+                handlers::handle_semantic_tokens_full,
+            )?
+            .on::<lsp_types::request::SemanticTokensFullDeltaRequest>(
+                handlers::handle_semantic_tokens_full_delta,
+            )?
             .finish();
 
         Ok(())
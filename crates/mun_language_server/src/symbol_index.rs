@@ -0,0 +1,176 @@
+//! Provides an index of the symbols that are defined in the source files of the workspace. The
+//! index is built from the item trees of the files and is used to search for symbols by name, e.g.
+//! for the `workspace/symbol` request.
+
+use crate::{navigation_target::NavigationTarget, SymbolKind};
+use hir::{InFile, Upcast};
+use std::sync::Arc;
+
+/// The maximum number of symbols that is returned by a search.
+const SEARCH_LIMIT: usize = 128;
+
+#[salsa::query_group(SymbolsDatabaseStorage)]
+pub(crate) trait SymbolsDatabase: hir::DefDatabase + Upcast<dyn hir::DefDatabase> {
+    /// Returns the functions, structs and type aliases that are defined in the specified file.
+    fn file_symbols(&self, file_id: hir::FileId) -> Arc<Vec<NavigationTarget>>;
+}
+
+fn file_symbols(db: &dyn SymbolsDatabase, file_id: hir::FileId) -> Arc<Vec<NavigationTarget>> {
+    let item_tree = db.item_tree(file_id);
+    let symbols = item_tree
+        .top_level_items()
+        .iter()
+        .filter_map(|item| match *item {
+            hir::ModItem::Function(id) => Some(NavigationTarget::from_named(
+                InFile::new(file_id, item_tree.source(db.upcast(), id)),
+                SymbolKind::Function,
+            )),
+            hir::ModItem::Struct(id) => Some(NavigationTarget::from_named(
+                InFile::new(file_id, item_tree.source(db.upcast(), id)),
+                SymbolKind::Struct,
+            )),
+            hir::ModItem::TypeAlias(id) => Some(NavigationTarget::from_named(
+                InFile::new(file_id, item_tree.source(db.upcast(), id)),
+                SymbolKind::TypeAlias,
+            )),
+            hir::ModItem::Import(_) => None,
+        })
+        .collect();
+    Arc::new(symbols)
+}
+
+/// Returns the symbols of all packages whose name fuzzily matches the `query`. The best matches
+/// are returned first.
+pub(crate) fn world_symbols(db: &dyn SymbolsDatabase, query: &str) -> Vec<NavigationTarget> {
+    let query = query.to_lowercase();
+
+    let packages = db.packages();
+    let mut matches = packages
+        .iter()
+        .flat_map(|package_id| {
+            let source_root = db.source_root(packages[package_id].source_root);
+            source_root.files().collect::<Vec<_>>()
+        })
+        .flat_map(|file_id| db.file_symbols(file_id).iter().cloned().collect::<Vec<_>>())
+        .filter_map(|symbol| Some((match_score(&symbol.name, &query)?, symbol)))
+        .collect::<Vec<_>>();
+
+    matches.sort_by(|(a_score, a), (b_score, b)| {
+        a_score
+            .cmp(b_score)
+            .then_with(|| a.name.len().cmp(&b.name.len()))
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| a.file_id.0.cmp(&b.file_id.0))
+            .then_with(|| a.full_range.start().cmp(&b.full_range.start()))
+    });
+
+    matches
+        .into_iter()
+        .take(SEARCH_LIMIT)
+        .map(|(_, symbol)| symbol)
+        .collect()
+}
+
+/// Returns how well the `name` of a symbol matches the lowercase `query`, or `None` if the name
+/// does not contain all the characters of the query in order. Lower scores are better matches: an
+/// exact match is better than a prefix, which is better than a substring, which is better than a
+/// scattered match. Matching is case-insensitive.
+fn match_score(name: &str, query: &str) -> Option<u8> {
+    let name = name.to_lowercase();
+    if name == query {
+        return Some(0);
+    } else if name.starts_with(query) {
+        return Some(1);
+    } else if name.contains(query) {
+        return Some(2);
+    }
+
+    let mut name_chars = name.chars();
+    if query.chars().all(|c| name_chars.any(|n| n == c)) {
+        Some(3)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{change_fixture::ChangeFixture, db::AnalysisDatabase};
+    use hir::SourceDatabase;
+
+    /// Renders the symbols in the `fixture` that match the `query`, in the order in which they are
+    /// returned.
+    fn world_symbols(fixture: &str, query: &str) -> String {
+        let change_fixture = ChangeFixture::parse(fixture);
+        let mut db = AnalysisDatabase::default();
+        db.apply_change(change_fixture.change);
+
+        super::world_symbols(&db, query)
+            .iter()
+            .map(|symbol| {
+                format!(
+                    "{} {:?} {}",
+                    db.file_relative_path(symbol.file_id),
+                    symbol.kind.unwrap(),
+                    symbol.name
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_world_symbols_all() {
+        insta::assert_snapshot!(world_symbols(
+            r#"
+        //- /mod.mun
+        use package::foo::Bar;
+        fn main() {}
+
+        //- /foo.mun
+        pub struct Bar;
+        pub type Baz = Bar;
+        "#,
+            ""
+        ), @r###"
+        foo.mun Struct Bar
+        foo.mun TypeAlias Baz
+        mod.mun Function main
+        "###);
+    }
+
+    #[test]
+    fn test_world_symbols_ranking() {
+        insta::assert_snapshot!(world_symbols(
+            r#"
+        //- /mod.mun
+        fn foo_bar() {}
+        fn bar() {}
+        fn ba() {}
+        fn barbar() {}
+        fn a_bar() {}
+        fn brave() {}
+        fn bear() {}
+        fn baz() {}
+        "#,
+            "BAR"
+        ), @r###"
+        mod.mun Function bar
+        mod.mun Function barbar
+        mod.mun Function a_bar
+        mod.mun Function foo_bar
+        mod.mun Function bear
+        "###);
+    }
+
+    #[test]
+    fn test_world_symbols_no_match() {
+        insta::assert_snapshot!(world_symbols(
+            r#"
+        fn foo() {}
+        struct Bar;
+        "#,
+            "qux"
+        ), @"");
+    }
+}
@@ -0,0 +1,472 @@
+//! Provides the [`highlight`] function, which classifies the tokens of a file for semantic syntax
+//! highlighting.
+//!
+//! Contrary to the grammar-based highlighting of an editor, identifiers are classified through name
+//! resolution. This enables distinguishing for instance a struct from a function or a `gc` struct
+//! from a `value` struct.
+
+use crate::{
+    db::AnalysisDatabase,
+    definition::{Definition, NameRefClass},
+};
+use hir::{semantics::Semantics, HasVisibility};
+use mun_syntax::{ast, AstNode, SyntaxKind, SyntaxToken, TextRange, WalkEvent};
+use rustc_hash::FxHashSet;
+use std::ops;
+
+/// A range of text in a file and how it should be highlighted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HlRange {
+    pub range: TextRange,
+    pub highlight: Highlight,
+}
+
+/// Describes how a range of text is highlighted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Highlight {
+    pub tag: HlTag,
+    pub mods: HlMods,
+}
+
+/// The kind of entity that is highlighted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HlTag {
+    BuiltinType,
+    Comment,
+    Field,
+    Function,
+    Keyword,
+    Local,
+    Module,
+    NumericLiteral,
+    Parameter,
+    StringLiteral,
+    Struct,
+    TypeAlias,
+}
+
+/// A modifier of a highlighted entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum HlMod {
+    /// The entity is declared at this location
+    Declaration = 0,
+    /// A local that is assigned to after it is declared
+    Mutable,
+    /// A function that is implemented outside of Mun
+    Extern,
+    /// An item or field that is accessible from outside its package
+    Public,
+    /// A struct that is garbage collected
+    Gc,
+    /// A struct that has value semantics
+    Value,
+}
+
+/// A set of [`HlMod`]s.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HlMods(u32);
+
+impl HlMod {
+    /// All the modifiers, ordered by their discriminant.
+    pub const ALL: &'static [HlMod] = &[
+        HlMod::Declaration,
+        HlMod::Mutable,
+        HlMod::Extern,
+        HlMod::Public,
+        HlMod::Gc,
+        HlMod::Value,
+    ];
+
+    fn mask(self) -> u32 {
+        1 << (self as u32)
+    }
+}
+
+impl HlMods {
+    /// Returns true if the set contains the specified modifier.
+    pub fn contains(self, modifier: HlMod) -> bool {
+        self.0 & modifier.mask() != 0
+    }
+
+    /// Iterates over all modifiers in the set.
+    pub fn iter(self) -> impl Iterator<Item = HlMod> {
+        HlMod::ALL
+            .iter()
+            .copied()
+            .filter(move |modifier| self.contains(*modifier))
+    }
+}
+
+impl From<HlTag> for Highlight {
+    fn from(tag: HlTag) -> Self {
+        Highlight {
+            tag,
+            mods: HlMods::default(),
+        }
+    }
+}
+
+impl ops::BitOrAssign<HlMod> for HlMods {
+    fn bitor_assign(&mut self, rhs: HlMod) {
+        self.0 |= rhs.mask();
+    }
+}
+
+impl ops::BitOr<HlMod> for Highlight {
+    type Output = Highlight;
+
+    fn bitor(mut self, rhs: HlMod) -> Highlight {
+        self.mods |= rhs;
+        self
+    }
+}
+
+/// Returns the highlighted ranges of the file with the specified `file_id`, ordered by their
+/// position in the file.
+pub(crate) fn highlight(db: &AnalysisDatabase, file_id: hir::FileId) -> Vec<HlRange> {
+    let sema = Semantics::new(db);
+    let file = sema.parse(file_id);
+    let assigned_locals = assigned_locals(&sema, &file);
+
+    file.syntax()
+        .preorder_with_tokens()
+        .filter_map(|event| match event {
+            WalkEvent::Enter(element) => element.into_token(),
+            WalkEvent::Leave(_) => None,
+        })
+        .filter_map(|token| {
+            let highlight = highlight_token(&sema, &assigned_locals, &token)?;
+            Some(HlRange {
+                range: token.text_range(),
+                highlight,
+            })
+        })
+        .collect()
+}
+
+/// Returns how the specified `token` is highlighted, or `None` if it is not highlighted.
+fn highlight_token(
+    sema: &Semantics,
+    assigned_locals: &FxHashSet<hir::semantics::Local>,
+    token: &SyntaxToken,
+) -> Option<Highlight> {
+    let highlight = match token.kind() {
+        SyntaxKind::COMMENT => HlTag::Comment.into(),
+        SyntaxKind::INT_NUMBER | SyntaxKind::FLOAT_NUMBER => HlTag::NumericLiteral.into(),
+        SyntaxKind::STRING => HlTag::StringLiteral.into(),
+        SyntaxKind::IDENT => {
+            let parent = token.parent()?;
+            if let Some(name) = ast::Name::cast(parent.clone()) {
+                let definition = Definition::from_name(sema, &name)?;
+                highlight_definition(sema, assigned_locals, definition) | HlMod::Declaration
+            } else {
+                let name_ref = ast::NameRef::cast(parent)?;
+                let definition = match NameRefClass::classify(sema, &name_ref)? {
+                    NameRefClass::Definition(definition) => definition,
+                    NameRefClass::FieldShorthand { local, .. } => Definition::Local(local),
+                };
+                highlight_definition(sema, assigned_locals, definition)
+            }
+        }
+        // `gc` and `value` are contextual keywords and therefore not reported by `is_keyword`
+        SyntaxKind::GC_KW | SyntaxKind::VALUE_KW => HlTag::Keyword.into(),
+        kind if kind.is_keyword() => HlTag::Keyword.into(),
+        _ => return None,
+    };
+    Some(highlight)
+}
+
+/// Returns how a reference to the `definition` is highlighted.
+fn highlight_definition(
+    sema: &Semantics,
+    assigned_locals: &FxHashSet<hir::semantics::Local>,
+    definition: Definition,
+) -> Highlight {
+    let db = sema.db;
+    match definition {
+        Definition::ModuleDef(hir::ModuleDef::Module(_)) => HlTag::Module.into(),
+        Definition::ModuleDef(hir::ModuleDef::PrimitiveType(_)) => HlTag::BuiltinType.into(),
+        Definition::ModuleDef(hir::ModuleDef::Function(function)) => {
+            let mut highlight = Highlight::from(HlTag::Function);
+            if function.is_extern(db) {
+                highlight.mods |= HlMod::Extern;
+            }
+            if function.visibility(db) == hir::Visibility::Public {
+                highlight.mods |= HlMod::Public;
+            }
+            highlight
+        }
+        Definition::ModuleDef(hir::ModuleDef::Struct(strukt)) => {
+            let memory_kind = match strukt.data(db.upcast()).memory_kind {
+                hir::StructMemoryKind::Gc => HlMod::Gc,
+                hir::StructMemoryKind::Value => HlMod::Value,
+            };
+            let mut highlight = Highlight::from(HlTag::Struct) | memory_kind;
+            if strukt.visibility(db) == hir::Visibility::Public {
+                highlight.mods |= HlMod::Public;
+            }
+            highlight
+        }
+        Definition::ModuleDef(hir::ModuleDef::TypeAlias(alias)) => {
+            let mut highlight = Highlight::from(HlTag::TypeAlias);
+            if alias.visibility(db) == hir::Visibility::Public {
+                highlight.mods |= HlMod::Public;
+            }
+            highlight
+        }
+        Definition::Field(field) => {
+            let mut highlight = Highlight::from(HlTag::Field);
+            if field.visibility(db) == hir::Visibility::Public {
+                highlight.mods |= HlMod::Public;
+            }
+            highlight
+        }
+        Definition::Local(local) => {
+            let is_param = local
+                .source(db)
+                .value
+                .syntax()
+                .ancestors()
+                .any(|node| ast::Param::can_cast(node.kind()));
+            let mut highlight = Highlight::from(if is_param {
+                HlTag::Parameter
+            } else {
+                HlTag::Local
+            });
+            if assigned_locals.contains(&local) {
+                highlight.mods |= HlMod::Mutable;
+            }
+            highlight
+        }
+    }
+}
+
+/// Returns all locals in the `file` that are assigned to after they are declared.
+fn assigned_locals(sema: &Semantics, file: &ast::SourceFile) -> FxHashSet<hir::semantics::Local> {
+    file.syntax()
+        .descendants()
+        .filter_map(ast::BinExpr::cast)
+        .filter(|bin_expr| {
+            bin_expr
+                .op_kind()
+                .map_or(false, |op_kind| op_kind.is_assignment())
+        })
+        .filter_map(|bin_expr| match bin_expr.lhs()?.kind() {
+            ast::ExprKind::PathExpr(path_expr) => path_expr.path(),
+            _ => None,
+        })
+        .filter_map(|path| match sema.resolve_path(&path)? {
+            hir::semantics::PathResolution::Local(local) => Some(local),
+            hir::semantics::PathResolution::Def(_) => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{change_fixture::ChangeFixture, db::AnalysisDatabase};
+    use hir::SourceDatabase;
+
+    /// Renders the highlighted ranges of the single file in the `fixture`, one range per line.
+    fn highlight(fixture: &str) -> String {
+        let change_fixture = ChangeFixture::parse(fixture);
+        let mut db = AnalysisDatabase::default();
+        db.apply_change(change_fixture.change);
+
+        let file_id = change_fixture.files[0];
+        let text = db.file_text(file_id);
+        super::highlight(&db, file_id)
+            .into_iter()
+            .map(|range| {
+                let mods = range
+                    .highlight
+                    .mods
+                    .iter()
+                    .map(|modifier| format!("{:?}", modifier))
+                    .collect::<Vec<_>>();
+                format!(
+                    "{} {:?} [{}]",
+                    &text[range.range],
+                    range.highlight.tag,
+                    mods.join(", ")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_highlight_structs() {
+        insta::assert_snapshot!(highlight(
+            r#"
+        pub struct Foo { pub a: i32, b: f64 }
+        struct(value) Bar(bool);
+
+        fn main() -> Foo {
+            let bar: Bar = Bar(true);
+            Foo { a: 3, b: 1.5 }
+        }
+        "#,
+        ), @r###"
+        pub Keyword []
+        struct Keyword []
+        Foo Struct [Declaration, Public, Gc]
+        pub Keyword []
+        a Field [Declaration, Public]
+        i32 BuiltinType []
+        b Field [Declaration]
+        f64 BuiltinType []
+        struct Keyword []
+        value Keyword []
+        Bar Struct [Declaration, Value]
+        bool BuiltinType []
+        fn Keyword []
+        main Function [Declaration]
+        Foo Struct [Public, Gc]
+        let Keyword []
+        bar Local [Declaration]
+        Bar Struct [Value]
+        Bar Struct [Value]
+        true Keyword []
+        Foo Struct [Public, Gc]
+        a Field [Public]
+        3 NumericLiteral []
+        b Field []
+        1.5 NumericLiteral []
+        "###);
+    }
+
+    #[test]
+    fn test_highlight_functions() {
+        insta::assert_snapshot!(highlight(
+            r#"
+        // Implemented by the host
+        extern fn random() -> i32;
+
+        pub fn add(a: i32, b: i32) -> i32 { a + b }
+
+        fn main() -> i32 {
+            add(random(), 5)
+        }
+        "#,
+        ), @r###"
+        // Implemented by the host Comment []
+        extern Keyword []
+        fn Keyword []
+        random Function [Declaration, Extern]
+        i32 BuiltinType []
+        pub Keyword []
+        fn Keyword []
+        add Function [Declaration, Public]
+        a Parameter [Declaration]
+        i32 BuiltinType []
+        b Parameter [Declaration]
+        i32 BuiltinType []
+        i32 BuiltinType []
+        a Parameter []
+        b Parameter []
+        fn Keyword []
+        main Function [Declaration]
+        i32 BuiltinType []
+        add Function [Public]
+        random Function [Extern]
+        5 NumericLiteral []
+        "###);
+    }
+
+    #[test]
+    fn test_highlight_mutable_locals() {
+        insta::assert_snapshot!(highlight(
+            r#"
+        fn main(n: i32) -> i32 {
+            let a = 0;
+            let b = 1;
+            a = b;
+            a += n;
+            n = a;
+            a
+        }
+        "#,
+        ), @r###"
+        fn Keyword []
+        main Function [Declaration]
+        n Parameter [Declaration, Mutable]
+        i32 BuiltinType []
+        i32 BuiltinType []
+        let Keyword []
+        a Local [Declaration, Mutable]
+        0 NumericLiteral []
+        let Keyword []
+        b Local [Declaration]
+        1 NumericLiteral []
+        a Local [Mutable]
+        b Local []
+        a Local [Mutable]
+        n Parameter [Mutable]
+        n Parameter [Mutable]
+        a Local [Mutable]
+        a Local [Mutable]
+        "###);
+    }
+
+    #[test]
+    fn test_highlight_field_shorthand() {
+        insta::assert_snapshot!(highlight(
+            r#"
+        type Int = i32;
+        struct Foo { a: Int }
+
+        fn main() -> Foo {
+            let a: Int = 5;
+            Foo { a }
+        }
+        "#,
+        ), @r###"
+        type Keyword []
+        Int TypeAlias [Declaration]
+        i32 BuiltinType []
+        struct Keyword []
+        Foo Struct [Declaration, Gc]
+        a Field [Declaration]
+        Int TypeAlias []
+        fn Keyword []
+        main Function [Declaration]
+        Foo Struct [Gc]
+        let Keyword []
+        a Local [Declaration]
+        Int TypeAlias []
+        5 NumericLiteral []
+        Foo Struct [Gc]
+        a Local []
+        "###);
+    }
+
+    #[test]
+    fn test_highlight_modules() {
+        insta::assert_snapshot!(highlight(
+            r#"
+        //- /mod.mun
+        use package::foo::Foo;
+
+        fn main() -> package::foo::Foo {
+            Foo
+        }
+
+        //- /foo.mun
+        pub struct Foo;
+        "#,
+        ), @r###"
+        use Keyword []
+        package Keyword []
+        foo Module []
+        Foo Struct [Public, Gc]
+        fn Keyword []
+        main Function [Declaration]
+        package Keyword []
+        foo Module []
+        Foo Struct [Public, Gc]
+        Foo Struct [Public, Gc]
+        "###);
+    }
+}
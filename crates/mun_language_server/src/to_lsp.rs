@@ -1,9 +1,20 @@
 use crate::completion::{CompletionItem, CompletionItemKind};
+use crate::diagnostics::Diagnostic;
+use crate::folding_ranges::{Fold, FoldKind};
+use crate::inlay_hints::{InlayHint, InlayKind};
+use crate::navigation_target::NavigationTarget;
+use crate::references::ReferenceAccess;
+use crate::semantic_tokens::{self, ModifierSet, SemanticTokensBuilder};
+use crate::signature_help::SignatureHelp;
+use crate::source_change::SourceChange;
 use crate::state::LanguageServerSnapshot;
 use crate::symbol_kind::SymbolKind;
-use lsp_types::Url;
+use crate::syntax_highlighting::{Highlight, HlMod, HlRange, HlTag};
+use crate::FileRange;
+use lsp_types::{SemanticTokenModifier, SemanticTokenType, Url};
 use mun_syntax::{TextRange, TextSize};
 use std::{
+    collections::HashMap,
     path::{Component, Path, Prefix},
     str::FromStr,
 };
@@ -86,6 +97,261 @@ pub(crate) fn url(snapshot: &LanguageServerSnapshot, file_id: hir::FileId) -> an
     Ok(url)
 }
 
+/// Converts a `FileRange` to an LSP `Location`.
+pub(crate) fn location(
+    snapshot: &LanguageServerSnapshot,
+    file_range: FileRange,
+) -> anyhow::Result<lsp_types::Location> {
+    let line_index = snapshot.analysis.file_line_index(file_range.file_id)?;
+    Ok(lsp_types::Location {
+        uri: url(snapshot, file_range.file_id)?,
+        range: range(file_range.range, &line_index),
+    })
+}
+
+/// Returns the LSP `Location` that the specified `NavigationTarget` focuses on.
+pub(crate) fn location_from_nav(
+    snapshot: &LanguageServerSnapshot,
+    nav: &NavigationTarget,
+) -> anyhow::Result<lsp_types::Location> {
+    location(
+        snapshot,
+        FileRange {
+            file_id: nav.file_id,
+            range: nav.focus_or_full_range(),
+        },
+    )
+}
+
+/// Converts a symbol found by a workspace symbol search to an LSP `SymbolInformation`.
+pub(crate) fn symbol_information(
+    snapshot: &LanguageServerSnapshot,
+    symbol: NavigationTarget,
+) -> anyhow::Result<lsp_types::SymbolInformation> {
+    #[allow(deprecated)]
+    Ok(lsp_types::SymbolInformation {
+        location: location_from_nav(snapshot, &symbol)?,
+        name: symbol.name,
+        kind: symbol.kind.map_or(lsp_types::SymbolKind::NULL, symbol_kind),
+        tags: None,
+        deprecated: None,
+        container_name: None,
+    })
+}
+
+/// Converts a `Fold` to an LSP `FoldingRange`. Only lines are folded, so the line that contains
+/// the closing delimiter of a block remains visible.
+pub(crate) fn folding_range(
+    line_index: &hir::line_index::LineIndex,
+    fold: Fold,
+) -> lsp_types::FoldingRange {
+    let range = range(fold.range, line_index);
+    let (end_line, kind) = match fold.kind {
+        FoldKind::Block => (range.end.line.saturating_sub(1), None),
+        FoldKind::Comment => (range.end.line, Some(lsp_types::FoldingRangeKind::Comment)),
+    };
+    lsp_types::FoldingRange {
+        start_line: range.start.line,
+        start_character: None,
+        end_line: end_line.max(range.start.line),
+        end_character: None,
+        kind,
+    }
+}
+
+/// Converts a `ReferenceAccess` to the kind of an LSP `DocumentHighlight`.
+pub(crate) fn document_highlight_kind(
+    access: Option<ReferenceAccess>,
+) -> lsp_types::DocumentHighlightKind {
+    match access {
+        Some(ReferenceAccess::Read) => lsp_types::DocumentHighlightKind::READ,
+        Some(ReferenceAccess::Write) => lsp_types::DocumentHighlightKind::WRITE,
+        None => lsp_types::DocumentHighlightKind::TEXT,
+    }
+}
+
+/// Converts a `TextEdit` to a list of LSP `TextEdit`s.
+pub(crate) fn text_edits(
+    edit: ra_ap_text_edit::TextEdit,
+    line_index: &hir::line_index::LineIndex,
+) -> Vec<lsp_types::TextEdit> {
+    edit.into_iter()
+        .map(|indel| lsp_types::TextEdit {
+            range: range(indel.delete, line_index),
+            new_text: indel.insert,
+        })
+        .collect()
+}
+
+/// Converts a `SourceChange` to an LSP `WorkspaceEdit`.
+pub(crate) fn workspace_edit(
+    snapshot: &LanguageServerSnapshot,
+    source_change: SourceChange,
+) -> anyhow::Result<lsp_types::WorkspaceEdit> {
+    let mut changes = HashMap::new();
+    for (file_id, edit) in source_change.source_file_edits {
+        let line_index = snapshot.analysis.file_line_index(file_id)?;
+        changes.insert(url(snapshot, file_id)?, text_edits(edit, &line_index));
+    }
+    Ok(lsp_types::WorkspaceEdit {
+        changes: Some(changes),
+        ..Default::default()
+    })
+}
+
+/// Converts a `Diagnostic` to an LSP `Diagnostic`.
+pub(crate) fn diagnostic(
+    snapshot: &LanguageServerSnapshot,
+    diagnostic: &Diagnostic,
+    line_index: &hir::line_index::LineIndex,
+) -> anyhow::Result<lsp_types::Diagnostic> {
+    let mut related_information = Vec::with_capacity(diagnostic.additional_annotations.len());
+    for annotation in &diagnostic.additional_annotations {
+        related_information.push(lsp_types::DiagnosticRelatedInformation {
+            location: location(
+                snapshot,
+                FileRange {
+                    file_id: annotation.range.file_id,
+                    range: annotation.range.value,
+                },
+            )?,
+            message: annotation.message.clone(),
+        });
+    }
+
+    Ok(lsp_types::Diagnostic {
+        range: range(diagnostic.range, line_index),
+        severity: Some(lsp_types::DiagnosticSeverity::ERROR),
+        code: None,
+        code_description: None,
+        source: Some("mun".to_string()),
+        message: diagnostic.message.clone(),
+        related_information: if related_information.is_empty() {
+            None
+        } else {
+            Some(related_information)
+        },
+        tags: None,
+        data: None,
+    })
+}
+
+/// Converts an `InlayHint` to an LSP `InlayHint`.
+pub(crate) fn inlay_hint(
+    line_index: &hir::line_index::LineIndex,
+    inlay_hint: InlayHint,
+) -> lsp_types::InlayHint {
+    let (label, kind) = match inlay_hint.kind {
+        InlayKind::Type | InlayKind::Chaining => (
+            format!(": {}", inlay_hint.label),
+            lsp_types::InlayHintKind::TYPE,
+        ),
+        InlayKind::Parameter => (
+            format!("{}:", inlay_hint.label),
+            lsp_types::InlayHintKind::PARAMETER,
+        ),
+    };
+    lsp_types::InlayHint {
+        position: position(inlay_hint.offset(), line_index),
+        label: lsp_types::InlayHintLabel::String(label),
+        kind: Some(kind),
+        text_edits: None,
+        tooltip: None,
+        padding_left: None,
+        padding_right: Some(inlay_hint.kind == InlayKind::Parameter),
+        data: None,
+    }
+}
+
+/// Converts a `SignatureHelp` to an LSP `SignatureHelp`. The parameters are referred to by their
+/// offsets in the label, which are expressed in UTF-16 code units.
+pub(crate) fn signature_help(help: SignatureHelp) -> lsp_types::SignatureHelp {
+    let utf16_offset =
+        |offset: TextSize| help.signature[..usize::from(offset)].encode_utf16().count() as u32;
+    let parameters = help
+        .parameters
+        .iter()
+        .map(|range| lsp_types::ParameterInformation {
+            label: lsp_types::ParameterLabel::LabelOffsets([
+                utf16_offset(range.start()),
+                utf16_offset(range.end()),
+            ]),
+            documentation: None,
+        })
+        .collect();
+    let active_parameter = help.active_parameter.map(|idx| idx as u32);
+    lsp_types::SignatureHelp {
+        signatures: vec![lsp_types::SignatureInformation {
+            label: help.signature,
+            documentation: None,
+            parameters: Some(parameters),
+            active_parameter,
+        }],
+        active_signature: Some(0),
+        active_parameter,
+    }
+}
+
+/// Converts highlighted ranges to LSP `SemanticTokens`. Ranges that span multiple lines are split
+/// into a token per line because not all clients support multiline tokens.
+pub(crate) fn semantic_tokens(
+    text: &str,
+    line_index: &hir::line_index::LineIndex,
+    highlights: Vec<HlRange>,
+) -> lsp_types::SemanticTokens {
+    let mut builder = SemanticTokensBuilder::new(semantic_tokens::next_result_id());
+
+    for highlight_range in highlights {
+        let (token_type, modifiers) = semantic_token_type_and_modifiers(highlight_range.highlight);
+        let token_index = semantic_tokens::type_index(token_type);
+
+        let mut offset = highlight_range.range.start();
+        for line in text[highlight_range.range].split_inclusive('\n') {
+            let line_len = TextSize::of(line);
+            let trimmed_len = TextSize::of(line.trim_end_matches(&['\r', '\n'][..]));
+            if trimmed_len > TextSize::from(0) {
+                let token_range = TextRange::at(offset, trimmed_len);
+                builder.push(range(token_range, line_index), token_index, modifiers.0);
+            }
+            offset += line_len;
+        }
+    }
+
+    builder.build()
+}
+
+/// Converts a `Highlight` to an LSP semantic token type and the set of its modifiers.
+fn semantic_token_type_and_modifiers(highlight: Highlight) -> (SemanticTokenType, ModifierSet) {
+    let token_type = match highlight.tag {
+        HlTag::BuiltinType => semantic_tokens::BUILTIN_TYPE,
+        HlTag::Comment => SemanticTokenType::COMMENT,
+        HlTag::Field => SemanticTokenType::PROPERTY,
+        HlTag::Function => SemanticTokenType::FUNCTION,
+        HlTag::Keyword => SemanticTokenType::KEYWORD,
+        HlTag::Local => SemanticTokenType::VARIABLE,
+        HlTag::Module => SemanticTokenType::NAMESPACE,
+        HlTag::NumericLiteral => SemanticTokenType::NUMBER,
+        HlTag::Parameter => SemanticTokenType::PARAMETER,
+        HlTag::StringLiteral => SemanticTokenType::STRING,
+        HlTag::Struct => SemanticTokenType::STRUCT,
+        HlTag::TypeAlias => semantic_tokens::TYPE_ALIAS,
+    };
+
+    let mut modifiers = ModifierSet::default();
+    for modifier in highlight.mods.iter() {
+        modifiers |= match modifier {
+            HlMod::Declaration => SemanticTokenModifier::DECLARATION,
+            HlMod::Mutable => semantic_tokens::MUTABLE,
+            HlMod::Extern => semantic_tokens::EXTERN,
+            HlMod::Public => semantic_tokens::PUBLIC,
+            HlMod::Gc => semantic_tokens::GC,
+            HlMod::Value => semantic_tokens::VALUE,
+        };
+    }
+
+    (token_type, modifiers)
+}
+
 /// Converts from our `CompletionItem` to an LSP `CompletionItem`
 pub(crate) fn completion_item(completion_item: CompletionItem) -> lsp_types::CompletionItem {
     lsp_types::CompletionItem {
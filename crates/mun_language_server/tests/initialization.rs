@@ -49,3 +49,36 @@ fn test_document_symbols() {
 
     insta::assert_debug_snapshot!(symbols);
 }
+
+#[test]
+fn test_formatting() {
+    let server = Project::with_fixture(
+        r#"
+    //- /mun.toml
+    [package]
+    name = "foo"
+    version = "0.0.0"
+
+    [format]
+    indent-width = 2
+
+    //- /src/mod.mun
+    fn main() -> i32 {
+    let a=5;
+        a
+    }
+    "#,
+    )
+    .server()
+    .wait_until_workspace_is_loaded();
+
+    let edits = server.send_request::<lsp_types::request::Formatting>(
+        lsp_types::DocumentFormattingParams {
+            text_document: server.doc_id("src/mod.mun"),
+            options: Default::default(),
+            work_done_progress_params: Default::default(),
+        },
+    );
+
+    insta::assert_debug_snapshot!(edits);
+}
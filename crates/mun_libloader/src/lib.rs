@@ -25,7 +25,30 @@ impl MunLibrary {
         // Although loading a library is technically unsafe, we assume here that this is not the
         // case for munlibs.
         let library = TempLibrary::new(library_path)?;
+        Self::from_library(library)
+    }
+
+    /// Loads a munlib library from the contents of a shared object stored in `bytes`.
+    ///
+    /// # Safety
+    ///
+    /// A munlib is simply a shared object. When a library is loaded, initialisation routines
+    /// contained within it are executed. For the purposes of safety, the execution of these
+    /// routines is conceptually the same calling an unknown foreign function and may impose
+    /// arbitrary requirements on the caller for the call to be sound.
+    ///
+    /// Additionally, the callers of this function must also ensure that execution of the
+    /// termination routines contained within the library is safe as well. These routines may be
+    /// executed when the library is unloaded.
+    ///
+    /// See [`libloading::Library::new`] for more information.
+    pub unsafe fn from_bytes(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        let library = TempLibrary::from_bytes(bytes)?;
+        Self::from_library(library)
+    }
 
+    /// Verifies that the loaded `library` is a munlib.
+    unsafe fn from_library(library: TempLibrary) -> Result<Self, anyhow::Error> {
         // Verify that the `*.munlib` contains all required functions. Note that this is an unsafe
         // operation because the loaded symbols don't actually contain type information. Casting
         // is therefore unsafe.
@@ -11,7 +11,7 @@ use libloading::Library;
 /// There is no risk of cleaning the temporary file while it is used because loading the library
 /// keeps the file open (Windows) or keeping the file is not required in the first place (*nix).
 pub struct TempLibrary {
-    _tmp_path: tempfile::TempPath,
+    _tmp_path: Option<tempfile::TempPath>,
     library: Library,
 }
 
@@ -41,11 +41,62 @@ impl TempLibrary {
         fs::copy(path, &tmp_path)?;
         let library = Library::new(&tmp_path)?;
         Ok(TempLibrary {
-            _tmp_path: tmp_path,
+            _tmp_path: Some(tmp_path),
             library,
         })
     }
 
+    /// Loads a dynamic library from the contents of a shared object stored in `bytes`.
+    ///
+    /// On Linux, the library is loaded from an anonymous in-memory file, which doesn't require a
+    /// writable file system. On other platforms, the contents are written to a temporary file.
+    ///
+    /// # Safety
+    ///
+    /// When a library is loaded, initialisation routines contained within it are executed.
+    /// For the purposes of safety, the execution of these routines is conceptually the same calling
+    /// an unknown foreign function and may impose arbitrary requirements on the caller for the call
+    /// to be sound.
+    ///
+    /// Additionally, the callers of this function must also ensure that execution of the
+    /// termination routines contained within the library is safe as well. These routines may be
+    /// executed when the library is unloaded.
+    ///
+    /// See [`libloading::Library::new`] for more information.
+    pub unsafe fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        #[cfg(target_os = "linux")]
+        {
+            use std::{ffi::CString, io::Write, os::unix::io::FromRawFd};
+
+            let name = CString::new("munlib").expect("Invalid memory file name");
+            let fd = libc::memfd_create(name.as_ptr(), libc::MFD_CLOEXEC);
+            if fd < 0 {
+                return Err(std::io::Error::last_os_error().into());
+            }
+
+            // The file descriptor can be closed once the library has been loaded, because the
+            // loader maps the contents of the file into memory.
+            let mut file = fs::File::from_raw_fd(fd);
+            file.write_all(bytes)?;
+            let library = Library::new(format!("/proc/self/fd/{}", fd))?;
+            Ok(TempLibrary {
+                _tmp_path: None,
+                library,
+            })
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            let tmp_path = tempfile::NamedTempFile::new()?.into_temp_path();
+            fs::write(&tmp_path, bytes)?;
+            let library = Library::new(&tmp_path)?;
+            Ok(TempLibrary {
+                _tmp_path: Some(tmp_path),
+                library,
+            })
+        }
+    }
+
     /// Returns the loaded library
     pub fn library(&self) -> &Library {
         &self.library
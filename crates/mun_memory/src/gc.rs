@@ -1,18 +1,129 @@
+mod allocator;
+mod generational;
+mod graph;
+mod incremental;
 mod mark_sweep;
 mod ptr;
 mod root_ptr;
+mod snapshot;
+mod weak_ptr;
 
 use crate::TypeMemory;
-use std::marker::PhantomData;
+use std::{fmt, marker::PhantomData, time::Duration};
 
+pub use allocator::{Allocator, GlobalAllocator};
+pub use generational::Generational;
+pub use graph::{deep_clone, structural_eq};
+pub use incremental::Incremental;
 pub use mark_sweep::MarkSweep;
 pub use ptr::{GcPtr, HasIndirectionPtr, RawGcPtr};
 pub use root_ptr::GcRootPtr;
+pub use snapshot::{GcSnapshot, HeapSnapshot, ObjectSnapshot};
+pub use weak_ptr::{GcWeakPtr, WeakRef};
 
 /// Contains stats about the current state of a GC implementation
 #[derive(Debug, Clone, Default)]
 pub struct Stats {
     pub allocated_memory: usize,
+    /// The number of collections that have been run
+    pub collections: usize,
+    /// The total number of bytes freed by all collections
+    pub freed_memory: usize,
+    /// The duration of the most recent collection
+    pub last_pause: Duration,
+    /// The longest duration of a single collection
+    pub max_pause: Duration,
+    /// The combined duration of all collections
+    pub total_pause: Duration,
+    /// The maximum number of bytes that can be allocated through [`GcRuntime::try_alloc`], if
+    /// limited
+    pub max_heap_size: Option<usize>,
+}
+
+/// Thresholds that determine when a GC implementation needs to collect memory, based on the
+/// allocations since the previous collection. Thresholds that are `None` are disabled, so the
+/// default never requires a collection.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CollectionThresholds {
+    /// Collect when the allocated memory has grown by this factor since the previous collection.
+    /// Before the first collection, and while less than [`Self::MIN_GROWTH_BASE`] bytes are
+    /// retained, growth is relative to [`Self::MIN_GROWTH_BASE`].
+    pub growth_factor: Option<f64>,
+    /// Collect when the allocated memory exceeds this number of bytes.
+    pub max_bytes: Option<usize>,
+    /// Collect when this number of objects has been allocated since the previous collection.
+    pub max_allocations: Option<usize>,
+}
+
+impl CollectionThresholds {
+    /// The minimum number of bytes that the `growth_factor` is applied to.
+    pub const MIN_GROWTH_BASE: usize = 1024 * 1024;
+
+    /// Returns whether any of the thresholds is exceeded, given the `allocated_memory`, the
+    /// `retained_memory` after the previous collection and the number of `allocations` since.
+    pub fn is_exceeded(
+        &self,
+        allocated_memory: usize,
+        retained_memory: usize,
+        allocations: usize,
+    ) -> bool {
+        let growth_exceeded = self.growth_factor.map_or(false, |growth_factor| {
+            let base = retained_memory.max(Self::MIN_GROWTH_BASE);
+            allocated_memory as f64 > base as f64 * growth_factor
+        });
+        let bytes_exceeded = self
+            .max_bytes
+            .map_or(false, |max_bytes| allocated_memory > max_bytes);
+        let allocations_exceeded = self
+            .max_allocations
+            .map_or(false, |max_allocations| allocations >= max_allocations);
+
+        growth_exceeded || bytes_exceeded || allocations_exceeded
+    }
+}
+
+/// An error that occurs when an allocation would exceed the maximum heap size of a GC runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeapLimitExceeded {
+    /// The number of bytes that were requested
+    pub requested: usize,
+    /// The maximum number of bytes that can be allocated
+    pub max_heap_size: usize,
+}
+
+impl fmt::Display for HeapLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "allocating {} bytes would exceed the maximum heap size of {} bytes",
+            self.requested, self.max_heap_size
+        )
+    }
+}
+
+impl std::error::Error for HeapLimitExceeded {}
+
+/// A callback that is invoked for every object right before it is deallocated, with the object's
+/// handle and type. The object's memory is still accessible, but the callback must not call back
+/// into the GC runtime, which is collecting at that moment.
+pub struct Finalizer<T>(Box<dyn Fn(GcPtr, &T) + Send + Sync>);
+
+impl<T> Finalizer<T> {
+    /// Constructs a `Finalizer` from the specified callback.
+    pub fn new<F: Fn(GcPtr, &T) + Send + Sync + 'static>(finalizer: F) -> Self {
+        Finalizer(Box::new(finalizer))
+    }
+
+    /// Invokes the finalizer for the object with the specified `handle` and type.
+    pub fn finalize(&self, handle: GcPtr, ty: &T) {
+        (self.0)(handle, ty)
+    }
+}
+
+impl<T> fmt::Debug for Finalizer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Finalizer").finish_non_exhaustive()
+    }
 }
 
 /// A trait used to trace an object type.
@@ -28,6 +139,13 @@ pub trait GcRuntime<T: TypeMemory + TypeTrace>: Send + Sync {
     /// Allocates an object of the given type returning a GcPtr
     fn alloc(&self, ty: T) -> GcPtr;
 
+    /// Allocates an object of the given type like `alloc`, unless that would exceed the runtime's
+    /// maximum heap size. In that case the runtime requests a collection, which its owner should
+    /// perform at a point where that is safe, and an error is returned.
+    fn try_alloc(&self, ty: T) -> Result<GcPtr, HeapLimitExceeded> {
+        Ok(self.alloc(ty))
+    }
+
     /// Returns the type of the specified `obj`.
     fn ptr_type(&self, obj: GcPtr) -> T;
 
@@ -42,6 +160,19 @@ pub trait GcRuntime<T: TypeMemory + TypeTrace>: Send + Sync {
     /// the same number of times as `root` was called before the object can be collected.
     fn unroot(&self, obj: GcPtr);
 
+    /// Returns a weak reference to the specified `obj`, which does not keep it alive. The weak
+    /// reference is cleared when `obj` is collected.
+    fn downgrade(&self, obj: GcPtr) -> WeakRef;
+
+    /// Roots and returns the object referenced by `weak`, or returns `None` if the object has been
+    /// collected. The object must be unrooted like an object rooted by `root`.
+    fn upgrade(&self, weak: &WeakRef) -> Option<GcPtr>;
+
+    /// Notifies the runtime that a reference to another object is about to be stored in the
+    /// specified `obj`. Incremental runtimes use this to make sure that the stored object is
+    /// traced, other runtimes can ignore it.
+    fn write_barrier(&self, _obj: GcPtr) {}
+
     /// Returns stats about the current state of the runtime.
     fn stats(&self) -> Stats;
 }
@@ -63,7 +194,8 @@ pub enum Event {
     /// A GC cycle started
     Start,
 
-    /// A deallocation took place
+    /// A deallocation took place. If a [`Finalizer`] has been set, it has been invoked for the
+    /// object before this event is emitted.
     Deallocation(GcPtr),
 
     /// A GC cycle ended
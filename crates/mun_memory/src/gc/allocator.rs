@@ -0,0 +1,86 @@
+use std::{alloc::Layout, fmt, ptr::NonNull};
+
+/// Provides the memory of garbage collected objects, and of the bookkeeping that a GC runtime
+/// stores for every object.
+///
+/// # Safety
+///
+/// Implementations must return memory that is valid for the requested layout, and that remains
+/// valid until it is passed to [`Allocator::dealloc`].
+pub unsafe trait Allocator: Send + Sync {
+    /// Allocates memory for the specified `layout`, or returns a null pointer if the allocation
+    /// failed. The size of the `layout` can be zero.
+    fn alloc(&self, layout: Layout) -> *mut u8;
+
+    /// Deallocates the memory at `ptr`, which was allocated with the same `layout`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by [`Allocator::alloc`] for the same `layout`, and must not
+    /// have been deallocated already.
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout);
+
+    /// Allocates zero-initialized memory for the specified `layout`, or returns a null pointer if
+    /// the allocation failed.
+    fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.alloc(layout);
+        if !ptr.is_null() {
+            unsafe { std::ptr::write_bytes(ptr, 0, layout.size()) };
+        }
+        ptr
+    }
+}
+
+impl fmt::Debug for dyn Allocator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Allocator").finish_non_exhaustive()
+    }
+}
+
+/// An [`Allocator`] that uses the global allocator. This is the default allocator of all GC
+/// runtimes.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GlobalAllocator;
+
+unsafe impl Allocator for GlobalAllocator {
+    fn alloc(&self, layout: Layout) -> *mut u8 {
+        if layout.size() == 0 {
+            // The global allocator does not support zero-sized allocations
+            dangling(layout)
+        } else {
+            unsafe { std::alloc::alloc(layout) }
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if layout.size() != 0 {
+            std::alloc::dealloc(ptr, layout)
+        }
+    }
+
+    fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        if layout.size() == 0 {
+            dangling(layout)
+        } else {
+            unsafe { std::alloc::alloc_zeroed(layout) }
+        }
+    }
+}
+
+/// Returns a non-null pointer that is aligned for the `layout`.
+fn dangling(layout: Layout) -> *mut u8 {
+    layout.align() as *mut u8
+}
+
+/// Allocates memory for the specified `layout` using the `allocator`, aborting if the allocation
+/// failed.
+pub(super) fn alloc_or_abort(allocator: &dyn Allocator, layout: Layout) -> NonNull<u8> {
+    NonNull::new(allocator.alloc(layout)).unwrap_or_else(|| std::alloc::handle_alloc_error(layout))
+}
+
+/// Allocates zero-initialized memory for the specified `layout` using the `allocator`, aborting
+/// if the allocation failed.
+pub(super) fn alloc_zeroed_or_abort(allocator: &dyn Allocator, layout: Layout) -> NonNull<u8> {
+    NonNull::new(allocator.alloc_zeroed(layout))
+        .unwrap_or_else(|| std::alloc::handle_alloc_error(layout))
+}
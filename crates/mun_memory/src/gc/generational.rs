@@ -0,0 +1,677 @@
+use super::mark_sweep::{map_objects, mark, CollectionTrigger, Color, ObjectBox, ObjectInfo};
+use crate::{
+    gc::{
+        allocator::alloc_or_abort, snapshot::snapshot_objects, weak_ptr::WeakRefs, Allocator,
+        CollectionThresholds, Event, Finalizer, GcPtr, GcRuntime, GcSnapshot, GlobalAllocator,
+        HeapLimitExceeded, HeapSnapshot, Observer, Stats, TypeTrace, WeakRef,
+    },
+    mapping::{Mapping, MemoryMapper},
+    TypeDesc, TypeMemory,
+};
+use parking_lot::{Mutex, RwLock};
+use std::{
+    alloc::Layout,
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    ptr::NonNull,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+/// Implements a generational garbage collector, that allocates objects in a nursery.
+///
+/// The memory of young objects is bump-allocated in the nursery. A minor collection, performed
+/// through [`Generational::collect_minor`], only traces young objects and promotes the ones that
+/// survive to the old generation, after which the nursery is reused. Objects that do not fit in the
+/// nursery are allocated in the old generation directly. The `ObjectInfo`s of all objects are
+/// bump-allocated in a separate arena, because their addresses are used as handles and thus cannot
+/// move when an object is promoted.
+///
+/// Old objects that references to young objects are stored in, are tracked through
+/// [`GcRuntime::write_barrier`]. The mutator has to call it for every object that a reference is
+/// stored in.
+#[derive(Debug)]
+pub struct Generational<T, O>
+where
+    T: TypeMemory + TypeTrace + Clone,
+    O: Observer<Event = Event>,
+{
+    objects: RwLock<HashMap<GcPtr, ObjectBox<T>>>,
+    allocator: Arc<dyn Allocator>,
+    nursery: Mutex<Nursery>,
+    observer: O,
+    stats: RwLock<Stats>,
+    trigger: RwLock<CollectionTrigger>,
+    weak: WeakRefs,
+    finalizer: RwLock<Option<Finalizer<T>>>,
+}
+
+/// A memory region in which young objects are bump-allocated.
+///
+/// The lock that guards the nursery must always be acquired before the lock that guards the
+/// objects.
+#[derive(Debug)]
+struct Nursery {
+    memory: NonNull<u8>,
+    layout: Layout,
+    allocator: Arc<dyn Allocator>,
+    /// The offset of the first free byte in the nursery
+    offset: usize,
+    /// Objects allocated in the nursery
+    young: Vec<GcPtr>,
+    /// Old objects that might reference young objects
+    remembered: HashSet<GcPtr>,
+    /// Whether an object did not fit in the nursery since the previous collection
+    overflowed: bool,
+    /// The memory in which `ObjectInfo`s are allocated
+    headers: HeaderArena,
+    /// Objects whose `ObjectInfo` was allocated by the allocator while mapping memory, instead of
+    /// in the arena
+    boxed_headers: HashSet<GcPtr>,
+}
+
+/// A `Nursery` is thread-safe.
+unsafe impl Send for Nursery {}
+unsafe impl Sync for Nursery {}
+
+impl Nursery {
+    /// The alignment of the nursery's memory. Objects that require a larger alignment are
+    /// allocated in the old generation.
+    const ALIGNMENT: usize = 16;
+
+    fn new(size: usize, header_layout: Layout, allocator: Arc<dyn Allocator>) -> Self {
+        assert!(size > 0, "the size of the nursery must be non-zero");
+
+        let layout =
+            Layout::from_size_align(size, Self::ALIGNMENT).expect("invalid size of the nursery");
+        let memory = alloc_or_abort(allocator.as_ref(), layout);
+
+        Self {
+            memory,
+            layout,
+            headers: HeaderArena::new(header_layout, allocator.clone()),
+            allocator,
+            offset: 0,
+            young: Vec::new(),
+            remembered: HashSet::new(),
+            overflowed: false,
+            boxed_headers: HashSet::new(),
+        }
+    }
+
+    /// Moves the `info` into the header arena.
+    fn alloc_header<T: TypeMemory + TypeTrace + Clone>(
+        &mut self,
+        info: ObjectInfo<T>,
+    ) -> ObjectBox<T> {
+        let ptr = self.headers.alloc().cast::<ObjectInfo<T>>();
+        unsafe { ObjectBox::write(ptr, info) }
+    }
+
+    /// Drops the `ObjectInfo` of the `object` and releases its memory.
+    ///
+    /// # Safety
+    ///
+    /// The `object` must not be used afterwards.
+    unsafe fn free_header<T: TypeMemory + TypeTrace + Clone>(&mut self, object: &mut ObjectBox<T>) {
+        if self.boxed_headers.remove(&object.handle()) {
+            object.free(self.allocator.as_ref());
+        } else {
+            object.drop_info();
+            self.headers
+                .free(NonNull::new_unchecked(object.as_ptr().cast()));
+        }
+    }
+
+    /// Allocates memory for the `layout` in the nursery, if it fits.
+    fn bump(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        if layout.align() > Self::ALIGNMENT {
+            return None;
+        }
+
+        let start = (self.offset + layout.align() - 1) & !(layout.align() - 1);
+        let end = start.checked_add(layout.size())?;
+
+        // Even zero-sized objects need to start within the nursery, to be recognized as young
+        if start >= self.layout.size() || end > self.layout.size() {
+            return None;
+        }
+
+        self.offset = end;
+        Some(unsafe { NonNull::new_unchecked(self.memory.as_ptr().add(start)) })
+    }
+
+    /// Returns whether the memory pointed to by `ptr` resides in the nursery.
+    fn contains(&self, ptr: *const u8) -> bool {
+        let start = self.memory.as_ptr() as usize;
+        (start..start + self.layout.size()).contains(&(ptr as usize))
+    }
+
+    /// Resets the nursery, after all young objects have been promoted or deallocated.
+    fn reset(&mut self) {
+        self.offset = 0;
+        self.young.clear();
+        self.remembered.clear();
+        self.overflowed = false;
+    }
+}
+
+impl Drop for Nursery {
+    fn drop(&mut self) {
+        unsafe { self.allocator.dealloc(self.memory.as_ptr(), self.layout) };
+    }
+}
+
+/// Blocks of memory in which `ObjectInfo`s are bump-allocated.
+///
+/// The memory of a deallocated `ObjectInfo` is reused for later allocations. Blocks are only
+/// deallocated when the arena is dropped.
+#[derive(Debug)]
+struct HeaderArena {
+    /// The layout of a single `ObjectInfo`
+    layout: Layout,
+    allocator: Arc<dyn Allocator>,
+    blocks: Vec<NonNull<u8>>,
+    /// The number of `ObjectInfo`s that were bump-allocated in the last block
+    used: usize,
+    /// The memory of deallocated `ObjectInfo`s
+    free: Vec<NonNull<u8>>,
+}
+
+/// A `HeaderArena` is thread-safe.
+unsafe impl Send for HeaderArena {}
+unsafe impl Sync for HeaderArena {}
+
+impl HeaderArena {
+    /// The number of `ObjectInfo`s that fit in a single block.
+    const BLOCK_LEN: usize = 1024;
+
+    fn new(layout: Layout, allocator: Arc<dyn Allocator>) -> Self {
+        Self {
+            layout: layout.pad_to_align(),
+            allocator,
+            blocks: Vec::new(),
+            used: 0,
+            free: Vec::new(),
+        }
+    }
+
+    /// Returns the layout of a single block.
+    fn block_layout(&self) -> Layout {
+        Layout::from_size_align(self.layout.size() * Self::BLOCK_LEN, self.layout.align())
+            .expect("invalid size of a header block")
+    }
+
+    /// Allocates memory for a single `ObjectInfo`.
+    fn alloc(&mut self) -> NonNull<u8> {
+        if let Some(ptr) = self.free.pop() {
+            return ptr;
+        }
+
+        let block = match self.blocks.last() {
+            Some(block) if self.used < Self::BLOCK_LEN => *block,
+            _ => {
+                let block = alloc_or_abort(self.allocator.as_ref(), self.block_layout());
+                self.blocks.push(block);
+                self.used = 0;
+                block
+            }
+        };
+
+        let ptr = unsafe { block.as_ptr().add(self.used * self.layout.size()) };
+        self.used += 1;
+        unsafe { NonNull::new_unchecked(ptr) }
+    }
+
+    /// Releases the memory of an `ObjectInfo`, so it can be reused.
+    fn free(&mut self, ptr: NonNull<u8>) {
+        self.free.push(ptr);
+    }
+}
+
+impl Drop for HeaderArena {
+    fn drop(&mut self) {
+        let layout = self.block_layout();
+        for block in self.blocks.drain(..) {
+            unsafe { self.allocator.dealloc(block.as_ptr(), layout) };
+        }
+    }
+}
+
+impl<T, O> Default for Generational<T, O>
+where
+    T: TypeMemory + TypeTrace + Clone,
+    O: Observer<Event = Event> + Default,
+{
+    fn default() -> Self {
+        Self::with_observer(O::default())
+    }
+}
+
+impl<T, O> Generational<T, O>
+where
+    T: TypeMemory + TypeTrace + Clone,
+    O: Observer<Event = Event>,
+{
+    /// The default size of the nursery in bytes.
+    pub const DEFAULT_NURSERY_SIZE: usize = 256 * 1024;
+
+    /// Creates a `Generational` memory collector with the specified `Observer`.
+    pub fn with_observer(observer: O) -> Self {
+        Self::with_nursery_size(observer, Self::DEFAULT_NURSERY_SIZE)
+    }
+
+    /// Creates a `Generational` memory collector with the specified `Observer` and a nursery of
+    /// `nursery_size` bytes.
+    pub fn with_nursery_size(observer: O, nursery_size: usize) -> Self {
+        Self::with_allocator(observer, nursery_size, Arc::new(GlobalAllocator))
+    }
+
+    /// Creates a `Generational` memory collector with the specified `Observer` and a nursery of
+    /// `nursery_size` bytes, that allocates all memory using the specified `Allocator`.
+    pub fn with_allocator(observer: O, nursery_size: usize, allocator: Arc<dyn Allocator>) -> Self {
+        Self {
+            objects: RwLock::new(HashMap::new()),
+            nursery: Mutex::new(Nursery::new(
+                nursery_size,
+                Layout::new::<ObjectInfo<T>>(),
+                allocator.clone(),
+            )),
+            allocator,
+            observer,
+            stats: RwLock::new(Stats::default()),
+            trigger: RwLock::new(CollectionTrigger::default()),
+            weak: WeakRefs::default(),
+            finalizer: RwLock::new(None),
+        }
+    }
+
+    /// Logs an allocation, whose memory was already added to the allocated memory
+    fn log_alloc(&self, handle: GcPtr) {
+        self.trigger.write().allocations += 1;
+
+        self.observer.event(Event::Allocation(handle));
+    }
+
+    /// Allocates an object of type `ty`, whose memory was already added to the allocated memory.
+    fn alloc_reserved(&self, ty: T) -> GcPtr {
+        let mut nursery = self.nursery.lock();
+        let (ptr, is_young) = match nursery.bump(ty.layout()) {
+            Some(ptr) => (ptr, true),
+            None => {
+                nursery.overflowed = true;
+                (alloc_or_abort(self.allocator.as_ref(), ty.layout()), false)
+            }
+        };
+        let object = nursery.alloc_header(ObjectInfo {
+            ptr: ptr.as_ptr(),
+            ty,
+            roots: AtomicU32::new(0),
+            color: Color::White,
+        });
+
+        // We want to return a pointer to the `ObjectInfo`, to be used as handle.
+        let handle = object.handle();
+
+        if is_young {
+            nursery.young.push(handle);
+        } else {
+            // Old objects are initialized without a write barrier, so they might reference young
+            // objects
+            nursery.remembered.insert(handle);
+        }
+
+        {
+            let mut objects = self.objects.write();
+            objects.insert(handle, object);
+        }
+        drop(nursery);
+
+        self.log_alloc(handle);
+        handle
+    }
+
+    /// Finalizes an object that is about to be deallocated
+    fn finalize(&self, handle: GcPtr, ty: &T) {
+        self.weak.clear(handle);
+        if let Some(finalizer) = self.finalizer.read().as_ref() {
+            finalizer.finalize(handle, ty);
+        }
+    }
+
+    /// Logs a deallocation
+    fn log_dealloc(&self, handle: GcPtr, ty: &T) {
+        self.observer.event(Event::Deallocation(handle));
+
+        let mut stats = self.stats.write();
+        stats.allocated_memory -= ty.layout().size();
+        stats.freed_memory += ty.layout().size();
+    }
+
+    /// Logs the end of a collection that started at `start_time`. Only a major collection resets
+    /// the allocations since the previous collection.
+    fn log_collection(&self, start_time: Instant, major: bool) {
+        {
+            let mut stats = self.stats.write();
+            let pause = start_time.elapsed();
+            stats.collections += 1;
+            stats.last_pause = pause;
+            stats.max_pause = stats.max_pause.max(pause);
+            stats.total_pause += pause;
+
+            if major {
+                self.trigger.write().reset(stats.allocated_memory);
+            }
+        }
+
+        self.observer.event(Event::End);
+    }
+
+    /// Returns the observer
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    /// Returns the thresholds that determine when a collection is needed.
+    pub fn thresholds(&self) -> CollectionThresholds {
+        self.trigger.read().thresholds
+    }
+
+    /// Sets the thresholds that determine when a collection is needed.
+    pub fn set_thresholds(&self, thresholds: CollectionThresholds) {
+        self.trigger.write().thresholds = thresholds;
+    }
+
+    /// Sets the finalizer that is invoked for every object right before it is deallocated.
+    pub fn set_finalizer(&self, finalizer: Finalizer<T>) {
+        *self.finalizer.write() = Some(finalizer);
+    }
+
+    /// Returns the maximum number of bytes that can be allocated through
+    /// [`GcRuntime::try_alloc`], if limited.
+    pub fn max_heap_size(&self) -> Option<usize> {
+        self.stats.read().max_heap_size
+    }
+
+    /// Sets the maximum number of bytes that can be allocated through [`GcRuntime::try_alloc`].
+    /// `None` removes the limit.
+    pub fn set_max_heap_size(&self, max_heap_size: Option<usize>) {
+        self.stats.write().max_heap_size = max_heap_size;
+    }
+
+    /// Returns whether the allocations since the previous collection exceeded the thresholds.
+    ///
+    /// The collector never collects on its own accord, because objects that are only referenced
+    /// from the stack are not rooted. The owner of the collector should call
+    /// [`Generational::collect`] at a point where that is safe.
+    pub fn needs_collection(&self) -> bool {
+        let allocated_memory = self.stats.read().allocated_memory;
+        self.trigger.read().is_exceeded(allocated_memory)
+    }
+
+    /// Returns whether an object did not fit in the nursery since the previous collection. Like
+    /// with [`Generational::needs_collection`], the owner of the collector should call
+    /// [`Generational::collect_minor`] at a point where that is safe.
+    pub fn needs_minor_collection(&self) -> bool {
+        self.nursery.lock().overflowed
+    }
+
+    /// Collects all young objects that are no longer referenced by rooted objects or old objects,
+    /// and promotes the surviving young objects to the old generation. Returns `true` if memory
+    /// was reclaimed, `false` otherwise.
+    pub fn collect_minor(&self) -> bool {
+        self.observer.event(Event::Start);
+        let start_time = Instant::now();
+
+        let mut nursery = self.nursery.lock();
+        let mut objects = self.objects.write();
+
+        // Get all young objects that are rooted or referenced by old objects
+        let mut gray = nursery
+            .young
+            .iter()
+            .filter(|handle| objects[*handle].roots.load(Ordering::Relaxed) > 0)
+            .copied()
+            .collect::<Vec<_>>();
+        for handle in nursery.remembered.iter() {
+            let ty = objects[handle].ty.clone();
+            gray.extend(
+                ty.trace(*handle)
+                    .filter(|reference| nursery.contains(objects[reference].ptr)),
+            );
+        }
+
+        // Trace all young objects, ignoring old objects
+        while let Some(next) = gray.pop() {
+            let obj = objects.get_mut(&next).expect("found invalid reference");
+            if obj.color == Color::Black {
+                continue;
+            }
+            obj.color = Color::Black;
+
+            let ty = obj.ty.clone();
+            for reference in ty.trace(next) {
+                let obj = objects.get(&reference).expect("found invalid reference");
+                if obj.color == Color::White && nursery.contains(obj.ptr) {
+                    gray.push(reference);
+                }
+            }
+        }
+
+        let reclaimed = self.evacuate(&mut nursery, &mut objects, true);
+
+        self.log_collection(start_time, false);
+
+        reclaimed
+    }
+
+    /// Collects all memory that is no longer referenced by rooted objects, in both generations.
+    /// Returns `true` if memory was reclaimed, `false` otherwise.
+    pub fn collect(&self) -> bool {
+        self.observer.event(Event::Start);
+        let start_time = Instant::now();
+
+        let mut nursery = self.nursery.lock();
+        let mut objects = self.objects.write();
+
+        mark(&mut objects);
+
+        // Sweep all non-reachable old objects
+        let size_before = objects.len();
+        objects.retain(|h, obj| {
+            if nursery.contains(obj.ptr) {
+                // Young objects are swept when the nursery is evacuated
+                true
+            } else if obj.color == Color::Black {
+                obj.color = Color::White;
+                true
+            } else {
+                self.finalize(*h, &obj.ty);
+                unsafe { self.allocator.dealloc(obj.ptr, obj.ty.layout()) };
+                self.log_dealloc(*h, &obj.ty);
+                unsafe { nursery.free_header(obj) };
+                false
+            }
+        });
+        let size_after = objects.len();
+
+        let reclaimed = self.evacuate(&mut nursery, &mut objects, true);
+
+        self.log_collection(start_time, true);
+
+        reclaimed || size_before != size_after
+    }
+
+    /// Moves all young objects to the old generation, after which the nursery is reset. If
+    /// `sweep` is `true`, only black objects are promoted and other young objects are deallocated.
+    /// Returns `true` if young objects were deallocated, `false` otherwise.
+    fn evacuate(
+        &self,
+        nursery: &mut Nursery,
+        objects: &mut HashMap<GcPtr, ObjectBox<T>>,
+        sweep: bool,
+    ) -> bool {
+        let mut reclaimed = false;
+        let young = std::mem::take(&mut nursery.young);
+        for handle in young.iter() {
+            let obj = objects.get_mut(handle).expect("found invalid reference");
+            if !sweep || obj.color == Color::Black {
+                let layout = obj.ty.layout();
+                let ptr = alloc_or_abort(self.allocator.as_ref(), layout).as_ptr();
+                unsafe { std::ptr::copy_nonoverlapping(obj.ptr, ptr, layout.size()) };
+                obj.ptr = ptr;
+                obj.color = Color::White;
+            } else {
+                let mut obj = objects.remove(handle).unwrap();
+                self.finalize(*handle, &obj.ty);
+                self.log_dealloc(*handle, &obj.ty);
+                unsafe { nursery.free_header(&mut obj) };
+                reclaimed = true;
+            }
+        }
+
+        nursery.reset();
+        reclaimed
+    }
+}
+
+impl<T, O> GcRuntime<T> for Generational<T, O>
+where
+    T: TypeMemory + TypeTrace + Clone,
+    O: Observer<Event = Event>,
+{
+    fn alloc(&self, ty: T) -> GcPtr {
+        self.stats.write().allocated_memory += ty.layout().size();
+        self.alloc_reserved(ty)
+    }
+
+    fn try_alloc(&self, ty: T) -> Result<GcPtr, HeapLimitExceeded> {
+        // The memory is reserved while the stats are locked, so concurrent allocations cannot
+        // exceed the limit together
+        {
+            let mut stats = self.stats.write();
+            self.trigger
+                .write()
+                .reserve_memory(&mut stats, ty.layout().size())?;
+        }
+        Ok(self.alloc_reserved(ty))
+    }
+
+    fn ptr_type(&self, handle: GcPtr) -> T {
+        let _lock = self.objects.read();
+
+        // Convert the handle to our internal representation
+        let object_info: *const ObjectInfo<T> = handle.into();
+
+        // Return the type of the object
+        unsafe { (*object_info).ty.clone() }
+    }
+
+    fn root(&self, handle: GcPtr) {
+        // Roots are counted atomically, so concurrent rooting only needs to prevent collection
+        let _lock = self.objects.read();
+
+        // Convert the handle to our internal representation
+        let object_info: *const ObjectInfo<T> = handle.into();
+
+        unsafe { (*object_info).roots.fetch_add(1, Ordering::Relaxed) };
+    }
+
+    fn unroot(&self, handle: GcPtr) {
+        // Roots are counted atomically, so concurrent unrooting only needs to prevent collection
+        let _lock = self.objects.read();
+
+        // Convert the handle to our internal representation
+        let object_info: *const ObjectInfo<T> = handle.into();
+
+        unsafe { (*object_info).roots.fetch_sub(1, Ordering::Relaxed) };
+    }
+
+    fn downgrade(&self, handle: GcPtr) -> WeakRef {
+        self.weak.downgrade(handle)
+    }
+
+    fn upgrade(&self, weak: &WeakRef) -> Option<GcPtr> {
+        // Weak references are cleared during collection, which cannot run while we hold the lock
+        let _lock = self.objects.read();
+        let handle = weak.get()?;
+
+        // Convert the handle to our internal representation
+        let object_info: *const ObjectInfo<T> = handle.into();
+
+        unsafe { (*object_info).roots.fetch_add(1, Ordering::Relaxed) };
+        Some(handle)
+    }
+
+    fn write_barrier(&self, handle: GcPtr) {
+        let mut nursery = self.nursery.lock();
+        let _lock = self.objects.read();
+
+        // Convert the handle to our internal representation
+        let object_info: *const ObjectInfo<T> = handle.into();
+
+        // An old object that is modified might reference young objects
+        if !nursery.young.is_empty() && !nursery.contains(unsafe { (*object_info).ptr }) {
+            nursery.remembered.insert(handle);
+        }
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.read().clone()
+    }
+}
+
+impl<T, O> Drop for Generational<T, O>
+where
+    T: TypeMemory + TypeTrace + Clone,
+    O: Observer<Event = Event>,
+{
+    fn drop(&mut self) {
+        // The memory of young objects is deallocated with the nursery
+        let nursery = self.nursery.get_mut();
+        for (_, mut obj) in self.objects.get_mut().drain() {
+            if !nursery.contains(obj.ptr) {
+                unsafe { self.allocator.dealloc(obj.ptr, obj.ty.layout()) };
+            }
+            unsafe { nursery.free_header(&mut obj) };
+        }
+    }
+}
+
+impl<T, O> GcSnapshot<T> for Generational<T, O>
+where
+    T: TypeDesc + TypeMemory + TypeTrace + Clone,
+    O: Observer<Event = Event>,
+{
+    fn snapshot(&self) -> HeapSnapshot {
+        snapshot_objects(&self.objects.read())
+    }
+}
+
+impl<T, O> MemoryMapper<T> for Generational<T, O>
+where
+    T: TypeDesc + TypeMemory + TypeTrace + Clone + Eq + Hash,
+    O: Observer<Event = Event>,
+{
+    fn map_memory(&self, mapping: Mapping<T, T>) -> Vec<GcPtr> {
+        let mut nursery = self.nursery.lock();
+        let mut objects = self.objects.write();
+
+        // Mapping reallocates the memory of converted objects, which requires all objects to be
+        // allocated in the old generation
+        self.evacuate(&mut nursery, &mut objects, false);
+
+        let (deleted, new_allocations) =
+            map_objects(self.allocator.as_ref(), &mut objects, mapping);
+        for (handle, ty) in new_allocations {
+            self.stats.write().allocated_memory += ty.layout().size();
+            nursery.boxed_headers.insert(handle);
+            self.log_alloc(handle);
+        }
+
+        deleted
+    }
+}
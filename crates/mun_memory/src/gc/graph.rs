@@ -0,0 +1,164 @@
+use super::{GcPtr, GcRuntime, HasIndirectionPtr, RawGcPtr, TypeTrace};
+use crate::{TypeDesc, TypeFields, TypeGroup, TypeMemory};
+use abi::HasStaticTypeInfo;
+use std::collections::{HashMap, HashSet};
+
+/// Allocates a deep copy of `obj`. All objects that are (indirectly) referenced by `obj` are
+/// copied as well, preserving the references between them, including cycles. Value structs are
+/// copied as part of the object that contains them.
+///
+/// The returned copy is not rooted.
+pub fn deep_clone<T, G>(runtime: &G, obj: GcPtr) -> GcPtr
+where
+    T: TypeDesc + TypeMemory + TypeFields<T> + TypeTrace + Clone,
+    G: GcRuntime<T> + ?Sized,
+{
+    // Copies are rooted until all references have been replaced, so they cannot be collected
+    let mut copies = HashMap::new();
+    let copy = copy_object(runtime, obj);
+    copies.insert(obj, copy);
+
+    let mut queue = vec![copy];
+    while let Some(copy) = queue.pop() {
+        let ty = runtime.ptr_type(copy);
+        for_each_reference(
+            &ty,
+            unsafe { copy.deref::<u8>() } as *mut u8,
+            &mut |field| {
+                let reference = unsafe { field.read_unaligned() };
+                if RawGcPtr::from(reference).is_null() {
+                    return;
+                }
+
+                let reference_copy = *copies.entry(reference).or_insert_with(|| {
+                    let reference_copy = copy_object(runtime, reference);
+                    queue.push(reference_copy);
+                    reference_copy
+                });
+                unsafe { field.write_unaligned(reference_copy) };
+            },
+        );
+        runtime.write_barrier(copy);
+    }
+
+    for copy in copies.values() {
+        runtime.unroot(*copy);
+    }
+    copies[&obj]
+}
+
+/// Returns whether `a` and `b` are structurally equal, i.e. whether they are of the same type and
+/// all of their fields are equal. Referenced objects are compared recursively, so cyclic object
+/// graphs are equal if they have the same shape and equal values.
+///
+/// Floating-point fields are compared by value, other primitive fields are compared bitwise.
+pub fn structural_eq<T, G>(runtime: &G, a: GcPtr, b: GcPtr) -> bool
+where
+    T: TypeDesc + TypeMemory + TypeFields<T> + TypeTrace + Clone,
+    G: GcRuntime<T> + ?Sized,
+{
+    // Pairs of objects that have been compared are assumed to be equal, which terminates cycles
+    let mut visited = HashSet::new();
+    let mut queue = vec![(a, b)];
+    while let Some((a, b)) = queue.pop() {
+        if a == b || !visited.insert((a, b)) {
+            continue;
+        }
+
+        let ty = runtime.ptr_type(a);
+        if ty.guid() != runtime.ptr_type(b).guid() {
+            return false;
+        }
+
+        let (a, b) = unsafe { (a.deref::<u8>(), b.deref::<u8>()) };
+        if !value_eq(&ty, a, b, &mut queue) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Allocates a rooted, shallow copy of `obj`.
+fn copy_object<T, G>(runtime: &G, obj: GcPtr) -> GcPtr
+where
+    T: TypeMemory + TypeTrace + Clone,
+    G: GcRuntime<T> + ?Sized,
+{
+    let ty = runtime.ptr_type(obj);
+    let mut copy = runtime.alloc(ty.clone());
+    runtime.root(copy);
+    unsafe {
+        std::ptr::copy_nonoverlapping(
+            obj.deref::<u8>(),
+            copy.deref_mut::<u8>(),
+            ty.layout().size(),
+        )
+    };
+    copy
+}
+
+/// Invokes `f` for every field of the value of type `ty` at `ptr` that references an object,
+/// including the fields of value structs that are stored inline.
+fn for_each_reference<T>(ty: &T, ptr: *mut u8, f: &mut impl FnMut(*mut GcPtr))
+where
+    T: TypeDesc + TypeMemory + TypeFields<T>,
+{
+    for ((_, field_ty), offset) in ty.fields().iter().zip(ty.offsets().iter()) {
+        if field_ty.group() == TypeGroup::Struct {
+            let field = unsafe { ptr.add(usize::from(*offset)) };
+            if field_ty.is_stack_allocated() {
+                for_each_reference(field_ty, field, f);
+            } else {
+                f(field.cast::<GcPtr>());
+            }
+        }
+    }
+}
+
+/// Returns whether the values of type `ty` at `a` and `b` are equal. Pairs of referenced objects
+/// are pushed onto the `queue` to be compared later.
+fn value_eq<T>(ty: &T, a: *const u8, b: *const u8, queue: &mut Vec<(GcPtr, GcPtr)>) -> bool
+where
+    T: TypeDesc + TypeMemory + TypeFields<T>,
+{
+    if ty.group() == TypeGroup::Primitive {
+        return unsafe { primitive_eq(ty.guid(), ty.layout().size(), a, b) };
+    }
+
+    ty.fields()
+        .iter()
+        .zip(ty.offsets().iter())
+        .all(|((_, field_ty), offset)| {
+            let offset = usize::from(*offset);
+            let (a, b) = unsafe { (a.add(offset), b.add(offset)) };
+            if field_ty.group() == TypeGroup::Struct && !field_ty.is_stack_allocated() {
+                let (a, b) = unsafe {
+                    (
+                        a.cast::<GcPtr>().read_unaligned(),
+                        b.cast::<GcPtr>().read_unaligned(),
+                    )
+                };
+                match (RawGcPtr::from(a).is_null(), RawGcPtr::from(b).is_null()) {
+                    (true, true) => true,
+                    (false, false) => {
+                        queue.push((a, b));
+                        true
+                    }
+                    _ => false,
+                }
+            } else {
+                value_eq(field_ty, a, b, queue)
+            }
+        })
+}
+
+/// Returns whether the primitive values of `size` bytes at `a` and `b` are equal.
+unsafe fn primitive_eq(guid: &abi::Guid, size: usize, a: *const u8, b: *const u8) -> bool {
+    if *guid == f32::type_info().guid {
+        a.cast::<f32>().read_unaligned() == b.cast::<f32>().read_unaligned()
+    } else if *guid == f64::type_info().guid {
+        a.cast::<f64>().read_unaligned() == b.cast::<f64>().read_unaligned()
+    } else {
+        std::slice::from_raw_parts(a, size) == std::slice::from_raw_parts(b, size)
+    }
+}
@@ -0,0 +1,499 @@
+use super::mark_sweep::{
+    alloc_obj, dealloc_objects, map_objects, CollectionTrigger, Color, ObjectBox, ObjectInfo,
+};
+use crate::{
+    gc::{
+        snapshot::snapshot_objects, weak_ptr::WeakRefs, Allocator, CollectionThresholds, Event,
+        Finalizer, GcPtr, GcRuntime, GcSnapshot, GlobalAllocator, HeapLimitExceeded, HeapSnapshot,
+        Observer, Stats, TypeTrace, WeakRef,
+    },
+    mapping::{Mapping, MemoryMapper},
+    TypeDesc, TypeMemory,
+};
+use parking_lot::{Mutex, RwLock};
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::{atomic::Ordering, Arc},
+    time::{Duration, Instant},
+};
+
+/// Implements an incremental tri-color mark-sweep garbage collector.
+///
+/// A collection cycle can be split into steps with [`Incremental::collect_step`], which allows
+/// hosts to spread the cost of a collection over multiple frames. In between steps the mutator is
+/// allowed to modify objects, as long as it calls [`GcRuntime::write_barrier`] for every object
+/// that a reference is stored in.
+#[derive(Debug)]
+pub struct Incremental<T, O>
+where
+    T: TypeMemory + TypeTrace + Clone,
+    O: Observer<Event = Event>,
+{
+    objects: RwLock<HashMap<GcPtr, ObjectBox<T>>>,
+    allocator: Arc<dyn Allocator>,
+    cycle: Mutex<Cycle>,
+    observer: O,
+    stats: RwLock<Stats>,
+    trigger: RwLock<CollectionTrigger>,
+    weak: WeakRefs,
+    finalizer: RwLock<Option<Finalizer<T>>>,
+}
+
+/// The phase of a collection cycle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Phase {
+    /// No collection cycle is in progress
+    Idle,
+
+    /// Gray objects are being traced
+    Mark,
+
+    /// Objects that remained white are being deallocated
+    Sweep,
+}
+
+/// The state of the collection cycle that is in progress.
+///
+/// The lock that guards the cycle must always be acquired before the lock that guards the
+/// objects, as it also guards the colors of the objects.
+#[derive(Debug)]
+struct Cycle {
+    phase: Phase,
+    /// Objects that have been seen, but whose references have not yet been traced
+    gray: Vec<GcPtr>,
+    /// Objects that have not yet been swept
+    unswept: Vec<GcPtr>,
+    /// Whether memory has been reclaimed in the current cycle
+    reclaimed: bool,
+}
+
+impl Default for Cycle {
+    fn default() -> Self {
+        Cycle {
+            phase: Phase::Idle,
+            gray: Vec::new(),
+            unswept: Vec::new(),
+            reclaimed: false,
+        }
+    }
+}
+
+impl<T, O> Default for Incremental<T, O>
+where
+    T: TypeMemory + TypeTrace + Clone,
+    O: Observer<Event = Event> + Default,
+{
+    fn default() -> Self {
+        Self::with_observer(O::default())
+    }
+}
+
+impl<T, O> Incremental<T, O>
+where
+    T: TypeMemory + TypeTrace + Clone,
+    O: Observer<Event = Event>,
+{
+    /// Creates an `Incremental` memory collector with the specified `Observer`.
+    pub fn with_observer(observer: O) -> Self {
+        Self::with_allocator(observer, Arc::new(GlobalAllocator))
+    }
+
+    /// Creates an `Incremental` memory collector with the specified `Observer`, that allocates
+    /// all memory using the specified `Allocator`.
+    pub fn with_allocator(observer: O, allocator: Arc<dyn Allocator>) -> Self {
+        Self {
+            objects: RwLock::new(HashMap::new()),
+            allocator,
+            cycle: Mutex::new(Cycle::default()),
+            observer,
+            stats: RwLock::new(Stats::default()),
+            trigger: RwLock::new(CollectionTrigger::default()),
+            weak: WeakRefs::default(),
+            finalizer: RwLock::new(None),
+        }
+    }
+
+    /// Logs an allocation, whose memory was already added to the allocated memory
+    fn log_alloc(&self, handle: GcPtr) {
+        self.trigger.write().allocations += 1;
+
+        self.observer.event(Event::Allocation(handle));
+    }
+
+    /// Allocates an object of type `ty`, whose memory was already added to the allocated memory.
+    fn alloc_reserved(&self, ty: T) -> GcPtr {
+        let mut object = alloc_obj(self.allocator.as_ref(), ty);
+
+        // We want to return a pointer to the `ObjectInfo`, to be used as handle.
+        let handle = object.handle();
+
+        {
+            let mut cycle = self.cycle.lock();
+
+            // Objects allocated while marking survive the current cycle. They are traced once
+            // they have been initialized, because references that are stored in a new object - e.g.
+            // by a struct literal - are not caught by the write barrier.
+            if cycle.phase == Phase::Mark {
+                object.color = Color::Gray;
+                cycle.gray.push(handle);
+            }
+
+            let mut objects = self.objects.write();
+            objects.insert(handle, object);
+        }
+
+        self.log_alloc(handle);
+        handle
+    }
+
+    /// Returns the observer
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    /// Returns the thresholds that determine when a collection is needed.
+    pub fn thresholds(&self) -> CollectionThresholds {
+        self.trigger.read().thresholds
+    }
+
+    /// Sets the thresholds that determine when a collection is needed.
+    pub fn set_thresholds(&self, thresholds: CollectionThresholds) {
+        self.trigger.write().thresholds = thresholds;
+    }
+
+    /// Sets the finalizer that is invoked for every object right before it is deallocated.
+    pub fn set_finalizer(&self, finalizer: Finalizer<T>) {
+        *self.finalizer.write() = Some(finalizer);
+    }
+
+    /// Returns the maximum number of bytes that can be allocated through
+    /// [`GcRuntime::try_alloc`], if limited.
+    pub fn max_heap_size(&self) -> Option<usize> {
+        self.stats.read().max_heap_size
+    }
+
+    /// Sets the maximum number of bytes that can be allocated through [`GcRuntime::try_alloc`].
+    /// `None` removes the limit.
+    pub fn set_max_heap_size(&self, max_heap_size: Option<usize>) {
+        self.stats.write().max_heap_size = max_heap_size;
+    }
+
+    /// Returns whether the allocations since the previous collection exceeded the thresholds.
+    ///
+    /// The collector never collects on its own accord, because objects that are only referenced
+    /// from the stack are not rooted. The owner of the collector should call
+    /// [`Incremental::collect_step`] or [`Incremental::collect`] at a point where that is safe.
+    pub fn needs_collection(&self) -> bool {
+        let allocated_memory = self.stats.read().allocated_memory;
+        self.trigger.read().is_exceeded(allocated_memory)
+    }
+
+    /// Returns whether a collection cycle is in progress.
+    pub fn is_collecting(&self) -> bool {
+        self.cycle.lock().phase != Phase::Idle
+    }
+
+    /// Performs a step of a collection cycle, starting a new cycle if none is in progress. The
+    /// step ends as soon as possible after the `budget` has been spent, but always makes some
+    /// progress. Returns `true` if the step completed the collection cycle, `false` otherwise.
+    ///
+    /// Every step is recorded as a pause in the [`Stats`], whereas
+    /// [`collections`](Stats::collections) only counts completed cycles.
+    pub fn collect_step(&self, budget: Duration) -> bool {
+        self.step(Some(budget)).is_some()
+    }
+
+    /// Collects all memory that is no longer referenced by rooted objects. A collection cycle that
+    /// is in progress is finished first, after which a full cycle is performed. Returns `true` if
+    /// memory was reclaimed, `false` otherwise.
+    pub fn collect(&self) -> bool {
+        let mut reclaimed = false;
+        if self.is_collecting() {
+            reclaimed = self.step(None).unwrap_or_default();
+        }
+        self.step(None).unwrap_or_default() || reclaimed
+    }
+
+    /// Performs a step of a collection cycle that spends at most `budget`, or finishes the cycle
+    /// if no budget is specified. Returns whether memory was reclaimed if the cycle completed,
+    /// `None` otherwise.
+    fn step(&self, budget: Option<Duration>) -> Option<bool> {
+        let start_time = Instant::now();
+
+        let mut cycle = self.cycle.lock();
+        let mut objects = self.objects.write();
+
+        if cycle.phase == Phase::Idle {
+            self.observer.event(Event::Start);
+
+            // Gray all roots
+            cycle.phase = Phase::Mark;
+            cycle.reclaimed = false;
+            for (handle, obj) in objects.iter_mut() {
+                if obj.roots.load(Ordering::Relaxed) > 0 {
+                    obj.color = Color::Gray;
+                    cycle.gray.push(*handle);
+                }
+            }
+        }
+
+        let reclaimed = loop {
+            match cycle.phase {
+                Phase::Mark => {
+                    if let Some(next) = cycle.gray.pop() {
+                        // Trace all other objects
+                        let ty = objects
+                            .get(&next)
+                            .expect("found invalid reference")
+                            .ty
+                            .clone();
+                        for reference in ty.trace(next) {
+                            let obj = objects
+                                .get_mut(&reference)
+                                .expect("found invalid reference");
+                            if obj.color == Color::White {
+                                obj.color = Color::Gray;
+                                cycle.gray.push(reference);
+                            }
+                        }
+
+                        // This object has been traced
+                        let obj = objects.get_mut(&next).unwrap();
+                        obj.color = Color::Black;
+                    } else {
+                        // All reachable objects have been marked. Objects allocated from here on
+                        // are not part of the sweep.
+                        cycle.phase = Phase::Sweep;
+                        cycle.unswept = objects.keys().copied().collect();
+
+                        // White objects are garbage, so they can no longer be upgraded from weak
+                        // references
+                        for (handle, obj) in objects.iter() {
+                            if obj.color == Color::White {
+                                self.weak.clear(*handle);
+                            }
+                        }
+                    }
+                }
+                Phase::Sweep => {
+                    if let Some(next) = cycle.unswept.pop() {
+                        let obj = objects.get_mut(&next).expect("found invalid reference");
+                        if obj.color == Color::Black {
+                            obj.color = Color::White;
+                        } else {
+                            let mut obj = objects.remove(&next).unwrap();
+                            self.weak.clear(next);
+                            if let Some(finalizer) = self.finalizer.read().as_ref() {
+                                finalizer.finalize(next, &obj.ty);
+                            }
+                            unsafe { self.allocator.dealloc(obj.ptr, obj.ty.layout()) };
+                            self.observer.event(Event::Deallocation(next));
+                            {
+                                let mut stats = self.stats.write();
+                                stats.allocated_memory -= obj.ty.layout().size();
+                                stats.freed_memory += obj.ty.layout().size();
+                            }
+                            unsafe { obj.free(self.allocator.as_ref()) };
+                            cycle.reclaimed = true;
+                        }
+                    } else {
+                        cycle.phase = Phase::Idle;
+                        break Some(cycle.reclaimed);
+                    }
+                }
+                Phase::Idle => unreachable!("a collection cycle must be in progress"),
+            }
+
+            if let Some(budget) = budget {
+                if start_time.elapsed() >= budget {
+                    break None;
+                }
+            }
+        };
+
+        {
+            let mut stats = self.stats.write();
+            let pause = start_time.elapsed();
+            stats.last_pause = pause;
+            stats.max_pause = stats.max_pause.max(pause);
+            stats.total_pause += pause;
+
+            if reclaimed.is_some() {
+                stats.collections += 1;
+                self.trigger.write().reset(stats.allocated_memory);
+            }
+        }
+
+        if reclaimed.is_some() {
+            self.observer.event(Event::End);
+        }
+
+        reclaimed
+    }
+
+    /// Grays the specified object, if a collection cycle is marking objects and the object's color
+    /// is not already gray. Only white objects are grayed, unless `include_black` is `true`.
+    fn gray(&self, handle: GcPtr, include_black: bool) {
+        let mut cycle = self.cycle.lock();
+        if cycle.phase != Phase::Mark {
+            return;
+        }
+
+        let _lock = self.objects.read();
+
+        // Convert the handle to our internal representation
+        let object_info: *mut ObjectInfo<T> = handle.into();
+
+        // The colors of objects are guarded by the cycle lock
+        let color = unsafe { &mut (*object_info).color };
+        if *color == Color::White || (include_black && *color == Color::Black) {
+            *color = Color::Gray;
+            cycle.gray.push(handle);
+        }
+    }
+}
+
+impl<T, O> GcRuntime<T> for Incremental<T, O>
+where
+    T: TypeMemory + TypeTrace + Clone,
+    O: Observer<Event = Event>,
+{
+    fn alloc(&self, ty: T) -> GcPtr {
+        self.stats.write().allocated_memory += ty.layout().size();
+        self.alloc_reserved(ty)
+    }
+
+    fn try_alloc(&self, ty: T) -> Result<GcPtr, HeapLimitExceeded> {
+        // The memory is reserved while the stats are locked, so concurrent allocations cannot
+        // exceed the limit together
+        {
+            let mut stats = self.stats.write();
+            self.trigger
+                .write()
+                .reserve_memory(&mut stats, ty.layout().size())?;
+        }
+        Ok(self.alloc_reserved(ty))
+    }
+
+    fn ptr_type(&self, handle: GcPtr) -> T {
+        let _lock = self.objects.read();
+
+        // Convert the handle to our internal representation
+        let object_info: *const ObjectInfo<T> = handle.into();
+
+        // Return the type of the object
+        unsafe { (*object_info).ty.clone() }
+    }
+
+    fn root(&self, handle: GcPtr) {
+        {
+            // Roots are counted atomically, so concurrent rooting only needs to prevent collection
+            let _lock = self.objects.read();
+
+            // Convert the handle to our internal representation
+            let object_info: *const ObjectInfo<T> = handle.into();
+
+            unsafe { (*object_info).roots.fetch_add(1, Ordering::Relaxed) };
+        }
+
+        // An object that is rooted while marking might only have been referenced by the stack
+        self.gray(handle, false);
+    }
+
+    fn unroot(&self, handle: GcPtr) {
+        // Roots are counted atomically, so concurrent unrooting only needs to prevent collection
+        let _lock = self.objects.read();
+
+        // Convert the handle to our internal representation
+        let object_info: *const ObjectInfo<T> = handle.into();
+
+        unsafe { (*object_info).roots.fetch_sub(1, Ordering::Relaxed) };
+    }
+
+    fn downgrade(&self, handle: GcPtr) -> WeakRef {
+        self.weak.downgrade(handle)
+    }
+
+    fn upgrade(&self, weak: &WeakRef) -> Option<GcPtr> {
+        // Weak references are cleared as soon as their objects are known to be garbage, which
+        // cannot happen while we hold the locks
+        let mut cycle = self.cycle.lock();
+        let _lock = self.objects.read();
+        let handle = weak.get()?;
+
+        // Convert the handle to our internal representation
+        let object_info: *mut ObjectInfo<T> = handle.into();
+
+        unsafe { (*object_info).roots.fetch_add(1, Ordering::Relaxed) };
+
+        // An object that is only weakly reachable might not have been marked. The colors of
+        // objects are guarded by the cycle lock.
+        let color = unsafe { &mut (*object_info).color };
+        if cycle.phase == Phase::Mark && *color == Color::White {
+            *color = Color::Gray;
+            cycle.gray.push(handle);
+        }
+
+        Some(handle)
+    }
+
+    fn write_barrier(&self, handle: GcPtr) {
+        // A black object that is modified while marking needs to be traced again
+        self.gray(handle, true);
+    }
+
+    fn stats(&self) -> Stats {
+        self.stats.read().clone()
+    }
+}
+
+impl<T, O> Drop for Incremental<T, O>
+where
+    T: TypeMemory + TypeTrace + Clone,
+    O: Observer<Event = Event>,
+{
+    fn drop(&mut self) {
+        dealloc_objects(self.allocator.as_ref(), self.objects.get_mut(), |_| true);
+    }
+}
+
+impl<T, O> GcSnapshot<T> for Incremental<T, O>
+where
+    T: TypeDesc + TypeMemory + TypeTrace + Clone,
+    O: Observer<Event = Event>,
+{
+    fn snapshot(&self) -> HeapSnapshot {
+        snapshot_objects(&self.objects.read())
+    }
+}
+
+impl<T, O> MemoryMapper<T> for Incremental<T, O>
+where
+    T: TypeDesc + TypeMemory + TypeTrace + Clone + Eq + Hash,
+    O: Observer<Event = Event>,
+{
+    fn map_memory(&self, mapping: Mapping<T, T>) -> Vec<GcPtr> {
+        let mut cycle = self.cycle.lock();
+        let mut objects = self.objects.write();
+
+        // Mapping invalidates the progress of the collection cycle, so it is aborted
+        if cycle.phase != Phase::Idle {
+            for obj in objects.values_mut() {
+                obj.color = Color::White;
+            }
+            *cycle = Cycle::default();
+
+            self.observer.event(Event::End);
+        }
+
+        let (deleted, new_allocations) =
+            map_objects(self.allocator.as_ref(), &mut objects, mapping);
+        for (handle, ty) in new_allocations {
+            self.stats.write().allocated_memory += ty.layout().size();
+            self.log_alloc(handle);
+        }
+
+        deleted
+    }
+}
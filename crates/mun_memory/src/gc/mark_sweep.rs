@@ -1,17 +1,30 @@
 use crate::{
     cast,
-    gc::{Event, GcPtr, GcRuntime, Observer, RawGcPtr, Stats, TypeTrace},
+    gc::{
+        allocator::{alloc_or_abort, alloc_zeroed_or_abort},
+        snapshot::snapshot_objects,
+        weak_ptr::WeakRefs,
+        Allocator, CollectionThresholds, Event, Finalizer, GcPtr, GcRuntime, GcSnapshot,
+        GlobalAllocator, HeapLimitExceeded, HeapSnapshot, Observer, RawGcPtr, Stats, TypeTrace,
+        WeakRef,
+    },
     mapping::{self, FieldMapping, MemoryMapper},
     TypeDesc, TypeGroup, TypeMemory,
 };
 use mapping::{Conversion, Mapping};
 use parking_lot::RwLock;
 use std::{
+    alloc::Layout,
     collections::{HashMap, VecDeque},
+    fmt,
     hash::Hash,
-    ops::Deref,
-    pin::Pin,
+    ops::{Deref, DerefMut},
     ptr::NonNull,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Instant,
 };
 
 /// Implements a simple mark-sweep type garbage collector.
@@ -21,9 +34,66 @@ where
     T: TypeMemory + TypeTrace + Clone,
     O: Observer<Event = Event>,
 {
-    objects: RwLock<HashMap<GcPtr, Pin<Box<ObjectInfo<T>>>>>,
+    objects: RwLock<HashMap<GcPtr, ObjectBox<T>>>,
+    allocator: Arc<dyn Allocator>,
     observer: O,
     stats: RwLock<Stats>,
+    trigger: RwLock<CollectionTrigger>,
+    weak: WeakRefs,
+    finalizer: RwLock<Option<Finalizer<T>>>,
+}
+
+/// Tracks the allocations since the previous collection, to determine whether the
+/// [`CollectionThresholds`] have been exceeded.
+#[derive(Debug, Default)]
+pub(super) struct CollectionTrigger {
+    pub thresholds: CollectionThresholds,
+    /// The number of allocations since the previous collection
+    pub allocations: usize,
+    /// The allocated memory after the previous collection
+    pub retained_memory: usize,
+    /// Whether an allocation failed because it would exceed the maximum heap size
+    pub heap_limit_exceeded: bool,
+}
+
+impl CollectionTrigger {
+    /// Returns whether the thresholds are exceeded, given the currently `allocated_memory`, or
+    /// whether the maximum heap size was exceeded.
+    pub fn is_exceeded(&self, allocated_memory: usize) -> bool {
+        self.heap_limit_exceeded
+            || self
+                .thresholds
+                .is_exceeded(allocated_memory, self.retained_memory, self.allocations)
+    }
+
+    /// Resets the allocations since the previous collection, which retained `retained_memory`.
+    pub fn reset(&mut self, retained_memory: usize) {
+        self.allocations = 0;
+        self.retained_memory = retained_memory;
+        self.heap_limit_exceeded = false;
+    }
+
+    /// Adds `size` bytes to the allocated memory in `stats`. Returns an error if that would exceed
+    /// the maximum heap size, in which case a collection is requested instead.
+    pub fn reserve_memory(
+        &mut self,
+        stats: &mut Stats,
+        size: usize,
+    ) -> Result<(), HeapLimitExceeded> {
+        match stats.max_heap_size {
+            Some(max_heap_size) if stats.allocated_memory.saturating_add(size) > max_heap_size => {
+                self.heap_limit_exceeded = true;
+                Err(HeapLimitExceeded {
+                    requested: size,
+                    max_heap_size,
+                })
+            }
+            _ => {
+                stats.allocated_memory += size;
+                Ok(())
+            }
+        }
+    }
 }
 
 impl<T, O> Default for MarkSweep<T, O>
@@ -32,11 +102,7 @@ where
     O: Observer<Event = Event> + Default,
 {
     fn default() -> Self {
-        MarkSweep {
-            objects: RwLock::new(HashMap::new()),
-            observer: O::default(),
-            stats: RwLock::new(Stats::default()),
-        }
+        Self::with_observer(O::default())
     }
 }
 
@@ -47,37 +113,119 @@ where
 {
     /// Creates a `MarkSweep` memory collector with the specified `Observer`.
     pub fn with_observer(observer: O) -> Self {
+        Self::with_allocator(observer, Arc::new(GlobalAllocator))
+    }
+
+    /// Creates a `MarkSweep` memory collector with the specified `Observer`, that allocates all
+    /// memory using the specified `Allocator`.
+    pub fn with_allocator(observer: O, allocator: Arc<dyn Allocator>) -> Self {
         Self {
             objects: RwLock::new(HashMap::new()),
+            allocator,
             observer,
             stats: RwLock::new(Stats::default()),
+            trigger: RwLock::new(CollectionTrigger::default()),
+            weak: WeakRefs::default(),
+            finalizer: RwLock::new(None),
         }
     }
 
-    /// Logs an allocation
-    fn log_alloc(&self, handle: GcPtr, ty: T) {
+    /// Logs an allocation, whose memory was already added to the allocated memory
+    fn log_alloc(&self, handle: GcPtr) {
+        self.trigger.write().allocations += 1;
+
+        self.observer.event(Event::Allocation(handle));
+    }
+
+    /// Allocates an object of type `ty`, whose memory was already added to the allocated memory.
+    fn alloc_reserved(&self, ty: T) -> GcPtr {
+        let object = alloc_obj(self.allocator.as_ref(), ty);
+
+        // We want to return a pointer to the `ObjectInfo`, to be used as handle.
+        let handle = object.handle();
+
         {
-            let mut stats = self.stats.write();
-            stats.allocated_memory += ty.layout().size();
+            let mut objects = self.objects.write();
+            objects.insert(handle, object);
         }
 
-        self.observer.event(Event::Allocation(handle));
+        self.log_alloc(handle);
+        handle
     }
 
     /// Returns the observer
     pub fn observer(&self) -> &O {
         &self.observer
     }
+
+    /// Returns the thresholds that determine when a collection is needed.
+    pub fn thresholds(&self) -> CollectionThresholds {
+        self.trigger.read().thresholds
+    }
+
+    /// Sets the thresholds that determine when a collection is needed.
+    pub fn set_thresholds(&self, thresholds: CollectionThresholds) {
+        self.trigger.write().thresholds = thresholds;
+    }
+
+    /// Sets the finalizer that is invoked for every object right before it is deallocated.
+    pub fn set_finalizer(&self, finalizer: Finalizer<T>) {
+        *self.finalizer.write() = Some(finalizer);
+    }
+
+    /// Returns the maximum number of bytes that can be allocated through
+    /// [`GcRuntime::try_alloc`], if limited.
+    pub fn max_heap_size(&self) -> Option<usize> {
+        self.stats.read().max_heap_size
+    }
+
+    /// Sets the maximum number of bytes that can be allocated through [`GcRuntime::try_alloc`].
+    /// `None` removes the limit.
+    pub fn set_max_heap_size(&self, max_heap_size: Option<usize>) {
+        self.stats.write().max_heap_size = max_heap_size;
+    }
+
+    /// Returns whether the allocations since the previous collection exceeded the thresholds.
+    ///
+    /// The collector never collects on its own accord, because objects that are only referenced
+    /// from the stack are not rooted. The owner of the collector should call
+    /// [`MarkSweep::collect`] at a point where that is safe.
+    pub fn needs_collection(&self) -> bool {
+        let allocated_memory = self.stats.read().allocated_memory;
+        self.trigger.read().is_exceeded(allocated_memory)
+    }
 }
 
-fn alloc_obj<T: Clone + TypeMemory + TypeTrace>(ty: T) -> Pin<Box<ObjectInfo<T>>> {
-    let ptr = unsafe { std::alloc::alloc(ty.layout()) };
-    Box::pin(ObjectInfo {
-        ptr,
-        ty,
-        roots: 0,
-        color: Color::White,
-    })
+/// Allocates the memory of an object of type `ty` and its `ObjectInfo` using the `allocator`.
+pub(super) fn alloc_obj<T: Clone + TypeMemory + TypeTrace>(
+    allocator: &dyn Allocator,
+    ty: T,
+) -> ObjectBox<T> {
+    let ptr = alloc_or_abort(allocator, ty.layout()).as_ptr();
+    ObjectBox::new(
+        allocator,
+        ObjectInfo {
+            ptr,
+            ty,
+            roots: AtomicU32::new(0),
+            color: Color::White,
+        },
+    )
+}
+
+/// Deallocates the memory of all `objects` and their `ObjectInfo`s using the `allocator`, except
+/// for the memory of objects for which `is_owned` returns `false`.
+pub(super) fn dealloc_objects<T: TypeMemory + TypeTrace + Clone>(
+    allocator: &dyn Allocator,
+    objects: &mut HashMap<GcPtr, ObjectBox<T>>,
+    is_owned: impl Fn(*const u8) -> bool,
+) {
+    for (_, mut obj) in objects.drain() {
+        if is_owned(obj.ptr) {
+            unsafe { allocator.dealloc(obj.ptr, obj.ty.layout()) };
+        }
+        unsafe { obj.free(allocator) };
+    }
 }
 
 impl<T, O> GcRuntime<T> for MarkSweep<T, O>
@@ -86,18 +234,20 @@ where
     O: Observer<Event = Event>,
 {
     fn alloc(&self, ty: T) -> GcPtr {
-        let object = alloc_obj(ty.clone());
-
-        // We want to return a pointer to the `ObjectInfo`, to be used as handle.
-        let handle = (object.as_ref().deref() as *const _ as RawGcPtr).into();
+        self.stats.write().allocated_memory += ty.layout().size();
+        self.alloc_reserved(ty)
+    }
 
+    fn try_alloc(&self, ty: T) -> Result<GcPtr, HeapLimitExceeded> {
+        // The memory is reserved while the stats are locked, so concurrent allocations cannot
+        // exceed the limit together
         {
-            let mut objects = self.objects.write();
-            objects.insert(handle, object);
+            let mut stats = self.stats.write();
+            self.trigger
+                .write()
+                .reserve_memory(&mut stats, ty.layout().size())?;
         }
-
-        self.log_alloc(handle, ty);
-        handle
+        Ok(self.alloc_reserved(ty))
     }
 
     fn ptr_type(&self, handle: GcPtr) -> T {
@@ -111,21 +261,39 @@ where
     }
 
     fn root(&self, handle: GcPtr) {
-        let _lock = self.objects.write();
+        // Roots are counted atomically, so concurrent rooting only needs to prevent collection
+        let _lock = self.objects.read();
 
         // Convert the handle to our internal representation
-        let object_info: *mut ObjectInfo<T> = handle.into();
+        let object_info: *const ObjectInfo<T> = handle.into();
 
-        unsafe { (*object_info).roots += 1 };
+        unsafe { (*object_info).roots.fetch_add(1, Ordering::Relaxed) };
     }
 
     fn unroot(&self, handle: GcPtr) {
-        let _lock = self.objects.write();
+        // Roots are counted atomically, so concurrent unrooting only needs to prevent collection
+        let _lock = self.objects.read();
 
         // Convert the handle to our internal representation
-        let object_info: *mut ObjectInfo<T> = handle.into();
+        let object_info: *const ObjectInfo<T> = handle.into();
+
+        unsafe { (*object_info).roots.fetch_sub(1, Ordering::Relaxed) };
+    }
 
-        unsafe { (*object_info).roots -= 1 };
+    fn downgrade(&self, handle: GcPtr) -> WeakRef {
+        self.weak.downgrade(handle)
+    }
+
+    fn upgrade(&self, weak: &WeakRef) -> Option<GcPtr> {
+        // Weak references are cleared during collection, which cannot run while we hold the lock
+        let _lock = self.objects.read();
+        let handle = weak.get()?;
+
+        // Convert the handle to our internal representation
+        let object_info: *const ObjectInfo<T> = handle.into();
+
+        unsafe { (*object_info).roots.fetch_add(1, Ordering::Relaxed) };
+        Some(handle)
     }
 
     fn stats(&self) -> Stats {
@@ -142,69 +310,74 @@ where
     /// was reclaimed, `false` otherwise.
     pub fn collect(&self) -> bool {
         self.observer.event(Event::Start);
+        let start_time = Instant::now();
 
         let mut objects = self.objects.write();
 
-        // Get all roots
-        let mut roots = objects
-            .iter()
-            .filter_map(|(_, obj)| {
-                if obj.roots > 0 {
-                    Some(obj.as_ref().get_ref() as *const _ as *mut ObjectInfo<T>)
-                } else {
-                    None
-                }
-            })
-            .collect::<VecDeque<_>>();
-
-        // Iterate over all roots
-        while let Some(next) = roots.pop_front() {
-            let handle = (next as *const _ as RawGcPtr).into();
-
-            // Trace all other objects
-            for reference in unsafe { (*next).ty.trace(handle) } {
-                let ref_ptr = objects
-                    .get_mut(&reference)
-                    .expect("found invalid reference");
-                if ref_ptr.color == Color::White {
-                    let ptr = ref_ptr.as_ref().get_ref() as *const _ as *mut ObjectInfo<T>;
-                    unsafe { (*ptr).color = Color::Gray };
-                    roots.push_back(ptr);
-                }
-            }
-
-            // This object has been traced
-            unsafe {
-                (*next).color = Color::Black;
-            }
-        }
+        mark(&mut objects);
 
         // Sweep all non-reachable objects
+        let finalizer = self.finalizer.read();
         let size_before = objects.len();
         objects.retain(|h, obj| {
             if obj.color == Color::Black {
-                unsafe {
-                    obj.as_mut().get_unchecked_mut().color = Color::White;
-                }
+                obj.color = Color::White;
                 true
             } else {
-                unsafe { std::alloc::dealloc(obj.ptr, obj.ty.layout()) };
+                self.weak.clear(*h);
+                if let Some(finalizer) = finalizer.as_ref() {
+                    finalizer.finalize(*h, &obj.ty);
+                }
+                unsafe { self.allocator.dealloc(obj.ptr, obj.ty.layout()) };
                 self.observer.event(Event::Deallocation(*h));
                 {
                     let mut stats = self.stats.write();
                     stats.allocated_memory -= obj.ty.layout().size();
+                    stats.freed_memory += obj.ty.layout().size();
                 }
+                unsafe { obj.free(self.allocator.as_ref()) };
                 false
             }
         });
         let size_after = objects.len();
 
+        {
+            let mut stats = self.stats.write();
+            let pause = start_time.elapsed();
+            stats.collections += 1;
+            stats.last_pause = pause;
+            stats.max_pause = stats.max_pause.max(pause);
+            stats.total_pause += pause;
+
+            self.trigger.write().reset(stats.allocated_memory);
+        }
+
         self.observer.event(Event::End);
 
         size_before != size_after
     }
 }
 
+impl<T, O> Drop for MarkSweep<T, O>
+where
+    T: TypeMemory + TypeTrace + Clone,
+    O: Observer<Event = Event>,
+{
+    fn drop(&mut self) {
+        dealloc_objects(self.allocator.as_ref(), self.objects.get_mut(), |_| true);
+    }
+}
+
+impl<T, O> GcSnapshot<T> for MarkSweep<T, O>
+where
+    T: TypeDesc + TypeMemory + TypeTrace + Clone,
+    O: Observer<Event = Event>,
+{
+    fn snapshot(&self) -> HeapSnapshot {
+        snapshot_objects(&self.objects.read())
+    }
+}
+
 impl<T, O> MemoryMapper<T> for MarkSweep<T, O>
 where
     T: TypeDesc + TypeMemory + TypeTrace + Clone + Eq + Hash,
@@ -212,284 +385,325 @@ where
 {
     fn map_memory(&self, mapping: Mapping<T, T>) -> Vec<GcPtr> {
         let mut objects = self.objects.write();
+        let (deleted, new_allocations) =
+            map_objects(self.allocator.as_ref(), &mut objects, mapping);
+        for (handle, ty) in new_allocations {
+            self.stats.write().allocated_memory += ty.layout().size();
+            self.log_alloc(handle);
+        }
 
-        // Determine which types are still allocated with deleted types
-        let deleted = objects
-            .iter()
-            .filter_map(|(ptr, object_info)| {
-                if mapping.deletions.contains(&object_info.ty) {
-                    Some(*ptr)
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        // Update type pointers of types that didn't change
-        for (old_ty, new_ty) in mapping.identical {
-            for object_info in objects.values_mut() {
-                if object_info.ty == old_ty {
-                    object_info.set(ObjectInfo {
-                        ptr: object_info.ptr,
-                        roots: object_info.roots,
-                        color: object_info.color,
-                        ty: new_ty.clone(),
-                    });
-                }
+        deleted
+    }
+}
+
+/// Marks all objects that are reachable from rooted objects black.
+pub(super) fn mark<T>(objects: &mut HashMap<GcPtr, ObjectBox<T>>)
+where
+    T: TypeMemory + TypeTrace + Clone,
+{
+    // Get all roots
+    let mut roots = objects
+        .iter()
+        .filter_map(|(_, obj)| {
+            if obj.roots.load(Ordering::Relaxed) > 0 {
+                Some(obj.as_ptr())
+            } else {
+                None
             }
+        })
+        .collect::<VecDeque<_>>();
+
+    // Iterate over all roots
+    while let Some(next) = roots.pop_front() {
+        let handle = (next as *const _ as RawGcPtr).into();
+
+        // Trace all other objects
+        for reference in unsafe { (*next).ty.trace(handle) } {
+            let ref_ptr = objects
+                .get_mut(&reference)
+                .expect("found invalid reference");
+            if ref_ptr.color == Color::White {
+                let ptr = ref_ptr.as_ptr();
+                unsafe { (*ptr).color = Color::Gray };
+                roots.push_back(ptr);
+            }
+        }
+
+        // This object has been traced
+        unsafe {
+            (*next).color = Color::Black;
         }
+    }
+}
 
-        let mut new_allocations = Vec::new();
+/// Maps the memory of all `objects` according to the `mapping`. Returns the objects that are still
+/// allocated with deleted types, and the handles and types of objects that were newly allocated
+/// while mapping. All memory is allocated using the `allocator`.
+pub(super) fn map_objects<T>(
+    allocator: &dyn Allocator,
+    objects: &mut HashMap<GcPtr, ObjectBox<T>>,
+    mapping: Mapping<T, T>,
+) -> (Vec<GcPtr>, Vec<(GcPtr, T)>)
+where
+    T: TypeDesc + TypeMemory + TypeTrace + Clone + Eq + Hash,
+{
+    // Determine which types are still allocated with deleted types
+    let deleted = objects
+        .iter()
+        .filter_map(|(ptr, object_info)| {
+            if mapping.deletions.contains(&object_info.ty) {
+                Some(*ptr)
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    // Update type pointers of types that didn't change
+    for (old_ty, new_ty) in mapping.identical {
+        for object_info in objects.values_mut() {
+            if object_info.ty == old_ty {
+                object_info.ty = new_ty.clone();
+            }
+        }
+    }
 
-        for (old_ty, conversion) in mapping.conversions.iter() {
-            for object_info in objects.values_mut() {
-                if object_info.ty == *old_ty {
-                    let src = unsafe { NonNull::new_unchecked(object_info.ptr) };
-                    let dest = unsafe {
-                        NonNull::new_unchecked(std::alloc::alloc_zeroed(conversion.new_ty.layout()))
-                    };
+    let mut new_allocations = Vec::new();
 
-                    map_fields(
-                        self,
-                        &mut new_allocations,
-                        &mapping.conversions,
-                        &conversion.field_mapping,
-                        src,
-                        dest,
-                    );
-
-                    unsafe { std::alloc::dealloc(src.as_ptr(), old_ty.layout()) };
-
-                    object_info.set(ObjectInfo {
-                        ptr: dest.as_ptr(),
-                        roots: object_info.roots,
-                        color: object_info.color,
-                        ty: conversion.new_ty.clone(),
-                    });
-                }
+    for (old_ty, conversion) in mapping.conversions.iter() {
+        for object_info in objects.values_mut() {
+            if object_info.ty == *old_ty {
+                let src = unsafe { NonNull::new_unchecked(object_info.ptr) };
+                let dest = alloc_zeroed_or_abort(allocator, conversion.new_ty.layout());
+
+                map_fields(
+                    allocator,
+                    &mut new_allocations,
+                    &mapping.conversions,
+                    &conversion.field_mapping,
+                    src,
+                    dest,
+                );
+
+                unsafe { allocator.dealloc(src.as_ptr(), old_ty.layout()) };
+
+                object_info.ptr = dest.as_ptr();
+                object_info.ty = conversion.new_ty.clone();
             }
         }
+    }
 
-        // Retroactively store newly allocated objects
-        // This cannot be done while mapping because we hold a mutable reference to objects
-        for object in new_allocations {
+    // Retroactively store newly allocated objects
+    // This cannot be done while mapping because we hold a mutable reference to objects
+    let new_allocations = new_allocations
+        .into_iter()
+        .map(|object| {
             let ty = object.ty.clone();
             // We want to return a pointer to the `ObjectInfo`, to
             // be used as handle.
-            let handle = (object.as_ref().deref() as *const _ as RawGcPtr).into();
+            let handle = object.handle();
             objects.insert(handle, object);
 
-            self.log_alloc(handle, ty);
-        }
-
-        return deleted;
-
-        fn map_fields<T, O>(
-            gc: &MarkSweep<T, O>,
-            new_allocations: &mut Vec<Pin<Box<ObjectInfo<T>>>>,
-            conversions: &HashMap<T, Conversion<T>>,
-            mapping: &[FieldMapping<T>],
-            src: NonNull<u8>,
-            dest: NonNull<u8>,
-        ) where
-            T: TypeDesc + TypeMemory + TypeTrace + Clone + Eq + Hash,
-            O: Observer<Event = Event>,
+            (handle, ty)
+        })
+        .collect();
+
+    return (deleted, new_allocations);
+
+    fn map_fields<T>(
+        allocator: &dyn Allocator,
+        new_allocations: &mut Vec<ObjectBox<T>>,
+        conversions: &HashMap<T, Conversion<T>>,
+        mapping: &[FieldMapping<T>],
+        src: NonNull<u8>,
+        dest: NonNull<u8>,
+    ) where
+        T: TypeDesc + TypeMemory + TypeTrace + Clone + Eq + Hash,
+    {
+        for FieldMapping {
+            new_ty,
+            new_offset,
+            action,
+        } in mapping.iter()
         {
-            for FieldMapping {
-                new_ty,
-                new_offset,
-                action,
-            } in mapping.iter()
-            {
-                let field_dest = {
-                    let mut dest = dest.as_ptr() as usize;
-                    dest += new_offset;
-                    dest as *mut u8
-                };
-
-                match action {
-                    mapping::Action::Cast { old_offset, old_ty } => {
-                        let field_src = {
-                            let mut src = src.as_ptr() as usize;
-                            src += old_offset;
-                            src as *mut u8
-                        };
-
-                        if old_ty.group() == TypeGroup::Struct {
-                            debug_assert_eq!(new_ty.group(), TypeGroup::Struct);
-
-                            // When the name is the same, we are dealing with the same struct,
-                            // but different internals
-                            let is_same_struct = old_ty.name() == new_ty.name();
-
-                            // If the same struct changed, there must also be a conversion
-                            let conversion = conversions.get(old_ty);
-
-                            if old_ty.is_stack_allocated() {
-                                if new_ty.is_stack_allocated() {
-                                    // struct(value) -> struct(value)
-                                    if is_same_struct {
-                                        // Map in-memory struct to in-memory struct
-                                        map_fields(
-                                            gc,
-                                            new_allocations,
-                                            conversions,
-                                            &conversion.as_ref().unwrap().field_mapping,
-                                            unsafe { NonNull::new_unchecked(field_src) },
-                                            unsafe { NonNull::new_unchecked(field_dest) },
-                                        );
-                                    } else {
-                                        // Use previously zero-initialized memory
-                                    }
-                                } else {
-                                    // struct(value) -> struct(gc)
-                                    let object = alloc_obj(new_ty.clone());
-
-                                    // We want to return a pointer to the `ObjectInfo`, to be used as handle.
-                                    let handle =
-                                        (object.as_ref().deref() as *const _ as RawGcPtr).into();
-
-                                    if is_same_struct {
-                                        // Map in-memory struct to heap-allocated struct
-                                        map_fields(
-                                            gc,
-                                            new_allocations,
-                                            conversions,
-                                            &conversion.as_ref().unwrap().field_mapping,
-                                            unsafe { NonNull::new_unchecked(field_src) },
-                                            unsafe { NonNull::new_unchecked(object.ptr) },
-                                        );
-                                    } else {
-                                        // Zero initialize heap-allocated object
-                                        unsafe {
-                                            std::ptr::write_bytes(
-                                                (*object).ptr,
-                                                0,
-                                                new_ty.layout().size(),
-                                            )
-                                        };
-                                    }
-
-                                    // Write handle to field
-                                    let field_handle = field_dest.cast::<GcPtr>();
-                                    unsafe { *field_handle = handle };
-
-                                    new_allocations.push(object);
-                                }
-                            } else if !new_ty.is_stack_allocated() {
-                                // struct(gc) -> struct(gc)
-                                let field_src = field_src.cast::<GcPtr>();
-                                let field_dest = field_dest.cast::<GcPtr>();
+            let field_dest = {
+                let mut dest = dest.as_ptr() as usize;
+                dest += new_offset;
+                dest as *mut u8
+            };
+
+            match action {
+                mapping::Action::Cast { old_offset, old_ty } => {
+                    let field_src = {
+                        let mut src = src.as_ptr() as usize;
+                        src += old_offset;
+                        src as *mut u8
+                    };
+
+                    if old_ty.group() == TypeGroup::Struct {
+                        debug_assert_eq!(new_ty.group(), TypeGroup::Struct);
+
+                        // When the name is the same, we are dealing with the same struct,
+                        // but different internals
+                        let is_same_struct = old_ty.name() == new_ty.name();
+
+                        // If the same struct changed, there must also be a conversion
+                        let conversion = conversions.get(old_ty);
 
+                        if old_ty.is_stack_allocated() {
+                            if new_ty.is_stack_allocated() {
+                                // struct(value) -> struct(value)
                                 if is_same_struct {
-                                    // Only copy the `GcPtr`. Memory will already be mapped.
-                                    unsafe {
-                                        *field_dest = *field_src;
-                                    }
+                                    // Map in-memory struct to in-memory struct
+                                    map_fields(
+                                        allocator,
+                                        new_allocations,
+                                        conversions,
+                                        &conversion.as_ref().unwrap().field_mapping,
+                                        unsafe { NonNull::new_unchecked(field_src) },
+                                        unsafe { NonNull::new_unchecked(field_dest) },
+                                    );
                                 } else {
-                                    let object = alloc_obj(new_ty.clone());
+                                    // Use previously zero-initialized memory
+                                }
+                            } else {
+                                // struct(value) -> struct(gc)
+                                let object = alloc_obj(allocator, new_ty.clone());
 
-                                    // We want to return a pointer to the `ObjectInfo`, to
-                                    // be used as handle.
-                                    let handle =
-                                        (object.as_ref().deref() as *const _ as RawGcPtr).into();
+                                // We want to return a pointer to the `ObjectInfo`, to be used as handle.
+                                let handle = object.handle();
 
-                                    // Zero-initialize heap-allocated object
+                                if is_same_struct {
+                                    // Map in-memory struct to heap-allocated struct
+                                    map_fields(
+                                        allocator,
+                                        new_allocations,
+                                        conversions,
+                                        &conversion.as_ref().unwrap().field_mapping,
+                                        unsafe { NonNull::new_unchecked(field_src) },
+                                        unsafe { NonNull::new_unchecked(object.ptr) },
+                                    );
+                                } else {
+                                    // Zero initialize heap-allocated object
                                     unsafe {
                                         std::ptr::write_bytes(object.ptr, 0, new_ty.layout().size())
                                     };
+                                }
 
-                                    // Write handle to field
-                                    unsafe {
-                                        *field_dest = handle;
-                                    }
+                                // Write handle to field
+                                let field_handle = field_dest.cast::<GcPtr>();
+                                unsafe { *field_handle = handle };
+
+                                new_allocations.push(object);
+                            }
+                        } else if !new_ty.is_stack_allocated() {
+                            // struct(gc) -> struct(gc)
+                            let field_src = field_src.cast::<GcPtr>();
+                            let field_dest = field_dest.cast::<GcPtr>();
 
-                                    new_allocations.push(object);
+                            if is_same_struct {
+                                // Only copy the `GcPtr`. Memory will already be mapped.
+                                unsafe {
+                                    *field_dest = *field_src;
                                 }
                             } else {
-                                // struct(gc) -> struct(value)
-                                let field_handle = unsafe { *field_src.cast::<GcPtr>() };
+                                let object = alloc_obj(allocator, new_ty.clone());
 
-                                // Convert the handle to our internal representation
-                                // Safety: we already hold a write lock on `objects`, so
-                                // this is legal.
-                                let obj: *mut ObjectInfo<T> = field_handle.into();
-                                let obj = unsafe { &*obj };
+                                // We want to return a pointer to the `ObjectInfo`, to
+                                // be used as handle.
+                                let handle = object.handle();
 
-                                if is_same_struct {
-                                    if obj.ty == *old_ty {
-                                        // The object still needs to be mapped
-                                        // Map heap-allocated struct to in-memory struct
-                                        map_fields(
-                                            gc,
-                                            new_allocations,
-                                            conversions,
-                                            &conversion.as_ref().unwrap().field_mapping,
-                                            unsafe { NonNull::new_unchecked(obj.ptr) },
-                                            unsafe { NonNull::new_unchecked(field_dest) },
-                                        );
-                                    } else {
-                                        // The object was already mapped
-                                        debug_assert!(obj.ty == *new_ty);
-
-                                        // Copy from heap-allocated struct to in-memory struct
-                                        unsafe {
-                                            std::ptr::copy_nonoverlapping(
-                                                obj.ptr,
-                                                field_dest,
-                                                obj.ty.layout().size(),
-                                            )
-                                        };
-                                    }
+                                // Zero-initialize heap-allocated object
+                                unsafe {
+                                    std::ptr::write_bytes(object.ptr, 0, new_ty.layout().size())
+                                };
+
+                                // Write handle to field
+                                unsafe {
+                                    *field_dest = handle;
+                                }
+
+                                new_allocations.push(object);
+                            }
+                        } else {
+                            // struct(gc) -> struct(value)
+                            let field_handle = unsafe { *field_src.cast::<GcPtr>() };
+
+                            // Convert the handle to our internal representation
+                            // Safety: we already hold a write lock on `objects`, so
+                            // this is legal.
+                            let obj: *mut ObjectInfo<T> = field_handle.into();
+                            let obj = unsafe { &*obj };
+
+                            if is_same_struct {
+                                if obj.ty == *old_ty {
+                                    // The object still needs to be mapped
+                                    // Map heap-allocated struct to in-memory struct
+                                    map_fields(
+                                        allocator,
+                                        new_allocations,
+                                        conversions,
+                                        &conversion.as_ref().unwrap().field_mapping,
+                                        unsafe { NonNull::new_unchecked(obj.ptr) },
+                                        unsafe { NonNull::new_unchecked(field_dest) },
+                                    );
                                 } else {
-                                    // Use previously zero-initialized memory
+                                    // The object was already mapped
+                                    debug_assert!(obj.ty == *new_ty);
+
+                                    // Copy from heap-allocated struct to in-memory struct
+                                    unsafe {
+                                        std::ptr::copy_nonoverlapping(
+                                            obj.ptr,
+                                            field_dest,
+                                            obj.ty.layout().size(),
+                                        )
+                                    };
                                 }
+                            } else {
+                                // Use previously zero-initialized memory
                             }
-                        } else if !cast::try_cast_from_to(
-                            *old_ty.guid(),
-                            *new_ty.guid(),
-                            unsafe { NonNull::new_unchecked(field_src) },
-                            unsafe { NonNull::new_unchecked(field_dest) },
-                        ) {
-                            // Failed to cast. Use the previously zero-initialized value instead
                         }
+                    } else if !cast::try_cast_from_to(
+                        *old_ty.guid(),
+                        *new_ty.guid(),
+                        unsafe { NonNull::new_unchecked(field_src) },
+                        unsafe { NonNull::new_unchecked(field_dest) },
+                    ) {
+                        // Failed to cast. Use the previously zero-initialized value instead
                     }
-                    mapping::Action::Copy { old_offset } => {
-                        let field_src = {
-                            let mut src = src.as_ptr() as usize;
-                            src += old_offset;
-                            src as *mut u8
-                        };
-
-                        unsafe {
-                            std::ptr::copy_nonoverlapping(
-                                field_src,
-                                field_dest,
-                                new_ty.layout().size(),
-                            )
-                        };
-                    }
-                    mapping::Action::Insert => {
-                        if !new_ty.is_stack_allocated() {
-                            let object = alloc_obj(new_ty.clone());
+                }
+                mapping::Action::Copy { old_offset } => {
+                    let field_src = {
+                        let mut src = src.as_ptr() as usize;
+                        src += old_offset;
+                        src as *mut u8
+                    };
 
-                            // We want to return a pointer to the `ObjectInfo`, to be used as
-                            // handle.
-                            let handle = (object.as_ref().deref() as *const _ as RawGcPtr).into();
+                    unsafe {
+                        std::ptr::copy_nonoverlapping(field_src, field_dest, new_ty.layout().size())
+                    };
+                }
+                mapping::Action::Insert => {
+                    if !new_ty.is_stack_allocated() {
+                        let object = alloc_obj(allocator, new_ty.clone());
 
-                            // Zero-initialize heap-allocated object
-                            unsafe { std::ptr::write_bytes(object.ptr, 0, new_ty.layout().size()) };
+                        // We want to return a pointer to the `ObjectInfo`, to be used as
+                        // handle.
+                        let handle = object.handle();
 
-                            // Write handle to field
-                            let field_dest = field_dest.cast::<GcPtr>();
-                            unsafe {
-                                *field_dest = handle;
-                            }
+                        // Zero-initialize heap-allocated object
+                        unsafe { std::ptr::write_bytes(object.ptr, 0, new_ty.layout().size()) };
 
-                            new_allocations.push(object);
-                        } else {
-                            // Use the previously zero-initialized value
+                        // Write handle to field
+                        let field_dest = field_dest.cast::<GcPtr>();
+                        unsafe {
+                            *field_dest = handle;
                         }
+
+                        new_allocations.push(object);
+                    } else {
+                        // Use the previously zero-initialized value
                     }
                 }
             }
@@ -499,7 +713,7 @@ where
 
 /// Coloring used in the Mark Sweep phase.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum Color {
+pub(super) enum Color {
     /// A white object has not been seen yet by the mark phase
     White,
 
@@ -514,9 +728,9 @@ enum Color {
 /// meta information.
 #[derive(Debug)]
 #[repr(C)]
-struct ObjectInfo<T: TypeMemory + TypeTrace + Clone> {
+pub(super) struct ObjectInfo<T: TypeMemory + TypeTrace + Clone> {
     pub ptr: *mut u8,
-    pub roots: u32,
+    pub roots: AtomicU32,
     pub color: Color,
     pub ty: T,
 }
@@ -525,6 +739,89 @@ struct ObjectInfo<T: TypeMemory + TypeTrace + Clone> {
 unsafe impl<T: TypeMemory + TypeTrace + Clone> Send for ObjectInfo<T> {}
 unsafe impl<T: TypeMemory + TypeTrace + Clone> Sync for ObjectInfo<T> {}
 
+/// An owned `ObjectInfo`, whose memory is usually allocated by an [`Allocator`]. The address of
+/// the `ObjectInfo` is used as the handle of its object, so it never moves.
+///
+/// An `ObjectBox` is not deallocated when it is dropped; it must be freed using
+/// [`ObjectBox::free`] with the allocator that allocated it, or using [`ObjectBox::drop_info`] if
+/// it was created with [`ObjectBox::write`].
+pub(super) struct ObjectBox<T: TypeMemory + TypeTrace + Clone>(NonNull<ObjectInfo<T>>);
+
+/// An `ObjectBox` is thread-safe, because its `ObjectInfo` is.
+unsafe impl<T: TypeMemory + TypeTrace + Clone> Send for ObjectBox<T> {}
+unsafe impl<T: TypeMemory + TypeTrace + Clone> Sync for ObjectBox<T> {}
+
+impl<T: TypeMemory + TypeTrace + Clone> ObjectBox<T> {
+    /// Moves the `info` into memory allocated by the `allocator`.
+    pub fn new(allocator: &dyn Allocator, info: ObjectInfo<T>) -> Self {
+        let ptr = alloc_or_abort(allocator, Layout::new::<ObjectInfo<T>>()).cast::<ObjectInfo<T>>();
+        unsafe { Self::write(ptr, info) }
+    }
+
+    /// Moves the `info` into the memory pointed to by `ptr`, which is owned by the caller.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for writes and properly aligned for an `ObjectInfo<T>`. The resulting
+    /// `ObjectBox` must be released using [`ObjectBox::drop_info`], after which the caller can
+    /// reuse the memory.
+    pub unsafe fn write(ptr: NonNull<ObjectInfo<T>>, info: ObjectInfo<T>) -> Self {
+        ptr.as_ptr().write(info);
+        Self(ptr)
+    }
+
+    /// Returns the handle of the object.
+    pub fn handle(&self) -> GcPtr {
+        (self.0.as_ptr() as RawGcPtr).into()
+    }
+
+    /// Returns a pointer to the `ObjectInfo`.
+    pub fn as_ptr(&self) -> *mut ObjectInfo<T> {
+        self.0.as_ptr()
+    }
+
+    /// Drops the `ObjectInfo` and deallocates its memory using the `allocator`. This does not
+    /// deallocate the memory of the object.
+    ///
+    /// # Safety
+    ///
+    /// The `allocator` must be the allocator that allocated the `ObjectBox`, and the `ObjectBox`
+    /// must not be used afterwards.
+    pub unsafe fn free(&mut self, allocator: &dyn Allocator) {
+        self.drop_info();
+        allocator.dealloc(self.0.as_ptr().cast(), Layout::new::<ObjectInfo<T>>());
+    }
+
+    /// Drops the `ObjectInfo` without deallocating its memory.
+    ///
+    /// # Safety
+    ///
+    /// The `ObjectBox` must not be used afterwards.
+    pub unsafe fn drop_info(&mut self) {
+        std::ptr::drop_in_place(self.0.as_ptr());
+    }
+}
+
+impl<T: TypeMemory + TypeTrace + Clone> Deref for ObjectBox<T> {
+    type Target = ObjectInfo<T>;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.0.as_ref() }
+    }
+}
+
+impl<T: TypeMemory + TypeTrace + Clone> DerefMut for ObjectBox<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.0.as_mut() }
+    }
+}
+
+impl<T: TypeMemory + TypeTrace + Clone + fmt::Debug> fmt::Debug for ObjectBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
 impl<T: TypeMemory + TypeTrace + Clone> From<GcPtr> for *const ObjectInfo<T> {
     fn from(ptr: GcPtr) -> Self {
         ptr.as_ptr() as Self
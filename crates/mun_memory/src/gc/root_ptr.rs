@@ -36,6 +36,15 @@ impl<T: TypeMemory + TypeTrace, G: GcRuntime<T>> GcRootPtr<T, G> {
         }
     }
 
+    /// Constructs a new GCRootHandle from a runtime and a handle that has already been rooted
+    pub(super) fn from_rooted(runtime: &Arc<G>, handle: GcPtr) -> Self {
+        Self {
+            handle,
+            runtime: Arc::downgrade(runtime),
+            ty: PhantomData,
+        }
+    }
+
     /// Returns the runtime that owns the memory
     pub fn runtime(&self) -> &Weak<G> {
         &self.runtime
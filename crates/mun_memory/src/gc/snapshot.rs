@@ -0,0 +1,168 @@
+use super::{mark_sweep::ObjectBox, GcPtr, GcRuntime, TypeTrace};
+use crate::{TypeDesc, TypeMemory};
+use std::{
+    collections::HashMap,
+    fmt::{self, Write},
+    sync::atomic::Ordering,
+};
+
+/// A `GcRuntime` that can take a snapshot of all of its live objects.
+pub trait GcSnapshot<T: TypeDesc + TypeMemory + TypeTrace>: GcRuntime<T> {
+    /// Returns a snapshot of all objects that are currently allocated, including objects that are
+    /// no longer reachable but have not been collected yet.
+    fn snapshot(&self) -> HeapSnapshot;
+}
+
+/// Describes a single object in a [`HeapSnapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectSnapshot {
+    /// The handle of the object
+    pub handle: GcPtr,
+    /// The name of the object's type
+    pub type_name: String,
+    /// The size of the object in bytes
+    pub size: usize,
+    /// The number of times the object is rooted
+    pub roots: u32,
+    /// The objects that are referenced by the object
+    pub references: Vec<GcPtr>,
+}
+
+/// A snapshot of the object graph of a `GcRuntime`, which can be exported to JSON or to the
+/// Graphviz DOT format.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeapSnapshot {
+    /// The objects in the heap, ordered by their handle
+    pub objects: Vec<ObjectSnapshot>,
+}
+
+impl HeapSnapshot {
+    /// Returns the object with the specified `handle`, if it is part of the snapshot.
+    pub fn get(&self, handle: GcPtr) -> Option<&ObjectSnapshot> {
+        self.objects
+            .binary_search_by_key(&handle, |object| object.handle)
+            .ok()
+            .map(|idx| &self.objects[idx])
+    }
+
+    /// Returns an iterator over all objects that are rooted.
+    pub fn roots(&self) -> impl Iterator<Item = &ObjectSnapshot> {
+        self.objects.iter().filter(|object| object.roots > 0)
+    }
+
+    /// Returns the combined size of all objects in bytes.
+    pub fn total_size(&self) -> usize {
+        self.objects.iter().map(|object| object.size).sum()
+    }
+
+    /// Exports the snapshot to JSON, e.g.:
+    ///
+    /// ```json
+    /// {"objects":[{"handle":"0x1000","type":"Foo","size":8,"roots":1,"references":["0x2000"]}]}
+    /// ```
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\"objects\":[");
+        for (idx, object) in self.objects.iter().enumerate() {
+            if idx > 0 {
+                json.push(',');
+            }
+            write!(
+                json,
+                "{{\"handle\":\"{}\",\"type\":\"{}\",\"size\":{},\"roots\":{},\"references\":[",
+                Address(object.handle),
+                Escaped(&object.type_name),
+                object.size,
+                object.roots
+            )
+            .unwrap();
+            for (idx, reference) in object.references.iter().enumerate() {
+                if idx > 0 {
+                    json.push(',');
+                }
+                write!(json, "\"{}\"", Address(*reference)).unwrap();
+            }
+            json.push_str("]}");
+        }
+        json.push_str("]}");
+        json
+    }
+
+    /// Exports the snapshot to the Graphviz DOT format. Rooted objects are drawn in bold.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph heap {\n");
+        for object in self.objects.iter() {
+            write!(
+                dot,
+                "    \"{}\" [label=\"{}\\n{} bytes\"",
+                Address(object.handle),
+                Escaped(&object.type_name),
+                object.size
+            )
+            .unwrap();
+            if object.roots > 0 {
+                dot.push_str(", style=bold");
+            }
+            dot.push_str("];\n");
+        }
+        for object in self.objects.iter() {
+            for reference in object.references.iter() {
+                writeln!(
+                    dot,
+                    "    \"{}\" -> \"{}\";",
+                    Address(object.handle),
+                    Address(*reference)
+                )
+                .unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Creates a snapshot of the specified `objects`.
+pub(super) fn snapshot_objects<T>(objects: &HashMap<GcPtr, ObjectBox<T>>) -> HeapSnapshot
+where
+    T: TypeDesc + TypeMemory + TypeTrace + Clone,
+{
+    let mut objects: Vec<_> = objects
+        .iter()
+        .map(|(handle, object)| ObjectSnapshot {
+            handle: *handle,
+            type_name: object.ty.name().to_owned(),
+            size: object.ty.layout().size(),
+            roots: object.roots.load(Ordering::Acquire),
+            references: object.ty.trace(*handle).collect(),
+        })
+        .collect();
+    objects.sort_by_key(|object| object.handle);
+
+    HeapSnapshot { objects }
+}
+
+/// Formats the address of a `GcPtr`.
+struct Address(GcPtr);
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:p}", self.0.as_ptr())
+    }
+}
+
+/// Formats a string as the contents of a JSON or DOT string literal.
+struct Escaped<'a>(&'a str);
+
+impl fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{
+    gc::{GcPtr, GcRootPtr, GcRuntime, TypeTrace},
+    TypeMemory,
+};
+use parking_lot::{Mutex, RwLock};
+use std::{
+    collections::HashMap,
+    marker::PhantomData,
+    sync::{Arc, Weak},
+};
+
+/// A weak reference to an object, obtained through [`GcRuntime::downgrade`]. A weak reference
+/// does not keep its object alive, and is cleared when the object is collected.
+#[derive(Clone, Debug)]
+pub struct WeakRef(Arc<RwLock<Option<GcPtr>>>);
+
+impl WeakRef {
+    /// Returns the referenced object, or `None` if the object has been collected.
+    pub(super) fn get(&self) -> Option<GcPtr> {
+        *self.0.read()
+    }
+}
+
+/// Tracks the weak references to the objects of a `GcRuntime`.
+///
+/// All weak references to an object share the same `WeakRef`, which must be cleared while the
+/// runtime prevents concurrent upgrades, i.e. before the object is deallocated.
+#[derive(Debug, Default)]
+pub(super) struct WeakRefs(Mutex<HashMap<GcPtr, WeakRef>>);
+
+impl WeakRefs {
+    /// Returns a weak reference to the specified `handle`.
+    pub fn downgrade(&self, handle: GcPtr) -> WeakRef {
+        self.0
+            .lock()
+            .entry(handle)
+            .or_insert_with(|| WeakRef(Arc::new(RwLock::new(Some(handle)))))
+            .clone()
+    }
+
+    /// Clears all weak references to the specified `handle`.
+    pub fn clear(&self, handle: GcPtr) {
+        if let Some(weak) = self.0.lock().remove(&handle) {
+            *weak.0.write() = None;
+        }
+    }
+}
+
+/// A `GcPtr` that does not keep its object alive. The object can be accessed by upgrading the
+/// `GcWeakPtr` to a `GcRootPtr`, as long as it has not been collected.
+pub struct GcWeakPtr<T: TypeMemory + TypeTrace, G: GcRuntime<T>> {
+    weak: WeakRef,
+    runtime: Weak<G>,
+    ty: PhantomData<T>,
+}
+
+impl<T: TypeMemory + TypeTrace, G: GcRuntime<T>> Clone for GcWeakPtr<T, G> {
+    fn clone(&self) -> Self {
+        Self {
+            weak: self.weak.clone(),
+            runtime: self.runtime.clone(),
+            ty: PhantomData,
+        }
+    }
+}
+
+impl<T: TypeMemory + TypeTrace, G: GcRuntime<T>> GcWeakPtr<T, G> {
+    /// Constructs a new `GcWeakPtr` from a runtime and a handle
+    pub fn new(runtime: &Arc<G>, handle: GcPtr) -> Self {
+        Self {
+            weak: runtime.downgrade(handle),
+            runtime: Arc::downgrade(runtime),
+            ty: PhantomData,
+        }
+    }
+
+    /// Returns the runtime that owns the memory
+    pub fn runtime(&self) -> &Weak<G> {
+        &self.runtime
+    }
+
+    /// Roots the object, returning `None` if the object has been collected or the runtime has been
+    /// dropped.
+    pub fn upgrade(&self) -> Option<GcRootPtr<T, G>> {
+        let runtime = self.runtime.upgrade()?;
+        let handle = runtime.upgrade(&self.weak)?;
+        Some(GcRootPtr::from_rooted(&runtime, handle))
+    }
+
+    /// Returns whether the object has been collected.
+    pub fn is_collected(&self) -> bool {
+        self.runtime.strong_count() == 0 || self.weak.get().is_none()
+    }
+}
@@ -4,6 +4,7 @@ mod cast;
 pub mod diff;
 pub mod gc;
 pub mod mapping;
+pub mod serialize;
 
 pub mod prelude {
     pub use crate::diff::{diff, Diff, FieldDiff, FieldEditKind};
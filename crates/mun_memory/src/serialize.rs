@@ -0,0 +1,825 @@
+//! Serialization of the objects of a [`GcRuntime`] to a portable byte format.
+//!
+//! [`serialize`] stores all objects that are reachable from a set of roots, together with a
+//! description of their types. [`deserialize`] allocates the objects again, possibly after the
+//! types have changed. Objects of changed types are converted using a [`Mapping`] between the
+//! serialized and the current types, the same way memory is mapped when hot reloading.
+//!
+//! All values are stored in little-endian byte order. References between objects are stored as
+//! indices into the serialized objects.
+
+use crate::{
+    cast,
+    gc::{GcPtr, GcRootPtr, GcRuntime, HasIndirectionPtr, RawGcPtr, TypeTrace},
+    mapping::{Action, FieldMapping, Mapping},
+    TypeDesc, TypeFields, TypeGroup, TypeMemory,
+};
+use std::{
+    alloc::Layout,
+    cell::RefCell,
+    collections::HashMap,
+    convert::TryInto,
+    fmt,
+    hash::{Hash, Hasher},
+    ptr::NonNull,
+    sync::Arc,
+};
+
+/// Identifies serialized data
+const MAGIC: &[u8; 4] = b"MUNH";
+
+/// The version of the serialized format
+const VERSION: u32 = 1;
+
+/// The object index that is stored for a null reference
+const NULL_INDEX: u32 = u32::MAX;
+
+/// An error that occurs when deserializing data that was not created by [`serialize`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeserializeError {
+    /// The data does not start with the expected magic bytes
+    InvalidMagic,
+    /// The data was serialized with an unsupported version of the format
+    UnsupportedVersion(u32),
+    /// The data ended unexpectedly
+    UnexpectedEnd,
+    /// A string in the data is not UTF-8 encoded
+    InvalidUtf8,
+    /// The description of the type with the specified name is invalid
+    InvalidType(String),
+    /// The data refers to a type that does not exist
+    InvalidTypeIndex(u32),
+    /// The data refers to an object that does not exist
+    InvalidObjectIndex(u32),
+    /// A root object is of a type with the specified name, which no longer exists
+    DeletedType(String),
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeserializeError::InvalidMagic => write!(f, "the data is not a serialized heap"),
+            DeserializeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported serialization version: {}", version)
+            }
+            DeserializeError::UnexpectedEnd => write!(f, "unexpected end of data"),
+            DeserializeError::InvalidUtf8 => write!(f, "a string is not UTF-8 encoded"),
+            DeserializeError::InvalidType(name) => write!(f, "invalid type `{}`", name),
+            DeserializeError::InvalidTypeIndex(index) => write!(f, "invalid type index: {}", index),
+            DeserializeError::InvalidObjectIndex(index) => {
+                write!(f, "invalid object index: {}", index)
+            }
+            DeserializeError::DeletedType(name) => {
+                write!(f, "the type of a root object no longer exists: `{}`", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DeserializeError {}
+
+/// Serializes all objects that are reachable from the specified `roots` to a portable byte
+/// format. The objects can be restored with [`deserialize`], which returns the `roots` in the same
+/// order.
+pub fn serialize<T, G>(runtime: &G, roots: &[GcPtr]) -> Vec<u8>
+where
+    T: TypeDesc + TypeMemory + TypeFields<T> + TypeTrace + Clone,
+    G: GcRuntime<T>,
+{
+    let mut serializer = Serializer {
+        runtime,
+        types: TypeTable::default(),
+        objects: Vec::new(),
+        indices: HashMap::new(),
+        data: Vec::new(),
+    };
+
+    let roots: Vec<u32> = roots
+        .iter()
+        .map(|root| serializer.object_index(*root))
+        .collect();
+
+    // Serializing an object appends the objects it references
+    let mut index = 0;
+    while index < serializer.objects.len() {
+        let (handle, ty) = serializer.objects[index];
+        write_u32(&mut serializer.data, to_u32(ty));
+        serializer.write_value(ty, unsafe { handle.deref::<u8>() }, true);
+        index += 1;
+    }
+
+    let mut bytes = Vec::with_capacity(serializer.data.len());
+    bytes.extend_from_slice(MAGIC);
+    write_u32(&mut bytes, VERSION);
+
+    write_u32(&mut bytes, to_u32(serializer.types.entries.len()));
+    for entry in serializer.types.entries.iter() {
+        bytes.extend_from_slice(&entry.guid.0);
+        write_str(&mut bytes, &entry.name);
+        bytes.push(match entry.group {
+            TypeGroup::Primitive => 0,
+            TypeGroup::Struct => 1,
+        });
+        bytes.push(u8::from(entry.is_stack_allocated));
+        write_u32(&mut bytes, to_u32(entry.layout.size()));
+        write_u32(&mut bytes, to_u32(entry.layout.align()));
+        write_u32(&mut bytes, to_u32(entry.field_types.len()));
+        for ((name, ty), offset) in entry
+            .field_names
+            .iter()
+            .zip(entry.field_types.iter())
+            .zip(entry.field_offsets.iter())
+        {
+            write_str(&mut bytes, name);
+            write_u32(&mut bytes, to_u32(*ty));
+            bytes.extend_from_slice(&offset.to_le_bytes());
+        }
+    }
+
+    write_u32(&mut bytes, to_u32(serializer.objects.len()));
+    bytes.append(&mut serializer.data);
+
+    write_u32(&mut bytes, to_u32(roots.len()));
+    for root in roots {
+        write_u32(&mut bytes, root);
+    }
+
+    bytes
+}
+
+/// Deserializes objects that were serialized by [`serialize`] and returns the rooted objects that
+/// correspond to the serialized roots.
+///
+/// The objects are allocated with the specified `types`. Serialized types that no longer exist
+/// or that changed are mapped onto `types`, converting the objects' memory accordingly. Fields
+/// that cannot be converted are zero-initialized.
+pub fn deserialize<T, G>(
+    runtime: &Arc<G>,
+    types: &[T],
+    bytes: &[u8],
+) -> Result<Vec<GcRootPtr<T, G>>, DeserializeError>
+where
+    T: TypeDesc + TypeMemory + TypeFields<T> + TypeTrace + Clone,
+    G: GcRuntime<T>,
+{
+    let mut reader = Reader { bytes, position: 0 };
+    if reader.read_bytes(MAGIC.len())? != MAGIC {
+        return Err(DeserializeError::InvalidMagic);
+    }
+    let version = reader.read_u32()?;
+    if version != VERSION {
+        return Err(DeserializeError::UnsupportedVersion(version));
+    }
+
+    let old_types = reader.read_types()?;
+    let num_objects = reader.read_u32()?;
+    let values = (0..num_objects)
+        .map(|_| {
+            let ty = reader.read_type_index(&old_types)?;
+            let value = reader.read_value(&old_types, ty, true, num_objects, 0)?;
+            Ok((ty, value))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let roots = (0..reader.read_u32()?)
+        .map(|_| match reader.read_u32()? {
+            index if index < num_objects => Ok(index as usize),
+            index => Err(DeserializeError::InvalidObjectIndex(index)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut new_types = TypeTable::default();
+    for ty in types {
+        new_types.insert(ty);
+    }
+
+    let old_refs: Vec<_> = (0..old_types.len())
+        .map(|index| TypeRef {
+            entries: &old_types,
+            index,
+        })
+        .collect();
+    let new_refs: Vec<_> = (0..new_types.entries.len())
+        .map(|index| TypeRef {
+            entries: &new_types.entries,
+            index,
+        })
+        .collect();
+    let mapping = Mapping::new(&old_refs, &new_refs);
+
+    let mut resolved = HashMap::new();
+    for (old_ty, new_ty) in mapping.identical.iter() {
+        resolved.insert(old_ty.index, (new_ty.index, None));
+    }
+    for (old_ty, conversion) in mapping.conversions.iter() {
+        resolved.insert(
+            old_ty.index,
+            (
+                conversion.new_ty.index,
+                Some(conversion.field_mapping.as_slice()),
+            ),
+        );
+    }
+
+    let mut deserializer = Deserializer {
+        runtime,
+        old_types: &old_types,
+        new_types: &new_types,
+        resolved,
+        values: &values,
+        objects: Vec::with_capacity(values.len()),
+        temporaries: RefCell::new(Vec::new()),
+    };
+
+    // All objects are allocated before they are written, so references can be resolved
+    for (old_ty, _) in values.iter() {
+        let object = deserializer
+            .resolve(*old_ty)
+            .map(|(new_ty, _)| (deserializer.alloc(new_ty), new_ty));
+        deserializer.objects.push(object);
+    }
+
+    for ((old_ty, value), object) in values.iter().zip(deserializer.objects.iter()) {
+        if let Some((object, new_ty)) = object {
+            let dest = unsafe { object.deref::<u8>() } as *mut u8;
+            match value {
+                Value::Struct(fields) => {
+                    let (_, mapping) = deserializer
+                        .resolve(*old_ty)
+                        .expect("an allocated object must have a resolved type");
+                    deserializer.write_fields(fields, *old_ty, *new_ty, mapping, dest);
+                }
+                value => deserializer.write(value, *old_ty, *new_ty, dest),
+            }
+            runtime.write_barrier(object.handle());
+        }
+    }
+
+    roots
+        .into_iter()
+        .map(|index| match &deserializer.objects[index] {
+            Some((object, _)) => Ok(object.clone()),
+            None => Err(DeserializeError::DeletedType(
+                old_types[values[index].0].name.clone(),
+            )),
+        })
+        .collect()
+}
+
+/// The description of a type, in which field types are identified by their index in a
+/// [`TypeTable`] or in the serialized type table.
+struct TypeEntry {
+    guid: abi::Guid,
+    name: String,
+    group: TypeGroup,
+    is_stack_allocated: bool,
+    layout: Layout,
+    field_names: Vec<String>,
+    field_types: Vec<usize>,
+    field_offsets: Vec<u16>,
+}
+
+/// Assigns an index to every type and its field types, based on their `Guid`s.
+struct TypeTable<T> {
+    entries: Vec<TypeEntry>,
+    types: Vec<T>,
+    indices: HashMap<abi::Guid, usize>,
+}
+
+impl<T> Default for TypeTable<T> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            types: Vec::new(),
+            indices: HashMap::new(),
+        }
+    }
+}
+
+impl<T: TypeDesc + TypeMemory + TypeFields<T> + Clone> TypeTable<T> {
+    /// Inserts the specified type and its field types, returning the index of the type.
+    fn insert(&mut self, ty: &T) -> usize {
+        if let Some(index) = self.indices.get(ty.guid()) {
+            return *index;
+        }
+
+        // The index is assigned before inserting field types, as a type can reference itself
+        let index = self.entries.len();
+        self.indices.insert(*ty.guid(), index);
+        self.types.push(ty.clone());
+        self.entries.push(TypeEntry {
+            guid: *ty.guid(),
+            name: ty.name().to_owned(),
+            group: ty.group(),
+            is_stack_allocated: ty.is_stack_allocated(),
+            layout: ty.layout(),
+            field_names: Vec::new(),
+            field_types: Vec::new(),
+            field_offsets: ty.offsets().to_vec(),
+        });
+
+        let fields = ty.fields();
+        let field_types = fields
+            .iter()
+            .map(|(_, field_ty)| self.insert(field_ty))
+            .collect();
+        let entry = &mut self.entries[index];
+        entry.field_names = fields.iter().map(|(name, _)| (*name).to_owned()).collect();
+        entry.field_types = field_types;
+        index
+    }
+}
+
+/// A reference to a [`TypeEntry`], which allows a [`Mapping`] to be created between serialized
+/// types and current types.
+#[derive(Clone, Copy)]
+struct TypeRef<'t> {
+    entries: &'t [TypeEntry],
+    index: usize,
+}
+
+impl<'t> TypeRef<'t> {
+    fn entry(&self) -> &'t TypeEntry {
+        &self.entries[self.index]
+    }
+}
+
+impl PartialEq for TypeRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entry().guid == other.entry().guid
+    }
+}
+
+impl Eq for TypeRef<'_> {}
+
+impl Hash for TypeRef<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.entry().guid.hash(state);
+    }
+}
+
+impl TypeDesc for TypeRef<'_> {
+    fn name(&self) -> &str {
+        &self.entry().name
+    }
+
+    fn guid(&self) -> &abi::Guid {
+        &self.entry().guid
+    }
+
+    fn group(&self) -> TypeGroup {
+        self.entry().group
+    }
+}
+
+impl TypeMemory for TypeRef<'_> {
+    fn layout(&self) -> Layout {
+        self.entry().layout
+    }
+
+    fn is_stack_allocated(&self) -> bool {
+        self.entry().is_stack_allocated
+    }
+}
+
+impl<'t> TypeFields<TypeRef<'t>> for TypeRef<'t> {
+    fn fields(&self) -> Vec<(&str, TypeRef<'t>)> {
+        let entry = self.entry();
+        entry
+            .field_names
+            .iter()
+            .zip(entry.field_types.iter())
+            .map(|(name, index)| {
+                (
+                    name.as_str(),
+                    TypeRef {
+                        entries: self.entries,
+                        index: *index,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn offsets(&self) -> &[u16] {
+        &self.entry().field_offsets
+    }
+}
+
+/// Writes the objects that are reachable from the roots.
+struct Serializer<'r, T, G> {
+    runtime: &'r G,
+    types: TypeTable<T>,
+    objects: Vec<(GcPtr, usize)>,
+    indices: HashMap<GcPtr, u32>,
+    data: Vec<u8>,
+}
+
+impl<T, G> Serializer<'_, T, G>
+where
+    T: TypeDesc + TypeMemory + TypeFields<T> + TypeTrace + Clone,
+    G: GcRuntime<T>,
+{
+    /// Returns the index of the object with the specified `handle`, assigning an index if the
+    /// object was not referenced before.
+    fn object_index(&mut self, handle: GcPtr) -> u32 {
+        if RawGcPtr::from(handle).is_null() {
+            return NULL_INDEX;
+        }
+
+        if let Some(index) = self.indices.get(&handle) {
+            return *index;
+        }
+
+        let ty = self.types.insert(&self.runtime.ptr_type(handle));
+        let index = to_u32(self.objects.len());
+        self.objects.push((handle, ty));
+        self.indices.insert(handle, index);
+        index
+    }
+
+    /// Writes the value of type `ty` that is stored at `ptr`. Unless the value is stored `inline`,
+    /// a garbage collected struct is written as a reference.
+    fn write_value(&mut self, ty: usize, ptr: *const u8, inline: bool) {
+        let entry = &self.types.entries[ty];
+        match entry.group {
+            TypeGroup::Primitive => {
+                let mut bytes =
+                    unsafe { std::slice::from_raw_parts(ptr, entry.layout.size()) }.to_vec();
+                if cfg!(target_endian = "big") {
+                    bytes.reverse();
+                }
+                self.data.append(&mut bytes);
+            }
+            TypeGroup::Struct if !inline && !entry.is_stack_allocated => {
+                let handle = unsafe { ptr.cast::<GcPtr>().read_unaligned() };
+                let index = self.object_index(handle);
+                write_u32(&mut self.data, index);
+            }
+            TypeGroup::Struct => {
+                for field in 0..entry.field_types.len() {
+                    let entry = &self.types.entries[ty];
+                    let field_ty = entry.field_types[field];
+                    let offset = usize::from(entry.field_offsets[field]);
+                    self.write_value(field_ty, unsafe { ptr.add(offset) }, false);
+                }
+            }
+        }
+    }
+}
+
+/// A deserialized value.
+enum Value {
+    /// The bytes of a primitive value, in native byte order
+    Primitive(Vec<u8>),
+    /// The fields of a struct
+    Struct(Vec<Value>),
+    /// A reference to a garbage collected object
+    Object(Option<usize>),
+}
+
+/// Reads serialized data.
+struct Reader<'b> {
+    bytes: &'b [u8],
+    position: usize,
+}
+
+impl<'b> Reader<'b> {
+    fn read_bytes(&mut self, len: usize) -> Result<&'b [u8], DeserializeError> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(DeserializeError::UnexpectedEnd)?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, DeserializeError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, DeserializeError> {
+        Ok(u16::from_le_bytes(self.read_bytes(2)?.try_into().unwrap()))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, DeserializeError> {
+        Ok(u32::from_le_bytes(self.read_bytes(4)?.try_into().unwrap()))
+    }
+
+    fn read_str(&mut self) -> Result<String, DeserializeError> {
+        let len = self.read_u32()? as usize;
+        let bytes = self.read_bytes(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| DeserializeError::InvalidUtf8)
+    }
+
+    fn read_type_index(&mut self, types: &[TypeEntry]) -> Result<usize, DeserializeError> {
+        match self.read_u32()? {
+            index if (index as usize) < types.len() => Ok(index as usize),
+            index => Err(DeserializeError::InvalidTypeIndex(index)),
+        }
+    }
+
+    /// Reads the serialized type table.
+    fn read_types(&mut self) -> Result<Vec<TypeEntry>, DeserializeError> {
+        let num_types = self.read_u32()?;
+        let mut types = Vec::new();
+        for _ in 0..num_types {
+            let guid = abi::Guid(self.read_bytes(16)?.try_into().unwrap());
+            let name = self.read_str()?;
+            let group = match self.read_u8()? {
+                0 => TypeGroup::Primitive,
+                1 => TypeGroup::Struct,
+                _ => return Err(DeserializeError::InvalidType(name)),
+            };
+            let is_stack_allocated = self.read_u8()? != 0;
+            let size = self.read_u32()? as usize;
+            let align = self.read_u32()? as usize;
+            let layout = match Layout::from_size_align(size, align) {
+                Ok(layout) => layout,
+                Err(_) => return Err(DeserializeError::InvalidType(name)),
+            };
+
+            let num_fields = self.read_u32()?;
+            let mut field_names = Vec::new();
+            let mut field_types = Vec::new();
+            let mut field_offsets = Vec::new();
+            for _ in 0..num_fields {
+                field_names.push(self.read_str()?);
+                match self.read_u32()? {
+                    index if index < num_types => field_types.push(index as usize),
+                    index => return Err(DeserializeError::InvalidTypeIndex(index)),
+                }
+                field_offsets.push(self.read_u16()?);
+            }
+
+            types.push(TypeEntry {
+                guid,
+                name,
+                group,
+                is_stack_allocated,
+                layout,
+                field_names,
+                field_types,
+                field_offsets,
+            });
+        }
+        Ok(types)
+    }
+
+    /// Reads a value of type `ty`. Unless the value is stored `inline`, a garbage collected struct
+    /// is read as a reference.
+    fn read_value(
+        &mut self,
+        types: &[TypeEntry],
+        ty: usize,
+        inline: bool,
+        num_objects: u32,
+        depth: usize,
+    ) -> Result<Value, DeserializeError> {
+        let entry = &types[ty];
+
+        // Value structs that (indirectly) contain themselves would be infinitely large
+        if depth > types.len() {
+            return Err(DeserializeError::InvalidType(entry.name.clone()));
+        }
+
+        match entry.group {
+            TypeGroup::Primitive => {
+                let mut bytes = self.read_bytes(entry.layout.size())?.to_vec();
+                if cfg!(target_endian = "big") {
+                    bytes.reverse();
+                }
+                Ok(Value::Primitive(bytes))
+            }
+            TypeGroup::Struct if !inline && !entry.is_stack_allocated => match self.read_u32()? {
+                NULL_INDEX => Ok(Value::Object(None)),
+                index if index < num_objects => Ok(Value::Object(Some(index as usize))),
+                index => Err(DeserializeError::InvalidObjectIndex(index)),
+            },
+            TypeGroup::Struct => entry
+                .field_types
+                .iter()
+                .map(|field_ty| self.read_value(types, *field_ty, false, num_objects, depth + 1))
+                .collect::<Result<_, _>>()
+                .map(Value::Struct),
+        }
+    }
+}
+
+/// The current type of a serialized type and, if the type changed, the mapping of its fields.
+type Resolved<'a> = (usize, Option<&'a [FieldMapping<TypeRef<'a>>]>);
+
+/// Writes deserialized values to newly allocated objects, converting them to the current types.
+struct Deserializer<'a, T: TypeMemory + TypeTrace, G: GcRuntime<T>> {
+    runtime: &'a Arc<G>,
+    old_types: &'a [TypeEntry],
+    new_types: &'a TypeTable<T>,
+    resolved: HashMap<usize, Resolved<'a>>,
+    values: &'a [(usize, Value)],
+    objects: Vec<Option<(GcRootPtr<T, G>, usize)>>,
+    temporaries: RefCell<Vec<GcRootPtr<T, G>>>,
+}
+
+impl<'a, T, G> Deserializer<'a, T, G>
+where
+    T: TypeDesc + TypeMemory + TypeFields<T> + TypeTrace + Clone,
+    G: GcRuntime<T>,
+{
+    /// Returns the current type of the serialized type `old_ty` and, if the type changed, the
+    /// mapping of its fields.
+    fn resolve(&self, old_ty: usize) -> Option<Resolved<'a>> {
+        let entry = &self.old_types[old_ty];
+        match entry.group {
+            TypeGroup::Primitive => self
+                .new_types
+                .indices
+                .get(&entry.guid)
+                .map(|new_ty| (*new_ty, None)),
+            TypeGroup::Struct => self.resolved.get(&old_ty).copied(),
+        }
+    }
+
+    /// Allocates a zero-initialized object of type `new_ty`.
+    fn alloc(&self, new_ty: usize) -> GcRootPtr<T, G> {
+        let mut handle = self.runtime.alloc(self.new_types.types[new_ty].clone());
+        let size = self.new_types.entries[new_ty].layout.size();
+        unsafe { std::ptr::write_bytes(handle.deref_mut::<u8>(), 0, size) };
+        GcRootPtr::new(self.runtime, handle)
+    }
+
+    /// Allocates a zero-initialized object of type `new_ty`, which is kept alive until
+    /// deserialization finishes, and writes its handle to `dest`.
+    fn alloc_field(&self, new_ty: usize, dest: *mut u8) -> *mut u8 {
+        let object = self.alloc(new_ty);
+        unsafe { dest.cast::<GcPtr>().write_unaligned(object.handle()) };
+        let ptr = unsafe { object.deref::<u8>() } as *mut u8;
+        self.temporaries.borrow_mut().push(object);
+        ptr
+    }
+
+    /// Writes the `fields` of a struct of serialized type `old_ty` to `dest`, using the `mapping`
+    /// to convert them to the fields of `new_ty`.
+    fn write_fields(
+        &self,
+        fields: &[Value],
+        old_ty: usize,
+        new_ty: usize,
+        mapping: Option<&[FieldMapping<TypeRef<'a>>]>,
+        dest: *mut u8,
+    ) {
+        let old_entry = &self.old_types[old_ty];
+        let new_entry = &self.new_types.entries[new_ty];
+
+        let mapping = match mapping {
+            Some(mapping) => mapping,
+            None => {
+                // The type did not change, so neither did its fields
+                for ((value, old_field_ty), (new_field_ty, offset)) in
+                    fields.iter().zip(old_entry.field_types.iter()).zip(
+                        new_entry
+                            .field_types
+                            .iter()
+                            .zip(new_entry.field_offsets.iter()),
+                    )
+                {
+                    let field_dest = unsafe { dest.add(usize::from(*offset)) };
+                    self.write(value, *old_field_ty, *new_field_ty, field_dest);
+                }
+                return;
+            }
+        };
+
+        for FieldMapping {
+            new_ty: new_field_ty,
+            new_offset,
+            action,
+        } in mapping.iter()
+        {
+            let field_dest = unsafe { dest.add(*new_offset) };
+            let (old_offset, old_field_guid) = match action {
+                Action::Cast { old_offset, old_ty } => (*old_offset, old_ty.guid()),
+                Action::Copy { old_offset } => (*old_offset, new_field_ty.guid()),
+                Action::Insert => {
+                    if !new_field_ty.is_stack_allocated() {
+                        self.alloc_field(new_field_ty.index, field_dest);
+                    }
+                    continue;
+                }
+            };
+
+            let old_field = old_entry
+                .field_offsets
+                .iter()
+                .zip(old_entry.field_types.iter())
+                .position(|(offset, ty)| {
+                    usize::from(*offset) == old_offset
+                        && self.old_types[*ty].guid == *old_field_guid
+                })
+                .expect("a mapped field must exist");
+            self.write(
+                &fields[old_field],
+                old_entry.field_types[old_field],
+                new_field_ty.index,
+                field_dest,
+            );
+        }
+    }
+
+    /// Writes the `value` of serialized type `old_ty` to `dest`, converting it to `new_ty`.
+    /// Values that cannot be converted are left zero-initialized.
+    fn write(&self, value: &Value, old_ty: usize, new_ty: usize, dest: *mut u8) {
+        let new_entry = &self.new_types.entries[new_ty];
+        match value {
+            Value::Primitive(bytes) => {
+                if new_entry.group == TypeGroup::Primitive {
+                    self.write_primitive(bytes, old_ty, new_ty, dest)
+                }
+            }
+            Value::Struct(fields) => {
+                if new_entry.group != TypeGroup::Struct {
+                    return;
+                }
+
+                let mapping = self
+                    .resolve(old_ty)
+                    .filter(|(ty, _)| *ty == new_ty)
+                    .map(|(_, mapping)| mapping);
+                let dest = if new_entry.is_stack_allocated {
+                    dest
+                } else {
+                    // struct(value) -> struct(gc)
+                    self.alloc_field(new_ty, dest)
+                };
+                if let Some(mapping) = mapping {
+                    self.write_fields(fields, old_ty, new_ty, mapping, dest);
+                }
+            }
+            Value::Object(None) => (),
+            Value::Object(Some(index)) => {
+                if new_entry.group != TypeGroup::Struct {
+                    return;
+                }
+
+                if !new_entry.is_stack_allocated {
+                    match &self.objects[*index] {
+                        // struct(gc) -> struct(gc)
+                        Some((object, ty)) if *ty == new_ty => unsafe {
+                            dest.cast::<GcPtr>().write_unaligned(object.handle())
+                        },
+                        // The object's type changed into another struct
+                        _ => {
+                            self.alloc_field(new_ty, dest);
+                        }
+                    }
+                } else {
+                    // struct(gc) -> struct(value)
+                    let (object_ty, object_value) = &self.values[*index];
+                    let mapping = self
+                        .resolve(*object_ty)
+                        .filter(|(ty, _)| *ty == new_ty)
+                        .map(|(_, mapping)| mapping);
+                    if let (Some(mapping), Value::Struct(fields)) = (mapping, object_value) {
+                        self.write_fields(fields, *object_ty, new_ty, mapping, dest);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Writes the `bytes` of a primitive of serialized type `old_ty` to `dest`, casting them to
+    /// `new_ty` if the type changed.
+    fn write_primitive(&self, bytes: &[u8], old_ty: usize, new_ty: usize, dest: *mut u8) {
+        let old_entry = &self.old_types[old_ty];
+        let new_entry = &self.new_types.entries[new_ty];
+        if old_entry.guid == new_entry.guid {
+            let size = bytes.len().min(new_entry.layout.size());
+            unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), dest, size) };
+        } else if bytes.len() <= std::mem::size_of::<u128>() {
+            // Casts read the value in place, so it needs to be aligned
+            let mut src = 0u128;
+            let src = NonNull::from(&mut src).cast::<u8>();
+            unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), src.as_ptr(), bytes.len()) };
+            if !cast::try_cast_from_to(old_entry.guid, new_entry.guid, src, unsafe {
+                NonNull::new_unchecked(dest)
+            }) {
+                // Failed to cast. Use the zero-initialized value instead
+            }
+        }
+    }
+}
+
+fn write_u32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_str(bytes: &mut Vec<u8>, value: &str) {
+    write_u32(bytes, to_u32(value.len()));
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+fn to_u32(value: usize) -> u32 {
+    value
+        .try_into()
+        .expect("cannot serialize more than u32::MAX elements")
+}
@@ -1,5 +1,5 @@
 use super::util::{EventAggregator, HasTypeInfo, TypeInfo};
-use mun_memory::gc::{Event, GcRootPtr, GcRuntime, MarkSweep};
+use mun_memory::gc::{CollectionThresholds, Event, GcRootPtr, GcRuntime, MarkSweep};
 use std::sync::Arc;
 
 #[test]
@@ -63,3 +63,128 @@ fn collect_rooted() {
     assert_eq!(events.next(), Some(Event::End));
     assert_eq!(events.next(), None);
 }
+
+#[test]
+fn collect_stats() {
+    let runtime = MarkSweep::<&'static TypeInfo, EventAggregator<Event>>::default();
+    runtime.alloc(i64::type_info());
+    runtime.alloc(i32::type_info());
+
+    let stats = runtime.stats();
+    assert_eq!(stats.allocated_memory, 12);
+    assert_eq!(stats.collections, 0);
+
+    runtime.collect();
+    runtime.collect();
+
+    let stats = runtime.stats();
+    assert_eq!(stats.allocated_memory, 0);
+    assert_eq!(stats.collections, 2);
+    assert_eq!(stats.freed_memory, 12);
+    assert!(stats.max_pause >= stats.last_pause);
+    assert!(stats.total_pause >= stats.max_pause);
+}
+
+#[test]
+fn needs_collection() {
+    let runtime = Arc::new(MarkSweep::<&'static TypeInfo, EventAggregator<Event>>::default());
+
+    // By default, a collection is never needed
+    runtime.alloc(i64::type_info());
+    assert!(!runtime.needs_collection());
+
+    runtime.set_thresholds(CollectionThresholds {
+        max_allocations: Some(2),
+        ..CollectionThresholds::default()
+    });
+    assert!(!runtime.needs_collection());
+    runtime.alloc(i64::type_info());
+    assert!(runtime.needs_collection());
+
+    // A collection resets the number of allocations
+    runtime.collect();
+    assert!(!runtime.needs_collection());
+
+    runtime.set_thresholds(CollectionThresholds {
+        max_bytes: Some(8),
+        ..CollectionThresholds::default()
+    });
+    let _rooted = GcRootPtr::new(&runtime, runtime.alloc(i64::type_info()));
+    assert!(!runtime.needs_collection());
+    runtime.alloc(i64::type_info());
+    assert!(runtime.needs_collection());
+
+    // The rooted object is retained, so any further allocation exceeds the limit
+    runtime.collect();
+    assert!(!runtime.needs_collection());
+    runtime.alloc(i32::type_info());
+    assert!(runtime.needs_collection());
+}
+
+#[test]
+fn growth_factor() {
+    let thresholds = CollectionThresholds {
+        growth_factor: Some(2.0),
+        ..CollectionThresholds::default()
+    };
+
+    // Growth is relative to the minimum base, while little memory is retained
+    let base = CollectionThresholds::MIN_GROWTH_BASE;
+    assert!(!thresholds.is_exceeded(2 * base, 0, 1));
+    assert!(thresholds.is_exceeded(2 * base + 1, 0, 1));
+
+    // Growth is relative to the retained memory otherwise
+    assert!(!thresholds.is_exceeded(8 * base, 4 * base, 1));
+    assert!(thresholds.is_exceeded(8 * base + 1, 4 * base, 1));
+}
+
+#[test]
+fn max_heap_size() {
+    let runtime = Arc::new(MarkSweep::<&'static TypeInfo, EventAggregator<Event>>::default());
+    runtime.set_max_heap_size(Some(16));
+    assert_eq!(runtime.stats().max_heap_size, Some(16));
+
+    let _rooted = GcRootPtr::new(&runtime, runtime.try_alloc(i64::type_info()).unwrap());
+    runtime.try_alloc(i64::type_info()).unwrap();
+    assert!(!runtime.needs_collection());
+
+    // Exceeding the limit fails the allocation and requests a collection
+    let err = runtime.try_alloc(i64::type_info()).unwrap_err();
+    assert_eq!(err.requested, 8);
+    assert_eq!(err.max_heap_size, 16);
+    assert_eq!(runtime.stats().allocated_memory, 16);
+    assert!(runtime.needs_collection());
+
+    // The collection frees the unrooted object, making room for another allocation
+    runtime.collect();
+    assert!(!runtime.needs_collection());
+    runtime.try_alloc(i64::type_info()).unwrap();
+
+    runtime.set_max_heap_size(None);
+    runtime.try_alloc(i64::type_info()).unwrap();
+}
+
+#[test]
+fn max_heap_size_concurrent() {
+    let runtime = Arc::new(MarkSweep::<&'static TypeInfo, EventAggregator<Event>>::default());
+    runtime.set_max_heap_size(Some(64 * 8));
+
+    // Concurrent allocations cannot exceed the limit together
+    let threads = (0..8)
+        .map(|_| {
+            let runtime = runtime.clone();
+            std::thread::spawn(move || {
+                (0..16)
+                    .filter(|_| runtime.try_alloc(i64::type_info()).is_ok())
+                    .count()
+            })
+        })
+        .collect::<Vec<_>>();
+    let allocated: usize = threads
+        .into_iter()
+        .map(|thread| thread.join().unwrap())
+        .sum();
+
+    assert_eq!(allocated, 64);
+    assert_eq!(runtime.stats().allocated_memory, 64 * 8);
+}
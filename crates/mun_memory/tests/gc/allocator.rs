@@ -0,0 +1,89 @@
+use super::util::{EventAggregator, HasTypeInfo};
+use mun_memory::gc::{
+    Allocator, Event, GcRootPtr, GcRuntime, Generational, GlobalAllocator, MarkSweep,
+};
+use std::{
+    alloc::Layout,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// An allocator that counts its live allocations.
+#[derive(Default)]
+struct CountingAllocator {
+    allocations: AtomicUsize,
+}
+
+impl CountingAllocator {
+    fn allocations(&self) -> usize {
+        self.allocations.load(Ordering::SeqCst)
+    }
+}
+
+unsafe impl Allocator for CountingAllocator {
+    fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.allocations.fetch_add(1, Ordering::SeqCst);
+        GlobalAllocator.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.allocations.fetch_sub(1, Ordering::SeqCst);
+        GlobalAllocator.dealloc(ptr, layout)
+    }
+}
+
+#[test]
+fn mark_sweep_allocator() {
+    let allocator = Arc::new(CountingAllocator::default());
+    let runtime = Arc::new(MarkSweep::<_, EventAggregator<Event>>::with_allocator(
+        EventAggregator::default(),
+        allocator.clone(),
+    ));
+
+    // Both the memory of an object and its bookkeeping are allocated
+    let rooted = GcRootPtr::new(&runtime, runtime.alloc(i64::type_info()));
+    runtime.alloc(i64::type_info());
+    assert_eq!(allocator.allocations(), 4);
+
+    assert!(runtime.collect());
+    assert_eq!(allocator.allocations(), 2);
+
+    // Dropping the runtime deallocates all remaining objects
+    drop(rooted);
+    drop(runtime);
+    assert_eq!(allocator.allocations(), 0);
+}
+
+#[test]
+fn generational_allocator() {
+    let allocator = Arc::new(CountingAllocator::default());
+    let runtime = Arc::new(Generational::<_, EventAggregator<Event>>::with_allocator(
+        EventAggregator::default(),
+        16,
+        allocator.clone(),
+    ));
+
+    // The nursery is allocated up front and bookkeeping is allocated in blocks, so young objects
+    // share a single allocation
+    assert_eq!(allocator.allocations(), 1);
+    let rooted = GcRootPtr::new(&runtime, runtime.alloc(i64::type_info()));
+    runtime.alloc(i64::type_info());
+    assert_eq!(allocator.allocations(), 2);
+
+    // Objects that do not fit in the nursery are allocated directly
+    runtime.alloc(i64::type_info());
+    assert_eq!(allocator.allocations(), 3);
+
+    // Promoted objects are allocated outside of the nursery, whereas the bookkeeping of collected
+    // objects is kept for reuse
+    assert!(runtime.collect());
+    assert_eq!(allocator.allocations(), 3);
+    runtime.alloc(i64::type_info());
+    assert_eq!(allocator.allocations(), 3);
+
+    drop(rooted);
+    drop(runtime);
+    assert_eq!(allocator.allocations(), 0);
+}
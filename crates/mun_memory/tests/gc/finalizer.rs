@@ -0,0 +1,106 @@
+use super::util::{HasTypeInfo, TypeInfo};
+use mun_memory::gc::{
+    Event, Finalizer, GcPtr, GcRootPtr, GcRuntime, Generational, HasIndirectionPtr, Incremental,
+    MarkSweep, Observer,
+};
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Entry {
+    Event(Event),
+    Finalized(GcPtr, i64),
+}
+
+/// Logs events and finalizations in the order in which they happen
+#[derive(Clone, Default)]
+struct Log(Arc<Mutex<Vec<Entry>>>);
+
+impl Log {
+    fn finalizer(&self) -> Finalizer<&'static TypeInfo> {
+        let log = self.0.clone();
+        Finalizer::new(move |handle, _ty| {
+            let value = unsafe { *handle.deref::<i64>() };
+            log.lock().push(Entry::Finalized(handle, value));
+        })
+    }
+
+    fn take_all(&self) -> Vec<Entry> {
+        self.0.lock().drain(..).collect()
+    }
+}
+
+impl Observer for Log {
+    type Event = Event;
+
+    fn event(&self, event: Event) {
+        self.0.lock().push(Entry::Event(event))
+    }
+}
+
+fn finalize_garbage<G: GcRuntime<&'static TypeInfo>>(runtime: Arc<G>, log: Log, collect: fn(&G)) {
+    let mut garbage = runtime.alloc(i64::type_info());
+    unsafe { *garbage.deref_mut::<i64>() = 42 };
+    let mut rooted = GcRootPtr::new(&runtime, runtime.alloc(i64::type_info()));
+    unsafe { *rooted.deref_mut::<i64>() = 7 };
+    log.take_all();
+
+    collect(&runtime);
+
+    // The finalizer is invoked before the deallocation of the object, and only for garbage
+    let entries = log.take_all();
+    let finalized = entries
+        .iter()
+        .position(|entry| *entry == Entry::Finalized(garbage, 42))
+        .expect("garbage should be finalized");
+    let deallocated = entries
+        .iter()
+        .position(|entry| *entry == Entry::Event(Event::Deallocation(garbage)))
+        .expect("garbage should be deallocated");
+    assert_eq!(finalized + 1, deallocated);
+    assert!(!entries
+        .iter()
+        .any(|entry| matches!(entry, Entry::Finalized(handle, _) if *handle == rooted.handle())));
+}
+
+#[test]
+fn finalize_mark_sweep() {
+    let log = Log::default();
+    let runtime = Arc::new(MarkSweep::with_observer(log.clone()));
+    runtime.set_finalizer(log.finalizer());
+    finalize_garbage(runtime, log, |runtime| {
+        runtime.collect();
+    });
+}
+
+#[test]
+fn finalize_incremental() {
+    let log = Log::default();
+    let runtime = Arc::new(Incremental::with_observer(log.clone()));
+    runtime.set_finalizer(log.finalizer());
+    finalize_garbage(runtime, log, |runtime| {
+        runtime.collect();
+    });
+}
+
+#[test]
+fn finalize_generational_minor() {
+    let log = Log::default();
+    let runtime = Arc::new(Generational::with_observer(log.clone()));
+    runtime.set_finalizer(log.finalizer());
+    finalize_garbage(runtime, log, |runtime| {
+        runtime.collect_minor();
+    });
+}
+
+#[test]
+fn finalize_generational_major() {
+    let log = Log::default();
+
+    // Objects that do not fit in the nursery are allocated in the old generation
+    let runtime = Arc::new(Generational::with_nursery_size(log.clone(), 1));
+    runtime.set_finalizer(log.finalizer());
+    finalize_garbage(runtime, log, |runtime| {
+        runtime.collect();
+    });
+}
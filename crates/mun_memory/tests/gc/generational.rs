@@ -0,0 +1,110 @@
+use super::util::{EventAggregator, HasTypeInfo, Trace, TypeInfo};
+use crate::impl_struct_ty;
+use mun_memory::gc::{Event, GcPtr, GcRootPtr, GcRuntime, Generational, HasIndirectionPtr};
+use std::sync::Arc;
+
+struct Node {
+    next: Option<GcPtr>,
+}
+
+impl Trace for Node {
+    fn trace(&self, handles: &mut Vec<GcPtr>) {
+        handles.extend(self.next)
+    }
+}
+
+impl_struct_ty!(Node);
+
+type Runtime = Generational<&'static TypeInfo, EventAggregator<Event>>;
+
+#[test]
+fn collect_minor() {
+    let runtime = Arc::new(Runtime::default());
+    let garbage = runtime.alloc(i64::type_info());
+    let mut rooted = GcRootPtr::new(&runtime, runtime.alloc(i64::type_info()));
+    unsafe { *rooted.deref_mut::<i64>() = 42 };
+
+    assert!(runtime.collect_minor());
+
+    // The rooted object was promoted to the old generation, retaining its value
+    assert_eq!(unsafe { *rooted.deref::<i64>() }, 42);
+    assert!(!runtime.collect_minor());
+
+    let mut events = runtime.observer().take_all().into_iter();
+    assert_eq!(events.next(), Some(Event::Allocation(garbage)));
+    assert_eq!(events.next(), Some(Event::Allocation(rooted.handle())));
+    assert_eq!(events.next(), Some(Event::Start));
+    assert_eq!(events.next(), Some(Event::Deallocation(garbage)));
+    assert_eq!(events.next(), Some(Event::End));
+    assert_eq!(events.next(), Some(Event::Start));
+    assert_eq!(events.next(), Some(Event::End));
+    assert_eq!(events.next(), None);
+
+    // Only a major collection collects old objects
+    let rooted = rooted.unroot();
+    assert!(!runtime.collect_minor());
+    assert!(runtime.collect());
+
+    let events = runtime.observer().take_all();
+    assert!(events.contains(&Event::Deallocation(rooted)));
+    assert_eq!(runtime.stats().allocated_memory, 0);
+}
+
+#[test]
+fn nursery_overflow() {
+    let runtime = Runtime::with_nursery_size(EventAggregator::default(), 16);
+    runtime.alloc(i64::type_info());
+    runtime.alloc(i64::type_info());
+    assert!(!runtime.needs_minor_collection());
+
+    // Objects that do not fit in the nursery are allocated in the old generation
+    let old = runtime.alloc(i64::type_info());
+    assert!(runtime.needs_minor_collection());
+
+    assert!(runtime.collect_minor());
+    assert!(!runtime.needs_minor_collection());
+
+    let events = runtime.observer().take_all();
+    assert!(!events.contains(&Event::Deallocation(old)));
+    assert_eq!(runtime.stats().allocated_memory, 8);
+}
+
+#[test]
+fn trace_young_from_old() {
+    let runtime = Arc::new(Runtime::default());
+    let mut old = GcRootPtr::new(&runtime, runtime.alloc(Node::type_info()));
+    unsafe { (*old.deref_mut::<Node>()).next = None };
+    runtime.collect_minor();
+
+    // Store a young object in the promoted object
+    let mut young = runtime.alloc(i64::type_info());
+    unsafe {
+        *young.deref_mut::<i64>() = 7;
+        (*old.deref_mut::<Node>()).next = Some(young);
+    }
+    runtime.write_barrier(old.handle());
+
+    runtime.collect_minor();
+
+    let events = runtime.observer().take_all();
+    assert!(!events.contains(&Event::Deallocation(young)));
+    assert_eq!(unsafe { *young.deref::<i64>() }, 7);
+}
+
+#[test]
+fn trace_young_from_young() {
+    let runtime = Arc::new(Runtime::default());
+    let bar = runtime.alloc(i64::type_info());
+    let mut foo = GcRootPtr::new(&runtime, runtime.alloc(Node::type_info()));
+    unsafe { (*foo.deref_mut::<Node>()).next = Some(bar) };
+
+    assert!(!runtime.collect_minor());
+
+    // Drop foo, both foo and bar should be collected by a major collection
+    let foo = foo.unroot();
+    assert!(runtime.collect());
+
+    let events = runtime.observer().take_all();
+    assert!(events.contains(&Event::Deallocation(foo)));
+    assert!(events.contains(&Event::Deallocation(bar)));
+}
@@ -0,0 +1,76 @@
+use crate::util::{alloc, null, Bar, Node, Pos, Runtime, BAR, NODE};
+use mun_memory::gc::{deep_clone, structural_eq, GcPtr, GcRuntime, HasIndirectionPtr};
+use std::sync::Arc;
+
+fn node(id: i64, bar: GcPtr, next: GcPtr) -> Node {
+    Node {
+        id,
+        pos: Pos {
+            x: id as f64,
+            y: -id as f64,
+        },
+        bar,
+        next,
+    }
+}
+
+#[test]
+fn deep_clone_cycle() {
+    let runtime = Arc::new(Runtime::default());
+    let bar = alloc(&runtime, &BAR, Bar { value: 42 });
+    let mut first = alloc(&runtime, &NODE, node(1, bar.handle(), null()));
+    let second = alloc(&runtime, &NODE, node(2, bar.handle(), first.handle()));
+    unsafe { (*first.deref_mut::<Node>()).next = second.handle() };
+    let allocated_memory = runtime.stats().allocated_memory;
+
+    let copy = deep_clone(runtime.as_ref(), first.handle());
+    assert_ne!(copy, first.handle());
+    assert_eq!(runtime.stats().allocated_memory, 2 * allocated_memory);
+    assert!(structural_eq(runtime.as_ref(), first.handle(), copy));
+
+    // References between the copies, including cycles, are preserved
+    let first_copy = unsafe { &*copy.deref::<Node>() };
+    let second_copy = unsafe { &*first_copy.next.deref::<Node>() };
+    assert_eq!(first_copy.id, 1);
+    assert_eq!(second_copy.id, 2);
+    assert_eq!(second_copy.next, copy);
+    assert_eq!(first_copy.bar, second_copy.bar);
+    assert_ne!(first_copy.bar, bar.handle());
+
+    // The copies are not rooted
+    runtime.collect();
+    assert_eq!(runtime.stats().allocated_memory, allocated_memory);
+}
+
+#[test]
+fn structural_eq_fields() {
+    let runtime = Arc::new(Runtime::default());
+    let bar = alloc(&runtime, &BAR, Bar { value: 42 });
+    let other_bar = alloc(&runtime, &BAR, Bar { value: 42 });
+    let a = alloc(&runtime, &NODE, node(1, bar.handle(), null()));
+    let b = alloc(&runtime, &NODE, node(1, other_bar.handle(), null()));
+    let gc = runtime.as_ref();
+
+    // Referenced objects are compared by value
+    assert!(structural_eq(gc, a.handle(), b.handle()));
+    assert!(structural_eq(gc, a.handle(), a.handle()));
+
+    // Objects of different types are not equal
+    assert!(!structural_eq(gc, a.handle(), bar.handle()));
+
+    // Fields of value structs are compared
+    let mut c = alloc(&runtime, &NODE, node(1, bar.handle(), null()));
+    unsafe { (*c.deref_mut::<Node>()).pos.y = 0.0 };
+    assert!(!structural_eq(gc, a.handle(), c.handle()));
+
+    // Fields of referenced objects are compared
+    let mut different_bar = alloc(&runtime, &BAR, Bar { value: 42 });
+    let d = alloc(&runtime, &NODE, node(1, different_bar.handle(), null()));
+    assert!(structural_eq(gc, a.handle(), d.handle()));
+    unsafe { (*different_bar.deref_mut::<Bar>()).value = 43 };
+    assert!(!structural_eq(gc, a.handle(), d.handle()));
+
+    // A null reference only equals a null reference
+    let e = alloc(&runtime, &NODE, node(1, bar.handle(), a.handle()));
+    assert!(!structural_eq(gc, a.handle(), e.handle()));
+}
@@ -0,0 +1,154 @@
+use super::util::{EventAggregator, HasTypeInfo, Trace, TypeInfo};
+use crate::impl_struct_ty;
+use mun_memory::gc::{Event, GcPtr, GcRootPtr, GcRuntime, HasIndirectionPtr, Incremental};
+use std::{sync::Arc, time::Duration};
+
+struct Node {
+    next: Option<GcPtr>,
+}
+
+impl Trace for Node {
+    fn trace(&self, handles: &mut Vec<GcPtr>) {
+        handles.extend(self.next)
+    }
+}
+
+impl_struct_ty!(Node);
+
+type Runtime = Incremental<&'static TypeInfo, EventAggregator<Event>>;
+
+/// Allocates a `Node` that references `next`
+fn alloc_node(runtime: &Runtime, next: Option<GcPtr>) -> GcPtr {
+    let mut handle = runtime.alloc(Node::type_info());
+    unsafe { (*handle.deref_mut::<Node>()).next = next };
+    handle
+}
+
+/// Performs collection steps until the collection cycle completes, returning the number of steps
+fn finish_cycle(runtime: &Runtime) -> usize {
+    let mut steps = 1;
+    while !runtime.collect_step(Duration::ZERO) {
+        steps += 1;
+    }
+    steps
+}
+
+#[test]
+fn collect_simple() {
+    let runtime = Runtime::default();
+    let handle = runtime.alloc(i64::type_info());
+
+    assert!(runtime.collect());
+    assert!(!runtime.collect());
+
+    let mut events = runtime.observer().take_all().into_iter();
+    assert_eq!(events.next(), Some(Event::Allocation(handle)));
+    assert_eq!(events.next(), Some(Event::Start));
+    assert_eq!(events.next(), Some(Event::Deallocation(handle)));
+    assert_eq!(events.next(), Some(Event::End));
+    assert_eq!(events.next(), Some(Event::Start));
+    assert_eq!(events.next(), Some(Event::End));
+    assert_eq!(events.next(), None);
+}
+
+#[test]
+fn collect_step() {
+    let runtime = Arc::new(Runtime::default());
+    let bar = runtime.alloc(i64::type_info());
+    let foo = GcRootPtr::new(&runtime, alloc_node(&runtime, Some(bar)));
+    let garbage = runtime.alloc(i64::type_info());
+
+    // A zero budget performs a single unit of work per step
+    assert!(finish_cycle(&runtime) > 1);
+    assert!(!runtime.is_collecting());
+
+    let stats = runtime.stats();
+    assert_eq!(stats.collections, 1);
+    assert_eq!(stats.freed_memory, 8);
+
+    let mut events = runtime.observer().take_all().into_iter();
+    assert_eq!(events.next(), Some(Event::Allocation(bar)));
+    assert_eq!(events.next(), Some(Event::Allocation(foo.handle())));
+    assert_eq!(events.next(), Some(Event::Allocation(garbage)));
+    assert_eq!(events.next(), Some(Event::Start));
+    assert_eq!(events.next(), Some(Event::Deallocation(garbage)));
+    assert_eq!(events.next(), Some(Event::End));
+    assert_eq!(events.next(), None);
+
+    // A generous budget completes the cycle in a single step
+    let foo = foo.unroot();
+    assert!(runtime.collect_step(Duration::from_secs(60)));
+
+    let mut events = runtime.observer().take_all().into_iter();
+    assert_eq!(events.next(), Some(Event::Start));
+    let deallocations = [events.next(), events.next()];
+    assert!(deallocations.contains(&Some(Event::Deallocation(foo))));
+    assert!(deallocations.contains(&Some(Event::Deallocation(bar))));
+    assert_eq!(events.next(), Some(Event::End));
+    assert_eq!(events.next(), None);
+}
+
+#[test]
+fn write_barrier() {
+    let runtime = Arc::new(Runtime::default());
+    let mut foo = GcRootPtr::new(&runtime, alloc_node(&runtime, None));
+    let bar = runtime.alloc(i64::type_info());
+
+    // Trace `foo`, before `bar` is stored in it
+    assert!(!runtime.collect_step(Duration::ZERO));
+    assert!(runtime.is_collecting());
+
+    unsafe { (*foo.deref_mut::<Node>()).next = Some(bar) };
+    runtime.write_barrier(foo.handle());
+
+    finish_cycle(&runtime);
+
+    // `bar` is reachable through `foo`, so it should not have been collected
+    let events = runtime.observer().take_all();
+    assert!(!events.contains(&Event::Deallocation(bar)));
+}
+
+#[test]
+fn root_while_marking() {
+    let runtime = Arc::new(Runtime::default());
+    let _foo = GcRootPtr::new(&runtime, alloc_node(&runtime, None));
+    let bar = runtime.alloc(i64::type_info());
+
+    assert!(!runtime.collect_step(Duration::ZERO));
+
+    // Objects rooted or allocated while marking survive the current cycle
+    let bar = GcRootPtr::new(&runtime, bar);
+    let baz = runtime.alloc(i64::type_info());
+    finish_cycle(&runtime);
+
+    let events = runtime.observer().take_all();
+    assert!(!events.contains(&Event::Deallocation(bar.handle())));
+    assert!(!events.contains(&Event::Deallocation(baz)));
+
+    // Unreferenced objects are collected by the next cycle
+    runtime.collect();
+    let events = runtime.observer().take_all();
+    assert!(events.contains(&Event::Deallocation(baz)));
+}
+
+#[test]
+fn alloc_while_marking() {
+    let runtime = Arc::new(Runtime::default());
+    let mut foo = GcRootPtr::new(&runtime, alloc_node(&runtime, None));
+    let bar = runtime.alloc(i64::type_info());
+
+    // Trace `foo`, before a new object that references `bar` is stored in it
+    assert!(!runtime.collect_step(Duration::ZERO));
+
+    // Like a struct literal, the new object is initialized without a write barrier
+    let baz = alloc_node(&runtime, Some(bar));
+    unsafe { (*foo.deref_mut::<Node>()).next = Some(baz) };
+    runtime.write_barrier(foo.handle());
+
+    finish_cycle(&runtime);
+
+    // `bar` is reachable through `baz`, so neither should have been collected
+    let events = runtime.observer().take_all();
+    assert!(!events.contains(&Event::Deallocation(baz)));
+    assert!(!events.contains(&Event::Deallocation(bar)));
+}
@@ -1,4 +1,11 @@
 mod alloc;
+mod allocator;
+mod finalizer;
+mod generational;
+mod graph;
+mod incremental;
+mod snapshot;
 mod structs;
 #[macro_use]
 mod util;
+mod weak;
@@ -0,0 +1,116 @@
+use super::util::{EventAggregator, HasTypeInfo, Trace, TypeInfo};
+use crate::impl_struct_ty;
+use mun_memory::gc::{
+    Event, GcPtr, GcRootPtr, GcRuntime, GcSnapshot, Generational, HasIndirectionPtr, Incremental,
+    MarkSweep, RawGcPtr,
+};
+use std::sync::Arc;
+
+struct Foo {
+    bar: GcPtr,
+}
+
+impl Trace for Foo {
+    fn trace(&self, handles: &mut Vec<GcPtr>) {
+        handles.push(self.bar)
+    }
+}
+
+impl_struct_ty!(Foo);
+
+fn snapshot_graph<G>(runtime: Arc<G>)
+where
+    G: GcSnapshot<&'static TypeInfo>,
+{
+    let mut foo = GcRootPtr::new(&runtime, runtime.alloc(Foo::type_info()));
+    let bar = runtime.alloc(i64::type_info());
+    unsafe { (*foo.deref_mut::<Foo>()).bar = bar };
+
+    let snapshot = runtime.snapshot();
+    assert_eq!(snapshot.objects.len(), 2);
+    assert_eq!(snapshot.total_size(), std::mem::size_of::<Foo>() + 8);
+
+    let foo_snapshot = snapshot.get(foo.handle()).unwrap();
+    assert_eq!(foo_snapshot.type_name, "Foo");
+    assert_eq!(foo_snapshot.size, std::mem::size_of::<Foo>());
+    assert_eq!(foo_snapshot.roots, 1);
+    assert_eq!(foo_snapshot.references, vec![bar]);
+
+    let bar_snapshot = snapshot.get(bar).unwrap();
+    assert_eq!(bar_snapshot.type_name, "i64");
+    assert_eq!(bar_snapshot.roots, 0);
+    assert!(bar_snapshot.references.is_empty());
+
+    let roots: Vec<_> = snapshot.roots().map(|object| object.handle).collect();
+    assert_eq!(roots, vec![foo.handle()]);
+
+    // Unrooted objects remain part of the snapshot until they are collected
+    let foo = foo.unroot();
+    let snapshot = runtime.snapshot();
+    assert_eq!(snapshot.objects.len(), 2);
+    assert_eq!(snapshot.get(foo).unwrap().roots, 0);
+    assert_eq!(snapshot.roots().count(), 0);
+}
+
+#[test]
+fn snapshot_mark_sweep() {
+    snapshot_graph(Arc::new(MarkSweep::<
+        &'static TypeInfo,
+        EventAggregator<Event>,
+    >::default()));
+}
+
+#[test]
+fn snapshot_incremental() {
+    snapshot_graph(Arc::new(Incremental::<
+        &'static TypeInfo,
+        EventAggregator<Event>,
+    >::default()));
+}
+
+#[test]
+fn snapshot_generational() {
+    snapshot_graph(Arc::new(Generational::<
+        &'static TypeInfo,
+        EventAggregator<Event>,
+    >::default()));
+}
+
+fn address(handle: GcPtr) -> String {
+    format!("{:p}", RawGcPtr::from(handle))
+}
+
+#[test]
+fn snapshot_export() {
+    let runtime = Arc::new(MarkSweep::<&'static TypeInfo, EventAggregator<Event>>::default());
+    let mut foo = GcRootPtr::new(&runtime, runtime.alloc(Foo::type_info()));
+    let bar = runtime.alloc(i64::type_info());
+    unsafe { (*foo.deref_mut::<Foo>()).bar = bar };
+
+    let snapshot = runtime.snapshot();
+    let foo_address = address(foo.handle());
+    let bar_address = address(bar);
+
+    let json = snapshot.to_json();
+    assert!(json.starts_with("{\"objects\":["));
+    assert!(json.contains(&format!(
+        "{{\"handle\":\"{}\",\"type\":\"Foo\",\"size\":{},\"roots\":1,\"references\":[\"{}\"]}}",
+        foo_address,
+        std::mem::size_of::<Foo>(),
+        bar_address
+    )));
+    assert!(json.contains(&format!(
+        "{{\"handle\":\"{}\",\"type\":\"i64\",\"size\":8,\"roots\":0,\"references\":[]}}",
+        bar_address
+    )));
+
+    let dot = snapshot.to_dot();
+    assert!(dot.starts_with("digraph heap {\n"));
+    assert!(dot.contains(&format!(
+        "\"{}\" [label=\"Foo\\n{} bytes\", style=bold];",
+        foo_address,
+        std::mem::size_of::<Foo>()
+    )));
+    assert!(dot.contains(&format!("\"{}\" [label=\"i64\\n8 bytes\"];", bar_address)));
+    assert!(dot.contains(&format!("\"{}\" -> \"{}\";", foo_address, bar_address)));
+}
@@ -5,6 +5,7 @@ use parking_lot::Mutex;
 use std::alloc::Layout;
 
 pub struct TypeInfo {
+    pub name: &'static str,
     pub size: usize,
     pub alignment: usize,
     pub tracer: Option<&'static fn(handle: GcPtr) -> Vec<GcPtr>>,
@@ -27,6 +28,7 @@ macro_rules! impl_primitive_types {
             paste::item! {
                 #[allow(non_upper_case_globals)]
                 static [<TYPE_ $ty>]: TypeInfo = TypeInfo {
+                    name: stringify!($ty),
                     size: std::mem::size_of::<$ty>(),
                     alignment: std::mem::align_of::<$ty>(),
                     tracer: None
@@ -56,6 +58,7 @@ macro_rules! impl_struct_ty {
 
             #[allow(non_upper_case_globals)]
             static [<TYPE_ $ty>]: TypeInfo = TypeInfo {
+                name: stringify!($ty),
                 size: std::mem::size_of::<$ty>(),
                 alignment: std::mem::align_of::<$ty>(),
                 tracer: Some(&([<trace_ $ty>] as fn(handle: GcPtr) -> Vec<GcPtr>))
@@ -84,6 +87,25 @@ impl mun_memory::TypeMemory for &'static TypeInfo {
     }
 }
 
+impl mun_memory::TypeDesc for &'static TypeInfo {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn guid(&self) -> &abi::Guid {
+        // NOTE: This contrived test does not need unique type identifiers
+        &abi::Guid([0; 16])
+    }
+
+    fn group(&self) -> mun_memory::TypeGroup {
+        if self.tracer.is_some() {
+            mun_memory::TypeGroup::Struct
+        } else {
+            mun_memory::TypeGroup::Primitive
+        }
+    }
+}
+
 impl gc::TypeTrace for &'static TypeInfo {
     type Trace = <Vec<GcPtr> as IntoIterator>::IntoIter;
 
@@ -0,0 +1,82 @@
+use super::util::{EventAggregator, HasTypeInfo, TypeInfo};
+use mun_memory::gc::{
+    Event, GcRootPtr, GcRuntime, GcWeakPtr, Generational, HasIndirectionPtr, Incremental, MarkSweep,
+};
+use std::{sync::Arc, time::Duration};
+
+#[test]
+fn weak_cleared_by_collect() {
+    let runtime = Arc::new(MarkSweep::<&'static TypeInfo, EventAggregator<Event>>::default());
+    let mut rooted = GcRootPtr::new(&runtime, runtime.alloc(i64::type_info()));
+    unsafe { *rooted.deref_mut::<i64>() = 42 };
+
+    let weak = GcWeakPtr::new(&runtime, rooted.handle());
+    assert!(!weak.is_collected());
+
+    // A weak reference does not keep the object alive
+    runtime.collect();
+    let upgraded = weak.upgrade().expect("object should be alive");
+    assert_eq!(unsafe { *upgraded.deref::<i64>() }, 42);
+    assert_eq!(upgraded.handle(), rooted.handle());
+
+    drop(rooted);
+    runtime.collect();
+
+    // The upgraded pointer is still rooted
+    assert!(weak.upgrade().is_some());
+    let handle = upgraded.unroot();
+    runtime.collect();
+
+    assert!(weak.is_collected());
+    assert!(weak.upgrade().is_none());
+
+    let events = runtime.observer().take_all();
+    assert!(events.contains(&Event::Deallocation(handle)));
+}
+
+#[test]
+fn weak_runtime_dropped() {
+    let runtime = Arc::new(MarkSweep::<&'static TypeInfo, EventAggregator<Event>>::default());
+    let handle = runtime.alloc(i64::type_info());
+    let weak = GcWeakPtr::new(&runtime, handle);
+
+    drop(runtime);
+    assert!(weak.is_collected());
+    assert!(weak.upgrade().is_none());
+}
+
+#[test]
+fn weak_upgrade_while_marking() {
+    let runtime = Arc::new(Incremental::<&'static TypeInfo, EventAggregator<Event>>::default());
+    let _rooted = GcRootPtr::new(&runtime, runtime.alloc(i64::type_info()));
+    let handle = runtime.alloc(i64::type_info());
+    let weak = GcWeakPtr::new(&runtime, handle);
+
+    // Upgrading an unmarked object while marking keeps it alive
+    assert!(!runtime.collect_step(Duration::from_secs(0)));
+    let upgraded = weak.upgrade().expect("object should be alive");
+    while !runtime.collect_step(Duration::from_secs(0)) {}
+    assert!(!weak.is_collected());
+
+    // Objects that are garbage once marking has finished can no longer be upgraded
+    drop(upgraded);
+    while !runtime.collect_step(Duration::from_secs(0)) {}
+    assert!(weak.is_collected());
+    assert!(weak.upgrade().is_none());
+}
+
+#[test]
+fn weak_cleared_by_minor_collect() {
+    let runtime = Arc::new(Generational::<&'static TypeInfo, EventAggregator<Event>>::default());
+    let rooted = GcRootPtr::new(&runtime, runtime.alloc(i64::type_info()));
+    let weak_rooted = GcWeakPtr::new(&runtime, rooted.handle());
+    let weak_garbage = GcWeakPtr::new(&runtime, runtime.alloc(i64::type_info()));
+
+    assert!(runtime.collect_minor());
+    assert!(!weak_rooted.is_collected());
+    assert!(weak_garbage.is_collected());
+
+    // Promoted objects can still be upgraded
+    let upgraded = weak_rooted.upgrade().expect("object should be alive");
+    assert_eq!(upgraded.handle(), rooted.handle());
+}
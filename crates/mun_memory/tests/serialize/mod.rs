@@ -0,0 +1,165 @@
+use crate::util::{alloc, null, Bar, Node, Pos, Runtime, TypeInfo, BAR, F64, I32, I64, NODE, POS};
+use mun_memory::{
+    gc::{GcPtr, GcRuntime, HasIndirectionPtr},
+    serialize::{deserialize, serialize, DeserializeError},
+};
+use once_cell::sync::Lazy;
+use std::sync::Arc;
+
+#[test]
+fn serialize_graph() {
+    let runtime = Arc::new(Runtime::default());
+    let bar = alloc(&runtime, &BAR, Bar { value: 42 });
+    let mut first = alloc(
+        &runtime,
+        &NODE,
+        Node {
+            id: 1,
+            pos: Pos { x: 1.0, y: 2.0 },
+            bar: bar.handle(),
+            next: null(),
+        },
+    );
+    let second = alloc(
+        &runtime,
+        &NODE,
+        Node {
+            id: 2,
+            pos: Pos { x: 3.0, y: 4.0 },
+            bar: bar.handle(),
+            next: first.handle(),
+        },
+    );
+    unsafe { (*first.deref_mut::<Node>()).next = second.handle() };
+
+    let bytes = serialize(runtime.as_ref(), &[first.handle()]);
+    let allocated_memory = runtime.stats().allocated_memory;
+
+    let roots = deserialize(&runtime, &[&*NODE], &bytes).unwrap();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(runtime.stats().allocated_memory, 2 * allocated_memory);
+
+    // The restored objects are copies, with the same references between them
+    let first_copy = unsafe { &*roots[0].deref::<Node>() };
+    assert_ne!(roots[0].handle(), first.handle());
+    assert_eq!(first_copy.id, 1);
+    assert_eq!(first_copy.pos.x, 1.0);
+    assert_eq!(first_copy.pos.y, 2.0);
+    assert_eq!(unsafe { (*first_copy.bar.deref::<Bar>()).value }, 42);
+
+    let second_copy = unsafe { &*first_copy.next.deref::<Node>() };
+    assert_eq!(second_copy.id, 2);
+    assert_eq!(second_copy.pos.x, 3.0);
+    assert_eq!(second_copy.pos.y, 4.0);
+    assert_eq!(second_copy.bar, first_copy.bar);
+    assert_eq!(second_copy.next, roots[0].handle());
+
+    // Only the roots keep the restored objects alive
+    drop((first, second, bar));
+    runtime.collect();
+    assert_eq!(runtime.stats().allocated_memory, allocated_memory);
+    drop(roots);
+    runtime.collect();
+    assert_eq!(runtime.stats().allocated_memory, 0);
+}
+
+#[repr(C)]
+struct FooV1 {
+    a: i32,
+    b: f64,
+    bar: GcPtr,
+}
+
+static FOO_V1: Lazy<TypeInfo> = Lazy::new(|| {
+    TypeInfo::new_struct::<FooV1>(
+        "Foo",
+        4,
+        false,
+        &[("a", &I32, 0), ("b", &F64, 8), ("bar", &BAR, 16)],
+    )
+});
+
+#[repr(C)]
+struct FooV2 {
+    c: f64,
+    a: i64,
+    b: f64,
+    bar: GcPtr,
+}
+
+static FOO_V2: Lazy<TypeInfo> = Lazy::new(|| {
+    TypeInfo::new_struct::<FooV2>(
+        "Foo",
+        5,
+        false,
+        &[
+            ("c", &F64, 0),
+            ("a", &I64, 8),
+            ("b", &F64, 16),
+            ("bar", &BAR, 24),
+        ],
+    )
+});
+
+#[test]
+fn deserialize_changed_types() {
+    let runtime = Arc::new(Runtime::default());
+    let bar = alloc(&runtime, &BAR, Bar { value: 7 });
+    let foo = alloc(
+        &runtime,
+        &FOO_V1,
+        FooV1 {
+            a: -5,
+            b: 2.5,
+            bar: bar.handle(),
+        },
+    );
+    let bytes = serialize(runtime.as_ref(), &[foo.handle()]);
+
+    // Fields are inserted, cast and copied the same way as when mapping memory
+    let runtime = Arc::new(Runtime::default());
+    let roots = deserialize(&runtime, &[&*FOO_V2, &*BAR], &bytes).unwrap();
+    assert!(runtime.ptr_type(roots[0].handle()) == &*FOO_V2);
+
+    let foo = unsafe { &*roots[0].deref::<FooV2>() };
+    assert_eq!(foo.c, 0.0);
+    assert_eq!(foo.a, -5);
+    assert_eq!(foo.b, 2.5);
+    assert_eq!(unsafe { (*foo.bar.deref::<Bar>()).value }, 7);
+}
+
+#[test]
+fn deserialize_deleted_root() {
+    let runtime = Arc::new(Runtime::default());
+    let bar = alloc(&runtime, &BAR, Bar { value: 7 });
+    let bytes = serialize(runtime.as_ref(), &[bar.handle()]);
+
+    let result = deserialize(&runtime, &[&*POS], &bytes);
+    assert_eq!(
+        result.err(),
+        Some(DeserializeError::DeletedType("Bar".to_owned()))
+    );
+}
+
+#[test]
+fn deserialize_invalid_data() {
+    let runtime = Arc::new(Runtime::default());
+    let bar = alloc(&runtime, &BAR, Bar { value: 7 });
+    let bytes = serialize(runtime.as_ref(), &[bar.handle()]);
+
+    assert_eq!(
+        deserialize(&runtime, &[&*BAR], b"NOPE").err(),
+        Some(DeserializeError::InvalidMagic)
+    );
+    assert_eq!(
+        deserialize(&runtime, &[&*BAR], &bytes[..bytes.len() - 1]).err(),
+        Some(DeserializeError::UnexpectedEnd)
+    );
+
+    let mut unsupported = bytes.clone();
+    unsupported[4] = 2;
+    assert_eq!(
+        deserialize(&runtime, &[&*BAR], &unsupported).err(),
+        Some(DeserializeError::UnsupportedVersion(2))
+    );
+}
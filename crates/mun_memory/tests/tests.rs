@@ -1,2 +1,4 @@
 mod diff;
 mod gc;
+mod serialize;
+mod util;
@@ -0,0 +1,178 @@
+#![allow(dead_code)]
+
+use abi::HasStaticTypeInfo;
+use mun_memory::{
+    gc::{self, Event, GcPtr, GcRootPtr, GcRuntime, HasIndirectionPtr, MarkSweep},
+    TypeDesc, TypeFields, TypeGroup, TypeMemory,
+};
+use once_cell::sync::Lazy;
+use std::{alloc::Layout, hash::Hash, sync::Arc};
+
+/// A type with fields, of which the field types are lazily initialized to allow recursive types.
+pub struct TypeInfo {
+    name: &'static str,
+    guid: abi::Guid,
+    layout: Layout,
+    group: TypeGroup,
+    is_stack_allocated: bool,
+    fields: Vec<(&'static str, &'static Lazy<TypeInfo>)>,
+    offsets: Vec<u16>,
+}
+
+impl TypeInfo {
+    pub fn primitive<T: HasStaticTypeInfo>() -> Self {
+        let type_info = T::type_info();
+        Self {
+            name: Box::leak(type_info.name().to_owned().into_boxed_str()),
+            guid: type_info.guid,
+            layout: Layout::new::<T>(),
+            group: TypeGroup::Primitive,
+            is_stack_allocated: true,
+            fields: Vec::new(),
+            offsets: Vec::new(),
+        }
+    }
+
+    pub fn new_struct<T>(
+        name: &'static str,
+        guid: u8,
+        is_stack_allocated: bool,
+        fields: &[(&'static str, &'static Lazy<TypeInfo>, u16)],
+    ) -> Self {
+        Self {
+            name,
+            guid: abi::Guid([guid; 16]),
+            layout: Layout::new::<T>(),
+            group: TypeGroup::Struct,
+            is_stack_allocated,
+            fields: fields.iter().map(|(name, ty, _)| (*name, *ty)).collect(),
+            offsets: fields.iter().map(|(_, _, offset)| *offset).collect(),
+        }
+    }
+}
+
+impl PartialEq for TypeInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.guid == other.guid
+    }
+}
+
+impl Eq for TypeInfo {}
+
+impl Hash for TypeInfo {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.guid.hash(state)
+    }
+}
+
+impl TypeDesc for &'static TypeInfo {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn guid(&self) -> &abi::Guid {
+        &self.guid
+    }
+
+    fn group(&self) -> TypeGroup {
+        self.group
+    }
+}
+
+impl TypeMemory for &'static TypeInfo {
+    fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    fn is_stack_allocated(&self) -> bool {
+        self.is_stack_allocated
+    }
+}
+
+impl TypeFields<&'static TypeInfo> for &'static TypeInfo {
+    fn fields(&self) -> Vec<(&str, &'static TypeInfo)> {
+        self.fields
+            .iter()
+            .map(|(name, ty)| (*name, &***ty))
+            .collect()
+    }
+
+    fn offsets(&self) -> &[u16] {
+        &self.offsets
+    }
+}
+
+impl gc::TypeTrace for &'static TypeInfo {
+    type Trace = <Vec<GcPtr> as IntoIterator>::IntoIter;
+
+    fn trace(&self, obj: GcPtr) -> Self::Trace {
+        self.fields
+            .iter()
+            .zip(self.offsets.iter())
+            .filter(|((_, ty), _)| ty.group == TypeGroup::Struct && !ty.is_stack_allocated)
+            .map(|(_, offset)| unsafe { *obj.deref::<u8>().add(*offset as usize).cast::<GcPtr>() })
+            .filter(|handle| !gc::RawGcPtr::from(*handle).is_null())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+pub type Runtime = MarkSweep<&'static TypeInfo, gc::NoopObserver<Event>>;
+
+pub static I32: Lazy<TypeInfo> = Lazy::new(TypeInfo::primitive::<i32>);
+pub static I64: Lazy<TypeInfo> = Lazy::new(TypeInfo::primitive::<i64>);
+pub static F64: Lazy<TypeInfo> = Lazy::new(TypeInfo::primitive::<f64>);
+
+#[repr(C)]
+pub struct Bar {
+    pub value: i64,
+}
+
+pub static BAR: Lazy<TypeInfo> =
+    Lazy::new(|| TypeInfo::new_struct::<Bar>("Bar", 1, false, &[("value", &I64, 0)]));
+
+#[repr(C)]
+pub struct Pos {
+    pub x: f64,
+    pub y: f64,
+}
+
+pub static POS: Lazy<TypeInfo> =
+    Lazy::new(|| TypeInfo::new_struct::<Pos>("Pos", 2, true, &[("x", &F64, 0), ("y", &F64, 8)]));
+
+#[repr(C)]
+pub struct Node {
+    pub id: i64,
+    pub pos: Pos,
+    pub bar: GcPtr,
+    pub next: GcPtr,
+}
+
+pub static NODE: Lazy<TypeInfo> = Lazy::new(|| {
+    TypeInfo::new_struct::<Node>(
+        "Node",
+        3,
+        false,
+        &[
+            ("id", &I64, 0),
+            ("pos", &POS, 8),
+            ("bar", &BAR, 24),
+            ("next", &NODE, 32),
+        ],
+    )
+});
+
+/// Returns a null handle.
+pub fn null() -> GcPtr {
+    GcPtr::from(std::ptr::null::<*mut std::ffi::c_void>())
+}
+
+pub fn alloc<T>(
+    runtime: &Arc<Runtime>,
+    ty: &'static TypeInfo,
+    value: T,
+) -> GcRootPtr<&'static TypeInfo, Runtime> {
+    let mut handle = GcRootPtr::new(runtime, runtime.alloc(ty));
+    unsafe { handle.deref_mut::<T>().write(value) };
+    handle
+}
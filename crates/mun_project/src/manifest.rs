@@ -9,6 +9,7 @@ mod toml;
 pub struct Manifest {
     package_id: PackageId,
     metadata: ManifestMetadata,
+    format: FormatOptions,
 }
 
 /// General metadata for a package.
@@ -17,6 +18,30 @@ pub struct ManifestMetadata {
     pub authors: Vec<String>,
 }
 
+/// Options that control how the source code of a package is formatted. Read from the `format`
+/// section of a mun.toml file.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct FormatOptions {
+    /// The number of spaces per indentation level
+    pub indent_width: usize,
+
+    /// Indent with tabs instead of spaces
+    pub hard_tabs: bool,
+
+    /// The maximum number of consecutive empty lines that are preserved
+    pub max_blank_lines: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            indent_width: 4,
+            hard_tabs: false,
+            max_blank_lines: 1,
+        }
+    }
+}
+
 /// Unique identifier of a package and version
 #[derive(PartialEq, Clone, Debug)]
 pub struct PackageId {
@@ -52,6 +77,11 @@ impl Manifest {
     pub fn metadata(&self) -> &ManifestMetadata {
         &self.metadata
     }
+
+    /// Returns the options used to format the source code of the package
+    pub fn format_options(&self) -> &FormatOptions {
+        &self.format
+    }
 }
 
 impl PackageId {
@@ -85,7 +115,7 @@ impl std::str::FromStr for Manifest {
 
 #[cfg(test)]
 mod tests {
-    use crate::Manifest;
+    use crate::{FormatOptions, Manifest};
     use std::str::FromStr;
 
     #[test]
@@ -107,5 +137,43 @@ mod tests {
         );
         assert_eq!(manifest.metadata().authors, vec!["Mun Team"]);
         assert_eq!(format!("{}", manifest.package_id()), "test v0.2.0");
+        assert_eq!(manifest.format_options(), &FormatOptions::default());
+    }
+
+    #[test]
+    fn parse_format() {
+        let manifest = Manifest::from_str(
+            r#"
+        [package]
+        name="test"
+        version="0.2.0"
+
+        [format]
+        hard-tabs = true
+        max-blank-lines = 2
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest.format_options(),
+            &FormatOptions {
+                indent_width: 4,
+                hard_tabs: true,
+                max_blank_lines: 2,
+            }
+        );
+
+        assert!(Manifest::from_str(
+            r#"
+        [package]
+        name="test"
+        version="0.2.0"
+
+        [format]
+        indent-width = 0
+        "#,
+        )
+        .is_err());
     }
 }
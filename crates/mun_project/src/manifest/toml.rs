@@ -1,4 +1,4 @@
-use super::{Manifest, ManifestMetadata, PackageId};
+use super::{FormatOptions, Manifest, ManifestMetadata, PackageId};
 use serde_derive::{Deserialize, Serialize};
 
 /// A manifest as specified in a mun.toml file.
@@ -6,6 +6,7 @@ use serde_derive::{Deserialize, Serialize};
 #[serde(rename_all = "kebab-case")]
 pub struct TomlManifest {
     package: TomlProject,
+    format: Option<TomlFormat>,
 }
 
 /// Represents the `package` section of a mun.toml file.
@@ -16,6 +17,15 @@ pub struct TomlProject {
     authors: Option<Vec<String>>,
 }
 
+/// Represents the `format` section of a mun.toml file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct TomlFormat {
+    indent_width: Option<usize>,
+    hard_tabs: Option<bool>,
+    max_blank_lines: Option<usize>,
+}
+
 impl TomlManifest {
     /// Convert this toml manifest into a "real" manifest.
     pub fn into_real_manifest(self) -> Result<Manifest, anyhow::Error> {
@@ -24,6 +34,21 @@ impl TomlManifest {
             anyhow::bail!("package name cannot be an empty string");
         }
 
+        let default_format = FormatOptions::default();
+        let format = match self.format {
+            Some(format) => FormatOptions {
+                indent_width: format.indent_width.unwrap_or(default_format.indent_width),
+                hard_tabs: format.hard_tabs.unwrap_or(default_format.hard_tabs),
+                max_blank_lines: format
+                    .max_blank_lines
+                    .unwrap_or(default_format.max_blank_lines),
+            },
+            None => default_format,
+        };
+        if format.indent_width == 0 {
+            anyhow::bail!("format.indent-width must be greater than zero");
+        }
+
         Ok(Manifest {
             package_id: PackageId {
                 name: name.to_owned(),
@@ -32,6 +57,7 @@ impl TomlManifest {
             metadata: ManifestMetadata {
                 authors: self.package.authors.unwrap_or_default(),
             },
+            format,
         })
     }
 }
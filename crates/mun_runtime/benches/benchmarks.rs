@@ -1,5 +1,5 @@
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
-use mun_runtime::StructRef;
+use mun_runtime::{GcKind, StructRef};
 use wasmer::Store;
 
 mod util;
@@ -293,11 +293,42 @@ pub fn set_struct_field_benchmark(c: &mut Criterion) {
     group.finish();
 }
 
+/// A benchmark method to measure the overhead of allocating and collecting short-lived structs for
+/// several garbage collectors.
+pub fn alloc_benchmark(c: &mut Criterion) {
+    // Perform setup (not part of the benchmark)
+    let mark_sweep = util::runtime_from_file_with_gc("alloc.mun", GcKind::MarkSweep);
+    let generational = util::runtime_from_file_with_gc("alloc.mun", GcKind::Generational);
+
+    let mut group = c.benchmark_group("alloc");
+
+    // Iterate over a number of samples
+    for i in [100i64, 200i64, 500i64, 1000i64].iter() {
+        // Allocate temporaries and collect them, like a frame would
+        group.bench_with_input(BenchmarkId::new("mark sweep", i), i, |b, i| {
+            b.iter(|| {
+                let _: f32 = mark_sweep.invoke("simulate", (*i,)).unwrap();
+                mark_sweep.gc_collect();
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("generational", i), i, |b, i| {
+            b.iter(|| {
+                let _: f32 = generational.invoke("simulate", (*i,)).unwrap();
+                generational.gc_collect_minor();
+            })
+        });
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     fibonacci_benchmark,
     empty_benchmark,
     get_struct_field_benchmark,
-    set_struct_field_benchmark
+    set_struct_field_benchmark,
+    alloc_benchmark
 );
 criterion_main!(benches);
@@ -1,6 +1,6 @@
 use compiler::{Config, DisplayColor, Driver, OptimizationLevel, PathOrInline};
 use mlua::Lua;
-use mun_runtime::Runtime;
+use mun_runtime::{GcKind, Runtime};
 use std::path::{Path, PathBuf};
 use wasmer::{Instance, Module, Store};
 
@@ -11,6 +11,10 @@ fn compute_resource_path<P: AsRef<Path>>(p: P) -> PathBuf {
 }
 
 pub fn runtime_from_file<P: AsRef<Path>>(p: P) -> Runtime {
+    runtime_from_file_with_gc(p, GcKind::default())
+}
+
+pub fn runtime_from_file_with_gc<P: AsRef<Path>>(p: P, gc_kind: GcKind) -> Runtime {
     let path = PathOrInline::Path(compute_resource_path(p));
     let (mut driver, file_id) = Driver::with_file(
         Config {
@@ -29,7 +33,7 @@ pub fn runtime_from_file<P: AsRef<Path>>(p: P) -> Runtime {
 
     let out_path = driver.assembly_output_path_from_file(file_id);
     driver.write_all_assemblies(false).unwrap();
-    let builder = Runtime::builder(out_path);
+    let builder = Runtime::builder(out_path).with_gc(gc_kind);
 
     // Safety: we compiled the code ourselves, so this is safe.
     unsafe { builder.finish() }.unwrap()
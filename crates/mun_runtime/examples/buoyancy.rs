@@ -15,7 +15,7 @@ fn main() {
     let builder = Runtime::builder(lib_dir).insert_fn("log_f32", log_f32 as extern "C" fn(f32));
 
     // Safety: we assume here that the library passed on the command-line is safe.
-    let mut runtime = unsafe { builder.finish() }.expect("Failed to spawn Runtime");
+    let runtime = unsafe { builder.finish() }.expect("Failed to spawn Runtime");
 
     let ctx = runtime
         .invoke::<StructRef, ()>("new_sim", ())
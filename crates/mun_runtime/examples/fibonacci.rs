@@ -16,10 +16,12 @@ fn main() {
     loop {
         let n: i64 = runtime
             .invoke("nth", ())
-            .unwrap_or_else(|e| e.wait(&mut runtime));
+            .or_else(|e| e.wait(&mut runtime))
+            .unwrap();
         let result: i64 = runtime
             .invoke("fibonacci", (n,))
-            .unwrap_or_else(|e| e.wait(&mut runtime));
+            .or_else(|e| e.wait(&mut runtime))
+            .unwrap();
         println!("fibonacci({}) = {}", n, result);
 
         // Safety: we assume the updates are safe.
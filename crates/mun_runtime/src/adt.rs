@@ -1,4 +1,4 @@
-use crate::garbage_collector::{GcPtr, GcRootPtr, UnsafeTypeInfo};
+use crate::garbage_collector::{GcPtr, GcRootPtr, GcWeakPtr, UnsafeTypeInfo};
 use crate::{
     marshal::Marshal,
     reflection::{
@@ -16,7 +16,7 @@ use std::{
 /// Represents a Mun struct pointer.
 #[repr(transparent)]
 #[derive(Clone)]
-pub struct RawStruct(GcPtr);
+pub struct RawStruct(pub(crate) GcPtr);
 
 impl RawStruct {
     /// Returns a pointer to the struct memory.
@@ -25,31 +25,55 @@ impl RawStruct {
     }
 }
 
-/// Type-agnostic wrapper for interoperability with a Mun struct. This is merely a reference to the
-/// Mun struct, that will be garbage collected unless it is rooted.
+impl From<RawStruct> for GcPtr {
+    fn from(raw: RawStruct) -> Self {
+        raw.0
+    }
+}
+
+/// Type-agnostic wrapper for interoperability with a Mun struct. The Mun struct is kept alive for
+/// as long as the `StructRef` exists, but to outlive the borrow of the `Runtime` it has to be
+/// rooted.
 #[derive(Clone)]
 pub struct StructRef<'s> {
-    raw: RawStruct,
+    handle: GcRootPtr,
     runtime: &'s Runtime,
 }
 
 impl<'s> StructRef<'s> {
     /// Creates a `StructRef` that wraps a raw Mun struct.
-    fn new<'r>(raw: RawStruct, runtime: &'r Runtime) -> Self
+    ///
+    /// The struct is rooted on construction, so it cannot be collected once the caller releases
+    /// its lock on the linked assemblies of the `Runtime`.
+    pub(crate) fn new<'r>(raw: RawStruct, runtime: &'r Runtime) -> Self
     where
         'r: 's,
     {
-        Self { raw, runtime }
+        Self {
+            handle: GcRootPtr::new(&runtime.gc, raw.0),
+            runtime,
+        }
     }
 
-    /// Consumes the `StructRef`, returning a raw Mun struct.
+    /// Consumes the `StructRef`, returning a raw Mun struct. The raw struct is no longer kept
+    /// alive by the `StructRef`.
     pub fn into_raw(self) -> RawStruct {
-        self.raw
+        RawStruct(self.handle.handle())
     }
 
     /// Roots the `StructRef`.
     pub fn root(self) -> RootedStruct {
-        RootedStruct::new(&self.runtime.gc, self.raw)
+        RootedStruct::from_root(self.handle)
+    }
+
+    /// Creates a weak reference to the `StructRef`, which does not keep it alive.
+    pub fn downgrade(&self) -> WeakStruct {
+        WeakStruct::new(&self.runtime.gc, self.handle.handle())
+    }
+
+    /// Returns a raw Mun struct, that is kept alive by the `StructRef`.
+    pub(crate) fn raw(&self) -> RawStruct {
+        RawStruct(self.handle.handle())
     }
 
     /// Returns the type information of the struct.
@@ -57,7 +81,7 @@ impl<'s> StructRef<'s> {
         // Safety: The type returned from `ptr_type` is guaranteed to live at least as long as
         // `Runtime` does not change. As the lifetime of `TypeInfo` is tied to the lifetime of
         // `Runtime`, this is safe.
-        unsafe { &*self.runtime.gc.ptr_type(self.raw.0).into_inner().as_ptr() }
+        unsafe { &*self.runtime.gc.ptr_type(self.raw().0).into_inner().as_ptr() }
     }
 
     ///
@@ -72,7 +96,15 @@ impl<'s> StructRef<'s> {
     ) -> NonNull<T> {
         let offset = *struct_info.field_offsets().get_unchecked(field_idx);
         // Safety: self.raw's memory pointer is never null
-        NonNull::new_unchecked(self.raw.get_ptr().add(offset as usize).cast::<T>() as *mut _)
+        NonNull::new_unchecked(self.raw().get_ptr().add(offset as usize).cast::<T>() as *mut _)
+    }
+
+    /// Notifies the garbage collector that a struct is about to be stored in a field of type
+    /// `field_type`.
+    fn write_barrier(&self, field_type: &abi::TypeInfo) {
+        if field_type.as_struct().is_some() {
+            self.runtime.gc.write_barrier(self.handle.handle());
+        }
     }
 
     /// Retrieves the value of the field corresponding to the specified `field_name`.
@@ -143,6 +175,7 @@ impl<'s> StructRef<'s> {
         let field_ptr =
             unsafe { self.field_offset_unchecked::<T::MunType>(struct_info, field_idx) };
         let old = Marshal::marshal_from_ptr(field_ptr, self.runtime, Some(field_type));
+        self.write_barrier(field_type);
         Marshal::marshal_to_ptr(value, field_ptr, Some(field_type));
         Ok(old)
     }
@@ -175,6 +208,7 @@ impl<'s> StructRef<'s> {
 
         let field_ptr =
             unsafe { self.field_offset_unchecked::<T::MunType>(struct_info, field_idx) };
+        self.write_barrier(field_type);
         Marshal::marshal_to_ptr(value, field_ptr, Some(field_type));
         Ok(())
     }
@@ -184,13 +218,20 @@ impl<'r> ArgumentReflection for StructRef<'r> {
     fn type_guid(&self, runtime: &Runtime) -> abi::Guid {
         // Safety: The type returned from `ptr_type` is guaranteed to live at least as long as
         // `Runtime` does not change. As we hold a shared reference to `Runtime`, this is safe.
-        unsafe { runtime.gc().ptr_type(self.raw.0).into_inner().as_ref().guid }
+        unsafe {
+            runtime
+                .gc()
+                .ptr_type(self.raw().0)
+                .into_inner()
+                .as_ref()
+                .guid
+        }
     }
 
     fn type_name(&self, runtime: &Runtime) -> &str {
         // Safety: The type returned from `ptr_type` is guaranteed to live at least as long as
         // `Runtime` does not change. As we hold a shared reference to `Runtime`, this is safe.
-        unsafe { (&*runtime.gc().ptr_type(self.raw.0).into_inner().as_ptr()).name() }
+        unsafe { (&*runtime.gc().ptr_type(self.raw().0).into_inner().as_ptr()).name() }
     }
 }
 
@@ -274,7 +315,7 @@ impl<'s> Marshal<'s> for StructRef<'s> {
         if struct_info.memory_kind == abi::StructMemoryKind::Value {
             let dest = ptr.cast::<u8>().as_ptr();
             let size = type_info.size_in_bytes();
-            unsafe { ptr::copy_nonoverlapping(value.into_raw().get_ptr(), dest, size as usize) };
+            unsafe { ptr::copy_nonoverlapping(value.raw().get_ptr(), dest, size as usize) };
         } else {
             unsafe { *ptr.as_mut() = value.into_raw() };
         }
@@ -289,14 +330,14 @@ pub struct RootedStruct {
 }
 
 impl RootedStruct {
-    /// Creates a `RootedStruct` that wraps a raw Mun struct.
-    fn new(gc: &Arc<GarbageCollector>, raw: RawStruct) -> Self {
-        // Safety: The type returned from `ptr_type` is guaranteed to live at least as long as
-        // `Runtime` does not change. As we hold a shared reference to `Runtime`, this is safe.
-        assert!(unsafe { gc.ptr_type(raw.0).into_inner().as_ref().data.is_struct() });
-        Self {
-            handle: GcRootPtr::new(gc, raw.0),
-        }
+    /// Creates a `RootedStruct` from a rooted handle to a Mun struct.
+    pub(crate) fn from_root(handle: GcRootPtr) -> Self {
+        Self { handle }
+    }
+
+    /// Returns the handle of the rooted Mun struct.
+    pub(crate) fn handle(&self) -> GcPtr {
+        self.handle.handle()
     }
 
     /// Converts the `RootedStruct` into a `StructRef`, using an external shared reference to a
@@ -306,3 +347,30 @@ impl RootedStruct {
         StructRef::new(RawStruct(self.handle.handle()), runtime)
     }
 }
+
+/// Type-agnostic weak reference to a Mun struct, which does not keep the struct alive. The struct
+/// can be accessed by upgrading the `WeakStruct` to a `RootedStruct`, until it has been garbage
+/// collected.
+#[derive(Clone)]
+pub struct WeakStruct {
+    handle: GcWeakPtr,
+}
+
+impl WeakStruct {
+    /// Creates a `WeakStruct` that references a Mun struct.
+    fn new(gc: &Arc<GarbageCollector>, handle: GcPtr) -> Self {
+        Self {
+            handle: GcWeakPtr::new(gc, handle),
+        }
+    }
+
+    /// Roots the referenced struct, returning `None` if it has been garbage collected.
+    pub fn upgrade(&self) -> Option<RootedStruct> {
+        self.handle.upgrade().map(|handle| RootedStruct { handle })
+    }
+
+    /// Returns whether the referenced struct has been garbage collected.
+    pub fn is_collected(&self) -> bool {
+        self.handle.is_collected()
+    }
+}
@@ -8,13 +8,41 @@ use libloader::{MunLibrary, TempLibrary};
 use log::error;
 use memory::mapping::{Mapping, MemoryMapper};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     ffi::c_void,
+    fmt,
     path::{Path, PathBuf},
     ptr::NonNull,
     sync::Arc,
 };
 
+/// Identifies a linked assembly by the namespace it was loaded into and the path of its library.
+/// The same library can be loaded into multiple namespaces.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct AssemblyId {
+    pub namespace: Option<String>,
+    pub library_path: PathBuf,
+}
+
+impl AssemblyId {
+    /// Constructs the `AssemblyId` of the library at `library_path` in the `namespace`.
+    pub fn new(namespace: Option<String>, library_path: PathBuf) -> Self {
+        Self {
+            namespace,
+            library_path,
+        }
+    }
+}
+
+impl fmt::Display for AssemblyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.namespace {
+            Some(namespace) => write!(f, "{}::{}", namespace, self.library_path.display()),
+            None => write!(f, "{}", self.library_path.display()),
+        }
+    }
+}
+
 /// An assembly is a hot reloadable compilation unit, consisting of one or more Mun modules.
 pub struct Assembly {
     library_path: PathBuf,
@@ -22,6 +50,7 @@ pub struct Assembly {
     legacy_libs: Vec<TempLibrary>,
     info: AssemblyInfo,
     allocator: Arc<GarbageCollector>,
+    namespace: Option<String>,
 }
 
 impl Assembly {
@@ -44,8 +73,41 @@ impl Assembly {
         library_path: &Path,
         gc: Arc<GarbageCollector>,
     ) -> Result<Self, anyhow::Error> {
-        let mut library = MunLibrary::new(library_path)?;
+        let library = MunLibrary::new(library_path)?;
+        Self::from_library(library_path, library, gc)
+    }
+
+    /// Loads an assembly and its information from the contents of a shared library stored in
+    /// `bytes`. The `library_path` identifies the assembly, but is not read from. The resulting
+    /// `Assembly` is ensured to be linkable.
+    ///
+    /// # Safety
+    ///
+    /// A munlib is simply a shared object. When a library is loaded, initialisation routines
+    /// contained within it are executed. For the purposes of safety, the execution of these
+    /// routines is conceptually the same calling an unknown foreign function and may impose
+    /// arbitrary requirements on the caller for the call to be sound.
+    ///
+    /// Additionally, the callers of this function must also ensure that execution of the
+    /// termination routines contained within the library is safe as well. These routines may be
+    /// executed when the library is unloaded.
+    ///
+    /// See [`libloading::Library::new`] for more information.
+    pub unsafe fn load_from_bytes(
+        library_path: &Path,
+        bytes: &[u8],
+        gc: Arc<GarbageCollector>,
+    ) -> Result<Self, anyhow::Error> {
+        let library = MunLibrary::from_bytes(bytes)?;
+        Self::from_library(library_path, library, gc)
+    }
 
+    /// Constructs an assembly from a loaded munlib `library`.
+    unsafe fn from_library(
+        library_path: &Path,
+        mut library: MunLibrary,
+        gc: Arc<GarbageCollector>,
+    ) -> Result<Self, anyhow::Error> {
         let version = library.get_abi_version();
         if abi::ABI_VERSION != version {
             return Err(anyhow::anyhow!(
@@ -65,6 +127,7 @@ impl Assembly {
             legacy_libs: Vec::new(),
             info,
             allocator: gc,
+            namespace: None,
         };
 
         Ok(assembly)
@@ -73,7 +136,13 @@ impl Assembly {
     /// Private implementation of runtime linking
     fn link_all_impl<'a>(
         dispatch_table: &mut DispatchTable,
-        to_link: impl Iterator<Item = (&'a mut *const c_void, &'a FunctionPrototype)>,
+        to_link: impl Iterator<
+            Item = (
+                &'a mut *const c_void,
+                &'a FunctionPrototype,
+                Option<&'a str>,
+            ),
+        >,
     ) -> anyhow::Result<()> {
         let mut to_link: Vec<_> = to_link.collect();
 
@@ -83,9 +152,10 @@ impl Assembly {
             let mut failed_to_link = Vec::new();
 
             // Try to link outstanding entries
-            for (dispatch_ptr, fn_prototype) in to_link.into_iter() {
+            for (dispatch_ptr, fn_prototype, namespace) in to_link.into_iter() {
                 // Ensure that the function is in the runtime dispatch table
-                if let Some(fn_def) = dispatch_table.get_fn(fn_prototype.name()) {
+                let fn_path = Assembly::resolve_fn_path(dispatch_table, namespace, fn_prototype);
+                if let Some(fn_def) = dispatch_table.get_fn(&fn_path) {
                     // Ensure that the function's signature is the same.
                     if fn_prototype.signature != fn_def.prototype.signature {
                         return Err(anyhow!("Failed to link: function '{}' is missing. A function with the same name does exist, but the signatures do not match (expected: {}, found: {}).", fn_prototype.name(), fn_prototype, fn_def.prototype));
@@ -94,7 +164,7 @@ impl Assembly {
                     *dispatch_ptr = fn_def.fn_ptr;
                     retry = true;
                 } else {
-                    failed_to_link.push((dispatch_ptr, fn_prototype));
+                    failed_to_link.push((dispatch_ptr, fn_prototype, namespace));
                 }
             }
 
@@ -103,7 +173,7 @@ impl Assembly {
         }
 
         if !to_link.is_empty() {
-            for (_, fn_prototype) in to_link {
+            for (_, fn_prototype, _) in to_link {
                 error!(
                     "Failed to link: function `{}` is missing.",
                     fn_prototype.name()
@@ -120,26 +190,37 @@ impl Assembly {
     /// the original `dispatch_table` intact, in case of linking errors.
     pub(super) fn link_all<'a>(
         assemblies: impl Iterator<Item = &'a mut Assembly>,
+        linked_assemblies: &HashMap<AssemblyId, Assembly>,
         dispatch_table: &DispatchTable,
     ) -> anyhow::Result<DispatchTable> {
         let assemblies: Vec<&'a mut _> = assemblies.collect();
 
+        for assembly in assemblies.iter() {
+            let others = assemblies
+                .iter()
+                .map(|other| &**other)
+                .chain(linked_assemblies.values());
+            assembly.check_type_conflicts(others)?;
+        }
+
         // Clone the dispatch table, such that we can roll back if linking fails
         let mut dispatch_table = dispatch_table.clone();
 
         // Insert all assemblies' functions into the dispatch table
         for assembly in assemblies.iter() {
-            for function in assembly.info().symbols.functions() {
-                dispatch_table.insert_fn(function.prototype.name(), function.clone());
-            }
+            let others = assemblies
+                .iter()
+                .map(|other| &**other)
+                .chain(linked_assemblies.values());
+            assembly.insert_fns(&mut dispatch_table, others)?;
         }
 
-        let to_link = assemblies
-            .into_iter()
-            .flat_map(|asm| asm.info.dispatch_table.iter_mut())
-            // Only take signatures into account that do *not* yet have a function pointer assigned
-            // by the compiler.
-            .filter(|(ptr, _)| ptr.is_null());
+        // Functions can only be resolved once all functions have been inserted
+        for assembly in assemblies.iter() {
+            assembly.add_fn_dependencies(&mut dispatch_table);
+        }
+
+        let to_link = assemblies.into_iter().flat_map(Assembly::unlinked_entries);
 
         Assembly::link_all_impl(&mut dispatch_table, to_link)?;
 
@@ -149,44 +230,68 @@ impl Assembly {
     /// Tries to link the `assemblies`, resulting in a new [`DispatchTable`] on success. This leaves
     /// the original `dispatch_table` intact, in case of linking errors.
     pub(super) fn relink_all(
-        unlinked_assemblies: &mut HashMap<PathBuf, Assembly>,
-        linked_assemblies: &mut HashMap<PathBuf, Assembly>,
+        unlinked_assemblies: &mut HashMap<AssemblyId, Assembly>,
+        linked_assemblies: &mut HashMap<AssemblyId, Assembly>,
         dispatch_table: &DispatchTable,
     ) -> anyhow::Result<DispatchTable> {
         let mut assemblies = unlinked_assemblies
             .iter_mut()
-            .map(|(old_path, asm)| {
-                let old_assembly = linked_assemblies.get(old_path);
+            .map(|(old_id, asm)| {
+                let old_assembly = linked_assemblies.get(old_id);
 
                 (asm, old_assembly)
             })
             .collect::<Vec<_>>();
 
+        // Checks whether the `linked` assembly is replaced by one of the new assemblies
+        let is_replaced = |linked: &Assembly| {
+            assemblies.iter().any(|(_, old_assembly)| {
+                old_assembly.map_or(false, |old_assembly| old_assembly.id() == linked.id())
+            })
+        };
+
+        for (new_assembly, _) in assemblies.iter() {
+            let others = assemblies.iter().map(|(other, _)| &**other).chain(
+                linked_assemblies
+                    .values()
+                    .filter(|linked| !is_replaced(linked)),
+            );
+            new_assembly.check_type_conflicts(others)?;
+        }
+
         // Clone the dispatch table, such that we can roll back if linking fails
         let mut dispatch_table = dispatch_table.clone();
 
-        // Remove the old assemblies' functions from the dispatch table
+        // Remove the old assemblies' functions and dependencies from the dispatch table
         for (_, old_assembly) in assemblies.iter() {
             if let Some(assembly) = old_assembly {
                 for function in assembly.info.symbols.functions() {
-                    dispatch_table.remove_fn(function.prototype.name());
+                    dispatch_table.remove_fn(assembly.fn_path(function.prototype.name()));
                 }
+
+                dispatch_table.remove_fn_dependencies(assembly.id().to_string());
             }
         }
 
         // Insert all assemblies' functions into the dispatch table
         for (new_assembly, _) in assemblies.iter() {
-            for function in new_assembly.info().symbols.functions() {
-                dispatch_table.insert_fn(function.prototype.name(), function.clone());
-            }
+            // The functions of replaced assemblies were already removed
+            let others = assemblies.iter().map(|(other, _)| &**other).chain(
+                linked_assemblies
+                    .values()
+                    .filter(|linked| !is_replaced(linked)),
+            );
+            new_assembly.insert_fns(&mut dispatch_table, others)?;
+        }
+
+        // Functions can only be resolved once all functions have been inserted
+        for (new_assembly, _) in assemblies.iter() {
+            new_assembly.add_fn_dependencies(&mut dispatch_table);
         }
 
         let to_link = assemblies
             .iter_mut()
-            .flat_map(|(asm, _)| asm.info.dispatch_table.iter_mut())
-            // Only take signatures into account that do *not* yet have a function pointer assigned
-            // by the compiler.
-            .filter(|(ptr, _)| ptr.is_null());
+            .flat_map(|(asm, _)| asm.unlinked_entries());
 
         Assembly::link_all_impl(&mut dispatch_table, to_link)?;
 
@@ -197,53 +302,27 @@ impl Assembly {
 
         let mut assemblies_to_keep = HashMap::new();
         for (old_assembly, new_assembly) in assemblies_to_map.iter() {
-            let old_types: Vec<UnsafeTypeInfo> = old_assembly
-                .info
-                .symbols
-                .types()
-                .iter()
-                .map(|ty| {
-                    // Safety: `ty` is a shared reference, so is guaranteed to not be `ptr::null()`.
-                    UnsafeTypeInfo::new(unsafe {
-                        NonNull::new_unchecked(*ty as *const abi::TypeInfo as *mut _)
-                    })
-                })
-                .collect();
-
-            let new_types: Vec<UnsafeTypeInfo> = new_assembly
-                .info
-                .symbols
-                .types()
-                .iter()
-                .map(|ty| {
-                    // Safety: `ty` is a shared reference, so is guaranteed to not be `ptr::null()`.
-                    UnsafeTypeInfo::new(unsafe {
-                        NonNull::new_unchecked(*ty as *const abi::TypeInfo as *mut _)
-                    })
-                })
-                .collect();
+            let old_types = old_assembly.types();
+            let new_types = new_assembly.types();
 
             let mapping = Mapping::new(&old_types, &new_types);
             let deleted_objects = old_assembly.allocator.map_memory(mapping);
 
             if !deleted_objects.is_empty() {
                 // Retain the previous assembly
-                assemblies_to_keep.insert(
-                    old_assembly.library_path().to_path_buf(),
-                    new_assembly.library_path().to_path_buf(),
-                );
+                assemblies_to_keep.insert(old_assembly.id(), new_assembly.id());
             }
         }
 
         let mut newly_linked = HashMap::new();
         std::mem::swap(unlinked_assemblies, &mut newly_linked);
 
-        for (old_path, mut new_assembly) in newly_linked.into_iter() {
+        for (old_id, mut new_assembly) in newly_linked.into_iter() {
             let mut old_assembly = linked_assemblies
-                .remove(&old_path)
+                .remove(&old_id)
                 .expect("Assembly must exist.");
 
-            let new_path = if let Some(new_path) = assemblies_to_keep.remove(&old_path) {
+            let new_id = if let Some(new_id) = assemblies_to_keep.remove(&old_id) {
                 // Retain all existing legacy libs
                 new_assembly
                     .legacy_libs
@@ -251,17 +330,248 @@ impl Assembly {
 
                 new_assembly.legacy_libs.push(old_assembly.into_library());
 
-                new_path
+                new_id
             } else {
-                new_assembly.library_path().to_path_buf()
+                new_assembly.id()
             };
 
-            linked_assemblies.insert(new_path, new_assembly);
+            linked_assemblies.insert(new_id, new_assembly);
         }
 
         Ok(dispatch_table)
     }
 
+    /// Tries to unlink the assembly with `id` from the `linked_assemblies`, resulting in a new
+    /// [`DispatchTable`] on success. This leaves the original `dispatch_table` intact, in case
+    /// another assembly still depends on the assembly's functions.
+    ///
+    /// Objects of the assembly's types that are still alive after collecting garbage are
+    /// converted to identical types of the remaining assemblies. If that is not possible, the
+    /// assembly cannot be unlinked.
+    pub(super) fn unlink(
+        id: &AssemblyId,
+        linked_assemblies: &HashMap<AssemblyId, Assembly>,
+        dispatch_table: &DispatchTable,
+    ) -> anyhow::Result<DispatchTable> {
+        let assembly = linked_assemblies.get(id).expect("Assembly must exist.");
+        let assembly_path = id.to_string();
+
+        // Ensure that no other assembly depends on the assembly's functions
+        for function in assembly.info.symbols.functions() {
+            let fn_path = assembly.fn_path(function.prototype.name());
+            let dependant = dispatch_table
+                .get_fn_dependants(&fn_path)
+                .find(|dependant| *dependant != assembly_path);
+            if let Some(dependant) = dependant {
+                return Err(anyhow!(
+                    "Failed to unlink: function `{}` is used by `{}`.",
+                    fn_path,
+                    dependant
+                ));
+            }
+        }
+
+        // Objects that are no longer referenced don't need to be converted
+        assembly.allocator.collect();
+
+        let old_types = assembly.types();
+        let new_types: HashSet<UnsafeTypeInfo> = linked_assemblies
+            .values()
+            .filter(|other| other.id() != *id)
+            .flat_map(Assembly::types)
+            .filter(|ty| old_types.contains(ty))
+            .collect();
+        let new_types: Vec<UnsafeTypeInfo> = new_types.into_iter().collect();
+
+        let mapping = Mapping::new(&old_types, &new_types);
+        let deleted_objects = assembly.allocator.map_memory(mapping);
+        if !deleted_objects.is_empty() {
+            return Err(anyhow!(
+                "Failed to unlink: {} object(s) of types that only exist in `{}` are still alive.",
+                deleted_objects.len(),
+                assembly_path
+            ));
+        }
+
+        // Clone the dispatch table, such that we only return a modified version on success
+        let mut dispatch_table = dispatch_table.clone();
+        for function in assembly.info.symbols.functions() {
+            dispatch_table.remove_fn(assembly.fn_path(function.prototype.name()));
+        }
+        dispatch_table.remove_fn_dependencies(&assembly_path);
+
+        Ok(dispatch_table)
+    }
+
+    /// Inserts the assembly's functions into the `dispatch_table`, failing if one of the
+    /// functions' paths is already in use. The conflicting function is looked up in the `others`
+    /// assemblies, to report where it originates from.
+    fn insert_fns<'a>(
+        &self,
+        dispatch_table: &mut DispatchTable,
+        mut others: impl Iterator<Item = &'a Assembly>,
+    ) -> anyhow::Result<()> {
+        for function in self.info.symbols.functions() {
+            let fn_path = self.fn_path(function.prototype.name());
+            if dispatch_table.get_fn(&fn_path).is_some() {
+                let origin =
+                    others
+                        .find(|other| {
+                            other.id() != self.id()
+                                && other.info.symbols.functions().iter().any(|other_fn| {
+                                    other.fn_path(other_fn.prototype.name()) == fn_path
+                                })
+                        })
+                        .map_or_else(
+                            || "the runtime".to_string(),
+                            |other| format!("`{}`", other.id()),
+                        );
+
+                return Err(anyhow!(
+                    "Failed to link: function `{}` of `{}` conflicts with a function of {}.",
+                    fn_path,
+                    self.id(),
+                    origin
+                ));
+            }
+
+            dispatch_table.insert_fn(fn_path, function.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Ensures that the structs of the assembly don't conflict with structs of the `others`
+    /// assemblies in other namespaces. The garbage collector identifies types by their name, so
+    /// structs with the same name must be identical across namespaces.
+    fn check_type_conflicts<'a>(
+        &self,
+        others: impl Iterator<Item = &'a Assembly>,
+    ) -> anyhow::Result<()> {
+        for other in others.filter(|other| other.namespace() != self.namespace()) {
+            for ty in self.info.symbols.types() {
+                let other_ty = other
+                    .info
+                    .symbols
+                    .types()
+                    .iter()
+                    .find(|other_ty| **other_ty == *ty);
+                if let Some(other_ty) = other_ty {
+                    if !is_identical_struct(ty, other_ty) {
+                        return Err(anyhow!(
+                            "Failed to link: struct `{}` of `{}` conflicts with a different struct of the same name in `{}`.",
+                            self.type_path(ty),
+                            self.id(),
+                            other.id()
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds the functions that the assembly imports from other assemblies as dependencies to the
+    /// `dispatch_table`.
+    fn add_fn_dependencies(&self, dispatch_table: &mut DispatchTable) {
+        let assembly_path = self.id().to_string();
+        for (_, fn_prototype) in self
+            .info
+            .dispatch_table
+            .iter()
+            // Only signatures that do *not* have a function pointer assigned by the compiler are
+            // imported.
+            .filter(|(ptr, _)| ptr.is_null())
+        {
+            let fn_path = Assembly::resolve_fn_path(dispatch_table, self.namespace(), fn_prototype);
+            dispatch_table.add_fn_dependency(&assembly_path, fn_path, fn_prototype.clone());
+        }
+    }
+
+    /// Returns the dispatch table entries of the assembly that need to be linked, together with
+    /// the assembly's namespace.
+    fn unlinked_entries(
+        &mut self,
+    ) -> impl Iterator<Item = (&mut *const c_void, &FunctionPrototype, Option<&str>)> {
+        let namespace = self.namespace.as_deref();
+        self.info
+            .dispatch_table
+            .iter_mut()
+            // Only take signatures into account that do *not* yet have a function pointer assigned
+            // by the compiler.
+            .filter(|(ptr, _)| ptr.is_null())
+            .map(move |(ptr, fn_prototype)| (ptr, fn_prototype, namespace))
+    }
+
+    /// Resolves the path of the function with `fn_prototype`, imported by an assembly in
+    /// `namespace`. Functions in the `namespace` take precedence over functions in the root
+    /// namespace, such as the runtime's functions.
+    fn resolve_fn_path(
+        dispatch_table: &DispatchTable,
+        namespace: Option<&str>,
+        fn_prototype: &FunctionPrototype,
+    ) -> String {
+        if let Some(namespace) = namespace {
+            let fn_path = format!("{}::{}", namespace, fn_prototype.name());
+            if dispatch_table.get_fn(&fn_path).is_some() {
+                return fn_path;
+            }
+        }
+
+        fn_prototype.name().to_string()
+    }
+
+    /// Returns the path of the assembly's function called `fn_name` in the runtime's dispatch
+    /// table.
+    pub fn fn_path(&self, fn_name: &str) -> String {
+        match self.namespace() {
+            Some(namespace) => format!("{}::{}", namespace, fn_name),
+            None => fn_name.to_string(),
+        }
+    }
+
+    /// Returns the path of the assembly's type `type_info`, e.g. for
+    /// [`Runtime::get_type_info`](crate::Runtime::get_type_info). Like functions, structs are
+    /// prefixed with the assembly's namespace, while primitive types are not.
+    pub fn type_path(&self, type_info: &abi::TypeInfo) -> String {
+        match (self.namespace(), type_info.as_struct()) {
+            (Some(namespace), Some(_)) => format!("{}::{}", namespace, type_info.name()),
+            _ => type_info.name().to_string(),
+        }
+    }
+
+    /// Returns the identifier of the assembly, which consists of its namespace and library path.
+    pub(crate) fn id(&self) -> AssemblyId {
+        AssemblyId::new(self.namespace.clone(), self.library_path.clone())
+    }
+
+    /// Returns the namespace of the assembly's functions and structs, if any.
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    /// Sets the namespace of the assembly's functions and structs. This must happen before the
+    /// assembly is linked.
+    pub(super) fn set_namespace(&mut self, namespace: Option<String>) {
+        self.namespace = namespace;
+    }
+
+    /// Returns the types defined in the assembly.
+    pub(crate) fn types(&self) -> Vec<UnsafeTypeInfo> {
+        self.info
+            .symbols
+            .types()
+            .iter()
+            .map(|ty| {
+                // Safety: `ty` is a shared reference, so is guaranteed to not be `ptr::null()`.
+                UnsafeTypeInfo::new(unsafe {
+                    NonNull::new_unchecked(*ty as *const abi::TypeInfo as *mut _)
+                })
+            })
+            .collect()
+    }
+
     /// Returns the assembly's information.
     pub fn info(&self) -> &AssemblyInfo {
         &self.info
@@ -276,4 +586,27 @@ impl Assembly {
     pub fn into_library(self) -> TempLibrary {
         self.library
     }
+
+    /// Converts the `Assembly` into the `TempLibrary`s of its previous versions, consuming the
+    /// input in the process.
+    pub fn into_legacy_libs(self) -> Vec<TempLibrary> {
+        self.legacy_libs
+    }
+}
+
+/// Returns whether the types `a` and `b` have the same layout. Only structs are compared field by
+/// field, because other types with the same name are always identical.
+fn is_identical_struct(a: &abi::TypeInfo, b: &abi::TypeInfo) -> bool {
+    match (a.as_struct(), b.as_struct()) {
+        (Some(a_struct), Some(b_struct)) => {
+            a.size_in_bytes() == b.size_in_bytes()
+                && a.alignment() == b.alignment()
+                && a_struct.memory_kind == b_struct.memory_kind
+                && a_struct.field_names().eq(b_struct.field_names())
+                && a_struct.field_types() == b_struct.field_types()
+                && a_struct.field_offsets() == b_struct.field_offsets()
+        }
+        (None, None) => true,
+        _ => false,
+    }
 }
@@ -1,8 +1,10 @@
 use memory::{
-    gc::{self, HasIndirectionPtr},
+    gc::{self, GcRuntime, GcSnapshot, HasIndirectionPtr, TypeTrace},
+    mapping::{Mapping, MemoryMapper},
     TypeGroup,
 };
-use std::{alloc::Layout, hash::Hash, ptr::NonNull};
+use parking_lot::Mutex;
+use std::{alloc::Layout, hash::Hash, ptr::NonNull, sync::Arc, time::Duration};
 
 /// `UnsafeTypeInfo` is a type that wraps a `NonNull<TypeInfo>` and indicates unsafe interior
 /// operations on the wrapped `TypeInfo`. The unsafety originates from uncertainty about the
@@ -145,8 +147,314 @@ impl gc::TypeTrace for UnsafeTypeInfo {
     }
 }
 
+/// The kinds of garbage collectors that the `Runtime` can use.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GcKind {
+    /// A mark-sweep collector that collects all garbage at once.
+    MarkSweep,
+    /// An incremental tri-color collector, that allows a collection to be split into steps using
+    /// [`Runtime::gc_collect_step`](crate::Runtime::gc_collect_step).
+    Incremental,
+    /// A generational collector, that bump-allocates objects in a nursery. Short-lived objects
+    /// are collected by minor collections, which only trace the nursery.
+    Generational,
+}
+
+impl Default for GcKind {
+    fn default() -> Self {
+        GcKind::MarkSweep
+    }
+}
+
+/// The garbage collector implementations that the `Runtime` can use.
+enum Collector {
+    /// A mark-sweep garbage collector
+    MarkSweep(gc::MarkSweep<UnsafeTypeInfo, gc::NoopObserver<gc::Event>>),
+    /// An incremental garbage collector
+    Incremental(gc::Incremental<UnsafeTypeInfo, gc::NoopObserver<gc::Event>>),
+    /// A generational garbage collector
+    Generational(gc::Generational<UnsafeTypeInfo, gc::NoopObserver<gc::Event>>),
+}
+
+/// The Mun invocations that are executing.
+#[derive(Default)]
+struct Invocations {
+    /// The number of invocations
+    count: usize,
+    /// Objects that might only be referenced from the stack of an invocation
+    pinned: Vec<GcPtr>,
+}
+
 /// Defines the garbage collector used by the `Runtime`.
-pub type GarbageCollector = gc::MarkSweep<UnsafeTypeInfo, gc::NoopObserver<gc::Event>>;
+///
+/// Objects that are only referenced from the stack of a Mun function are not rooted. While Mun
+/// functions are invoked, all objects that might be referenced from their stacks are pinned: the
+/// objects that were allocated or unrooted, and the objects that were referenced by overwritten
+/// fields. This allows garbage to be collected when an allocation exceeds the maximum heap size.
+pub struct GarbageCollector {
+    collector: Collector,
+    invocations: Mutex<Invocations>,
+}
+
+impl GarbageCollector {
+    /// Constructs a garbage collector of the specified `kind`, that allocates all memory using the
+    /// specified `allocator`.
+    pub fn new(kind: GcKind, allocator: Arc<dyn gc::Allocator>) -> Self {
+        let collector = match kind {
+            GcKind::MarkSweep => {
+                Collector::MarkSweep(gc::MarkSweep::with_allocator(Default::default(), allocator))
+            }
+            GcKind::Incremental => Collector::Incremental(gc::Incremental::with_allocator(
+                Default::default(),
+                allocator,
+            )),
+            GcKind::Generational => {
+                type Generational = gc::Generational<UnsafeTypeInfo, gc::NoopObserver<gc::Event>>;
+                Collector::Generational(Generational::with_allocator(
+                    Default::default(),
+                    Generational::DEFAULT_NURSERY_SIZE,
+                    allocator,
+                ))
+            }
+        };
+
+        Self {
+            collector,
+            invocations: Mutex::new(Invocations::default()),
+        }
+    }
+
+    /// Returns the kind of the garbage collector.
+    pub fn kind(&self) -> GcKind {
+        match &self.collector {
+            Collector::MarkSweep(_) => GcKind::MarkSweep,
+            Collector::Incremental(_) => GcKind::Incremental,
+            Collector::Generational(_) => GcKind::Generational,
+        }
+    }
+
+    /// Returns the garbage collector as a `GcRuntime`.
+    fn as_runtime(&self) -> &dyn GcRuntime<UnsafeTypeInfo> {
+        match &self.collector {
+            Collector::MarkSweep(gc) => gc,
+            Collector::Incremental(gc) => gc,
+            Collector::Generational(gc) => gc,
+        }
+    }
+
+    /// Collects all memory that is no longer referenced by rooted objects. Returns `true` if memory
+    /// was reclaimed, `false` otherwise.
+    pub fn collect(&self) -> bool {
+        match &self.collector {
+            Collector::MarkSweep(gc) => gc.collect(),
+            Collector::Incremental(gc) => gc.collect(),
+            Collector::Generational(gc) => gc.collect(),
+        }
+    }
+
+    /// Collects young objects that are no longer referenced. Returns `true` if memory was
+    /// reclaimed, `false` otherwise. Only a generational collector distinguishes young objects,
+    /// other collectors perform a full collection.
+    pub fn collect_minor(&self) -> bool {
+        match &self.collector {
+            Collector::Generational(gc) => gc.collect_minor(),
+            _ => self.collect(),
+        }
+    }
+
+    /// Performs a step of a collection cycle that spends approximately `budget`. Returns `true` if
+    /// the step completed the collection cycle, `false` otherwise. A mark-sweep collector always
+    /// performs a full collection.
+    pub fn collect_step(&self, budget: Duration) -> bool {
+        match &self.collector {
+            Collector::MarkSweep(gc) => {
+                gc.collect();
+                true
+            }
+            Collector::Incremental(gc) => gc.collect_step(budget),
+            Collector::Generational(gc) => {
+                gc.collect();
+                true
+            }
+        }
+    }
+
+    /// Returns the thresholds that determine when a collection is needed.
+    pub fn thresholds(&self) -> gc::CollectionThresholds {
+        match &self.collector {
+            Collector::MarkSweep(gc) => gc.thresholds(),
+            Collector::Incremental(gc) => gc.thresholds(),
+            Collector::Generational(gc) => gc.thresholds(),
+        }
+    }
+
+    /// Sets the thresholds that determine when a collection is needed.
+    pub fn set_thresholds(&self, thresholds: gc::CollectionThresholds) {
+        match &self.collector {
+            Collector::MarkSweep(gc) => gc.set_thresholds(thresholds),
+            Collector::Incremental(gc) => gc.set_thresholds(thresholds),
+            Collector::Generational(gc) => gc.set_thresholds(thresholds),
+        }
+    }
+
+    /// Sets the maximum number of bytes that can be allocated through [`GcRuntime::try_alloc`].
+    /// `None` removes the limit.
+    pub fn set_max_heap_size(&self, max_heap_size: Option<usize>) {
+        match &self.collector {
+            Collector::MarkSweep(gc) => gc.set_max_heap_size(max_heap_size),
+            Collector::Incremental(gc) => gc.set_max_heap_size(max_heap_size),
+            Collector::Generational(gc) => gc.set_max_heap_size(max_heap_size),
+        }
+    }
+
+    /// Sets the finalizer that is invoked for every object right before it is deallocated.
+    pub fn set_finalizer(&self, finalizer: gc::Finalizer<UnsafeTypeInfo>) {
+        match &self.collector {
+            Collector::MarkSweep(gc) => gc.set_finalizer(finalizer),
+            Collector::Incremental(gc) => gc.set_finalizer(finalizer),
+            Collector::Generational(gc) => gc.set_finalizer(finalizer),
+        }
+    }
+
+    /// Returns whether the allocations since the previous collection exceeded the thresholds or,
+    /// for a generational collector, whether its nursery is full.
+    pub fn needs_collection(&self) -> bool {
+        match &self.collector {
+            Collector::MarkSweep(gc) => gc.needs_collection(),
+            Collector::Incremental(gc) => gc.needs_collection(),
+            Collector::Generational(gc) => gc.needs_collection() || gc.needs_minor_collection(),
+        }
+    }
+
+    /// Collects the garbage that [`GarbageCollector::needs_collection`] determined needs to be
+    /// collected. A generational collector only performs a minor collection, unless the
+    /// thresholds were exceeded. Returns `true` if memory was reclaimed, `false` otherwise.
+    pub fn collect_needed(&self) -> bool {
+        match &self.collector {
+            Collector::Generational(gc) if !gc.needs_collection() => gc.collect_minor(),
+            _ => self.collect(),
+        }
+    }
+
+    /// Notifies the garbage collector that a Mun invocation started. Until all invocations have
+    /// ended, objects that might be referenced from their stacks are pinned.
+    pub fn begin_invocation(&self) {
+        self.invocations.lock().count += 1;
+    }
+
+    /// Notifies the garbage collector that a Mun invocation ended.
+    pub fn end_invocation(&self) {
+        let mut invocations = self.invocations.lock();
+        invocations.count -= 1;
+        if invocations.count == 0 {
+            invocations.pinned.clear();
+        }
+    }
+
+    /// Collects all memory that is no longer referenced by rooted or pinned objects, while Mun
+    /// functions are being invoked. Returns `true` if memory was reclaimed, `false` otherwise.
+    ///
+    /// Garbage can only be collected if all invocations are executing on the current thread, i.e.
+    /// if `thread_invocations` equals the number of invocations, as the stacks of other threads
+    /// might reference objects that are not yet pinned. A generational collector never collects
+    /// during an invocation, because it moves young objects that are referenced from the stack.
+    pub fn collect_during_invocation(&self, thread_invocations: usize) -> bool {
+        if let Collector::Generational(_) = self.collector {
+            return false;
+        }
+
+        // Hold the lock during the collection, to prevent other threads from starting invocations
+        let invocations = self.invocations.lock();
+        if invocations.count == 0 || invocations.count != thread_invocations {
+            return false;
+        }
+
+        let gc = self.as_runtime();
+        invocations.pinned.iter().for_each(|obj| gc.root(*obj));
+        let reclaimed = self.collect();
+        invocations.pinned.iter().for_each(|obj| gc.unroot(*obj));
+        reclaimed
+    }
+
+    /// Pins the specified object if Mun functions are being invoked.
+    fn pin(&self, obj: GcPtr) {
+        let mut invocations = self.invocations.lock();
+        if invocations.count > 0 {
+            invocations.pinned.push(obj);
+        }
+    }
+}
+
+impl GcRuntime<UnsafeTypeInfo> for GarbageCollector {
+    fn alloc(&self, ty: UnsafeTypeInfo) -> GcPtr {
+        let obj = self.as_runtime().alloc(ty);
+        self.pin(obj);
+        obj
+    }
+
+    fn try_alloc(&self, ty: UnsafeTypeInfo) -> Result<GcPtr, gc::HeapLimitExceeded> {
+        let obj = self.as_runtime().try_alloc(ty)?;
+        self.pin(obj);
+        Ok(obj)
+    }
+
+    fn ptr_type(&self, obj: GcPtr) -> UnsafeTypeInfo {
+        self.as_runtime().ptr_type(obj)
+    }
+
+    fn root(&self, obj: GcPtr) {
+        self.as_runtime().root(obj)
+    }
+
+    fn unroot(&self, obj: GcPtr) {
+        // An unrooted object, e.g. an argument, might still be referenced by an invocation
+        self.pin(obj);
+        self.as_runtime().unroot(obj)
+    }
+
+    fn downgrade(&self, obj: GcPtr) -> gc::WeakRef {
+        self.as_runtime().downgrade(obj)
+    }
+
+    fn upgrade(&self, weak: &gc::WeakRef) -> Option<GcPtr> {
+        self.as_runtime().upgrade(weak)
+    }
+
+    fn write_barrier(&self, obj: GcPtr) {
+        // The write barrier is invoked before a field of `obj` is overwritten, so the objects that
+        // it references might still be referenced by an invocation
+        self.ptr_type(obj)
+            .trace(obj)
+            .for_each(|field| self.pin(field));
+        self.as_runtime().write_barrier(obj)
+    }
+
+    fn stats(&self) -> gc::Stats {
+        self.as_runtime().stats()
+    }
+}
+
+impl GcSnapshot<UnsafeTypeInfo> for GarbageCollector {
+    fn snapshot(&self) -> gc::HeapSnapshot {
+        match &self.collector {
+            Collector::MarkSweep(gc) => gc.snapshot(),
+            Collector::Incremental(gc) => gc.snapshot(),
+            Collector::Generational(gc) => gc.snapshot(),
+        }
+    }
+}
+
+impl MemoryMapper<UnsafeTypeInfo> for GarbageCollector {
+    fn map_memory(&self, mapping: Mapping<UnsafeTypeInfo, UnsafeTypeInfo>) -> Vec<GcPtr> {
+        match &self.collector {
+            Collector::MarkSweep(gc) => gc.map_memory(mapping),
+            Collector::Incremental(gc) => gc.map_memory(mapping),
+            Collector::Generational(gc) => gc.map_memory(mapping),
+        }
+    }
+}
 
 pub use gc::GcPtr;
 pub type GcRootPtr = gc::GcRootPtr<UnsafeTypeInfo, GarbageCollector>;
+pub type GcWeakPtr = gc::GcWeakPtr<UnsafeTypeInfo, GarbageCollector>;
@@ -10,35 +10,42 @@ mod garbage_collector;
 mod adt;
 mod marshal;
 mod reflection;
+mod watcher;
 
 use anyhow::Result;
+use assembly::AssemblyId;
 use garbage_collector::GarbageCollector;
-use log::{debug, error, info};
-use memory::gc::{self, GcRuntime};
-use mun_project::LOCKFILE_NAME;
-use notify::{RawEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use libloader::TempLibrary;
+use log::{error, info};
+use memory::gc::{self, GcRuntime, GcSnapshot};
+use parking_lot::{MappedRwLockReadGuard, Mutex, RwLock, RwLockReadGuard};
 use rustc_hash::FxHashMap;
 use std::{
+    cell::Cell,
     collections::{HashMap, VecDeque},
     ffi, io, mem,
     path::{Path, PathBuf},
-    ptr::NonNull,
+    ptr::{self, NonNull},
     string::ToString,
-    sync::{
-        mpsc::{channel, Receiver},
-        Arc,
-    },
+    sync::Arc,
+    time::Duration,
 };
 
 pub use crate::{
-    adt::{RootedStruct, StructRef},
+    adt::{RawStruct, RootedStruct, StructRef, WeakStruct},
     assembly::Assembly,
-    garbage_collector::UnsafeTypeInfo,
+    garbage_collector::{GcKind, UnsafeTypeInfo},
     marshal::Marshal,
     reflection::{ArgumentReflection, ReturnTypeReflection},
+    watcher::{AssemblyChange, AssemblyWatcher, ManualWatcher, NotifyWatcher},
 };
 use abi::FunctionSignature;
 pub use abi::IntoFunctionDefinition;
+pub use memory::gc::{
+    Allocator, CollectionThresholds, GlobalAllocator, HeapLimitExceeded, HeapSnapshot,
+    ObjectSnapshot,
+};
+pub use memory::serialize::DeserializeError;
 use std::ffi::c_void;
 use std::fmt::{Debug, Display, Formatter};
 
@@ -46,10 +53,39 @@ use std::fmt::{Debug, Display, Formatter};
 pub struct RuntimeOptions {
     /// Path to the entry point library
     pub library_path: PathBuf,
+    /// The contents of the entry point library. If `Some`, the library is loaded from memory
+    /// instead of from `library_path`, which is then only used to identify the assembly and to
+    /// locate its dependencies. Dependencies are always loaded from disk.
+    pub library_bytes: Option<Vec<u8>>,
+    /// Namespaces and paths of additional, independently compiled entry point libraries. Their
+    /// functions are accessible as `<namespace>::<function path>`.
+    pub packages: Vec<(String, PathBuf)>,
     /// Custom user injected functions
     pub user_functions: Vec<(abi::FunctionDefinition, abi::FunctionDefinitionStorage)>,
+    /// The watcher used to detect which assemblies need to be reloaded. If `None`, a
+    /// [`NotifyWatcher`] is used.
+    pub watcher: Option<Box<dyn AssemblyWatcher>>,
+    /// The thresholds that determine when garbage is automatically collected
+    pub gc_thresholds: CollectionThresholds,
+    /// The kind of garbage collector to use
+    pub gc_kind: GcKind,
+    /// The allocator that provides the memory of garbage collected structs. If `None`, a
+    /// [`GlobalAllocator`] is used.
+    pub gc_allocator: Option<Arc<dyn Allocator>>,
+    /// The maximum number of bytes that garbage collected structs can occupy. If `None`, the
+    /// heap size is unlimited.
+    pub gc_max_heap_size: Option<usize>,
+    /// The finalizers that are invoked right before structs of the named types are deallocated
+    pub finalizers: Vec<(String, StructFinalizer)>,
 }
 
+/// A callback that is invoked right before a struct is deallocated, with the struct and its type.
+/// This allows the host to release resources that are referenced by the struct.
+///
+/// Finalizers are invoked while the garbage collector is collecting, so they must not call back
+/// into the [`Runtime`].
+pub type StructFinalizer = Box<dyn Fn(RawStruct, &abi::TypeInfo) + Send + Sync>;
+
 /// Retrieve the allocator using the provided handle.
 ///
 /// # Safety
@@ -70,12 +106,83 @@ extern "C" fn new(
     let allocator = unsafe { get_allocator(alloc_handle) };
     // Safety: the Mun Compiler guarantees that `new` is never called with `ptr::null()`.
     let type_info = UnsafeTypeInfo::new(unsafe { NonNull::new_unchecked(type_info as *mut _) });
-    let handle = allocator.alloc(type_info);
+    let handle = allocator
+        .try_alloc(type_info)
+        .or_else(|e| {
+            // Make room by collecting the garbage of the invocations that are executing
+            if allocator.collect_during_invocation(INVOCATION_DEPTH.with(Cell::get)) {
+                allocator.try_alloc(type_info)
+            } else {
+                Err(e)
+            }
+        })
+        .map_err(|e| {
+            // Mun code cannot be aborted, because unwinding through its stack frames is undefined
+            // behavior. Instead, the Mun Compiler returns early from the calling function when
+            // `ptr::null()` is returned, and the error is reported once the invocation has
+            // finished.
+            HEAP_LIMIT_EXCEEDED.with(|error| {
+                if error.get().is_none() {
+                    error.set(Some(e));
+                }
+            });
+        });
+
+    // Prevent destruction of the allocator
+    mem::forget(allocator);
+
+    handle.map_or(ptr::null(), Into::into)
+}
+
+extern "C" fn heap_limit_exceeded(_alloc_handle: *mut ffi::c_void) -> bool {
+    // Mun functions that call a function that exceeded the maximum heap size, return early as well
+    HEAP_LIMIT_EXCEEDED.with(|error| error.get().is_some())
+}
+
+thread_local! {
+    /// The first allocation that exceeded the maximum heap size, since the Mun invocation that is
+    /// executing on this thread started.
+    static HEAP_LIMIT_EXCEEDED: Cell<Option<gc::HeapLimitExceeded>> = const { Cell::new(None) };
+
+    /// The number of Mun invocations through [`Runtime::invoke`] that are executing on this
+    /// thread.
+    static INVOCATION_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+extern "C" fn write_barrier(obj: *const *mut ffi::c_void, alloc_handle: *mut ffi::c_void) {
+    // Safety: `write_barrier` is only called from within Mun assemblies' core logic, so we are
+    // guaranteed that the `Runtime` and its `GarbageCollector` still exist if this function is
+    // called, and will continue to do so for the duration of this function.
+    let allocator = unsafe { get_allocator(alloc_handle) };
+    allocator.write_barrier(obj.into());
 
     // Prevent destruction of the allocator
     mem::forget(allocator);
+}
+
+/// Returns the canonical form of the `library_path` of an assembly. The library of an assembly
+/// that was loaded from memory doesn't necessarily exist on disk, in which case only its parent
+/// directory is canonicalized. Its dependencies are located relative to that directory.
+pub(crate) fn canonicalize_library_path(library_path: &Path) -> io::Result<PathBuf> {
+    if let Ok(library_path) = library_path.canonicalize() {
+        return Ok(library_path);
+    }
 
-    handle.into()
+    let file_name = library_path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Invalid library path: `{}`.",
+                library_path.to_string_lossy()
+            ),
+        )
+    })?;
+    let parent = match library_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.canonicalize()?,
+        _ => std::env::current_dir()?.canonicalize()?,
+    };
+
+    Ok(parent.join(file_name))
 }
 
 /// A builder for the [`Runtime`].
@@ -89,7 +196,15 @@ impl RuntimeBuilder {
         Self {
             options: RuntimeOptions {
                 library_path: library_path.into(),
+                library_bytes: None,
+                packages: Vec::new(),
                 user_functions: Default::default(),
+                watcher: None,
+                gc_thresholds: CollectionThresholds::default(),
+                gc_kind: GcKind::default(),
+                gc_allocator: None,
+                gc_max_heap_size: None,
+                finalizers: Vec::new(),
             },
         }
     }
@@ -104,6 +219,76 @@ impl RuntimeBuilder {
         self
     }
 
+    /// Loads the entry point library from `bytes`, instead of from the library path. The library
+    /// path is still used to identify the assembly and to locate its dependencies, which are
+    /// loaded from disk.
+    ///
+    /// The in-memory library is not watched for changes. It can only be reloaded from a library on
+    /// disk, using [`Runtime::reload_from`] or a [`ManualWatcher`].
+    pub fn with_bytes<B: Into<Vec<u8>>>(mut self, bytes: B) -> Self {
+        self.options.library_bytes = Some(bytes.into());
+        self
+    }
+
+    /// Adds the independently compiled entry point library at `library_path`, whose functions are
+    /// accessible as `<namespace>::<function path>`.
+    pub fn with_package<S: Into<String>, P: Into<PathBuf>>(
+        mut self,
+        namespace: S,
+        library_path: P,
+    ) -> Self {
+        self.options
+            .packages
+            .push((namespace.into(), library_path.into()));
+        self
+    }
+
+    /// Sets the watcher used to detect which assemblies need to be reloaded.
+    pub fn with_watcher<W: AssemblyWatcher + 'static>(mut self, watcher: W) -> Self {
+        self.options.watcher = Some(Box::new(watcher));
+        self
+    }
+
+    /// Sets the thresholds that determine when garbage is automatically collected. See
+    /// [`Runtime::update`] for more information.
+    pub fn with_gc_thresholds(mut self, thresholds: CollectionThresholds) -> Self {
+        self.options.gc_thresholds = thresholds;
+        self
+    }
+
+    /// Sets the kind of garbage collector to use.
+    pub fn with_gc(mut self, kind: GcKind) -> Self {
+        self.options.gc_kind = kind;
+        self
+    }
+
+    /// Sets the allocator that provides the memory of garbage collected structs.
+    pub fn with_allocator<A: Allocator + 'static>(mut self, allocator: A) -> Self {
+        self.options.gc_allocator = Some(Arc::new(allocator));
+        self
+    }
+
+    /// Sets the maximum number of bytes that garbage collected structs can occupy. See
+    /// [`Runtime::set_gc_max_heap_size`] for more information.
+    pub fn with_gc_max_heap_size(mut self, max_heap_size: usize) -> Self {
+        self.options.gc_max_heap_size = Some(max_heap_size);
+        self
+    }
+
+    /// Registers a `finalizer` that is invoked right before a struct of the type with the
+    /// specified name is deallocated. A finalizer that was previously registered for the same type
+    /// is replaced.
+    pub fn with_finalizer<S, F>(mut self, type_name: S, finalizer: F) -> Self
+    where
+        S: Into<String>,
+        F: Fn(RawStruct, &abi::TypeInfo) + Send + Sync + 'static,
+    {
+        self.options
+            .finalizers
+            .push((type_name.into(), Box::new(finalizer)));
+        self
+    }
+
     /// Constructs a [`Runtime`] with the builder's options.
     ///
     /// # Safety
@@ -190,10 +375,53 @@ impl DispatchTable {
             }
         }
     }
+
+    /// Removes all dependencies of `assembly_path`.
+    pub fn remove_fn_dependencies<S: AsRef<str>>(&mut self, assembly_path: S) {
+        self.fn_dependencies.remove(assembly_path.as_ref());
+    }
+
+    /// Returns the paths of all assemblies that depend on `fn_path`.
+    pub fn get_fn_dependants<'a>(&'a self, fn_path: &'a str) -> impl Iterator<Item = &'a str> {
+        self.fn_dependencies
+            .iter()
+            .filter(move |(_, dependencies)| dependencies.contains_key(fn_path))
+            .map(|(assembly_path, _)| assembly_path.as_str())
+    }
+}
+
+/// The assemblies of a [`Runtime`] and the dispatch table they are linked with.
+///
+/// Both are guarded by a single lock, which is read-locked for the duration of every invocation.
+/// This ensures that relinking waits for all in-flight invocations to finish.
+struct LinkedAssemblies {
+    assemblies: HashMap<AssemblyId, Assembly>,
+    dispatch_table: DispatchTable,
+    /// Previous versions of removed assemblies, whose types might still be used by allocated
+    /// objects.
+    legacy_libs: Vec<TempLibrary>,
+}
+
+impl LinkedAssemblies {
+    /// Returns the identifiers of the assemblies that were loaded from `library_path`, one for
+    /// every namespace that the library was loaded into.
+    fn assembly_ids(&self, library_path: &Path) -> Vec<AssemblyId> {
+        self.assemblies
+            .keys()
+            .filter(|id| id.library_path == library_path)
+            .cloned()
+            .collect()
+    }
 }
 
 /// A runtime for the Mun language.
 ///
+/// # Multithreading
+///
+/// A `Runtime` can be shared between threads, allowing Mun functions to be invoked concurrently.
+/// Reloading assemblies through [`Runtime::update`] and collecting garbage through
+/// [`Runtime::gc_collect`] wait until all in-flight invocations have finished.
+///
 /// # Logging
 ///
 /// The runtime uses [log] as a logging facade, but does not install a logger. To produce log
@@ -202,13 +430,10 @@ impl DispatchTable {
 /// [log]: https://docs.rs/log
 /// [log-impl]: https://docs.rs/log/0.4.13/log/#available-logging-implementations
 pub struct Runtime {
-    assemblies: HashMap<PathBuf, Assembly>,
-    /// Assemblies that have changed and thus need to be relinked. Maps the old to the (potentially) new path.
-    assemblies_to_relink: VecDeque<(PathBuf, PathBuf)>,
-    dispatch_table: DispatchTable,
-    watcher: RecommendedWatcher,
-    watcher_rx: Receiver<RawEvent>,
-    renamed_files: HashMap<u32, PathBuf>,
+    linked: RwLock<LinkedAssemblies>,
+    /// The watcher is locked while relinking, which ensures that only one relink takes place at
+    /// a time.
+    watcher: Mutex<Box<dyn AssemblyWatcher>>,
     gc: Arc<GarbageCollector>,
     _user_functions: Vec<abi::FunctionDefinitionStorage>,
 }
@@ -220,8 +445,8 @@ impl Runtime {
     }
 
     /// Constructs a new `Runtime` that loads the library at `library_path` and its
-    /// dependencies. The `Runtime` contains a file watcher that is triggered with an interval
-    /// of `dur`.
+    /// dependencies. The `Runtime` uses the `watcher` to detect which assemblies need to be
+    /// reloaded.
     ///
     /// # Safety
     ///
@@ -236,8 +461,6 @@ impl Runtime {
     ///
     /// See [`Assembly::load`] for more information.
     pub unsafe fn new(mut options: RuntimeOptions) -> anyhow::Result<Runtime> {
-        let (tx, rx) = channel();
-
         let mut dispatch_table = DispatchTable::default();
 
         // Add internal functions
@@ -245,6 +468,14 @@ impl Runtime {
             new as extern "C" fn(*const abi::TypeInfo, *mut ffi::c_void) -> *const *mut ffi::c_void,
             "new",
         ));
+        options.user_functions.push(IntoFunctionDefinition::into(
+            write_barrier as extern "C" fn(*const *mut ffi::c_void, *mut ffi::c_void),
+            "write_barrier",
+        ));
+        options.user_functions.push(IntoFunctionDefinition::into(
+            heap_limit_exceeded as extern "C" fn(*mut ffi::c_void) -> bool,
+            "heap_limit_exceeded",
+        ));
 
         let mut storages = Vec::with_capacity(options.user_functions.len());
         for (info, storage) in options.user_functions.into_iter() {
@@ -252,23 +483,97 @@ impl Runtime {
             storages.push(storage)
         }
 
-        let watcher: RecommendedWatcher = Watcher::new_raw(tx)?;
-        let mut runtime = Runtime {
-            assemblies: HashMap::new(),
-            assemblies_to_relink: VecDeque::new(),
-            dispatch_table,
-            watcher,
-            watcher_rx: rx,
-            renamed_files: HashMap::new(),
-            gc: Arc::new(self::garbage_collector::GarbageCollector::default()),
+        let watcher = match options.watcher {
+            Some(watcher) => watcher,
+            None => Box::new(NotifyWatcher::new()?),
+        };
+
+        let allocator = options
+            .gc_allocator
+            .unwrap_or_else(|| Arc::new(GlobalAllocator));
+        let gc = GarbageCollector::new(options.gc_kind, allocator);
+        gc.set_thresholds(options.gc_thresholds);
+        gc.set_max_heap_size(options.gc_max_heap_size);
+        if !options.finalizers.is_empty() {
+            let finalizers: HashMap<String, StructFinalizer> =
+                options.finalizers.into_iter().collect();
+            gc.set_finalizer(gc::Finalizer::new(move |handle, ty: &UnsafeTypeInfo| {
+                // Safety: Types are not removed while their objects are being collected
+                let ty = unsafe { ty.into_inner().as_ref() };
+                if let Some(finalizer) = finalizers.get(ty.name()) {
+                    finalizer(RawStruct(handle), ty);
+                }
+            }));
+        }
+
+        let runtime = Runtime {
+            linked: RwLock::new(LinkedAssemblies {
+                assemblies: HashMap::new(),
+                dispatch_table,
+                legacy_libs: Vec::new(),
+            }),
+            watcher: Mutex::new(watcher),
+            gc: Arc::new(gc),
             _user_functions: storages,
         };
 
-        runtime.add_assembly(&options.library_path)?;
+        runtime.add_assembly(&options.library_path, options.library_bytes, None)?;
+        for (namespace, library_path) in options.packages {
+            runtime.add_package(namespace, library_path)?;
+        }
+
         Ok(runtime)
     }
 
-    /// Adds an assembly corresponding to the library at `library_path`.
+    /// Adds the independently compiled entry point library at `library_path` and its
+    /// dependencies. Their functions are accessible as `<namespace>::<function path>`, which
+    /// allows multiple packages - e.g. mods - to export functions with the same path. Likewise,
+    /// their structs are accessible through [`Runtime::get_type_info`] as
+    /// `<namespace>::<struct path>`.
+    ///
+    /// Functions that the package imports are first resolved within the `namespace`, and otherwise
+    /// in the root namespace. Adding the package fails if one of its functions conflicts with an
+    /// existing function, or if one of its structs differs from a struct with the same name in
+    /// another namespace. The garbage collector identifies the types of objects by name, so such
+    /// structs cannot be told apart. Like all other assemblies, the package is reloaded
+    /// independently.
+    ///
+    /// Adding a package waits until all in-flight invocations have finished. Calling this function
+    /// from within a Mun invocation on the same thread will therefore deadlock.
+    ///
+    /// # Safety
+    ///
+    /// A munlib is simply a shared object. When a library is loaded, initialisation routines
+    /// contained within it are executed. For the purposes of safety, the execution of these
+    /// routines is conceptually the same calling an unknown foreign function and may impose
+    /// arbitrary requirements on the caller for the call to be sound.
+    ///
+    /// Additionally, the callers of this function must also ensure that execution of the
+    /// termination routines contained within the library is safe as well. These routines may be
+    /// executed when the library is unloaded.
+    ///
+    /// See [`Assembly::load`] for more information.
+    pub unsafe fn add_package<S: Into<String>, P: AsRef<Path>>(
+        &self,
+        namespace: S,
+        library_path: P,
+    ) -> anyhow::Result<()> {
+        let namespace = namespace.into();
+        if namespace.is_empty() || namespace.contains("::") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid namespace: `{}`.", namespace),
+            )
+            .into());
+        }
+
+        self.add_assembly(library_path.as_ref(), None, Some(namespace))
+    }
+
+    /// Adds an assembly corresponding to the library at `library_path`. If `library_bytes` is
+    /// provided, the library is loaded from memory instead and `library_path` is only used to
+    /// identify the assembly and to locate its dependencies, which are loaded from disk. The
+    /// functions of the assembly and its dependencies are added to the `namespace`, if any.
     ///
     /// # Safety
     ///
@@ -282,9 +587,25 @@ impl Runtime {
     /// executed when the library is unloaded.
     ///
     /// See [`Assembly::load`] for more information.
-    unsafe fn add_assembly(&mut self, library_path: &Path) -> anyhow::Result<()> {
-        let library_path = library_path.canonicalize()?;
-        if self.assemblies.contains_key(&library_path) {
+    unsafe fn add_assembly(
+        &self,
+        library_path: &Path,
+        mut library_bytes: Option<Vec<u8>>,
+        namespace: Option<String>,
+    ) -> anyhow::Result<()> {
+        // An in-memory library doesn't necessarily exist on disk
+        let library_path = if library_bytes.is_some() {
+            canonicalize_library_path(library_path)?
+        } else {
+            library_path.canonicalize()?
+        };
+        let in_memory_path = library_bytes.as_ref().map(|_| library_path.clone());
+
+        // Prevent the watcher from relinking at the same time
+        let mut watcher = self.watcher.lock();
+
+        let id = AssemblyId::new(namespace.clone(), library_path.clone());
+        if self.linked.read().assemblies.contains_key(&id) {
             return Err(io::Error::new(
                 io::ErrorKind::AlreadyExists,
                 "An assembly with the same name already exists.",
@@ -303,7 +624,12 @@ impl Runtime {
                 continue;
             }
 
-            let assembly = Assembly::load(&library_path, self.gc.clone())?;
+            // The entry point is always the first library to be loaded
+            let mut assembly = match library_bytes.take() {
+                Some(bytes) => Assembly::load_from_bytes(&library_path, &bytes, self.gc.clone())?,
+                None => Assembly::load(&library_path, self.gc.clone())?,
+            };
+            assembly.set_namespace(namespace.clone());
 
             let parent = library_path.parent().expect("Invalid library path");
             let extension = library_path.extension();
@@ -318,45 +644,141 @@ impl Runtime {
                     library_path = library_path.with_extension(extension);
                 }
 
-                if !loaded.contains_key(&library_path) {
+                // Dependencies that were loaded into another namespace are loaded again
+                let id = AssemblyId::new(namespace.clone(), library_path.clone());
+                if !loaded.contains_key(&library_path)
+                    && !self.linked.read().assemblies.contains_key(&id)
+                {
                     to_load.push_back(library_path);
                 }
             }
         }
 
-        self.dispatch_table = Assembly::link_all(loaded.values_mut(), &self.dispatch_table)?;
+        // Wait for all in-flight invocations to finish before linking
+        let mut linked = self.linked.write();
+        let linked = &mut *linked;
+        linked.dispatch_table = Assembly::link_all(
+            loaded.values_mut(),
+            &linked.assemblies,
+            &linked.dispatch_table,
+        )?;
 
         for (library_path, assembly) in loaded.into_iter() {
-            self.watcher
-                .watch(library_path.parent().unwrap(), RecursiveMode::NonRecursive)?;
+            // There is no file to watch for an in-memory library
+            if in_memory_path.as_ref() != Some(&library_path) {
+                watcher.watch(&library_path)?;
+            }
 
-            self.assemblies.insert(library_path, assembly);
+            linked.assemblies.insert(assembly.id(), assembly);
+        }
+
+        Ok(())
+    }
+
+    /// Removes the assembly that was loaded from `library_path`, unloading its library.
+    ///
+    /// This fails if another assembly still depends on one of the assembly's functions, or if
+    /// objects of its types are still alive and cannot be converted to identical types of the
+    /// remaining assemblies. Assemblies that were loaded as dependencies of the removed assembly
+    /// are not removed. If the library was loaded into multiple namespaces, it is removed from all
+    /// of them.
+    ///
+    /// Removal waits until all in-flight invocations have finished. Calling this function from
+    /// within a Mun invocation on the same thread will therefore deadlock.
+    ///
+    /// # Safety
+    ///
+    /// The callers of this function must ensure that execution of the termination routines
+    /// contained within the library is safe. These routines are executed when the library is
+    /// unloaded.
+    ///
+    /// See [`Assembly::load`] for more information.
+    pub unsafe fn remove_assembly<P: AsRef<Path>>(&self, library_path: P) -> anyhow::Result<()> {
+        let library_path = library_path.as_ref();
+
+        // Prevent the watcher from relinking at the same time
+        let _watcher = self.watcher.lock();
+
+        // Wait for all in-flight invocations to finish before unlinking
+        let mut linked = self.linked.write();
+        let linked = &mut *linked;
+
+        let ids = canonicalize_library_path(library_path)
+            .map(|library_path| linked.assembly_ids(&library_path))
+            .unwrap_or_default();
+        if ids.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "No assembly was loaded from `{}`.",
+                    library_path.to_string_lossy()
+                ),
+            )
+            .into());
+        }
+
+        for id in ids {
+            linked.dispatch_table =
+                Assembly::unlink(&id, &linked.assemblies, &linked.dispatch_table)?;
+
+            let assembly = linked.assemblies.remove(&id).expect("Assembly must exist.");
+            linked.legacy_libs.append(&mut assembly.into_legacy_libs());
         }
 
         Ok(())
     }
 
     /// Retrieves the function definition corresponding to `function_name`, if available.
-    pub fn get_function_definition(&self, function_name: &str) -> Option<&abi::FunctionDefinition> {
+    ///
+    /// The returned guard prevents the runtime from relinking until it is dropped.
+    pub fn get_function_definition(
+        &self,
+        function_name: &str,
+    ) -> Option<MappedRwLockReadGuard<'_, abi::FunctionDefinition>> {
         // TODO: Verify that when someone tries to invoke a non-public function, it should fail.
-        self.dispatch_table.get_fn(function_name)
+        RwLockReadGuard::try_map(self.linked.read_recursive(), |linked| {
+            linked.dispatch_table.get_fn(function_name)
+        })
+        .ok()
     }
 
-    /// Retrieves the type definition corresponding to `type_name`, if available.
-    pub fn get_type_info(&self, type_name: &str) -> Option<&abi::TypeInfo> {
-        for assembly in self.assemblies.values() {
-            for type_info in assembly.info().symbols.types().iter() {
-                if type_info.name() == type_name {
-                    return Some(type_info);
-                }
-            }
-        }
-
-        None
+    /// Retrieves the type definition corresponding to `type_name`, if available. Like functions,
+    /// the structs of a package are accessible as `<namespace>::<struct path>`.
+    ///
+    /// The returned guard prevents the runtime from relinking until it is dropped.
+    pub fn get_type_info(
+        &self,
+        type_name: &str,
+    ) -> Option<MappedRwLockReadGuard<'_, abi::TypeInfo>> {
+        RwLockReadGuard::try_map(self.linked.read_recursive(), |linked| {
+            linked
+                .assemblies
+                .values()
+                .flat_map(|assembly| {
+                    assembly
+                        .info()
+                        .symbols
+                        .types()
+                        .iter()
+                        .map(move |type_info| (assembly, *type_info))
+                })
+                .find(|(assembly, type_info)| assembly.type_path(type_info) == type_name)
+                .map(|(_, type_info)| type_info)
+        })
+        .ok()
     }
 
     /// Updates the state of the runtime. This includes checking for file changes, and reloading
     /// compiled assemblies.
+    ///
+    /// If the allocations since the previous collection exceeded the runtime's
+    /// [`CollectionThresholds`], garbage is collected as well. A [`GcKind::Generational`] garbage
+    /// collector also performs a minor collection when its nursery is full. Like with
+    /// [`Runtime::gc_collect`], objects that are not rooted might be deallocated.
+    ///
+    /// Relinking waits until all in-flight invocations have finished. Calling this function from
+    /// within a Mun invocation on the same thread will therefore deadlock.
+    ///
     /// # Safety
     ///
     /// A munlib is simply a shared object. When a library is loaded, initialisation routines
@@ -369,106 +791,170 @@ impl Runtime {
     /// executed when the library is unloaded.
     ///
     /// See [`Assembly::load`] for more information.
-    pub unsafe fn update(&mut self) -> bool {
-        fn is_lockfile(path: &Path) -> bool {
-            path.file_name().expect("Invalid file path.") == LOCKFILE_NAME
+    pub unsafe fn update(&self) -> bool {
+        let reloaded = self.reload_changed_assemblies();
+
+        // Objects that are only referenced from the stack of a running Mun function are not
+        // rooted, so garbage can only be collected automatically in between invocations.
+        if self.gc.needs_collection() {
+            let _linked = self.linked.write();
+            self.gc.collect_needed();
         }
 
-        unsafe fn relink_assemblies(runtime: &mut Runtime) -> anyhow::Result<DispatchTable> {
-            let mut loaded = HashMap::new();
-            let to_load = &mut runtime.assemblies_to_relink;
-
-            info!("Relinking assemblies:");
-            for (old_path, new_path) in to_load.iter() {
-                info!(
-                    "{} -> {}",
-                    old_path.to_string_lossy(),
-                    new_path.to_string_lossy()
-                );
+        reloaded
+    }
+
+    /// Reloads the assemblies that the watcher detected changes to. Returns `true` if assemblies
+    /// were reloaded.
+    ///
+    /// # Safety
+    ///
+    /// See [`Runtime::update`].
+    unsafe fn reload_changed_assemblies(&self) -> bool {
+        let mut watcher = self.watcher.lock();
+        while let Some(mut assemblies_to_relink) = watcher.poll() {
+            // Ignore changes to assemblies that were removed from the runtime
+            {
+                let linked = self.linked.read();
+                assemblies_to_relink
+                    .retain(|(old_path, _)| !linked.assembly_ids(old_path).is_empty());
+            }
+            if assemblies_to_relink.is_empty() {
+                continue;
             }
 
-            // Load all assemblies and their dependencies
-            while let Some((old_path, new_path)) = to_load.pop_front() {
-                // A dependency can be added by multiple dependants, so check that we didn't load it yet
-                if loaded.contains_key(&old_path) {
-                    continue;
+            match self.relink_assemblies(assemblies_to_relink.clone()) {
+                Ok(()) => {
+                    info!("Succesfully reloaded assemblies.");
+                    return true;
                 }
+                Err(e) => {
+                    error!("Failed to relink assemblies, due to {}.", e);
 
-                let assembly = Assembly::load(&new_path, runtime.gc.clone())?;
-
-                let parent = new_path.parent().expect("Invalid library path");
-                let extension = new_path.extension();
+                    // Retry the changes together with the next set of changes
+                    watcher.relink_failed(assemblies_to_relink);
+                    return false;
+                }
+            }
+        }
 
-                let dependencies: Vec<String> =
-                    assembly.info().dependencies().map(From::from).collect();
-                loaded.insert(old_path.clone(), assembly);
+        false
+    }
 
-                for dependency in dependencies {
-                    let mut library_path = parent.join(dependency);
-                    if let Some(extension) = extension {
-                        library_path = library_path.with_extension(extension);
-                    }
+    /// Reloads the assembly that was loaded from `library_path`, using the library at
+    /// `new_library_path`. This allows the host to decide when and what to reload, independent
+    /// of the runtime's [`AssemblyWatcher`].
+    ///
+    /// Relinking waits until all in-flight invocations have finished. Calling this function from
+    /// within a Mun invocation on the same thread will therefore deadlock.
+    ///
+    /// # Safety
+    ///
+    /// A munlib is simply a shared object. When a library is loaded, initialisation routines
+    /// contained within it are executed. For the purposes of safety, the execution of these
+    /// routines is conceptually the same calling an unknown foreign function and may impose
+    /// arbitrary requirements on the caller for the call to be sound.
+    ///
+    /// Additionally, the callers of this function must also ensure that execution of the
+    /// termination routines contained within the library is safe as well. These routines may be
+    /// executed when the library is unloaded.
+    ///
+    /// See [`Assembly::load`] for more information.
+    pub unsafe fn reload_from<P: AsRef<Path>, Q: Into<PathBuf>>(
+        &self,
+        library_path: P,
+        new_library_path: Q,
+    ) -> anyhow::Result<()> {
+        let library_path = canonicalize_library_path(library_path.as_ref())?;
 
-                    if !loaded.contains_key(&library_path)
-                        && !runtime.assemblies.contains_key(&library_path)
-                    {
-                        to_load.push_back((old_path.clone(), library_path));
-                    }
-                }
-            }
+        // Prevent the watcher from relinking at the same time
+        let _watcher = self.watcher.lock();
 
-            Assembly::relink_all(
-                &mut loaded,
-                &mut runtime.assemblies,
-                &runtime.dispatch_table,
+        if self.linked.read().assembly_ids(&library_path).is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "No assembly was loaded from `{}`.",
+                    library_path.to_string_lossy()
+                ),
             )
+            .into());
         }
 
-        while let Ok(event) = self.watcher_rx.try_recv() {
-            if let Some(path) = event.path {
-                let op = event.op.expect("Invalid event.");
-
-                if is_lockfile(&path) {
-                    if op.contains(notify::op::CREATE) {
-                        debug!("Lockfile created");
-                    }
-                    if op.contains(notify::op::REMOVE) {
-                        debug!("Lockfile deleted");
-
-                        match relink_assemblies(self) {
-                            Ok(table) => {
-                                info!("Succesfully reloaded assemblies.");
-
-                                self.dispatch_table = table;
-                                self.assemblies_to_relink.clear();
-
-                                return true;
-                            }
-                            Err(e) => error!("Failed to relink assemblies, due to {}.", e),
-                        }
-                    }
-                } else {
-                    let path = path.canonicalize().unwrap_or_else(|_| {
-                        panic!("Failed to canonicalize path: {}.", path.to_string_lossy())
-                    });
-
-                    if op.contains(notify::op::RENAME) {
-                        let cookie = event.cookie.expect("Invalid RENAME event.");
-                        if let Some(old_path) = self.renamed_files.remove(&cookie) {
-                            self.assemblies_to_relink.push_back((old_path, path));
-                        // on_file_changed(self, &old_path, &path);
-                        } else {
-                            self.renamed_files.insert(cookie, path);
-                        }
-                    } else if op.contains(notify::op::WRITE) {
-                        // TODO: don't overwrite existing
-                        self.assemblies_to_relink.push_back((path.clone(), path));
-                    }
+        self.relink_assemblies(vec![(library_path, new_library_path.into())])
+    }
+
+    /// Loads the changed assemblies and their dependencies, and relinks them. This leaves the
+    /// runtime intact, in case of errors.
+    ///
+    /// # Safety
+    ///
+    /// The caller must hold the lock on the `watcher`. See [`Assembly::load`] for more
+    /// information on the safety of loading assemblies.
+    unsafe fn relink_assemblies(
+        &self,
+        assemblies_to_relink: Vec<AssemblyChange>,
+    ) -> anyhow::Result<()> {
+        let mut loaded = HashMap::new();
+
+        // A library that was loaded into multiple namespaces is reloaded in all of them
+        let mut to_load: VecDeque<_> = {
+            let linked = self.linked.read();
+            assemblies_to_relink
+                .into_iter()
+                .flat_map(|(old_path, new_path)| {
+                    linked
+                        .assembly_ids(&old_path)
+                        .into_iter()
+                        .map(move |old_id| (old_id, new_path.clone()))
+                })
+                .collect()
+        };
+
+        info!("Relinking assemblies:");
+        for (old_id, new_path) in to_load.iter() {
+            info!("{} -> {}", old_id, new_path.to_string_lossy());
+        }
+
+        // Load all assemblies and their dependencies
+        while let Some((old_id, new_path)) = to_load.pop_front() {
+            // A dependency can be added by multiple dependants, so check that we didn't load it yet
+            if loaded.contains_key(&old_id) {
+                continue;
+            }
+
+            let mut assembly = Assembly::load(&new_path, self.gc.clone())?;
+
+            // The reloaded assembly remains in the namespace of the assembly it replaces
+            assembly.set_namespace(old_id.namespace.clone());
+
+            let parent = new_path.parent().expect("Invalid library path");
+            let extension = new_path.extension();
+
+            let dependencies: Vec<String> =
+                assembly.info().dependencies().map(From::from).collect();
+            loaded.insert(old_id.clone(), assembly);
+
+            for dependency in dependencies {
+                let mut library_path = parent.join(dependency);
+                if let Some(extension) = extension {
+                    library_path = library_path.with_extension(extension);
+                }
+
+                let id = AssemblyId::new(old_id.namespace.clone(), library_path.clone());
+                if !loaded.contains_key(&id) && !self.linked.read().assemblies.contains_key(&id) {
+                    to_load.push_back((old_id.clone(), library_path));
                 }
             }
         }
 
-        false
+        // Wait for all in-flight invocations to finish before relinking
+        let mut linked = self.linked.write();
+        let linked = &mut *linked;
+        linked.dispatch_table =
+            Assembly::relink_all(&mut loaded, &mut linked.assemblies, &linked.dispatch_table)?;
+
+        Ok(())
     }
 
     /// Returns a shared reference to the runtime's garbage collector.
@@ -481,21 +967,145 @@ impl Runtime {
 
     /// Collects all memory that is no longer referenced by rooted objects. Returns `true` if memory
     /// was reclaimed, `false` otherwise. This behavior will likely change in the future.
+    ///
+    /// Objects that are only referenced from the stack of a running Mun function are not rooted,
+    /// so the collection waits until all in-flight invocations have finished. Calling this
+    /// function from within a Mun invocation on the same thread will therefore deadlock.
     pub fn gc_collect(&self) -> bool {
+        let _linked = self.linked.write();
         self.gc.collect()
     }
 
+    /// Collects young objects that are no longer referenced by rooted objects or old objects.
+    /// Returns `true` if memory was reclaimed, `false` otherwise.
+    ///
+    /// Only a [`GcKind::Generational`] garbage collector distinguishes young objects, other garbage
+    /// collectors perform a full collection. Like with [`Runtime::gc_collect`], the collection
+    /// waits until all in-flight invocations have finished.
+    pub fn gc_collect_minor(&self) -> bool {
+        let _linked = self.linked.write();
+        self.gc.collect_minor()
+    }
+
+    /// Performs a step of a garbage collection cycle that spends approximately `budget`, which
+    /// allows hosts to spread the cost of a collection over multiple frames. Returns `true` if the
+    /// step completed the collection cycle, `false` otherwise.
+    ///
+    /// Only a [`GcKind::Incremental`] garbage collector can split a collection into steps, other
+    /// garbage collectors perform a full collection. Like with [`Runtime::gc_collect`], the step
+    /// waits until all in-flight invocations have finished.
+    pub fn gc_collect_step(&self, budget: Duration) -> bool {
+        let _linked = self.linked.write();
+        self.gc.collect_step(budget)
+    }
+
+    /// Returns the kind of the garbage collector.
+    pub fn gc_kind(&self) -> GcKind {
+        self.gc.kind()
+    }
+
+    /// Returns a snapshot of all objects that are currently allocated by the garbage collector,
+    /// including their types, sizes, root counts and the objects they reference. Snapshots can be
+    /// exported to JSON or DOT, which helps to find objects that are kept alive unintentionally.
+    ///
+    /// In-flight invocations might still be initializing the objects they allocated, so the
+    /// snapshot waits until all in-flight invocations have finished. Calling this function from
+    /// within a Mun invocation on the same thread will therefore deadlock.
+    pub fn gc_snapshot(&self) -> gc::HeapSnapshot {
+        let _linked = self.linked.write();
+        self.gc.snapshot()
+    }
+
+    /// Returns a deep copy of the struct `value`. Garbage collected structs that are (indirectly)
+    /// referenced by `value` are copied as well, preserving the references between them,
+    /// including cycles. Value structs are copied as part of the struct that contains them.
+    pub fn deep_clone<'r>(&'r self, value: &StructRef<'r>) -> StructRef<'r> {
+        let _linked = self.linked.read_recursive();
+        let handle = gc::deep_clone(self.gc.as_ref(), value.raw().into());
+        StructRef::new(RawStruct(handle), self)
+    }
+
+    /// Returns whether the structs `a` and `b` are structurally equal, i.e. whether they are of
+    /// the same type and all of their fields are equal. Referenced structs are compared
+    /// recursively, so cyclic structs are equal if they have the same shape and equal values.
+    pub fn structural_eq(&self, a: &StructRef, b: &StructRef) -> bool {
+        let _linked = self.linked.read_recursive();
+        gc::structural_eq(self.gc.as_ref(), a.raw().into(), b.raw().into())
+    }
+
+    /// Serializes all structs that are reachable from the `roots` to a portable byte format, e.g.
+    /// to save the state of a game. The structs can be restored with [`Runtime::gc_deserialize`].
+    pub fn gc_serialize(&self, roots: &[RootedStruct]) -> Vec<u8> {
+        let _linked = self.linked.read_recursive();
+        let roots: Vec<_> = roots.iter().map(RootedStruct::handle).collect();
+        memory::serialize::serialize(self.gc.as_ref(), &roots)
+    }
+
+    /// Deserializes structs that were serialized by [`Runtime::gc_serialize`], returning the
+    /// rooted structs that correspond to the serialized roots. The structs are allocated anew, so
+    /// a byte sequence can be deserialized multiple times.
+    ///
+    /// If the types of the structs changed since they were serialized, e.g. because an assembly
+    /// was hot reloaded in between, the structs are converted to the currently loaded types the
+    /// same way as when hot reloading.
+    pub fn gc_deserialize(&self, bytes: &[u8]) -> Result<Vec<RootedStruct>, DeserializeError> {
+        let linked = self.linked.read_recursive();
+        let types: Vec<UnsafeTypeInfo> = linked
+            .assemblies
+            .values()
+            .flat_map(Assembly::types)
+            .collect();
+
+        let roots = memory::serialize::deserialize(&self.gc, &types, bytes)?;
+        Ok(roots.into_iter().map(RootedStruct::from_root).collect())
+    }
+
     /// Returns statistics about the garbage collector.
     pub fn gc_stats(&self) -> gc::Stats {
         self.gc.stats()
     }
+
+    /// Returns the thresholds that determine when garbage is automatically collected.
+    pub fn gc_thresholds(&self) -> CollectionThresholds {
+        self.gc.thresholds()
+    }
+
+    /// Sets the thresholds that determine when garbage is automatically collected.
+    pub fn set_gc_thresholds(&self, thresholds: CollectionThresholds) {
+        self.gc.set_thresholds(thresholds)
+    }
+
+    /// Sets the maximum number of bytes that garbage collected structs can occupy, or removes the
+    /// limit if `None`.
+    ///
+    /// When an allocation would exceed the limit, garbage is collected first. If that does not
+    /// free enough memory, the allocation fails and the Mun functions that are executing return
+    /// early. Their results must be discarded. [`Runtime::invoke`] returns an [`InvokeErr`]
+    /// instead of the function's result, after collecting the remaining garbage. Mun functions
+    /// that are called directly, through their function pointers, report the error through
+    /// [`Runtime::take_gc_heap_limit_exceeded`].
+    pub fn set_gc_max_heap_size(&self, max_heap_size: Option<usize>) {
+        self.gc.set_max_heap_size(max_heap_size)
+    }
+
+    /// Returns the first allocation that exceeded the maximum heap size, since the previous call
+    /// on the current thread, and resets it. Allocations of invocations through
+    /// [`Runtime::invoke`] are not included, as those are reported by the invocation itself.
+    ///
+    /// Until the error is reset, Mun functions that are called directly return early after calling
+    /// a function that might allocate. The failed allocation requests a garbage collection, which
+    /// is performed by the next [`Runtime::update`].
+    pub fn take_gc_heap_limit_exceeded(&self) -> Option<HeapLimitExceeded> {
+        HEAP_LIMIT_EXCEEDED.with(Cell::take)
+    }
 }
 
 /// An error that might occur when calling a mun function from Rust.
 pub struct InvokeErr<'name, T> {
     msg: String,
     function_name: &'name str,
-    arguments: T,
+    /// The arguments of the invocation, unless they were consumed by the invoked function
+    arguments: Option<T>,
 }
 
 impl<'name, T> Debug for InvokeErr<'name, T> {
@@ -513,6 +1123,9 @@ impl<'name, T> Display for InvokeErr<'name, T> {
 impl<'name, T: InvokeArgs> InvokeErr<'name, T> {
     /// Retries a function invocation once, resulting in a potentially successful
     /// invocation.
+    ///
+    /// An invocation that exceeded the maximum heap size cannot be retried, because its arguments
+    /// were consumed. Retrying it returns the same error.
     // FIXME: `unwrap_or_else` does not compile for `StructRef`, due to
     // https://doc.rust-lang.org/nomicon/lifetime-mismatch.html#improperly-reduced-borrows
     pub fn retry<'r, 'o, Output>(self, runtime: &'r mut Runtime) -> Result<Output, Self>
@@ -520,53 +1133,62 @@ impl<'name, T: InvokeArgs> InvokeErr<'name, T> {
         Output: 'o + ReturnTypeReflection + Marshal<'o>,
         'r: 'o,
     {
-        // Safety: The output of `retry_impl` is guaranteed to only contain a shared
-        // reference.
+        // Safety: The safety requirements of reloading assemblies were accepted upon construction
+        // of the `Runtime`. The exclusive reference guarantees that no struct references are
+        // alive while the `Runtime` is updated.
         unsafe { self.retry_impl(runtime) }
     }
 
     /// Retries the function invocation until it succeeds, resulting in an output.
+    ///
+    /// An invocation that exceeded the maximum heap size cannot be retried, because its arguments
+    /// were consumed. In that case the error is returned.
     // FIXME: `unwrap_or_else` does not compile for `StructRef`, due to
     // https://doc.rust-lang.org/nomicon/lifetime-mismatch.html#improperly-reduced-borrows
-    pub fn wait<'r, 'o, Output>(mut self, runtime: &'r mut Runtime) -> Output
+    pub fn wait<'r, 'o, Output>(mut self, runtime: &'r mut Runtime) -> Result<Output, Self>
     where
         Output: 'o + ReturnTypeReflection + Marshal<'o>,
         'r: 'o,
     {
-        // Safety: The output of `retry_impl` is guaranteed to only contain a shared
-        // reference.
         let runtime = &*runtime;
 
         loop {
+            if self.arguments.is_none() {
+                return Err(self);
+            }
+
+            // Safety: The safety requirements of reloading assemblies were accepted upon
+            // construction of the `Runtime`. The exclusive reference guarantees that no struct
+            // references are alive while the `Runtime` is updated.
             self = match unsafe { self.retry_impl(runtime) } {
-                Ok(output) => return output,
+                Ok(output) => return Ok(output),
                 Err(e) => e,
             };
         }
     }
 
     /// Inner implementation that retries a function invocation once, resulting in a
-    /// potentially successful invocation. This is a workaround for:
-    /// https://doc.rust-lang.org/nomicon/lifetime-mismatch.html
+    /// potentially successful invocation.
     ///
     /// # Safety
     ///
-    /// When calling this function, you have to guarantee that `runtime` is mutably
-    /// borrowed. The `Output` value can only contain a shared borrow of `runtime`.
+    /// This function updates the `runtime`, see [`Runtime::update`] for more information.
     unsafe fn retry_impl<'r, 'o, Output>(self, runtime: &'r Runtime) -> Result<Output, Self>
     where
         Output: 'o + ReturnTypeReflection + Marshal<'o>,
         'r: 'o,
     {
-        #[allow(clippy::cast_ref_to_mut)]
-        let runtime = &mut *(runtime as *const Runtime as *mut Runtime);
+        let arguments = match self.arguments {
+            Some(arguments) => arguments,
+            None => return Err(self),
+        };
 
         eprintln!("{}", self.msg);
         while !runtime.update() {
             // Wait until there has been an update that might fix the error
         }
 
-        runtime.invoke(self.function_name, self.arguments)
+        runtime.invoke(self.function_name, arguments)
     }
 }
 
@@ -642,8 +1264,12 @@ impl Runtime {
     where
         'runtime: 'ret,
     {
+        // Hold a read lock during the invocation, to prevent relinking while the function is
+        // executing.
+        let linked = self.linked.read_recursive();
+
         // Get the function information from the runtime
-        let function_info = match self.get_function_definition(function_name).ok_or_else(|| {
+        let function_info = match linked.dispatch_table.get_fn(function_name).ok_or_else(|| {
             format!(
                 "failed to obtain function '{}', no such function exists.",
                 function_name
@@ -654,7 +1280,7 @@ impl Runtime {
                 return Err(InvokeErr {
                     msg,
                     function_name,
-                    arguments,
+                    arguments: Some(arguments),
                 })
             }
         };
@@ -666,7 +1292,7 @@ impl Runtime {
                 return Err(InvokeErr {
                     msg,
                     function_name,
-                    arguments,
+                    arguments: Some(arguments),
                 })
             }
         };
@@ -690,12 +1316,40 @@ impl Runtime {
                         expected, found,
                     ),
                     function_name,
-                    arguments,
+                    arguments: Some(arguments),
                 })
             }
         }
 
+        // The heap limit errors of an enclosing invocation, that called into the host, are
+        // restored afterwards
+        let outer_error = HEAP_LIMIT_EXCEEDED.with(Cell::take);
+        INVOCATION_DEPTH.with(|depth| depth.set(depth.get() + 1));
+        self.gc.begin_invocation();
         let result: ReturnType::MunType = unsafe { arguments.invoke(function_info.fn_ptr) };
-        Ok(Marshal::marshal_from(result, self))
+        self.gc.end_invocation();
+        INVOCATION_DEPTH.with(|depth| depth.set(depth.get() - 1));
+        let error = HEAP_LIMIT_EXCEEDED.with(|error| error.replace(outer_error));
+
+        match error {
+            None => Ok(Marshal::marshal_from(result, self)),
+            Some(error) => {
+                // The invoked function returned early, so its result is discarded. Garbage that
+                // could not be collected during the invocation, is collected afterwards.
+                drop(linked);
+                if INVOCATION_DEPTH.with(Cell::get) == 0 {
+                    self.gc_collect();
+                }
+
+                Err(InvokeErr {
+                    msg: format!(
+                        "invocation of '{}' exceeded the maximum heap size: {}",
+                        function_name, error
+                    ),
+                    function_name,
+                    arguments: None,
+                })
+            }
+        }
     }
 }
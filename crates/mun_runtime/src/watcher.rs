@@ -0,0 +1,194 @@
+use crate::canonicalize_library_path;
+use log::debug;
+use mun_project::LOCKFILE_NAME;
+use notify::{RawEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    io, mem,
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{channel, Receiver},
+        Arc,
+    },
+};
+
+/// An assembly that needs to be relinked, consisting of the path it was loaded from and the
+/// (potentially) new path of its library.
+pub type AssemblyChange = (PathBuf, PathBuf);
+
+/// A trait used by the [`Runtime`](crate::Runtime) to detect which assemblies need to be
+/// reloaded.
+pub trait AssemblyWatcher: Send {
+    /// Starts watching the assembly that was loaded from `library_path`.
+    fn watch(&mut self, library_path: &Path) -> anyhow::Result<()>;
+
+    /// Returns the next set of assemblies that need to be relinked together, if any.
+    fn poll(&mut self) -> Option<Vec<AssemblyChange>>;
+
+    /// Called when relinking a set of assemblies returned by [`AssemblyWatcher::poll`] failed. The
+    /// watcher should return the `changes` again, together with the next set of assemblies that
+    /// need to be relinked, so they are retried.
+    fn relink_failed(&mut self, changes: Vec<AssemblyChange>);
+}
+
+/// Merges `changes` that failed to be relinked into the set of `pending` changes. Pending changes
+/// take precedence, because they are more recent.
+fn merge_failed_changes(pending: &mut Vec<AssemblyChange>, changes: Vec<AssemblyChange>) {
+    for change in changes {
+        if !pending.iter().any(|(old_path, _)| *old_path == change.0) {
+            pending.push(change);
+        }
+    }
+}
+
+/// An [`AssemblyWatcher`] that watches the file system for changes to libraries.
+///
+/// Changed libraries are collected until the lockfile of the Mun compiler is removed, at which
+/// point they are reported together.
+pub struct NotifyWatcher {
+    watcher: RecommendedWatcher,
+    watcher_rx: Receiver<RawEvent>,
+    renamed_files: HashMap<u32, PathBuf>,
+    /// Assemblies that have changed and thus need to be relinked.
+    assemblies_to_relink: Vec<AssemblyChange>,
+    /// Assemblies that failed to be relinked, which are retried with the next set of changes.
+    failed_to_relink: Vec<AssemblyChange>,
+}
+
+impl NotifyWatcher {
+    /// Constructs a new `NotifyWatcher`.
+    pub fn new() -> anyhow::Result<Self> {
+        let (tx, rx) = channel();
+        let watcher: RecommendedWatcher = Watcher::new_raw(tx)?;
+
+        Ok(Self {
+            watcher,
+            watcher_rx: rx,
+            renamed_files: HashMap::new(),
+            assemblies_to_relink: Vec::new(),
+            failed_to_relink: Vec::new(),
+        })
+    }
+}
+
+impl AssemblyWatcher for NotifyWatcher {
+    fn watch(&mut self, library_path: &Path) -> anyhow::Result<()> {
+        self.watcher.watch(
+            library_path.parent().expect("Invalid library path"),
+            RecursiveMode::NonRecursive,
+        )?;
+
+        Ok(())
+    }
+
+    fn poll(&mut self) -> Option<Vec<AssemblyChange>> {
+        fn is_lockfile(path: &Path) -> bool {
+            path.file_name().expect("Invalid file path.") == LOCKFILE_NAME
+        }
+
+        while let Ok(event) = self.watcher_rx.try_recv() {
+            if let Some(path) = event.path {
+                let op = event.op.expect("Invalid event.");
+
+                if is_lockfile(&path) {
+                    if op.contains(notify::op::CREATE) {
+                        debug!("Lockfile created");
+                    }
+                    if op.contains(notify::op::REMOVE) {
+                        debug!("Lockfile deleted");
+
+                        let mut changes = mem::take(&mut self.assemblies_to_relink);
+                        merge_failed_changes(&mut changes, mem::take(&mut self.failed_to_relink));
+                        return Some(changes);
+                    }
+                } else {
+                    let path = path.canonicalize().unwrap_or_else(|_| {
+                        panic!("Failed to canonicalize path: {}.", path.to_string_lossy())
+                    });
+
+                    if op.contains(notify::op::RENAME) {
+                        let cookie = event.cookie.expect("Invalid RENAME event.");
+                        if let Some(old_path) = self.renamed_files.remove(&cookie) {
+                            self.assemblies_to_relink.push((old_path, path));
+                        } else {
+                            self.renamed_files.insert(cookie, path);
+                        }
+                    } else if op.contains(notify::op::WRITE) {
+                        // TODO: don't overwrite existing
+                        self.assemblies_to_relink.push((path.clone(), path));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    fn relink_failed(&mut self, changes: Vec<AssemblyChange>) {
+        merge_failed_changes(&mut self.failed_to_relink, changes);
+    }
+}
+
+/// An [`AssemblyWatcher`] that leaves it up to the host to signal which assemblies need to be
+/// reloaded.
+///
+/// A `ManualWatcher` can be cloned before it is passed to the
+/// [`RuntimeBuilder`](crate::RuntimeBuilder). All clones share the same set of scheduled reloads,
+/// which are applied the next time [`Runtime::update`](crate::Runtime::update) is called.
+/// Reloads that fail are retried together with the next scheduled reload.
+#[derive(Clone, Default)]
+pub struct ManualWatcher {
+    assemblies_to_relink: Arc<Mutex<Vec<AssemblyChange>>>,
+    failed_to_relink: Arc<Mutex<Vec<AssemblyChange>>>,
+}
+
+impl ManualWatcher {
+    /// Constructs a new `ManualWatcher`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Schedules the assembly that was loaded from `library_path` to be reloaded from the same
+    /// path.
+    pub fn reload<P: AsRef<Path>>(&self, library_path: P) -> io::Result<()> {
+        let library_path = library_path.as_ref();
+        self.reload_from(library_path, library_path)
+    }
+
+    /// Schedules the assembly that was loaded from `library_path` to be reloaded from the library
+    /// at `new_library_path`.
+    pub fn reload_from<P: AsRef<Path>, Q: Into<PathBuf>>(
+        &self,
+        library_path: P,
+        new_library_path: Q,
+    ) -> io::Result<()> {
+        let library_path = canonicalize_library_path(library_path.as_ref())?;
+        self.assemblies_to_relink
+            .lock()
+            .push((library_path, new_library_path.into()));
+
+        Ok(())
+    }
+}
+
+impl AssemblyWatcher for ManualWatcher {
+    fn watch(&mut self, _library_path: &Path) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn poll(&mut self) -> Option<Vec<AssemblyChange>> {
+        let mut assemblies_to_relink = self.assemblies_to_relink.lock();
+        if assemblies_to_relink.is_empty() {
+            None
+        } else {
+            let mut changes = mem::take(&mut *assemblies_to_relink);
+            merge_failed_changes(&mut changes, mem::take(&mut *self.failed_to_relink.lock()));
+            Some(changes)
+        }
+    }
+
+    fn relink_failed(&mut self, changes: Vec<AssemblyChange>) {
+        merge_failed_changes(&mut self.failed_to_relink.lock(), changes);
+    }
+}
@@ -1,7 +1,8 @@
 #[macro_use]
 mod util;
 
-use mun_test::CompileAndRunTestDriver;
+use mun_runtime::{ManualWatcher, Runtime};
+use mun_test::{CompileAndRunTestDriver, CompileTestDriver};
 
 #[test]
 fn hotreloadable() {
@@ -62,3 +63,158 @@ fn hotreload_struct_decl() {
     "#,
     );
 }
+
+#[test]
+fn manual_watcher() {
+    let mut driver = CompileTestDriver::from_file(
+        r"
+    pub fn main() -> i32 { 5 }
+    ",
+    );
+
+    let watcher = ManualWatcher::new();
+    let builder = Runtime::builder(driver.lib_path()).with_watcher(watcher.clone());
+
+    // Safety: We compiled the library ourselves, therefor loading the munlib is safe.
+    let runtime = unsafe { builder.finish() }.expect("Failed to build runtime");
+    let result: i32 = runtime.invoke("main", ()).unwrap();
+    assert_eq!(result, 5);
+
+    driver.update(
+        "mod.mun",
+        r"
+    pub fn main() -> i32 { 10 }
+    ",
+    );
+
+    // Nothing is reloaded until the host schedules it
+    assert!(!unsafe { runtime.update() });
+    let result: i32 = runtime.invoke("main", ()).unwrap();
+    assert_eq!(result, 5);
+
+    watcher.reload(driver.lib_path()).unwrap();
+    assert!(unsafe { runtime.update() });
+    let result: i32 = runtime.invoke("main", ()).unwrap();
+    assert_eq!(result, 10);
+}
+
+#[test]
+fn reload_from() {
+    let mut driver = CompileTestDriver::from_file(
+        r"
+    pub fn main() -> i32 { 5 }
+    ",
+    );
+
+    let builder = Runtime::builder(driver.lib_path()).with_watcher(ManualWatcher::new());
+
+    // Safety: We compiled the library ourselves, therefor loading the munlib is safe.
+    let runtime = unsafe { builder.finish() }.expect("Failed to build runtime");
+    let result: i32 = runtime.invoke("main", ()).unwrap();
+    assert_eq!(result, 5);
+
+    driver.update(
+        "mod.mun",
+        r"
+    pub fn main() -> i32 { 10 }
+    ",
+    );
+
+    // Safety: We compiled the library ourselves, therefor reloading the munlib is safe.
+    unsafe { runtime.reload_from(driver.lib_path(), driver.lib_path()) }
+        .expect("Failed to reload assembly");
+    let result: i32 = runtime.invoke("main", ()).unwrap();
+    assert_eq!(result, 10);
+}
+
+#[test]
+fn reload_package() {
+    let base = CompileTestDriver::from_file(
+        r"
+    pub fn main() -> i32 { 5 }
+    ",
+    );
+    let mut package = CompileTestDriver::from_file(
+        r"
+    pub fn main() -> i32 { 6 }
+    ",
+    );
+
+    let watcher = ManualWatcher::new();
+    let builder = Runtime::builder(base.lib_path())
+        .with_package("my_mod", package.lib_path())
+        .with_watcher(watcher.clone());
+
+    // Safety: We compiled the libraries ourselves, therefor loading the munlibs is safe.
+    let runtime = unsafe { builder.finish() }.expect("Failed to build runtime");
+
+    package.update(
+        "mod.mun",
+        r"
+    pub fn main() -> i32 { 10 }
+    ",
+    );
+    watcher
+        .reload(package.lib_path())
+        .expect("Failed to schedule reload");
+
+    // Safety: We compiled the library ourselves, therefor reloading the munlib is safe.
+    assert!(unsafe { runtime.update() });
+
+    // Only the package was reloaded and it remains in its namespace
+    let result: i32 = runtime.invoke("main", ()).unwrap();
+    assert_eq!(result, 5);
+    let result: i32 = runtime.invoke("my_mod::main", ()).unwrap();
+    assert_eq!(result, 10);
+}
+
+#[test]
+fn retry_failed_reload() {
+    let base = CompileTestDriver::from_file(
+        r"
+    pub fn main() -> i32 { 5 }
+    ",
+    );
+    let mut package = CompileTestDriver::from_file(
+        r"
+    pub fn main() -> i32 { 6 }
+    ",
+    );
+
+    let watcher = ManualWatcher::new();
+    let builder = Runtime::builder(base.lib_path())
+        .with_package("my_mod", package.lib_path())
+        .with_watcher(watcher.clone());
+
+    // Safety: We compiled the libraries ourselves, therefor loading the munlibs is safe.
+    let runtime = unsafe { builder.finish() }.expect("Failed to build runtime");
+
+    package.update(
+        "mod.mun",
+        r"
+    pub fn main() -> i32 { 10 }
+    ",
+    );
+
+    // Reloading from a library that does not exist yet fails
+    let new_library_path = package.lib_path().with_file_name("retry.munlib");
+    watcher
+        .reload_from(package.lib_path(), &new_library_path)
+        .expect("Failed to schedule reload");
+
+    // Safety: We compiled the library ourselves, therefor reloading the munlib is safe.
+    assert!(!unsafe { runtime.update() });
+    let result: i32 = runtime.invoke("my_mod::main", ()).unwrap();
+    assert_eq!(result, 6);
+
+    // The failed reload is retried together with the next scheduled reload
+    std::fs::copy(package.lib_path(), &new_library_path).expect("Failed to copy library");
+    watcher
+        .reload(base.lib_path())
+        .expect("Failed to schedule reload");
+
+    // Safety: We compiled the library ourselves, therefor reloading the munlib is safe.
+    assert!(unsafe { runtime.update() });
+    let result: i32 = runtime.invoke("my_mod::main", ()).unwrap();
+    assert_eq!(result, 10);
+}
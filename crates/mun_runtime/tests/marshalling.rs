@@ -461,7 +461,7 @@ fn marshal_struct() {
         .invoke("baz_new_transitive", (int_data.0, bool_data.0))
         .unwrap();
     // TODO: Find an ergonomic solution for this:
-    // .unwrap_or_else(|e| e.wait(&mut runtime_ref));
+    // .or_else(|e| e.wait(&mut runtime_ref)).unwrap();
 
     let c1: StructRef = driver
         .runtime
@@ -1,5 +1,15 @@
-use mun_runtime::StructRef;
+use mun_runtime::{
+    Allocator, CollectionThresholds, DeserializeError, GcKind, GlobalAllocator, StructRef,
+};
 use mun_test::CompileAndRunTestDriver;
+use std::{
+    alloc::Layout,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
 
 #[macro_use]
 mod util;
@@ -43,6 +53,673 @@ fn gc_trace() {
     assert_eq!(runtime.gc_stats().allocated_memory, 0);
 }
 
+#[test]
+fn gc_collect_automatically() {
+    let driver = CompileAndRunTestDriver::new(
+        r#"
+    pub struct Foo {
+        quz: f64,
+    }
+
+    pub fn new_foo() -> Foo {
+        Foo { quz: 1.0 }
+    }
+    "#,
+        |builder| {
+            builder.with_gc_thresholds(CollectionThresholds {
+                max_allocations: Some(3),
+                ..CollectionThresholds::default()
+            })
+        },
+    )
+    .expect("Failed to build test driver");
+
+    let runtime = &driver.runtime;
+    for _ in 0..2 {
+        let _value: StructRef = runtime.invoke("new_foo", ()).unwrap();
+    }
+
+    // Safety: No assemblies were changed, so nothing is reloaded.
+    assert!(!unsafe { runtime.update() });
+    assert_eq!(runtime.gc_stats().collections, 0);
+
+    let _: StructRef = runtime.invoke("new_foo", ()).unwrap();
+    assert!(!unsafe { runtime.update() });
+
+    let stats = runtime.gc_stats();
+    assert_eq!(stats.collections, 1);
+    assert_eq!(stats.allocated_memory, 0);
+    assert!(stats.freed_memory > 0);
+}
+
+#[test]
+fn gc_collect_incrementally() {
+    let driver = CompileAndRunTestDriver::new(
+        r#"
+    pub struct Pair {
+        src: Foo,
+        dest: Foo,
+    }
+
+    pub struct Foo {
+        quz: f64,
+        bar: Bar,
+    }
+
+    pub struct Bar {
+        baz: i64
+    }
+
+    pub fn new_pair(src_baz: i64, dest_baz: i64) -> Pair {
+        Pair {
+            src: new_foo(src_baz),
+            dest: new_foo(dest_baz),
+        }
+    }
+
+    pub fn new_foo(baz: i64) -> Foo {
+        Foo {
+            quz: 1.0,
+            bar: Bar { baz }
+        }
+    }
+
+    pub fn move_bar(pair: Pair) {
+        pair.dest.bar = pair.src.bar;
+        pair.src.bar = Bar { baz: 0 };
+    }
+    "#,
+        |builder| builder.with_gc(GcKind::Incremental),
+    )
+    .expect("Failed to build test driver");
+
+    let runtime = &driver.runtime;
+    assert_eq!(runtime.gc_kind(), GcKind::Incremental);
+
+    let pair: StructRef = runtime.invoke("new_pair", (6i64, 5i64)).unwrap();
+    let pair = pair.root();
+
+    // Trace `pair` and `pair.dest`, before `pair.src` is traced
+    assert!(!runtime.gc_collect_step(Duration::ZERO));
+    assert!(!runtime.gc_collect_step(Duration::ZERO));
+
+    let _: () = runtime.invoke("move_bar", (pair.as_ref(runtime),)).unwrap();
+
+    // The write barrier ensures that the previous `src.bar` is traced through `dest`, whereas the
+    // previous `dest.bar` can only be collected by the next cycle.
+    while !runtime.gc_collect_step(Duration::ZERO) {}
+    assert_eq!(runtime.gc_stats().collections, 1);
+    assert_eq!(runtime.gc_stats().allocated_memory, 72);
+
+    assert!(runtime.gc_collect());
+    assert_eq!(runtime.gc_stats().allocated_memory, 64);
+
+    let dest: StructRef = pair.as_ref(runtime).get("dest").unwrap();
+    let bar: StructRef = dest.get("bar").unwrap();
+    assert_eq!(bar.get::<i64>("baz").unwrap(), 6);
+}
+
+#[test]
+fn gc_alloc_while_marking() {
+    let driver = CompileAndRunTestDriver::new(
+        r#"
+    pub struct Wrapper {
+        foo: Foo,
+    }
+
+    pub struct Foo {
+        bar: Bar,
+    }
+
+    pub struct Bar {
+        baz: i64
+    }
+
+    pub fn new_wrapper(baz: i64) -> Wrapper {
+        Wrapper { foo: new_foo(baz) }
+    }
+
+    pub fn new_foo(baz: i64) -> Foo {
+        Foo { bar: Bar { baz } }
+    }
+
+    pub fn rewrap(dest: Wrapper) {
+        let bar = dest.foo.bar;
+        dest.foo.bar = Bar { baz: 0 };
+        dest.foo = Foo { bar };
+    }
+    "#,
+        |builder| builder.with_gc(GcKind::Incremental),
+    )
+    .expect("Failed to build test driver");
+
+    let runtime = &driver.runtime;
+
+    let dest: StructRef = runtime.invoke("new_wrapper", (6i64,)).unwrap();
+    let dest = dest.root();
+
+    // Trace `dest`, before `dest.foo` is traced
+    assert!(!runtime.gc_collect_step(Duration::ZERO));
+
+    let _: () = runtime.invoke("rewrap", (dest.as_ref(runtime),)).unwrap();
+
+    // The new `Foo` is allocated while marking and references the previous `dest.foo.bar` without
+    // a write barrier, so it must be traced for that `Bar` to survive. The previous `dest.foo` can
+    // only be collected by the next cycle.
+    while !runtime.gc_collect_step(Duration::ZERO) {}
+    assert_eq!(runtime.gc_stats().collections, 1);
+    assert_eq!(runtime.gc_stats().allocated_memory, 40);
+
+    assert!(runtime.gc_collect());
+    assert_eq!(runtime.gc_stats().allocated_memory, 24);
+
+    let foo: StructRef = dest.as_ref(runtime).get("foo").unwrap();
+    let bar: StructRef = foo.get("bar").unwrap();
+    assert_eq!(bar.get::<i64>("baz").unwrap(), 6);
+}
+
+#[test]
+fn weak_struct() {
+    let driver = CompileAndRunTestDriver::new(
+        r#"
+    pub struct Foo {
+        quz: f64,
+        bar: Bar,
+    }
+
+    pub struct Bar {
+        baz: i64
+    }
+
+    pub fn new_foo(baz: i64) -> Foo {
+        Foo {
+            quz: 1.0,
+            bar: Bar { baz }
+        }
+    }
+    "#,
+        |builder| builder,
+    )
+    .expect("Failed to build test driver");
+
+    let runtime = &driver.runtime;
+    let foo: StructRef = runtime.invoke("new_foo", (5i64,)).unwrap();
+    let weak_foo = foo.downgrade();
+    let weak_bar = foo.get::<StructRef>("bar").unwrap().downgrade();
+    let rooted = foo.root();
+
+    // Weak references do not keep structs alive, but rooted structs do
+    runtime.gc_collect();
+    assert!(!weak_foo.is_collected());
+    assert!(!weak_bar.is_collected());
+
+    let foo = weak_foo.upgrade().expect("struct should be alive");
+    assert_eq!(foo.as_ref(runtime).get::<f64>("quz").unwrap(), 1.0);
+
+    drop(rooted);
+    drop(foo);
+    runtime.gc_collect();
+    assert!(weak_foo.is_collected());
+    assert!(weak_bar.is_collected());
+    assert!(weak_foo.upgrade().is_none());
+}
+
+#[test]
+fn finalizer() {
+    let finalized_foos = Arc::new(AtomicUsize::new(0));
+    let finalized_bars = Arc::new(Mutex::new(Vec::new()));
+
+    let driver = CompileAndRunTestDriver::new(
+        r#"
+    pub struct Foo {
+        quz: f64,
+        bar: Bar,
+    }
+
+    pub struct Bar {
+        baz: i64
+    }
+
+    pub fn new_foo(baz: i64) -> Foo {
+        Foo {
+            quz: 1.0,
+            bar: Bar { baz }
+        }
+    }
+    "#,
+        |builder| {
+            let finalized_foos = finalized_foos.clone();
+            let finalized_bars = finalized_bars.clone();
+            builder
+                .with_finalizer("Foo", move |_, type_info| {
+                    assert_eq!(type_info.name(), "Foo");
+                    finalized_foos.fetch_add(1, Ordering::Relaxed);
+                })
+                .with_finalizer("Bar", move |raw, _| {
+                    // The struct's memory is still accessible
+                    let baz = unsafe { *raw.get_ptr().cast::<i64>() };
+                    finalized_bars.lock().unwrap().push(baz);
+                })
+        },
+    )
+    .expect("Failed to build test driver");
+
+    let runtime = &driver.runtime;
+    let foo: StructRef = runtime.invoke("new_foo", (5i64,)).unwrap();
+    let foo = foo.root();
+    let _: StructRef = runtime.invoke("new_foo", (6i64,)).unwrap();
+
+    // Only garbage is finalized
+    assert!(runtime.gc_collect());
+    assert_eq!(finalized_foos.load(Ordering::Relaxed), 1);
+    assert_eq!(*finalized_bars.lock().unwrap(), vec![6]);
+
+    drop(foo);
+    assert!(runtime.gc_collect());
+    assert_eq!(finalized_foos.load(Ordering::Relaxed), 2);
+    assert_eq!(*finalized_bars.lock().unwrap(), vec![6, 5]);
+}
+
+/// An allocator that counts the bytes of its live allocations.
+struct CountingAllocator(Arc<AtomicUsize>);
+
+unsafe impl Allocator for CountingAllocator {
+    fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.0.fetch_add(layout.size(), Ordering::Relaxed);
+        GlobalAllocator.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.0.fetch_sub(layout.size(), Ordering::Relaxed);
+        GlobalAllocator.dealloc(ptr, layout)
+    }
+}
+
+#[test]
+fn gc_allocator() {
+    let allocated = Arc::new(AtomicUsize::new(0));
+
+    let driver = CompileAndRunTestDriver::new(
+        r#"
+    pub struct Foo {
+        quz: f64,
+        bar: Bar,
+    }
+
+    pub struct Bar {
+        baz: i64
+    }
+
+    pub fn new_foo(baz: i64) -> Foo {
+        Foo {
+            quz: 1.0,
+            bar: Bar { baz }
+        }
+    }
+    "#,
+        |builder| builder.with_allocator(CountingAllocator(allocated.clone())),
+    )
+    .expect("Failed to build test driver");
+
+    let runtime = &driver.runtime;
+    let foo: StructRef = runtime.invoke("new_foo", (5i64,)).unwrap();
+    let foo = foo.root();
+
+    // The allocator provides the memory of structs and their bookkeeping
+    let allocated_memory = runtime.gc_stats().allocated_memory;
+    assert!(allocated.load(Ordering::Relaxed) > allocated_memory);
+
+    let before = allocated.load(Ordering::Relaxed);
+    let _: StructRef = runtime.invoke("new_foo", (6i64,)).unwrap();
+    assert!(allocated.load(Ordering::Relaxed) > before);
+    assert!(runtime.gc_collect());
+    assert_eq!(allocated.load(Ordering::Relaxed), before);
+
+    drop(foo);
+    assert!(runtime.gc_collect());
+    assert_eq!(allocated.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn gc_max_heap_size() {
+    let driver = CompileAndRunTestDriver::new(
+        r#"
+    pub struct Foo {
+        a: i64,
+    }
+
+    pub fn alloc_foos(n: i64) -> i64 {
+        let i = 0;
+        while i < n {
+            let foo = Foo { a: i };
+            i += 1;
+        }
+        i
+    }
+    "#,
+        |builder| builder.with_gc_max_heap_size(64),
+    )
+    .expect("Failed to build test driver");
+
+    let runtime = &driver.runtime;
+    assert_eq!(runtime.gc_stats().max_heap_size, Some(64));
+
+    let result: i64 = runtime.invoke("alloc_foos", (8i64,)).unwrap();
+    assert_eq!(result, 8);
+
+    // The garbage of the previous invocation is collected before an allocation is refused
+    let collections = runtime.gc_stats().collections;
+    let result: i64 = runtime.invoke("alloc_foos", (8i64,)).unwrap();
+    assert_eq!(result, 8);
+    assert!(runtime.gc_stats().collections > collections);
+
+    // Exceeding the limit fails the invocation instead of the process
+    let result: Result<i64, _> = runtime.invoke("alloc_foos", (1000i64,));
+    let err = result.unwrap_err();
+    assert!(err.to_string().contains("maximum heap size of 64 bytes"));
+    assert_eq!(runtime.gc_stats().allocated_memory, 0);
+    assert!(runtime.take_gc_heap_limit_exceeded().is_none());
+
+    runtime.set_gc_max_heap_size(None);
+    let result: i64 = runtime.invoke("alloc_foos", (1000i64,)).unwrap();
+    assert_eq!(result, 1000);
+}
+
+#[test]
+fn gc_max_heap_size_nested() {
+    let mut driver = CompileAndRunTestDriver::new(
+        r#"
+    pub struct Foo {
+        a: i64,
+    }
+
+    fn alloc_foo(a: i64) -> Foo {
+        Foo { a }
+    }
+
+    pub fn alloc_foos(n: i64) -> i64 {
+        let i = 0;
+        while i < n {
+            alloc_foo(i);
+            i += 1;
+        }
+        i
+    }
+
+    pub fn alloc_foos_nested(n: i64) -> i64 {
+        alloc_foos(n) + 1
+    }
+
+    pub fn leak() {
+        loop {
+            alloc_foo(0);
+        }
+    }
+    "#,
+        |builder| builder.with_gc_max_heap_size(64),
+    )
+    .expect("Failed to build test driver");
+
+    // Functions that call a function that exceeded the limit, return early as well
+    let result: Result<i64, _> = driver.runtime.invoke("alloc_foos_nested", (1000i64,));
+    assert!(result.is_err());
+
+    let result: i64 = driver.runtime.invoke("alloc_foos_nested", (4i64,)).unwrap();
+    assert_eq!(result, 5);
+
+    // A function that never returns by itself, is stopped
+    let result: Result<(), _> = driver.runtime.invoke("leak", ());
+    let err = result.unwrap_err();
+    assert!(err.to_string().contains("exceeded the maximum heap size"));
+
+    // The invocation cannot be retried
+    let result: Result<(), _> = err.wait(&mut driver.runtime);
+    assert!(result.is_err());
+}
+
+#[test]
+fn gc_snapshot() {
+    let driver = CompileAndRunTestDriver::new(
+        r#"
+    pub struct Foo {
+        quz: f64,
+        bar: Bar,
+    }
+
+    pub struct Bar {
+        baz: i64
+    }
+
+    pub fn new_foo(baz: i64) -> Foo {
+        Foo {
+            quz: 1.0,
+            bar: Bar { baz }
+        }
+    }
+    "#,
+        |builder| builder,
+    )
+    .expect("Failed to build test driver");
+
+    let runtime = &driver.runtime;
+    let foo: StructRef = runtime.invoke("new_foo", (5i64,)).unwrap();
+    let foo_name = foo.type_info().name().to_owned();
+    let bar_name = foo
+        .get::<StructRef>("bar")
+        .unwrap()
+        .type_info()
+        .name()
+        .to_owned();
+    let _foo = foo.root();
+    let _: StructRef = runtime.invoke("new_foo", (6i64,)).unwrap();
+
+    let snapshot = runtime.gc_snapshot();
+    assert_eq!(snapshot.objects.len(), 4);
+    assert_eq!(snapshot.total_size(), runtime.gc_stats().allocated_memory);
+
+    let roots: Vec<_> = snapshot.roots().collect();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(roots[0].type_name, foo_name);
+    assert_eq!(roots[0].size, 16);
+    assert_eq!(roots[0].references.len(), 1);
+
+    let bar = snapshot.get(roots[0].references[0]).unwrap();
+    assert_eq!(bar.type_name, bar_name);
+    assert_eq!(bar.size, 8);
+    assert_eq!(bar.roots, 0);
+
+    // Only the rooted object and the object it references survive a collection
+    assert!(runtime.gc_collect());
+    let snapshot = runtime.gc_snapshot();
+    assert_eq!(snapshot.objects.len(), 2);
+    assert!(snapshot
+        .to_dot()
+        .contains(&format!("{}\\n16 bytes\", style=bold", foo_name)));
+}
+
+#[test]
+fn deep_clone() {
+    let driver = CompileAndRunTestDriver::new(
+        r#"
+    pub struct Foo {
+        quz: f64,
+        bar: Bar,
+        pos: Pos,
+    }
+
+    pub struct Bar {
+        baz: i64
+    }
+
+    pub struct(value) Pos {
+        x: f32,
+        y: f32,
+    }
+
+    pub fn new_foo(baz: i64) -> Foo {
+        Foo {
+            quz: 1.0,
+            bar: Bar { baz },
+            pos: Pos { x: 1.0, y: 2.0 },
+        }
+    }
+    "#,
+        |builder| builder,
+    )
+    .expect("Failed to build test driver");
+
+    let runtime = &driver.runtime;
+    let foo: StructRef = runtime.invoke("new_foo", (5i64,)).unwrap();
+    let mut other: StructRef = runtime.invoke("new_foo", (5i64,)).unwrap();
+    assert!(runtime.structural_eq(&foo, &other));
+
+    let allocated_memory = runtime.gc_stats().allocated_memory;
+    let copy = runtime.deep_clone(&foo);
+    assert_eq!(
+        runtime.gc_stats().allocated_memory,
+        allocated_memory + allocated_memory / 2
+    );
+    assert!(runtime.structural_eq(&foo, &copy));
+
+    // Referenced gc structs are copied as well
+    let mut bar: StructRef = copy.get("bar").unwrap();
+    bar.set("baz", 6i64).unwrap();
+    let bar: StructRef = foo.get("bar").unwrap();
+    assert_eq!(bar.get::<i64>("baz").unwrap(), 5);
+    assert!(!runtime.structural_eq(&foo, &copy));
+
+    // Value structs are compared by their fields
+    let mut pos: StructRef = other.get("pos").unwrap();
+    pos.set("y", 3.0f32).unwrap();
+    other.set("pos", pos).unwrap();
+    assert!(!runtime.structural_eq(&foo, &other));
+}
+
+#[test]
+fn gc_serialize() {
+    let mut driver = CompileAndRunTestDriver::new(
+        r#"
+    pub struct Foo {
+        a: i32,
+        bar: Bar,
+    }
+
+    pub struct Bar {
+        b: f64,
+    }
+
+    pub fn new_foo(a: i32, b: f64) -> Foo {
+        Foo { a, bar: Bar { b } }
+    }
+    "#,
+        |builder| builder,
+    )
+    .expect("Failed to build test driver");
+
+    let foo: StructRef = driver.runtime.invoke("new_foo", (5i32, 3.0f64)).unwrap();
+    let foo = foo.root();
+    let bytes = driver.runtime.gc_serialize(&[foo.clone()]);
+
+    {
+        // Deserializing allocates copies of the serialized structs
+        let runtime = &driver.runtime;
+        let copies = runtime.gc_deserialize(&bytes).unwrap();
+        assert_eq!(copies.len(), 1);
+        let mut copy = copies[0].as_ref(runtime);
+        assert_eq!(copy.get::<i32>("a").unwrap(), 5);
+        let bar = copy.get::<StructRef>("bar").unwrap();
+        assert_eq!(bar.get::<f64>("b").unwrap(), 3.0);
+
+        // Modifying a copy does not affect the original
+        copy.set("a", 6i32).unwrap();
+        assert_eq!(foo.as_ref(runtime).get::<i32>("a").unwrap(), 5);
+    }
+
+    drop(foo);
+    driver.update(
+        "mod.mun",
+        r#"
+    pub struct Foo {
+        c: f64,
+        a: i64,
+        bar: Bar,
+    }
+
+    pub struct Bar {
+        b: f64,
+    }
+    "#,
+    );
+
+    // Structs are converted to the types that are currently loaded
+    let runtime = &driver.runtime;
+    let foo = runtime.gc_deserialize(&bytes).unwrap().remove(0);
+    let foo = foo.as_ref(runtime);
+    assert_eq!(foo.get::<f64>("c").unwrap(), 0.0);
+    assert_eq!(foo.get::<i64>("a").unwrap(), 5);
+    let bar = foo.get::<StructRef>("bar").unwrap();
+    assert_eq!(bar.get::<f64>("b").unwrap(), 3.0);
+
+    assert_eq!(
+        runtime.gc_deserialize(b"garbage").err(),
+        Some(DeserializeError::InvalidMagic)
+    );
+}
+
+#[test]
+fn gc_collect_minor() {
+    let driver = CompileAndRunTestDriver::new(
+        r#"
+    pub struct Foo {
+        quz: f64,
+        bar: Bar,
+    }
+
+    pub struct Bar {
+        baz: i64
+    }
+
+    pub fn new_foo(baz: i64) -> Foo {
+        Foo {
+            quz: 1.0,
+            bar: Bar { baz }
+        }
+    }
+    "#,
+        |builder| builder.with_gc(GcKind::Generational),
+    )
+    .expect("Failed to build test driver");
+
+    let runtime = &driver.runtime;
+    let foo: StructRef = runtime.invoke("new_foo", (5i64,)).unwrap();
+    let foo = foo.root();
+    let _: StructRef = runtime.invoke("new_foo", (6i64,)).unwrap();
+
+    // The rooted objects are promoted to the old generation
+    assert!(runtime.gc_collect_minor());
+    assert_eq!(runtime.gc_stats().allocated_memory, 24);
+
+    let bar: StructRef = foo.as_ref(runtime).get("bar").unwrap();
+    assert_eq!(bar.get::<i64>("baz").unwrap(), 5);
+
+    // Store a young object in the promoted object
+    let young_bar: StructRef = {
+        let young: StructRef = runtime.invoke("new_foo", (7i64,)).unwrap();
+        young.get("bar").unwrap()
+    };
+    foo.as_ref(runtime).set("bar", young_bar).unwrap();
+
+    // The previous `bar` is old, so it can only be collected by a major collection
+    assert!(runtime.gc_collect_minor());
+    assert_eq!(runtime.gc_stats().allocated_memory, 32);
+
+    let bar: StructRef = foo.as_ref(runtime).get("bar").unwrap();
+    assert_eq!(bar.get::<i64>("baz").unwrap(), 7);
+
+    assert!(runtime.gc_collect());
+    assert_eq!(runtime.gc_stats().allocated_memory, 24);
+}
+
 #[test]
 fn map_struct_insert_field1() {
     let mut driver = CompileAndRunTestDriver::new(
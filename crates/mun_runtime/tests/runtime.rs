@@ -1,4 +1,5 @@
-use mun_test::CompileAndRunTestDriver;
+use mun_runtime::{Runtime, StructRef};
+use mun_test::{CompileAndRunTestDriver, CompileTestDriver};
 use std::io;
 
 #[macro_use]
@@ -128,3 +129,232 @@ fn arg_missing_bug() {
     );
     driver.unwrap();
 }
+
+#[test]
+fn from_bytes() {
+    let driver = CompileTestDriver::from_file(
+        r#"
+    pub fn sum(a: i32, b: i32) -> i32 { a + b }
+    "#,
+    );
+    let bytes = std::fs::read(driver.lib_path()).expect("Failed to read library");
+
+    // The library path only identifies the in-memory assembly, so it doesn't need to exist
+    let builder = Runtime::builder("in_memory.munlib").with_bytes(bytes);
+
+    // Safety: We compiled the library ourselves, therefor loading the munlib is safe.
+    let runtime = unsafe { builder.finish() }.expect("Failed to build runtime");
+
+    let result: i32 = runtime.invoke("sum", (123i32, 456i32)).unwrap();
+    assert_eq!(123 + 456, result);
+}
+
+#[test]
+fn remove_assembly() {
+    let driver = CompileTestDriver::from_file(
+        r#"
+    pub struct(gc) Foo {
+        a: i32,
+    }
+
+    pub fn new_foo(a: i32) -> Foo { Foo { a } }
+    "#,
+    );
+
+    // Safety: We compiled the library ourselves, therefor loading the munlib is safe.
+    let runtime =
+        unsafe { Runtime::builder(driver.lib_path()).finish() }.expect("Failed to build runtime");
+
+    let foo: StructRef = runtime.invoke("new_foo", (5i32,)).unwrap();
+    let foo = foo.root();
+
+    // The assembly cannot be removed while an object of its type is alive
+    assert!(unsafe { runtime.remove_assembly(driver.lib_path()) }.is_err());
+    assert_eq!(foo.as_ref(&runtime).get::<i32>("a").unwrap(), 5);
+
+    drop(foo);
+    unsafe { runtime.remove_assembly(driver.lib_path()) }.expect("Failed to remove assembly");
+    assert_eq!(runtime.gc_stats().allocated_memory, 0);
+    assert!(runtime.get_function_definition("new_foo").is_none());
+    assert!(runtime.get_type_info("Foo").is_none());
+
+    // The assembly was already removed
+    assert!(unsafe { runtime.remove_assembly(driver.lib_path()) }.is_err());
+}
+
+#[test]
+fn packages() {
+    let base = CompileTestDriver::from_file(
+        r#"
+    pub fn value() -> i32 { 1 }
+    "#,
+    );
+    let package = CompileTestDriver::from_file(
+        r#"
+    pub fn value() -> i32 { 2 }
+    "#,
+    );
+
+    let builder = Runtime::builder(base.lib_path()).with_package("my_mod", package.lib_path());
+
+    // Safety: We compiled the libraries ourselves, therefor loading the munlibs is safe.
+    let runtime = unsafe { builder.finish() }.expect("Failed to build runtime");
+
+    let result: i32 = runtime.invoke("value", ()).unwrap();
+    assert_eq!(result, 1);
+    let result: i32 = runtime.invoke("my_mod::value", ()).unwrap();
+    assert_eq!(result, 2);
+}
+
+#[test]
+fn error_package_conflict() {
+    let base = CompileTestDriver::from_file(
+        r#"
+    pub fn value() -> i32 { 1 }
+    "#,
+    );
+    let package = CompileTestDriver::from_file(
+        r#"
+    pub fn value() -> i32 { 2 }
+    "#,
+    );
+    let conflicting_package = CompileTestDriver::from_file(
+        r#"
+    pub fn value() -> i32 { 3 }
+    "#,
+    );
+
+    // Safety: We compiled the libraries ourselves, therefor loading the munlibs is safe.
+    let runtime =
+        unsafe { Runtime::builder(base.lib_path()).finish() }.expect("Failed to build runtime");
+    unsafe { runtime.add_package("my_mod", package.lib_path()) }.expect("Failed to add package");
+
+    let err = unsafe { runtime.add_package("my_mod", conflicting_package.lib_path()) }
+        .expect_err("Conflicting packages should fail to link");
+    assert!(err.to_string().contains("my_mod::value"));
+
+    // The runtime is left intact
+    let result: i32 = runtime.invoke("my_mod::value", ()).unwrap();
+    assert_eq!(result, 2);
+}
+
+#[test]
+fn package_structs() {
+    let base = CompileTestDriver::from_file(
+        r#"
+    pub struct(gc) Foo { a: i32 }
+
+    pub fn new_foo() -> Foo { Foo { a: 1 } }
+    "#,
+    );
+    let package = CompileTestDriver::from_file(
+        r#"
+    pub struct(gc) Foo { a: i32 }
+
+    pub fn new_foo() -> Foo { Foo { a: 2 } }
+    "#,
+    );
+
+    let builder = Runtime::builder(base.lib_path()).with_package("my_mod", package.lib_path());
+
+    // Safety: We compiled the libraries ourselves, therefor loading the munlibs is safe.
+    let runtime = unsafe { builder.finish() }.expect("Failed to build runtime");
+
+    // Like functions, the structs of a package are namespaced
+    assert!(runtime.get_type_info("Foo").is_some());
+    assert!(runtime.get_type_info("my_mod::Foo").is_some());
+    assert!(runtime.get_type_info("other_mod::Foo").is_none());
+
+    let foo: StructRef = runtime.invoke("my_mod::new_foo", ()).unwrap();
+    assert_eq!(foo.get::<i32>("a").unwrap(), 2);
+}
+
+#[test]
+fn error_package_struct_conflict() {
+    let base = CompileTestDriver::from_file(
+        r#"
+    pub struct(gc) Foo { a: i32 }
+
+    pub fn new_foo() -> Foo { Foo { a: 1 } }
+    "#,
+    );
+    let package = CompileTestDriver::from_file(
+        r#"
+    pub struct(gc) Foo { a: f32 }
+
+    pub fn new_foo() -> Foo { Foo { a: 2.0 } }
+    "#,
+    );
+
+    // Safety: We compiled the libraries ourselves, therefor loading the munlibs is safe.
+    let runtime =
+        unsafe { Runtime::builder(base.lib_path()).finish() }.expect("Failed to build runtime");
+
+    let err = unsafe { runtime.add_package("my_mod", package.lib_path()) }
+        .expect_err("Conflicting structs should fail to link");
+    assert!(err.to_string().contains("my_mod::Foo"));
+
+    // The runtime is left intact
+    assert!(runtime.get_type_info("my_mod::Foo").is_none());
+    assert!(runtime.get_function_definition("my_mod::new_foo").is_none());
+}
+
+#[test]
+fn package_shares_library() {
+    let base = CompileTestDriver::from_file(
+        r#"
+    pub fn value() -> i32 { 1 }
+    "#,
+    );
+
+    // The same library can be loaded into multiple namespaces
+    let builder = Runtime::builder(base.lib_path()).with_package("my_mod", base.lib_path());
+
+    // Safety: We compiled the library ourselves, therefor loading the munlib is safe.
+    let runtime = unsafe { builder.finish() }.expect("Failed to build runtime");
+
+    let result: i32 = runtime.invoke("value", ()).unwrap();
+    assert_eq!(result, 1);
+    let result: i32 = runtime.invoke("my_mod::value", ()).unwrap();
+    assert_eq!(result, 1);
+
+    // Removing the library removes it from all namespaces
+    unsafe { runtime.remove_assembly(base.lib_path()) }.expect("Failed to remove assembly");
+    assert!(runtime.get_function_definition("value").is_none());
+    assert!(runtime.get_function_definition("my_mod::value").is_none());
+}
+
+#[test]
+fn from_bytes_with_dependency() {
+    let driver = CompileTestDriver::from_fixture(
+        r#"
+    //- /mun.toml
+    [package]
+    name="foo"
+    version="0.0.0"
+
+    //- /src/mod.mun
+    pub fn main() -> i32 { foo::get_value() }
+
+    //- /src/foo.mun
+    pub(super) fn get_value() -> i32 { 5 }
+    "#,
+    );
+    let bytes = std::fs::read(driver.lib_path()).expect("Failed to read library");
+
+    // The dependencies of the in-memory library are loaded from disk, relative to its path
+    let library_path = driver.lib_path().with_file_name("in_memory.munlib");
+    let builder = Runtime::builder(&library_path).with_bytes(bytes);
+
+    // Safety: We compiled the library ourselves, therefor loading the munlib is safe.
+    let runtime = unsafe { builder.finish() }.expect("Failed to build runtime");
+
+    let result: i32 = runtime.invoke("main", ()).unwrap();
+    assert_eq!(result, 5);
+
+    // Safety: We compiled the library ourselves, therefor reloading the munlib is safe.
+    unsafe { runtime.reload_from(&library_path, driver.lib_path()) }
+        .expect("Failed to reload assembly");
+    let result: i32 = runtime.invoke("main", ()).unwrap();
+    assert_eq!(result, 5);
+}
@@ -1,5 +1,63 @@
-use mun_runtime::Runtime;
+use mun_runtime::{Runtime, StructRef};
+use mun_test::CompileTestDriver;
+use std::{sync::Arc, thread};
 
 // Ensures the [`Runtime`] is Send
 trait IsSend: Send {}
 impl IsSend for Runtime {}
+
+// Ensures the [`Runtime`] is Sync
+trait IsSync: Sync {}
+impl IsSync for Runtime {}
+
+#[test]
+fn multithreaded_invoke() {
+    let driver = CompileTestDriver::from_file(
+        r#"
+    pub struct(gc) Foo {
+        a: i32,
+    }
+
+    pub fn sum(a: i32, b: i32) -> i32 { a + b }
+
+    pub fn new_foo(a: i32) -> Foo { Foo { a } }
+    "#,
+    );
+
+    // Safety: We compiled the library ourselves, therefor loading the munlib is safe.
+    let runtime = Arc::new(
+        unsafe { Runtime::builder(driver.lib_path()).finish() }.expect("Failed to build runtime"),
+    );
+
+    let threads: Vec<_> = (0..4)
+        .map(|i| {
+            let runtime = runtime.clone();
+            thread::spawn(move || {
+                (0..100)
+                    .map(|j| {
+                        let result: i32 = runtime.invoke("sum", (i, j)).unwrap();
+                        assert_eq!(result, i + j);
+
+                        let foo: StructRef = runtime.invoke("new_foo", (result,)).unwrap();
+                        foo.root()
+                    })
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+
+    let rooted: Vec<_> = threads
+        .into_iter()
+        .flat_map(|thread| thread.join().unwrap())
+        .collect();
+
+    // All objects are rooted, so nothing should be collected
+    assert!(!runtime.gc_collect());
+    for foo in rooted.iter() {
+        assert!(foo.as_ref(&runtime).get::<i32>("a").unwrap() >= 0);
+    }
+
+    drop(rooted);
+    assert!(runtime.gc_collect());
+    assert_eq!(runtime.gc_stats().allocated_memory, 0);
+}
@@ -2,11 +2,137 @@
 
 use crate::{ErrorHandle, RuntimeHandle, HUB};
 use anyhow::anyhow;
+use memory::gc::{RawGcPtr, WeakRef};
 use runtime::Runtime;
+use std::{
+    alloc::Layout,
+    ffi::{c_void, CString},
+    os::raw::c_char,
+    ptr,
+    time::Duration,
+};
 
 pub use memory::gc::GcPtr;
 pub use runtime::UnsafeTypeInfo;
 
+/// Statistics about the garbage collector of a runtime, retrieved through [`mun_gc_stats`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GcStats {
+    /// The number of bytes that are currently allocated
+    pub allocated_memory: usize,
+    /// The number of collections that have been run
+    pub collections: usize,
+    /// The total number of bytes freed by all collections
+    pub freed_memory: usize,
+    /// The duration of the most recent collection, in nanoseconds
+    pub last_pause_ns: u64,
+    /// The longest duration of a single collection, in nanoseconds
+    pub max_pause_ns: u64,
+    /// The combined duration of all collections, in nanoseconds
+    pub total_pause_ns: u64,
+    /// The maximum number of bytes that can be allocated, or zero if unlimited
+    pub max_heap_size: usize,
+}
+
+/// A finalizer that is invoked right before a struct of the type with the specified name is
+/// deallocated, passed to [`mun_runtime_create`] through the runtime options.
+///
+/// The callback is invoked while the garbage collector is collecting, so it must not call back
+/// into the runtime.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Finalizer {
+    /// The name of the struct type
+    pub type_name: *const c_char,
+    /// The callback that is invoked with the struct, its type and the `user_data`
+    pub callback: Option<extern "C" fn(GcPtr, UnsafeTypeInfo, *mut c_void)>,
+    /// User data that is passed to the callback
+    pub user_data: *mut c_void,
+}
+
+/// User data of a [`Finalizer`].
+///
+/// The host is responsible for making sure that the user data can be used from the thread that
+/// collects garbage.
+pub(crate) struct FinalizerData(pub *mut c_void);
+
+impl FinalizerData {
+    /// Returns the pointer to the user data.
+    pub fn as_ptr(&self) -> *mut c_void {
+        self.0
+    }
+}
+
+unsafe impl Send for FinalizerData {}
+unsafe impl Sync for FinalizerData {}
+
+/// An allocator that provides the memory of garbage collected structs, passed to
+/// [`mun_runtime_create`] through the runtime options. If both callbacks are null, the global
+/// allocator is used.
+///
+/// The callbacks can be invoked from any thread that allocates or collects garbage.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Allocator {
+    /// The callback that allocates memory of the specified size and alignment, which is a power
+    /// of two, and the `user_data`. It returns a null pointer if the allocation failed. The size
+    /// can be zero.
+    pub alloc: Option<extern "C" fn(usize, usize, *mut c_void) -> *mut c_void>,
+    /// The callback that deallocates memory that was allocated by `alloc`, with the same size and
+    /// alignment, and the `user_data`.
+    pub dealloc: Option<extern "C" fn(*mut c_void, usize, usize, *mut c_void)>,
+    /// User data that is passed to the callbacks
+    pub user_data: *mut c_void,
+}
+
+impl Default for Allocator {
+    fn default() -> Self {
+        Allocator {
+            alloc: None,
+            dealloc: None,
+            user_data: ptr::null_mut(),
+        }
+    }
+}
+
+/// An [`Allocator`] of which both callbacks are set.
+pub(crate) struct HostAllocator {
+    pub alloc: extern "C" fn(usize, usize, *mut c_void) -> *mut c_void,
+    pub dealloc: extern "C" fn(*mut c_void, usize, usize, *mut c_void),
+    pub user_data: *mut c_void,
+}
+
+/// The host is responsible for making sure that the allocator can be used from the threads that
+/// allocate or collect garbage.
+unsafe impl Send for HostAllocator {}
+unsafe impl Sync for HostAllocator {}
+
+unsafe impl runtime::Allocator for HostAllocator {
+    fn alloc(&self, layout: Layout) -> *mut u8 {
+        (self.alloc)(layout.size(), layout.align(), self.user_data).cast()
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        (self.dealloc)(ptr.cast(), layout.size(), layout.align(), self.user_data)
+    }
+}
+
+/// A C-style handle to a weak reference to an object, created through [`mun_gc_downgrade`].
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct WeakHandle(pub *const c_void);
+
+/// The formats that a heap snapshot can be exported to by [`mun_gc_snapshot`].
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotFormat {
+    /// A JSON object that lists all objects with their type, size, root count and references
+    Json,
+    /// A Graphviz DOT graph of all objects and their references
+    Dot,
+}
+
 /// Allocates an object in the runtime of the given `type_info`. If successful, `obj` is set,
 /// otherwise a non-zero error handle is returned.
 ///
@@ -138,6 +264,217 @@ pub unsafe extern "C" fn mun_gc_unroot(handle: RuntimeHandle, obj: GcPtr) -> Err
     ErrorHandle::default()
 }
 
+/// Notifies the garbage collector that a struct is about to be stored in a field of the specified
+/// `obj`. If successful, the garbage collector has been notified, otherwise a non-zero error handle
+/// is returned.
+///
+/// Incremental and generational garbage collectors rely on this notification to trace the stored
+/// struct, so it must be called every time the host stores a struct in a field of a garbage
+/// collected struct, right before writing to the memory of `obj`.
+///
+/// If a non-zero error handle is returned, it must be manually destructed using
+/// [`mun_error_destroy`].
+///
+/// # Safety
+///
+/// This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+/// an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+#[no_mangle]
+pub unsafe extern "C" fn mun_gc_write_barrier(handle: RuntimeHandle, obj: GcPtr) -> ErrorHandle {
+    let runtime = match (handle.0 as *mut Runtime).as_ref() {
+        Some(runtime) => runtime,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'runtime' is null pointer."))
+        }
+    };
+
+    runtime.gc().write_barrier(obj);
+    ErrorHandle::default()
+}
+
+/// Creates a weak reference to the specified `obj`, which does not keep it alive. If successful,
+/// `weak` is set, otherwise a non-zero error handle is returned.
+///
+/// The weak reference must be manually destructed using [`mun_gc_weak_destroy`].
+///
+/// If a non-zero error handle is returned, it must be manually destructed using
+/// [`mun_error_destroy`].
+///
+/// # Safety
+///
+/// This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+/// an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+#[no_mangle]
+pub unsafe extern "C" fn mun_gc_downgrade(
+    handle: RuntimeHandle,
+    obj: GcPtr,
+    weak: *mut WeakHandle,
+) -> ErrorHandle {
+    let runtime = match (handle.0 as *mut Runtime).as_ref() {
+        Some(runtime) => runtime,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'runtime' is null pointer."))
+        }
+    };
+
+    let weak = match weak.as_mut() {
+        Some(weak) => weak,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'weak' is null pointer."))
+        }
+    };
+
+    let weak_ref = runtime.gc().downgrade(obj);
+    weak.0 = Box::into_raw(Box::new(weak_ref)) as *const _;
+    ErrorHandle::default()
+}
+
+/// Roots the object referenced by `weak`. If successful, `obj` is set, otherwise a non-zero error
+/// handle is returned. If the object has been collected, `obj` is set to a null pointer.
+/// Otherwise, the object must be unrooted using [`mun_gc_unroot`].
+///
+/// If a non-zero error handle is returned, it must be manually destructed using
+/// [`mun_error_destroy`].
+///
+/// # Safety
+///
+/// This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+/// an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+#[no_mangle]
+pub unsafe extern "C" fn mun_gc_upgrade(
+    handle: RuntimeHandle,
+    weak: WeakHandle,
+    obj: *mut GcPtr,
+) -> ErrorHandle {
+    let runtime = match (handle.0 as *mut Runtime).as_ref() {
+        Some(runtime) => runtime,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'runtime' is null pointer."))
+        }
+    };
+
+    let weak = match (weak.0 as *const WeakRef).as_ref() {
+        Some(weak) => weak,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'weak' is null pointer."))
+        }
+    };
+
+    let obj = match obj.as_mut() {
+        Some(obj) => obj,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'obj' is null pointer."))
+        }
+    };
+
+    *obj = runtime
+        .gc()
+        .upgrade(weak)
+        .unwrap_or_else(|| (ptr::null() as RawGcPtr).into());
+    ErrorHandle::default()
+}
+
+/// Destructs the weak reference corresponding to `weak`.
+#[no_mangle]
+pub extern "C" fn mun_gc_weak_destroy(weak: WeakHandle) {
+    if !weak.0.is_null() {
+        let _weak = unsafe { Box::from_raw(weak.0 as *mut WeakRef) };
+    }
+}
+
+/// Allocates a deep copy of the specified `obj`. Objects that are (indirectly) referenced by
+/// `obj` are copied as well, preserving the references between them, including cycles. If
+/// successful, `clone` is set to the copy, otherwise a non-zero error handle is returned. The copy
+/// is not rooted.
+///
+/// If a non-zero error handle is returned, it must be manually destructed using
+/// [`mun_error_destroy`].
+///
+/// # Safety
+///
+/// This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+/// an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+#[no_mangle]
+pub unsafe extern "C" fn mun_gc_deep_clone(
+    handle: RuntimeHandle,
+    obj: GcPtr,
+    clone: *mut GcPtr,
+) -> ErrorHandle {
+    let runtime = match (handle.0 as *mut Runtime).as_ref() {
+        Some(runtime) => runtime,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'runtime' is null pointer."))
+        }
+    };
+
+    let clone = match clone.as_mut() {
+        Some(clone) => clone,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'clone' is null pointer."))
+        }
+    };
+
+    *clone = memory::gc::deep_clone(runtime.gc(), obj);
+    ErrorHandle::default()
+}
+
+/// Compares the specified objects `a` and `b` structurally. If successful, `equal` is set,
+/// otherwise a non-zero error handle is returned. If `equal` is `true`, the objects are of the
+/// same type and all of their fields are equal, where referenced objects are compared
+/// recursively.
+///
+/// If a non-zero error handle is returned, it must be manually destructed using
+/// [`mun_error_destroy`].
+///
+/// # Safety
+///
+/// This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+/// an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+#[no_mangle]
+pub unsafe extern "C" fn mun_gc_structural_eq(
+    handle: RuntimeHandle,
+    a: GcPtr,
+    b: GcPtr,
+    equal: *mut bool,
+) -> ErrorHandle {
+    let runtime = match (handle.0 as *mut Runtime).as_ref() {
+        Some(runtime) => runtime,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'runtime' is null pointer."))
+        }
+    };
+
+    let equal = match equal.as_mut() {
+        Some(equal) => equal,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'equal' is null pointer."))
+        }
+    };
+
+    *equal = memory::gc::structural_eq(runtime.gc(), a, b);
+    ErrorHandle::default()
+}
+
 /// Collects all memory that is no longer referenced by rooted objects. If successful, `reclaimed`
 /// is set, otherwise a non-zero error handle is returned. If `reclaimed` is `true`, memory was
 /// reclaimed, otherwise nothing happend. This behavior will likely change in the future.
@@ -175,3 +512,218 @@ pub unsafe extern "C" fn mun_gc_collect(
     *reclaimed = runtime.gc_collect();
     ErrorHandle::default()
 }
+
+/// Performs a step of a garbage collection cycle that spends approximately `budget_ns`
+/// nanoseconds. If successful, `completed` is set, otherwise a non-zero error handle is returned.
+/// If `completed` is `true`, the step completed the collection cycle.
+///
+/// Only an incremental garbage collector can split a collection into steps, other garbage
+/// collectors perform a full collection.
+///
+/// If a non-zero error handle is returned, it must be manually destructed using
+/// [`mun_error_destroy`].
+///
+/// # Safety
+///
+/// This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+/// an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+#[no_mangle]
+pub unsafe extern "C" fn mun_gc_collect_step(
+    handle: RuntimeHandle,
+    budget_ns: u64,
+    completed: *mut bool,
+) -> ErrorHandle {
+    let runtime = match (handle.0 as *mut Runtime).as_ref() {
+        Some(runtime) => runtime,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'runtime' is null pointer."))
+        }
+    };
+
+    let completed = match completed.as_mut() {
+        Some(completed) => completed,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'completed' is null pointer."))
+        }
+    };
+
+    *completed = runtime.gc_collect_step(Duration::from_nanos(budget_ns));
+    ErrorHandle::default()
+}
+
+/// Collects young objects that are no longer referenced by rooted objects or old objects. If
+/// successful, `reclaimed` is set, otherwise a non-zero error handle is returned. If `reclaimed`
+/// is `true`, memory was reclaimed, otherwise nothing happend.
+///
+/// Only a generational garbage collector distinguishes young objects, other garbage collectors
+/// perform a full collection.
+///
+/// If a non-zero error handle is returned, it must be manually destructed using
+/// [`mun_error_destroy`].
+///
+/// # Safety
+///
+/// This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+/// an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+#[no_mangle]
+pub unsafe extern "C" fn mun_gc_collect_minor(
+    handle: RuntimeHandle,
+    reclaimed: *mut bool,
+) -> ErrorHandle {
+    let runtime = match (handle.0 as *mut Runtime).as_ref() {
+        Some(runtime) => runtime,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'runtime' is null pointer."))
+        }
+    };
+
+    let reclaimed = match reclaimed.as_mut() {
+        Some(reclaimed) => reclaimed,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'reclaimed' is null pointer."))
+        }
+    };
+
+    *reclaimed = runtime.gc_collect_minor();
+    ErrorHandle::default()
+}
+
+/// Retrieves statistics about the garbage collector of the runtime corresponding to `handle`. If
+/// successful, `stats` is set, otherwise a non-zero error handle is returned.
+///
+/// If a non-zero error handle is returned, it must be manually destructed using
+/// [`mun_error_destroy`].
+///
+/// # Safety
+///
+/// This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+/// an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+#[no_mangle]
+pub unsafe extern "C" fn mun_gc_stats(handle: RuntimeHandle, stats: *mut GcStats) -> ErrorHandle {
+    let runtime = match (handle.0 as *mut Runtime).as_ref() {
+        Some(runtime) => runtime,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'runtime' is null pointer."))
+        }
+    };
+
+    let stats = match stats.as_mut() {
+        Some(stats) => stats,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'stats' is null pointer."))
+        }
+    };
+
+    let gc_stats = runtime.gc_stats();
+    *stats = GcStats {
+        allocated_memory: gc_stats.allocated_memory,
+        collections: gc_stats.collections,
+        freed_memory: gc_stats.freed_memory,
+        last_pause_ns: gc_stats.last_pause.as_nanos() as u64,
+        max_pause_ns: gc_stats.max_pause.as_nanos() as u64,
+        total_pause_ns: gc_stats.total_pause.as_nanos() as u64,
+        max_heap_size: gc_stats.max_heap_size.unwrap_or(0),
+    };
+    ErrorHandle::default()
+}
+
+/// Determines whether an allocation of a Mun function that was called on the current thread
+/// exceeded the maximum heap size of the runtime corresponding to `handle`, since the previous
+/// call. If successful, `exceeded` is set, otherwise a non-zero error handle is returned.
+///
+/// An allocation that exceeds the limit, after garbage was collected, fails and the Mun functions
+/// return early. The host should discard the result of the Mun function. Until the error is
+/// reset, Mun functions return early after calling a function that might allocate.
+///
+/// If a non-zero error handle is returned, it must be manually destructed using
+/// [`mun_error_destroy`].
+///
+/// # Safety
+///
+/// This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+/// an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+#[no_mangle]
+pub unsafe extern "C" fn mun_gc_take_heap_limit_exceeded(
+    handle: RuntimeHandle,
+    exceeded: *mut bool,
+) -> ErrorHandle {
+    let runtime = match (handle.0 as *mut Runtime).as_ref() {
+        Some(runtime) => runtime,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'runtime' is null pointer."))
+        }
+    };
+
+    let exceeded = match exceeded.as_mut() {
+        Some(exceeded) => exceeded,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'exceeded' is null pointer."))
+        }
+    };
+
+    *exceeded = runtime.take_gc_heap_limit_exceeded().is_some();
+    ErrorHandle::default()
+}
+
+/// Takes a snapshot of all objects that are currently allocated by the runtime and exports it in
+/// the specified `format`. If successful, `snapshot` is set, otherwise a non-zero error handle is
+/// returned.
+///
+/// The snapshot must be manually destructed using [`mun_destroy_string`].
+///
+/// If a non-zero error handle is returned, it must be manually destructed using
+/// [`mun_error_destroy`].
+///
+/// # Safety
+///
+/// This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+/// an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+#[no_mangle]
+pub unsafe extern "C" fn mun_gc_snapshot(
+    handle: RuntimeHandle,
+    format: SnapshotFormat,
+    snapshot: *mut *const c_char,
+) -> ErrorHandle {
+    let runtime = match (handle.0 as *mut Runtime).as_ref() {
+        Some(runtime) => runtime,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'runtime' is null pointer."))
+        }
+    };
+
+    let snapshot = match snapshot.as_mut() {
+        Some(snapshot) => snapshot,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'snapshot' is null pointer."))
+        }
+    };
+
+    let heap_snapshot = runtime.gc_snapshot();
+    let exported = match format {
+        SnapshotFormat::Json => heap_snapshot.to_json(),
+        SnapshotFormat::Dot => heap_snapshot.to_dot(),
+    };
+
+    *snapshot = CString::new(exported).unwrap().into_raw() as *const _;
+    ErrorHandle::default()
+}
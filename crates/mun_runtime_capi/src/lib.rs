@@ -14,9 +14,15 @@ mod tests;
 use std::{
     ffi::{c_void, CStr, CString},
     os::raw::c_char,
+    ptr::NonNull,
+    sync::Arc,
 };
 
-use crate::{error::ErrorHandle, hub::HUB};
+use crate::{
+    error::ErrorHandle,
+    gc::{Allocator, Finalizer, FinalizerData, HostAllocator, UnsafeTypeInfo},
+    hub::HUB,
+};
 use anyhow::anyhow;
 use runtime::Runtime;
 
@@ -55,6 +61,39 @@ pub struct RuntimeOptions {
 
     /// The number of functions in the [`functions`] array.
     pub num_functions: u32,
+
+    /// The factor by which the allocated memory may grow since the previous collection, before
+    /// garbage is automatically collected. Zero disables the threshold.
+    pub gc_growth_factor: f64,
+
+    /// The number of allocated bytes above which garbage is automatically collected. Zero
+    /// disables the threshold.
+    pub gc_max_bytes: usize,
+
+    /// The number of allocations since the previous collection, after which garbage is
+    /// automatically collected. Zero disables the threshold.
+    pub gc_max_allocations: usize,
+
+    /// The kind of garbage collector to use.
+    pub gc_kind: runtime::GcKind,
+
+    /// Finalizers that are invoked right before structs of their types are deallocated.
+    ///
+    /// If the [`num_finalizers`] field is non-zero this field must contain a pointer to an array
+    /// of [`Finalizer`]s.
+    pub finalizers: *const Finalizer,
+
+    /// The number of finalizers in the [`finalizers`] array.
+    pub num_finalizers: u32,
+
+    /// The allocator that provides the memory of garbage collected structs. If its callbacks are
+    /// null, the global allocator is used.
+    pub gc_allocator: Allocator,
+
+    /// The maximum number of bytes that garbage collected structs can occupy. Zero disables the
+    /// limit. Allocations that exceed the limit are reported through
+    /// [`mun_gc_take_heap_limit_exceeded`].
+    pub gc_max_heap_size: usize,
 }
 
 impl Default for RuntimeOptions {
@@ -62,6 +101,14 @@ impl Default for RuntimeOptions {
         RuntimeOptions {
             functions: std::ptr::null(),
             num_functions: 0,
+            gc_growth_factor: 0.0,
+            gc_max_bytes: 0,
+            gc_max_allocations: 0,
+            gc_kind: runtime::GcKind::MarkSweep,
+            finalizers: std::ptr::null(),
+            num_finalizers: 0,
+            gc_allocator: Allocator::default(),
+            gc_max_heap_size: 0,
         }
     }
 }
@@ -96,6 +143,12 @@ pub unsafe extern "C" fn mun_runtime_create(
             .register(anyhow!("Invalid argument: 'functions' is null pointer."));
     }
 
+    if options.num_finalizers > 0 && options.finalizers.is_null() {
+        return HUB
+            .errors
+            .register(anyhow!("Invalid argument: 'finalizers' is null pointer."));
+    }
+
     let library_path = match CStr::from_ptr(library_path).to_str() {
         Ok(path) => path,
         Err(_) => {
@@ -127,9 +180,85 @@ pub unsafe extern "C" fn mun_runtime_create(
             })
             .collect();
 
+    let mut finalizers: Vec<(String, runtime::StructFinalizer)> =
+        Vec::with_capacity(options.num_finalizers as usize);
+    for (idx, finalizer) in
+        std::slice::from_raw_parts(options.finalizers, options.num_finalizers as usize)
+            .iter()
+            .enumerate()
+    {
+        if finalizer.type_name.is_null() {
+            return HUB.errors.register(anyhow!(
+                "Invalid argument: 'finalizers[{}].type_name' is null pointer.",
+                idx
+            ));
+        }
+
+        let type_name = match CStr::from_ptr(finalizer.type_name).to_str() {
+            Ok(type_name) => type_name,
+            Err(_) => {
+                return HUB.errors.register(anyhow!(
+                    "Invalid argument: 'finalizers[{}].type_name' is not UTF-8 encoded.",
+                    idx
+                ))
+            }
+        };
+
+        let callback = match finalizer.callback {
+            Some(callback) => callback,
+            None => {
+                return HUB.errors.register(anyhow!(
+                    "Invalid argument: 'finalizers[{}].callback' is null pointer.",
+                    idx
+                ))
+            }
+        };
+
+        let user_data = FinalizerData(finalizer.user_data);
+        finalizers.push((
+            type_name.to_owned(),
+            Box::new(move |raw, type_info| {
+                let type_info = UnsafeTypeInfo::new(NonNull::from(type_info));
+                callback(GcPtr::from(raw), type_info, user_data.as_ptr())
+            }),
+        ));
+    }
+
+    let gc_allocator = match (options.gc_allocator.alloc, options.gc_allocator.dealloc) {
+        (None, None) => None,
+        (Some(alloc), Some(dealloc)) => Some(Arc::new(HostAllocator {
+            alloc,
+            dealloc,
+            user_data: options.gc_allocator.user_data,
+        }) as Arc<dyn runtime::Allocator>),
+        (None, Some(_)) => {
+            return HUB.errors.register(anyhow!(
+                "Invalid argument: 'gc_allocator.alloc' is null pointer."
+            ))
+        }
+        (Some(_), None) => {
+            return HUB.errors.register(anyhow!(
+                "Invalid argument: 'gc_allocator.dealloc' is null pointer."
+            ))
+        }
+    };
+
     let runtime_options = runtime::RuntimeOptions {
         library_path: library_path.into(),
+        library_bytes: None,
+        packages: Vec::new(),
         user_functions,
+        watcher: None,
+        gc_thresholds: runtime::CollectionThresholds {
+            growth_factor: Some(options.gc_growth_factor).filter(|factor| *factor > 0.0),
+            max_bytes: Some(options.gc_max_bytes).filter(|max_bytes| *max_bytes > 0),
+            max_allocations: Some(options.gc_max_allocations)
+                .filter(|max_allocations| *max_allocations > 0),
+        },
+        gc_kind: options.gc_kind,
+        gc_allocator,
+        gc_max_heap_size: Some(options.gc_max_heap_size).filter(|max_heap_size| *max_heap_size > 0),
+        finalizers,
     };
 
     let runtime = match Runtime::new(runtime_options) {
@@ -235,7 +364,7 @@ pub unsafe extern "C" fn mun_runtime_update(
     handle: RuntimeHandle,
     updated: *mut bool,
 ) -> ErrorHandle {
-    let runtime = match (handle.0 as *mut Runtime).as_mut() {
+    let runtime = match (handle.0 as *mut Runtime).as_ref() {
         Some(runtime) => runtime,
         None => {
             return HUB
@@ -257,6 +386,68 @@ pub unsafe extern "C" fn mun_runtime_update(
     ErrorHandle::default()
 }
 
+/// Adds the independently compiled library at `library_path` to the runtime corresponding to
+/// `handle`. The functions of the library and its dependencies are accessible as
+/// `<namespace>::<function path>`.
+///
+/// If a non-zero error handle is returned, it must be manually destructed using
+/// [`mun_error_destroy`].
+///
+/// # Safety
+///
+/// This function receives raw pointers as parameters. If any of the arguments is a null pointer,
+/// an error will be returned. Passing pointers to invalid data, will lead to undefined behavior.
+#[no_mangle]
+pub unsafe extern "C" fn mun_runtime_add_package(
+    handle: RuntimeHandle,
+    namespace: *const c_char,
+    library_path: *const c_char,
+) -> ErrorHandle {
+    let runtime = match (handle.0 as *mut Runtime).as_ref() {
+        Some(runtime) => runtime,
+        None => {
+            return HUB
+                .errors
+                .register(anyhow!("Invalid argument: 'runtime' is null pointer."))
+        }
+    };
+
+    if namespace.is_null() {
+        return HUB
+            .errors
+            .register(anyhow!("Invalid argument: 'namespace' is null pointer."));
+    }
+
+    let namespace = match CStr::from_ptr(namespace).to_str() {
+        Ok(namespace) => namespace,
+        Err(_) => {
+            return HUB.errors.register(anyhow!(
+                "Invalid argument: 'namespace' is not UTF-8 encoded."
+            ))
+        }
+    };
+
+    if library_path.is_null() {
+        return HUB
+            .errors
+            .register(anyhow!("Invalid argument: 'library_path' is null pointer."));
+    }
+
+    let library_path = match CStr::from_ptr(library_path).to_str() {
+        Ok(path) => path,
+        Err(_) => {
+            return HUB.errors.register(anyhow!(
+                "Invalid argument: 'library_path' is not UTF-8 encoded.",
+            ))
+        }
+    };
+
+    match runtime.add_package(namespace, library_path) {
+        Ok(()) => ErrorHandle::default(),
+        Err(e) => HUB.errors.register(e),
+    }
+}
+
 /// Deallocates a string that was allocated by the runtime.
 ///
 /// # Safety
@@ -1,13 +1,15 @@
 use crate::{error::*, gc::*, *};
 use compiler::{Config, DisplayColor, Driver, PathOrInline, RelativePathBuf};
 use memory::gc::{GcPtr, HasIndirectionPtr, RawGcPtr};
-use runtime::UnsafeTypeInfo;
+use runtime::{Allocator as _, GlobalAllocator, UnsafeTypeInfo};
 use std::{
-    ffi::CString,
+    alloc::Layout,
+    ffi::{c_void, CString},
     io::stderr,
     mem::{self, MaybeUninit},
     path::Path,
     ptr::{self, NonNull},
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
 /// Combines a compiler and runtime in one. Use of the TestDriver allows for quick testing of Mun
@@ -20,6 +22,11 @@ struct TestDriver {
 impl TestDriver {
     /// Constructs a new `TestDriver` from Mun source
     fn new(text: &str) -> Self {
+        Self::with_options(text, RuntimeOptions::default())
+    }
+
+    /// Constructs a new `TestDriver` from Mun source, using the specified runtime `options`
+    fn with_options(text: &str, options: RuntimeOptions) -> Self {
         let temp_dir = tempfile::TempDir::new().unwrap();
         let config = Config {
             out_dir: Some(temp_dir.path().to_path_buf()),
@@ -38,7 +45,7 @@ impl TestDriver {
         }
         let out_path = driver.assembly_output_path_from_file(file_id);
         driver.write_all_assemblies(false).unwrap();
-        let runtime = make_runtime(&out_path);
+        let runtime = make_runtime(&out_path, options);
         TestDriver {
             _temp_dir: temp_dir,
             runtime,
@@ -52,22 +59,35 @@ impl Drop for TestDriver {
     }
 }
 
-fn make_runtime(lib_path: &Path) -> RuntimeHandle {
+fn make_runtime(lib_path: &Path, options: RuntimeOptions) -> RuntimeHandle {
     let lib_path = lib_path.to_str().expect("Invalid lib path");
     let lib_path = CString::new(lib_path).unwrap();
 
     let mut handle = RuntimeHandle(ptr::null_mut());
-    let error = unsafe {
-        mun_runtime_create(
-            lib_path.as_ptr(),
-            RuntimeOptions::default(),
-            &mut handle as *mut _,
-        )
-    };
+    let error = unsafe { mun_runtime_create(lib_path.as_ptr(), options, &mut handle as *mut _) };
     assert_eq!(error.token(), 0, "Failed to create runtime");
     handle
 }
 
+/// Returns the function pointer of the function called `fn_name`.
+fn get_fn_ptr(runtime: RuntimeHandle, fn_name: &str) -> *const c_void {
+    let fn_name = CString::new(fn_name).expect("Invalid function name");
+    let mut has_fn_info = false;
+    let mut fn_definition = MaybeUninit::uninit();
+    let handle = unsafe {
+        mun_runtime_get_function_definition(
+            runtime,
+            fn_name.as_ptr(),
+            &mut has_fn_info as *mut _,
+            fn_definition.as_mut_ptr(),
+        )
+    };
+    assert_eq!(handle.token(), 0);
+    assert!(has_fn_info);
+
+    unsafe { fn_definition.assume_init() }.fn_ptr
+}
+
 macro_rules! test_invalid_runtime {
     ($(
         $name:ident($($arg:expr),*)
@@ -96,11 +116,25 @@ macro_rules! test_invalid_runtime {
 test_invalid_runtime!(
     runtime_get_function_definition(ptr::null(), ptr::null_mut(), ptr::null_mut()),
     runtime_update(ptr::null_mut()),
+    runtime_add_package(ptr::null(), ptr::null()),
     gc_alloc(UnsafeTypeInfo::new(NonNull::dangling()), ptr::null_mut()),
     gc_ptr_type(mem::zeroed::<GcPtr>(), ptr::null_mut()),
     gc_root(mem::zeroed::<GcPtr>()),
     gc_unroot(mem::zeroed::<GcPtr>()),
-    gc_collect(ptr::null_mut())
+    gc_write_barrier(mem::zeroed::<GcPtr>()),
+    gc_downgrade(mem::zeroed::<GcPtr>(), ptr::null_mut()),
+    gc_upgrade(WeakHandle(ptr::null()), ptr::null_mut()),
+    gc_deep_clone(mem::zeroed::<GcPtr>(), ptr::null_mut()),
+    gc_structural_eq(
+        mem::zeroed::<GcPtr>(),
+        mem::zeroed::<GcPtr>(),
+        ptr::null_mut()
+    ),
+    gc_collect(ptr::null_mut()),
+    gc_collect_step(0, ptr::null_mut()),
+    gc_collect_minor(ptr::null_mut()),
+    gc_snapshot(SnapshotFormat::Json, ptr::null_mut()),
+    gc_stats(ptr::null_mut())
 );
 
 #[test]
@@ -162,6 +196,133 @@ fn test_runtime_create_invalid_handle() {
     unsafe { mun_destroy_string(message.as_ptr()) };
 }
 
+#[test]
+fn test_runtime_create_invalid_finalizers() {
+    let lib_path = CString::new("some/path").expect("Invalid library path");
+    let options = RuntimeOptions {
+        num_finalizers: 1,
+        ..RuntimeOptions::default()
+    };
+
+    let mut handle = MaybeUninit::uninit();
+    let handle = unsafe { mun_runtime_create(lib_path.as_ptr(), options, handle.as_mut_ptr()) };
+    assert_ne!(handle.token(), 0);
+
+    let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "Invalid argument: 'finalizers' is null pointer."
+    );
+
+    unsafe { mun_destroy_string(message.as_ptr()) };
+}
+
+extern "C" fn count_finalizations(_obj: GcPtr, _type_info: UnsafeTypeInfo, user_data: *mut c_void) {
+    let counter = unsafe { &*(user_data as *const AtomicUsize) };
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+#[test]
+fn test_runtime_create_invalid_finalizer_type_name() {
+    let lib_path = CString::new("some/path").expect("Invalid library path");
+    let finalizer = Finalizer {
+        type_name: ptr::null(),
+        callback: Some(count_finalizations),
+        user_data: ptr::null_mut(),
+    };
+    let options = RuntimeOptions {
+        finalizers: &finalizer as *const _,
+        num_finalizers: 1,
+        ..RuntimeOptions::default()
+    };
+
+    let mut handle = MaybeUninit::uninit();
+    let handle = unsafe { mun_runtime_create(lib_path.as_ptr(), options, handle.as_mut_ptr()) };
+    assert_ne!(handle.token(), 0);
+
+    let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "Invalid argument: 'finalizers[0].type_name' is null pointer."
+    );
+
+    unsafe { mun_destroy_string(message.as_ptr()) };
+}
+
+#[test]
+fn test_runtime_create_invalid_finalizer_callback() {
+    let lib_path = CString::new("some/path").expect("Invalid library path");
+    let type_name = CString::new("Foo").expect("Invalid type name");
+    let finalizer = Finalizer {
+        type_name: type_name.as_ptr(),
+        callback: None,
+        user_data: ptr::null_mut(),
+    };
+    let options = RuntimeOptions {
+        finalizers: &finalizer as *const _,
+        num_finalizers: 1,
+        ..RuntimeOptions::default()
+    };
+
+    let mut handle = MaybeUninit::uninit();
+    let handle = unsafe { mun_runtime_create(lib_path.as_ptr(), options, handle.as_mut_ptr()) };
+    assert_ne!(handle.token(), 0);
+
+    let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "Invalid argument: 'finalizers[0].callback' is null pointer."
+    );
+
+    unsafe { mun_destroy_string(message.as_ptr()) };
+}
+
+extern "C" fn counting_alloc(size: usize, align: usize, user_data: *mut c_void) -> *mut c_void {
+    let counter = unsafe { &*(user_data as *const AtomicUsize) };
+    counter.fetch_add(1, Ordering::Relaxed);
+
+    let layout = Layout::from_size_align(size, align).unwrap();
+    GlobalAllocator.alloc(layout).cast()
+}
+
+extern "C" fn counting_dealloc(
+    ptr: *mut c_void,
+    size: usize,
+    align: usize,
+    user_data: *mut c_void,
+) {
+    let counter = unsafe { &*(user_data as *const AtomicUsize) };
+    counter.fetch_sub(1, Ordering::Relaxed);
+
+    let layout = Layout::from_size_align(size, align).unwrap();
+    unsafe { GlobalAllocator.dealloc(ptr.cast(), layout) };
+}
+
+#[test]
+fn test_runtime_create_invalid_gc_allocator() {
+    let lib_path = CString::new("some/path").expect("Invalid library path");
+    let options = RuntimeOptions {
+        gc_allocator: Allocator {
+            alloc: Some(counting_alloc),
+            dealloc: None,
+            user_data: ptr::null_mut(),
+        },
+        ..RuntimeOptions::default()
+    };
+
+    let mut handle = MaybeUninit::uninit();
+    let handle = unsafe { mun_runtime_create(lib_path.as_ptr(), options, handle.as_mut_ptr()) };
+    assert_ne!(handle.token(), 0);
+
+    let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "Invalid argument: 'gc_allocator.dealloc' is null pointer."
+    );
+
+    unsafe { mun_destroy_string(message.as_ptr()) };
+}
+
 #[test]
 fn test_runtime_get_function_info_invalid_fn_name() {
     let driver = TestDriver::new(
@@ -326,6 +487,46 @@ fn test_runtime_update() {
     assert_eq!(handle.token(), 0);
 }
 
+#[test]
+fn test_runtime_add_package_invalid_namespace() {
+    let driver = TestDriver::new(
+        r#"
+        pub fn main() -> i32 { 3 }
+    "#,
+    );
+
+    let handle = unsafe { mun_runtime_add_package(driver.runtime, ptr::null(), ptr::null()) };
+
+    let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "Invalid argument: 'namespace' is null pointer."
+    );
+
+    unsafe { mun_destroy_string(message.as_ptr()) };
+}
+
+#[test]
+fn test_runtime_add_package_invalid_library_path() {
+    let driver = TestDriver::new(
+        r#"
+        pub fn main() -> i32 { 3 }
+    "#,
+    );
+
+    let namespace = CString::new("mod").expect("Invalid namespace");
+    let handle =
+        unsafe { mun_runtime_add_package(driver.runtime, namespace.as_ptr(), ptr::null()) };
+
+    let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "Invalid argument: 'library_path' is null pointer."
+    );
+
+    unsafe { mun_destroy_string(message.as_ptr()) };
+}
+
 #[test]
 fn test_gc_alloc_invalid_obj() {
     let driver = TestDriver::new(
@@ -528,22 +729,696 @@ fn test_gc_rooting() {
 }
 
 #[test]
-fn test_gc_ptr_collect_invalid_reclaimed() {
-    let driver = TestDriver::new(
+fn test_gc_finalizer() {
+    let finalizations = AtomicUsize::new(0);
+    let type_name = CString::new("Foo").expect("Invalid type name");
+    let finalizer = Finalizer {
+        type_name: type_name.as_ptr(),
+        callback: Some(count_finalizations),
+        user_data: &finalizations as *const _ as *mut _,
+    };
+    let driver = TestDriver::with_options(
+        r#"
+        pub struct Foo;
+
+        pub fn main() -> Foo { Foo }
+    "#,
+        RuntimeOptions {
+            finalizers: &finalizer as *const _,
+            num_finalizers: 1,
+            ..RuntimeOptions::default()
+        },
+    );
+    let fn_name = CString::new("main").expect("Invalid function name");
+    let mut has_fn_info = false;
+    let mut fn_definition = MaybeUninit::uninit();
+    let handle = unsafe {
+        mun_runtime_get_function_definition(
+            driver.runtime,
+            fn_name.as_ptr(),
+            &mut has_fn_info as *mut _,
+            fn_definition.as_mut_ptr(),
+        )
+    };
+    assert_eq!(handle.token(), 0);
+
+    let fn_definition = unsafe { fn_definition.assume_init() };
+    // TODO: Simplify this once we have `mun_runtime_find_type_info`
+    let return_type = fn_definition.prototype.signature.return_type().unwrap();
+    let return_type =
+        UnsafeTypeInfo::new(NonNull::new(return_type as *const abi::TypeInfo as *mut _).unwrap());
+
+    let mut obj = MaybeUninit::uninit();
+    let handle = unsafe { mun_gc_alloc(driver.runtime, return_type, obj.as_mut_ptr()) };
+    assert_eq!(handle.token(), 0);
+
+    let mut reclaimed = false;
+    let handle = unsafe { mun_gc_collect(driver.runtime, &mut reclaimed as *mut _) };
+    assert_eq!(handle.token(), 0);
+    assert!(reclaimed);
+    assert_eq!(finalizations.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn test_gc_allocator() {
+    let allocations = AtomicUsize::new(0);
+    let driver = TestDriver::with_options(
         r#"
         pub struct Foo;
 
         pub fn main() -> Foo { Foo }
     "#,
+        RuntimeOptions {
+            gc_allocator: Allocator {
+                alloc: Some(counting_alloc),
+                dealloc: Some(counting_dealloc),
+                user_data: &allocations as *const _ as *mut _,
+            },
+            ..RuntimeOptions::default()
+        },
     );
+    let fn_name = CString::new("main").expect("Invalid function name");
+    let mut has_fn_info = false;
+    let mut fn_definition = MaybeUninit::uninit();
+    let handle = unsafe {
+        mun_runtime_get_function_definition(
+            driver.runtime,
+            fn_name.as_ptr(),
+            &mut has_fn_info as *mut _,
+            fn_definition.as_mut_ptr(),
+        )
+    };
+    assert_eq!(handle.token(), 0);
 
-    let handle = unsafe { mun_gc_collect(driver.runtime, ptr::null_mut()) };
+    let fn_definition = unsafe { fn_definition.assume_init() };
+    // TODO: Simplify this once we have `mun_runtime_find_type_info`
+    let return_type = fn_definition.prototype.signature.return_type().unwrap();
+    let return_type =
+        UnsafeTypeInfo::new(NonNull::new(return_type as *const abi::TypeInfo as *mut _).unwrap());
+
+    // Both the struct's memory and its bookkeeping are allocated
+    let allocations_before = allocations.load(Ordering::Relaxed);
+    let mut obj = MaybeUninit::uninit();
+    let handle = unsafe { mun_gc_alloc(driver.runtime, return_type, obj.as_mut_ptr()) };
+    assert_eq!(handle.token(), 0);
+    assert_eq!(allocations.load(Ordering::Relaxed), allocations_before + 2);
+
+    let mut reclaimed = false;
+    let handle = unsafe { mun_gc_collect(driver.runtime, &mut reclaimed as *mut _) };
+    assert_eq!(handle.token(), 0);
+    assert!(reclaimed);
+    assert_eq!(allocations.load(Ordering::Relaxed), allocations_before);
+}
+
+#[test]
+fn test_gc_downgrade_invalid_weak() {
+    let driver = TestDriver::new(
+        r#"
+        pub fn main() -> i32 { 3 }
+    "#,
+    );
+
+    let handle =
+        unsafe { mun_gc_downgrade(driver.runtime, mem::zeroed::<GcPtr>(), ptr::null_mut()) };
 
     let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
     assert_eq!(
         message.to_str().unwrap(),
-        "Invalid argument: 'reclaimed' is null pointer."
+        "Invalid argument: 'weak' is null pointer."
+    );
+
+    unsafe { mun_destroy_string(message.as_ptr()) };
+}
+
+#[test]
+fn test_gc_upgrade_invalid_weak() {
+    let driver = TestDriver::new(
+        r#"
+        pub fn main() -> i32 { 3 }
+    "#,
+    );
+
+    let mut obj = MaybeUninit::uninit();
+    let handle =
+        unsafe { mun_gc_upgrade(driver.runtime, WeakHandle(ptr::null()), obj.as_mut_ptr()) };
+
+    let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "Invalid argument: 'weak' is null pointer."
+    );
+
+    unsafe { mun_destroy_string(message.as_ptr()) };
+}
+
+#[test]
+fn test_gc_upgrade_invalid_obj() {
+    let driver = TestDriver::new(
+        r#"
+        pub fn main() -> i32 { 3 }
+    "#,
+    );
+
+    let mut weak = MaybeUninit::uninit();
+    let handle = unsafe { mun_gc_downgrade(driver.runtime, mem::zeroed(), weak.as_mut_ptr()) };
+    assert_eq!(handle.token(), 0);
+
+    let weak = unsafe { weak.assume_init() };
+    let handle = unsafe { mun_gc_upgrade(driver.runtime, weak, ptr::null_mut()) };
+
+    let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "Invalid argument: 'obj' is null pointer."
     );
 
     unsafe { mun_destroy_string(message.as_ptr()) };
+    mun_gc_weak_destroy(weak);
+}
+
+#[test]
+fn test_gc_weak() {
+    let driver = TestDriver::new(
+        r#"
+        pub struct Foo;
+
+        pub fn main() -> Foo { Foo }
+    "#,
+    );
+    let fn_name = CString::new("main").expect("Invalid function name");
+    let mut has_fn_info = false;
+    let mut fn_definition = MaybeUninit::uninit();
+    let handle = unsafe {
+        mun_runtime_get_function_definition(
+            driver.runtime,
+            fn_name.as_ptr(),
+            &mut has_fn_info as *mut _,
+            fn_definition.as_mut_ptr(),
+        )
+    };
+    assert_eq!(handle.token(), 0);
+
+    let fn_definition = unsafe { fn_definition.assume_init() };
+    // TODO: Simplify this once we have `mun_runtime_find_type_info`
+    let return_type = fn_definition.prototype.signature.return_type().unwrap();
+    let return_type =
+        UnsafeTypeInfo::new(NonNull::new(return_type as *const abi::TypeInfo as *mut _).unwrap());
+
+    let mut obj = MaybeUninit::uninit();
+    let handle = unsafe { mun_gc_alloc(driver.runtime, return_type, obj.as_mut_ptr()) };
+    assert_eq!(handle.token(), 0);
+    let obj = unsafe { obj.assume_init() };
+
+    let mut weak = MaybeUninit::uninit();
+    let handle = unsafe { mun_gc_downgrade(driver.runtime, obj, weak.as_mut_ptr()) };
+    assert_eq!(handle.token(), 0);
+    let weak = unsafe { weak.assume_init() };
+
+    // Upgrading roots the object
+    let mut upgraded = MaybeUninit::uninit();
+    let handle = unsafe { mun_gc_upgrade(driver.runtime, weak, upgraded.as_mut_ptr()) };
+    assert_eq!(handle.token(), 0);
+    assert_eq!(unsafe { upgraded.assume_init() }, obj);
+
+    let mut reclaimed = false;
+    let handle = unsafe { mun_gc_collect(driver.runtime, &mut reclaimed as *mut _) };
+    assert_eq!(handle.token(), 0);
+    assert!(!reclaimed);
+
+    let handle = unsafe { mun_gc_unroot(driver.runtime, obj) };
+    assert_eq!(handle.token(), 0);
+
+    let handle = unsafe { mun_gc_collect(driver.runtime, &mut reclaimed as *mut _) };
+    assert_eq!(handle.token(), 0);
+    assert!(reclaimed);
+
+    // The collected object can no longer be upgraded
+    let mut upgraded = MaybeUninit::uninit();
+    let handle = unsafe { mun_gc_upgrade(driver.runtime, weak, upgraded.as_mut_ptr()) };
+    assert_eq!(handle.token(), 0);
+    let upgraded: RawGcPtr = unsafe { upgraded.assume_init() }.into();
+    assert!(upgraded.is_null());
+
+    mun_gc_weak_destroy(weak);
+}
+
+#[test]
+fn test_gc_deep_clone_invalid_clone() {
+    let driver = TestDriver::new(
+        r#"
+        pub fn main() -> i32 { 3 }
+    "#,
+    );
+
+    let handle =
+        unsafe { mun_gc_deep_clone(driver.runtime, mem::zeroed::<GcPtr>(), ptr::null_mut()) };
+
+    let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "Invalid argument: 'clone' is null pointer."
+    );
+
+    unsafe { mun_destroy_string(message.as_ptr()) };
+}
+
+#[test]
+fn test_gc_structural_eq_invalid_equal() {
+    let driver = TestDriver::new(
+        r#"
+        pub fn main() -> i32 { 3 }
+    "#,
+    );
+
+    let handle = unsafe {
+        mun_gc_structural_eq(
+            driver.runtime,
+            mem::zeroed::<GcPtr>(),
+            mem::zeroed::<GcPtr>(),
+            ptr::null_mut(),
+        )
+    };
+
+    let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "Invalid argument: 'equal' is null pointer."
+    );
+
+    unsafe { mun_destroy_string(message.as_ptr()) };
+}
+
+#[test]
+fn test_gc_deep_clone() {
+    let driver = TestDriver::new(
+        r#"
+        pub struct Foo { a: i32, b: f64 }
+
+        pub fn main() -> Foo { Foo { a: 1, b: 2.0 } }
+    "#,
+    );
+    let fn_name = CString::new("main").expect("Invalid function name");
+    let mut has_fn_info = false;
+    let mut fn_definition = MaybeUninit::uninit();
+    let handle = unsafe {
+        mun_runtime_get_function_definition(
+            driver.runtime,
+            fn_name.as_ptr(),
+            &mut has_fn_info as *mut _,
+            fn_definition.as_mut_ptr(),
+        )
+    };
+    assert_eq!(handle.token(), 0);
+
+    let fn_definition = unsafe { fn_definition.assume_init() };
+    // TODO: Simplify this once we have `mun_runtime_find_type_info`
+    let return_type = fn_definition.prototype.signature.return_type().unwrap();
+    let return_type =
+        UnsafeTypeInfo::new(NonNull::new(return_type as *const abi::TypeInfo as *mut _).unwrap());
+
+    let mut obj = MaybeUninit::uninit();
+    let handle = unsafe { mun_gc_alloc(driver.runtime, return_type, obj.as_mut_ptr()) };
+    assert_eq!(handle.token(), 0);
+    let mut obj = unsafe { obj.assume_init() };
+
+    #[repr(C)]
+    struct Foo {
+        a: i32,
+        b: f64,
+    }
+    unsafe {
+        obj.deref_mut::<Foo>().write(Foo { a: 1, b: 2.0 });
+    }
+
+    let mut clone = MaybeUninit::uninit();
+    let handle = unsafe { mun_gc_deep_clone(driver.runtime, obj, clone.as_mut_ptr()) };
+    assert_eq!(handle.token(), 0);
+    let mut clone = unsafe { clone.assume_init() };
+    assert_ne!(clone, obj);
+
+    let mut equal = false;
+    let handle = unsafe { mun_gc_structural_eq(driver.runtime, obj, clone, &mut equal as *mut _) };
+    assert_eq!(handle.token(), 0);
+    assert!(equal);
+
+    unsafe { (*clone.deref_mut::<Foo>()).b = 3.0 };
+    let handle = unsafe { mun_gc_structural_eq(driver.runtime, obj, clone, &mut equal as *mut _) };
+    assert_eq!(handle.token(), 0);
+    assert!(!equal);
+}
+
+#[test]
+fn test_gc_ptr_collect_invalid_reclaimed() {
+    let driver = TestDriver::new(
+        r#"
+        pub struct Foo;
+
+        pub fn main() -> Foo { Foo }
+    "#,
+    );
+
+    let handle = unsafe { mun_gc_collect(driver.runtime, ptr::null_mut()) };
+
+    let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "Invalid argument: 'reclaimed' is null pointer."
+    );
+
+    unsafe { mun_destroy_string(message.as_ptr()) };
+}
+
+#[test]
+fn test_gc_stats_invalid_stats() {
+    let driver = TestDriver::new(
+        r#"
+        pub fn main() -> i32 { 3 }
+    "#,
+    );
+
+    let handle = unsafe { mun_gc_stats(driver.runtime, ptr::null_mut()) };
+
+    let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "Invalid argument: 'stats' is null pointer."
+    );
+
+    unsafe { mun_destroy_string(message.as_ptr()) };
+}
+
+#[test]
+fn test_gc_stats() {
+    let driver = TestDriver::new(
+        r#"
+        pub fn main() -> i32 { 3 }
+    "#,
+    );
+
+    let mut reclaimed = false;
+    let handle = unsafe { mun_gc_collect(driver.runtime, &mut reclaimed as *mut _) };
+    assert_eq!(handle.token(), 0);
+
+    let mut stats = GcStats::default();
+    let handle = unsafe { mun_gc_stats(driver.runtime, &mut stats as *mut _) };
+    assert_eq!(handle.token(), 0);
+    assert_eq!(stats.allocated_memory, 0);
+    assert_eq!(stats.collections, 1);
+}
+
+#[test]
+fn test_gc_take_heap_limit_exceeded_invalid_exceeded() {
+    let driver = TestDriver::new(
+        r#"
+        pub fn main() -> i32 { 3 }
+    "#,
+    );
+
+    let handle = unsafe { mun_gc_take_heap_limit_exceeded(driver.runtime, ptr::null_mut()) };
+
+    let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "Invalid argument: 'exceeded' is null pointer."
+    );
+
+    unsafe { mun_destroy_string(message.as_ptr()) };
+}
+
+#[test]
+fn test_gc_take_heap_limit_exceeded() {
+    let driver = TestDriver::with_options(
+        r#"
+        pub struct Foo { a: i64 }
+
+        pub fn alloc_foos(n: i64) -> i64 {
+            let i = 0;
+            while i < n {
+                let foo = Foo { a: i };
+                i += 1;
+            }
+            i
+        }
+    "#,
+        RuntimeOptions {
+            gc_max_heap_size: 64,
+            ..RuntimeOptions::default()
+        },
+    );
+    let fn_name = CString::new("alloc_foos").expect("Invalid function name");
+    let mut has_fn_info = false;
+    let mut fn_definition = MaybeUninit::uninit();
+    let handle = unsafe {
+        mun_runtime_get_function_definition(
+            driver.runtime,
+            fn_name.as_ptr(),
+            &mut has_fn_info as *mut _,
+            fn_definition.as_mut_ptr(),
+        )
+    };
+    assert_eq!(handle.token(), 0);
+
+    let fn_definition = unsafe { fn_definition.assume_init() };
+    let alloc_foos: extern "C" fn(i64) -> i64 = unsafe { mem::transmute(fn_definition.fn_ptr) };
+
+    let mut stats = GcStats::default();
+    let handle = unsafe { mun_gc_stats(driver.runtime, &mut stats as *mut _) };
+    assert_eq!(handle.token(), 0);
+    assert_eq!(stats.max_heap_size, 64);
+
+    let mut exceeded = true;
+    assert_eq!(alloc_foos(8), 8);
+    let handle =
+        unsafe { mun_gc_take_heap_limit_exceeded(driver.runtime, &mut exceeded as *mut _) };
+    assert_eq!(handle.token(), 0);
+    assert!(!exceeded);
+
+    // The function returned early, so its result is discarded
+    alloc_foos(100);
+    let handle =
+        unsafe { mun_gc_take_heap_limit_exceeded(driver.runtime, &mut exceeded as *mut _) };
+    assert_eq!(handle.token(), 0);
+    assert!(exceeded);
+
+    // The error is reset once it has been taken
+    let handle =
+        unsafe { mun_gc_take_heap_limit_exceeded(driver.runtime, &mut exceeded as *mut _) };
+    assert_eq!(handle.token(), 0);
+    assert!(!exceeded);
+}
+
+#[test]
+fn test_gc_collect_step_invalid_completed() {
+    let driver = TestDriver::new(
+        r#"
+        pub fn main() -> i32 { 3 }
+    "#,
+    );
+
+    let handle = unsafe { mun_gc_collect_step(driver.runtime, 0, ptr::null_mut()) };
+
+    let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "Invalid argument: 'completed' is null pointer."
+    );
+
+    unsafe { mun_destroy_string(message.as_ptr()) };
+}
+
+#[test]
+fn test_gc_collect_step() {
+    let driver = TestDriver::new(
+        r#"
+        pub fn main() -> i32 { 3 }
+    "#,
+    );
+
+    // A mark-sweep garbage collector always completes a collection cycle
+    let mut completed = false;
+    let handle = unsafe { mun_gc_collect_step(driver.runtime, 0, &mut completed as *mut _) };
+    assert_eq!(handle.token(), 0);
+    assert!(completed);
+}
+
+#[test]
+fn test_gc_collect_minor_invalid_reclaimed() {
+    let driver = TestDriver::new(
+        r#"
+        pub fn main() -> i32 { 3 }
+    "#,
+    );
+
+    let handle = unsafe { mun_gc_collect_minor(driver.runtime, ptr::null_mut()) };
+
+    let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "Invalid argument: 'reclaimed' is null pointer."
+    );
+
+    unsafe { mun_destroy_string(message.as_ptr()) };
+}
+
+#[test]
+fn test_gc_write_barrier() {
+    let driver = TestDriver::with_options(
+        r#"
+        pub struct Foo { bar: Bar }
+        pub struct Bar { baz: i64 }
+
+        pub fn new_foo() -> Foo { Foo { bar: new_bar(1) } }
+        pub fn new_bar(baz: i64) -> Bar { Bar { baz } }
+    "#,
+        RuntimeOptions {
+            gc_kind: runtime::GcKind::Incremental,
+            ..RuntimeOptions::default()
+        },
+    );
+    let new_foo: extern "C" fn() -> GcPtr =
+        unsafe { mem::transmute(get_fn_ptr(driver.runtime, "new_foo")) };
+    let new_bar: extern "C" fn(i64) -> GcPtr =
+        unsafe { mem::transmute(get_fn_ptr(driver.runtime, "new_bar")) };
+
+    let mut foo = new_foo();
+    let handle = unsafe { mun_gc_root(driver.runtime, foo) };
+    assert_eq!(handle.token(), 0);
+
+    let bar = new_bar(2);
+    let mut weak = MaybeUninit::uninit();
+    let handle = unsafe { mun_gc_downgrade(driver.runtime, bar, weak.as_mut_ptr()) };
+    assert_eq!(handle.token(), 0);
+    let weak = unsafe { weak.assume_init() };
+
+    // Trace `foo`, before `bar` is stored in it
+    let mut completed = true;
+    let handle = unsafe { mun_gc_collect_step(driver.runtime, 0, &mut completed as *mut _) };
+    assert_eq!(handle.token(), 0);
+    assert!(!completed);
+
+    let handle = unsafe { mun_gc_write_barrier(driver.runtime, foo) };
+    assert_eq!(handle.token(), 0);
+    unsafe { *foo.deref_mut::<GcPtr>() = bar };
+
+    while !completed {
+        let handle = unsafe { mun_gc_collect_step(driver.runtime, 0, &mut completed as *mut _) };
+        assert_eq!(handle.token(), 0);
+    }
+
+    // The write barrier ensures that `bar` is traced through `foo`
+    let mut upgraded = MaybeUninit::uninit();
+    let handle = unsafe { mun_gc_upgrade(driver.runtime, weak, upgraded.as_mut_ptr()) };
+    assert_eq!(handle.token(), 0);
+    assert_eq!(unsafe { upgraded.assume_init() }, bar);
+    assert_eq!(unsafe { *bar.deref::<i64>() }, 2);
+
+    let handle = unsafe { mun_gc_unroot(driver.runtime, bar) };
+    assert_eq!(handle.token(), 0);
+    let handle = unsafe { mun_gc_unroot(driver.runtime, foo) };
+    assert_eq!(handle.token(), 0);
+
+    mun_gc_weak_destroy(weak);
+}
+
+#[test]
+fn test_gc_write_barrier_generational() {
+    let driver = TestDriver::with_options(
+        r#"
+        pub struct Foo { bar: Bar }
+        pub struct Bar { baz: i64 }
+
+        pub fn new_foo() -> Foo { Foo { bar: new_bar(1) } }
+        pub fn new_bar(baz: i64) -> Bar { Bar { baz } }
+    "#,
+        RuntimeOptions {
+            gc_kind: runtime::GcKind::Generational,
+            ..RuntimeOptions::default()
+        },
+    );
+    let new_foo: extern "C" fn() -> GcPtr =
+        unsafe { mem::transmute(get_fn_ptr(driver.runtime, "new_foo")) };
+    let new_bar: extern "C" fn(i64) -> GcPtr =
+        unsafe { mem::transmute(get_fn_ptr(driver.runtime, "new_bar")) };
+
+    let mut foo = new_foo();
+    let handle = unsafe { mun_gc_root(driver.runtime, foo) };
+    assert_eq!(handle.token(), 0);
+
+    // Promote `foo` to the old generation
+    let mut reclaimed = false;
+    let handle = unsafe { mun_gc_collect(driver.runtime, &mut reclaimed as *mut _) };
+    assert_eq!(handle.token(), 0);
+
+    let bar = new_bar(2);
+    let mut weak = MaybeUninit::uninit();
+    let handle = unsafe { mun_gc_downgrade(driver.runtime, bar, weak.as_mut_ptr()) };
+    assert_eq!(handle.token(), 0);
+    let weak = unsafe { weak.assume_init() };
+
+    let handle = unsafe { mun_gc_write_barrier(driver.runtime, foo) };
+    assert_eq!(handle.token(), 0);
+    unsafe { *foo.deref_mut::<GcPtr>() = bar };
+
+    let handle = unsafe { mun_gc_collect_minor(driver.runtime, &mut reclaimed as *mut _) };
+    assert_eq!(handle.token(), 0);
+
+    // The write barrier ensures that `bar` is traced through the remembered `foo`
+    let mut upgraded = MaybeUninit::uninit();
+    let handle = unsafe { mun_gc_upgrade(driver.runtime, weak, upgraded.as_mut_ptr()) };
+    assert_eq!(handle.token(), 0);
+    assert_eq!(unsafe { upgraded.assume_init() }, bar);
+    assert_eq!(unsafe { *bar.deref::<i64>() }, 2);
+
+    let handle = unsafe { mun_gc_unroot(driver.runtime, bar) };
+    assert_eq!(handle.token(), 0);
+    let handle = unsafe { mun_gc_unroot(driver.runtime, foo) };
+    assert_eq!(handle.token(), 0);
+
+    mun_gc_weak_destroy(weak);
+}
+
+#[test]
+fn test_gc_snapshot_invalid_snapshot() {
+    let driver = TestDriver::new(
+        r#"
+        pub fn main() -> i32 { 3 }
+    "#,
+    );
+
+    let handle = unsafe { mun_gc_snapshot(driver.runtime, SnapshotFormat::Json, ptr::null_mut()) };
+
+    let message = unsafe { CStr::from_ptr(mun_error_message(handle)) };
+    assert_eq!(
+        message.to_str().unwrap(),
+        "Invalid argument: 'snapshot' is null pointer."
+    );
+
+    unsafe { mun_destroy_string(message.as_ptr()) };
+}
+
+#[test]
+fn test_gc_snapshot() {
+    let driver = TestDriver::new(
+        r#"
+        pub fn main() -> i32 { 3 }
+    "#,
+    );
+
+    let mut snapshot = MaybeUninit::uninit();
+    let handle =
+        unsafe { mun_gc_snapshot(driver.runtime, SnapshotFormat::Json, snapshot.as_mut_ptr()) };
+    assert_eq!(handle.token(), 0);
+
+    let snapshot = unsafe { CStr::from_ptr(snapshot.assume_init()) };
+    assert_eq!(snapshot.to_str().unwrap(), "{\"objects\":[]}");
+    unsafe { mun_destroy_string(snapshot.as_ptr()) };
+
+    let mut snapshot = MaybeUninit::uninit();
+    let handle =
+        unsafe { mun_gc_snapshot(driver.runtime, SnapshotFormat::Dot, snapshot.as_mut_ptr()) };
+    assert_eq!(handle.token(), 0);
+
+    let snapshot = unsafe { CStr::from_ptr(snapshot.assume_init()) };
+    assert_eq!(snapshot.to_str().unwrap(), "digraph heap {\n}\n");
+    unsafe { mun_destroy_string(snapshot.as_ptr()) };
 }
@@ -88,6 +88,26 @@ pub enum BinOp {
     Greater,
 }
 
+impl BinOp {
+    /// Returns true if this operator assigns to its left-hand side, e.g. `=` or `+=`.
+    pub fn is_assignment(self) -> bool {
+        matches!(
+            self,
+            BinOp::Assign
+                | BinOp::AddAssign
+                | BinOp::SubtractAssign
+                | BinOp::MultiplyAssign
+                | BinOp::DivideAssign
+                | BinOp::RemainderAssign
+                | BinOp::LeftShiftAssign
+                | BinOp::RightShiftAssign
+                | BinOp::BitAndAssign
+                | BinOp::BitOrAssign
+                | BinOp::BitXorAssign
+        )
+    }
+}
+
 impl BinExpr {
     pub fn op_details(&self) -> Option<(SyntaxToken, BinOp)> {
         self.syntax()
@@ -1,4 +1,4 @@
-use crate::{AstNode, SyntaxNode, TextSize};
+use crate::{AstNode, SyntaxKind, SyntaxNode, SyntaxToken, TextSize};
 use itertools::Itertools;
 
 /// Returns ancestors of the node at the offset, sorted by length. This should do the right thing at
@@ -25,3 +25,16 @@ pub fn ancestors_at_offset(
 pub fn find_node_at_offset<N: AstNode>(syntax: &SyntaxNode, offset: TextSize) -> Option<N> {
     ancestors_at_offset(syntax, offset).find_map(N::cast)
 }
+
+/// Returns the token at the offset that the user is most likely interested in. If the offset is in
+/// between two tokens, as in `foo$0(`, identifiers are preferred over other tokens and trivia like
+/// whitespace is avoided.
+pub fn pick_best_token(syntax: &SyntaxNode, offset: TextSize) -> Option<SyntaxToken> {
+    syntax
+        .token_at_offset(offset)
+        .max_by_key(|token| match token.kind() {
+            SyntaxKind::IDENT | SyntaxKind::INDEX | SyntaxKind::INT_NUMBER => 2,
+            SyntaxKind::WHITESPACE | SyntaxKind::COMMENT => 0,
+            _ => 1,
+        })
+}